@@ -0,0 +1,51 @@
+use code_fuzzy_match::corpus::{CorpusGenerator, IdentifierStyle};
+use code_fuzzy_match::{FuzzyMatcher, TieBreak};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let paths = CorpusGenerator::new(1).file_paths(10000);
+    let identifiers = CorpusGenerator::new(2).identifiers(10000, IdentifierStyle::CamelCase);
+    let commands = CorpusGenerator::new(3).commands(1000);
+
+    c.bench_function("corpus_paths", |b| {
+        let mut matcher = FuzzyMatcher::new();
+        b.iter(|| {
+            for path in &paths {
+                black_box(matcher.fuzzy_match(path, black_box("srcparse")));
+            }
+        })
+    });
+    // Compare with and without the `batch-lanes` feature, which scores the paths ranked here
+    // several at a time
+    for query in ["srcparse", "mat", "m"] {
+        c.bench_function(&format!("corpus_paths_rank_{query}"), |b| {
+            let mut matcher = FuzzyMatcher::new();
+            b.iter(|| {
+                black_box(matcher.rank(
+                    paths.iter().map(String::as_str),
+                    black_box(query),
+                    TieBreak::DEFAULT,
+                ))
+            })
+        });
+    }
+    c.bench_function("corpus_identifiers", |b| {
+        let mut matcher = FuzzyMatcher::new();
+        b.iter(|| {
+            for identifier in &identifiers {
+                black_box(matcher.fuzzy_match(identifier, black_box("getVal")));
+            }
+        })
+    });
+    c.bench_function("corpus_commands", |b| {
+        let mut matcher = FuzzyMatcher::new();
+        b.iter(|| {
+            for command in &commands {
+                black_box(matcher.fuzzy_match(command, black_box("togpan")));
+            }
+        })
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -0,0 +1,329 @@
+//! Interactive picker that filters a list as you type, highlighting the matched characters.
+//! Candidates are read from standard input, or are the files under the current directory
+//! when standard input is a terminal. The selected candidate is printed when Enter is
+//! pressed.
+//!
+//! ```text
+//! git ls-files | cargo run --example picker --features tui
+//! ```
+//!
+//! Matching runs on a [`MatcherWorker`] thread, so the list updates while long lists are
+//! still being matched, and typing another character abandons the previous query.
+
+use code_fuzzy_match::{FuzzyMatcher, MatcherWorker, Scored};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Color, Print, SetAttribute, SetForegroundColor};
+use crossterm::{cursor, terminal, QueueableCommand};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+use std::time::Duration;
+
+/// What to do after a key press.
+#[derive(Debug, PartialEq, Eq)]
+enum Action {
+    Continue,
+    Accept,
+    Abort,
+}
+
+/// State of the picker, independent of the terminal.
+struct Picker {
+    worker: MatcherWorker,
+    matcher: FuzzyMatcher,
+    query: String,
+    generation: u64,
+    matches: Vec<Scored<usize>>,
+    complete: bool,
+    selected: usize,
+}
+
+impl Picker {
+    fn new(candidates: Vec<String>) -> Self {
+        Picker {
+            worker: MatcherWorker::new(candidates, Default::default()),
+            matcher: FuzzyMatcher::new(),
+            query: String::new(),
+            generation: 0,
+            matches: Vec::new(),
+            complete: true,
+            selected: 0,
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Action {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Enter => return Action::Accept,
+            KeyCode::Esc => return Action::Abort,
+            KeyCode::Char('c') if control => return Action::Abort,
+            KeyCode::Up => self.move_selection(-1),
+            KeyCode::Char('p') if control => self.move_selection(-1),
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::Char('n') if control => self.move_selection(1),
+            KeyCode::Char('u') if control => self.set_query(String::new()),
+            KeyCode::Backspace => {
+                let mut query = self.query.clone();
+                query.pop();
+                self.set_query(query);
+            }
+            KeyCode::Char(c) if !control => {
+                let mut query = self.query.clone();
+                query.push(c);
+                self.set_query(query);
+            }
+            _ => (),
+        }
+        Action::Continue
+    }
+
+    fn set_query(&mut self, query: String) {
+        if query == self.query {
+            return;
+        }
+        self.query = query;
+        self.selected = 0;
+        if self.query.is_empty() {
+            self.matches.clear();
+            self.complete = true;
+        } else {
+            // The results of the previous query stay visible until the first update arrives
+            self.generation += 1;
+            self.complete = false;
+            self.worker.query(&self.query, self.generation);
+        }
+    }
+
+    /// Applies the newest update from the worker, returning true if the list changed.
+    fn poll(&mut self) -> bool {
+        match self.worker.try_recv() {
+            Some(results) if results.generation == self.generation && !self.query.is_empty() => {
+                self.matches = results.matches;
+                self.complete = results.complete;
+                self.selected = self.selected.min(self.len().saturating_sub(1));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Number of candidates in the list. Every candidate is listed when the query is empty.
+    fn len(&self) -> usize {
+        if self.query.is_empty() {
+            self.worker.targets().len()
+        } else {
+            self.matches.len()
+        }
+    }
+
+    /// Index into the candidates of the given row of the list.
+    fn candidate(&self, row: usize) -> usize {
+        if self.query.is_empty() {
+            row
+        } else {
+            self.matches[row].item
+        }
+    }
+
+    fn selection(&self) -> Option<&str> {
+        (self.selected < self.len())
+            .then(|| self.worker.targets()[self.candidate(self.selected)].as_str())
+    }
+
+    /// Character indices of the given row that match the query.
+    fn highlights(&mut self, row: usize) -> Vec<usize> {
+        let target = &self.worker.targets()[self.candidate(row)];
+        self.matcher
+            .fuzzy_match_details(target, &self.query)
+            .map(|result| result.positions)
+            .unwrap_or_default()
+    }
+
+    fn move_selection(&mut self, offset: isize) {
+        let last = self.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(offset).min(last);
+    }
+
+    fn draw(&mut self, out: &mut impl Write) -> io::Result<()> {
+        let (width, height) = terminal::size()?;
+        let (width, height) = (width as usize, height as usize);
+        out.queue(terminal::Clear(terminal::ClearType::All))?;
+
+        let status = format!(
+            "{}/{}{}",
+            self.len(),
+            self.worker.targets().len(),
+            if self.complete { "" } else { " …" }
+        );
+        out.queue(cursor::MoveTo(0, 0))?
+            .queue(Print(format!("> {}", self.query)))?;
+        out.queue(cursor::MoveTo(0, 1))?
+            .queue(SetForegroundColor(Color::DarkGrey))?
+            .queue(Print(status))?
+            .queue(SetForegroundColor(Color::Reset))?;
+
+        let rows = self.len().min(height.saturating_sub(2));
+        let first = self.selected.saturating_sub(rows.saturating_sub(1));
+        for row in first..first + rows {
+            let highlights = self.highlights(row);
+            let target = &self.worker.targets()[self.candidate(row)];
+            out.queue(cursor::MoveTo(0, (row - first + 2) as u16))?;
+            if row == self.selected {
+                out.queue(SetAttribute(Attribute::Reverse))?;
+            }
+            let mut highlights = highlights.iter().peekable();
+            for (idx, c) in target.chars().take(width).enumerate() {
+                if highlights.next_if_eq(&&idx).is_some() {
+                    out.queue(SetForegroundColor(Color::Green))?
+                        .queue(Print(c))?
+                        .queue(SetForegroundColor(Color::Reset))?;
+                } else {
+                    out.queue(Print(c))?;
+                }
+            }
+            out.queue(SetAttribute(Attribute::Reset))?;
+        }
+
+        let cursor_column = 2 + self.query.chars().count();
+        out.queue(cursor::MoveTo(cursor_column.min(width) as u16, 0))?;
+        out.flush()
+    }
+}
+
+/// Puts the terminal into raw mode on an alternate screen until dropped.
+struct Terminal;
+
+impl Terminal {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        crossterm::execute!(io::stderr(), terminal::EnterAlternateScreen)?;
+        Ok(Terminal)
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = crossterm::execute!(io::stderr(), terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn list_files(dir: &Path, prefix: &str, files: &mut Vec<String>) {
+    let Ok(entries) = dir.read_dir() else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') {
+            continue;
+        }
+        let path = format!("{prefix}{name}");
+        if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+            list_files(&entry.path(), &format!("{path}/"), files);
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+fn main() -> io::Result<()> {
+    let stdin = io::stdin();
+    let candidates = if stdin.is_terminal() {
+        let mut files = Vec::new();
+        list_files(Path::new("."), "", &mut files);
+        files
+    } else {
+        stdin.lock().lines().collect::<io::Result<_>>()?
+    };
+
+    let mut picker = Picker::new(candidates);
+    let selection = {
+        // The list is drawn on standard error so that the selection can be piped
+        let _terminal = Terminal::enter()?;
+        let mut out = io::BufWriter::new(io::stderr());
+        picker.draw(&mut out)?;
+        loop {
+            let mut changed = false;
+            if event::poll(Duration::from_millis(16))? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind != KeyEventKind::Release {
+                        match picker.handle_key(key) {
+                            Action::Continue => changed = true,
+                            Action::Accept => break picker.selection().map(String::from),
+                            Action::Abort => break None,
+                        }
+                    }
+                }
+            }
+            changed |= picker.poll();
+            if changed {
+                picker.draw(&mut out)?;
+            }
+        }
+    };
+
+    if let Some(selection) = selection {
+        println!("{selection}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Action, Picker};
+    use code_fuzzy_match::{FuzzyMatcher, TieBreak};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    fn press(picker: &mut Picker, code: KeyCode) -> Action {
+        picker.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_picker() {
+        let candidates: Vec<String> = (0..20_000)
+            .map(|idx| format!("src/module_{idx}/lib.rs"))
+            .collect();
+        let mut picker = Picker::new(candidates.clone());
+        assert_eq!(picker.len(), candidates.len());
+        assert_eq!(picker.selection(), Some("src/module_0/lib.rs"));
+
+        // Typing supersedes the queries for the earlier characters
+        for c in "m12lx".chars() {
+            press(&mut picker, KeyCode::Char(c));
+        }
+        press(&mut picker, KeyCode::Backspace);
+        while !picker.complete {
+            picker.poll();
+        }
+        let expected: Vec<usize> = FuzzyMatcher::new()
+            .rank(
+                candidates.iter().map(String::as_str),
+                "m12l",
+                TieBreak::DEFAULT,
+            )
+            .iter()
+            .map(|ranked| ranked.index)
+            .collect();
+        let matches: Vec<usize> = picker.matches.iter().map(|result| result.item).collect();
+        assert_eq!(matches, expected);
+
+        // Matched characters are highlighted
+        let target = &candidates[expected[0]];
+        let highlighted: String = picker
+            .highlights(0)
+            .iter()
+            .map(|&idx| target.chars().nth(idx).unwrap())
+            .collect();
+        assert!(highlighted.eq_ignore_ascii_case("m12l"));
+
+        press(&mut picker, KeyCode::Down);
+        press(&mut picker, KeyCode::Down);
+        press(&mut picker, KeyCode::Up);
+        assert_eq!(press(&mut picker, KeyCode::Enter), Action::Accept);
+        assert_eq!(picker.selection(), Some(candidates[expected[1]].as_str()));
+
+        // Clearing the query lists every candidate again
+        picker.handle_key(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL));
+        assert_eq!(picker.len(), candidates.len());
+        assert_eq!(press(&mut picker, KeyCode::Esc), Action::Abort);
+    }
+}
@@ -0,0 +1,58 @@
+/* C API for code-fuzzy-match. Build the library with:
+ *
+ *     cargo rustc --release --features ffi --crate-type cdylib
+ *
+ * Strings are UTF-8 bytes with a length in bytes, and do not need to be null terminated.
+ * Strings that are not valid UTF-8 never match. A matcher must not be used from more than
+ * one thread at a time, and no pointer passed to the library is kept after a call returns.
+ */
+
+#ifndef CODE_FUZZY_MATCH_H
+#define CODE_FUZZY_MATCH_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Holds the options and scratch memory of a fuzzy matcher. */
+typedef struct FuzzyMatcher FuzzyMatcher;
+
+/* Creates a matcher with the default options. Destroy it with cfm_matcher_free. */
+FuzzyMatcher *cfm_matcher_new(void);
+
+/* Destroys a matcher. Passing NULL does nothing. */
+void cfm_matcher_free(FuzzyMatcher *matcher);
+
+/* Fuzzy match a string against a query string. Returns true and writes the score to
+ * `score` if the query matches the target, or returns false if it does not. `score` may
+ * be NULL. */
+bool cfm_fuzzy_match(FuzzyMatcher *matcher, const uint8_t *target, size_t target_len,
+                     const uint8_t *query, size_t query_len, size_t *score);
+
+/* Same as cfm_fuzzy_match, but also writes up to `positions_capacity` character indices
+ * of the matched characters in the target to `positions`, in ascending order. The total
+ * number of matched characters is written to `positions_len`, which may be larger than
+ * the capacity. A buffer with room for one index per character of the query is always
+ * enough. `score` and `positions_len` may be NULL. */
+bool cfm_fuzzy_match_positions(FuzzyMatcher *matcher, const uint8_t *target, size_t target_len,
+                               const uint8_t *query, size_t query_len, size_t *score,
+                               size_t *positions, size_t positions_capacity,
+                               size_t *positions_len);
+
+/* Same as cfm_fuzzy_match_positions, but the target is `target_len` UTF-16 code units, and
+ * the offset in UTF-16 code units of each matched character is written to `positions`.
+ * Unpaired surrogates in the target are matched as U+FFFD replacement characters. */
+bool cfm_fuzzy_match_utf16(FuzzyMatcher *matcher, const uint16_t *target, size_t target_len,
+                           const uint8_t *query, size_t query_len, size_t *score,
+                           size_t *positions, size_t positions_capacity,
+                           size_t *positions_len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,212 @@
+//! Candidates known by several names, such as a localized title and an English title.
+
+use crate::{Index, MatchOptions};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+/// Best matching alias of a candidate, as returned by [`AliasSet::match_all`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AliasMatch {
+    /// Identifier of the candidate.
+    pub id: usize,
+    /// Index of the alias that matched best among the aliases of the candidate, in the
+    /// order they were added.
+    pub alias: usize,
+    /// Score of the alias that matched best.
+    pub score: usize,
+}
+
+/// Set of candidates that each have several aliases, such as a command with a localized
+/// title, an English title and the name of its keybinding. Candidates are identified by
+/// identifiers chosen by the caller, and a query is matched against every alias of every
+/// candidate, keeping the best scoring alias of each candidate. Aliases are preprocessed as
+/// they are added, as in an [`Index`].
+///
+/// # Examples
+///
+/// ```
+/// use code_fuzzy_match::AliasSet;
+///
+/// let mut set = AliasSet::new();
+/// set.add(7, "Datei öffnen");
+/// set.add(7, "Open File");
+/// set.add(7, "workbench.action.files.openFile");
+/// set.add(8, "Speichern");
+/// set.add(8, "Save");
+///
+/// let matches = set.match_all("open");
+/// assert_eq!(matches.len(), 1);
+/// assert_eq!(matches[0].id, 7);
+/// assert_eq!(set.alias(7, matches[0].alias), Some("Open File"));
+/// ```
+pub struct AliasSet {
+    index: Index,
+    /// Candidate identifier and alias number of each entry of the index.
+    owners: Vec<(usize, usize)>,
+    /// Entries of the index holding the aliases of each candidate, in the order they were
+    /// added.
+    candidates: BTreeMap<usize, Vec<usize>>,
+}
+
+impl AliasSet {
+    /// Creates an empty alias set.
+    pub fn new() -> Self {
+        Self::with_options(MatchOptions::default())
+    }
+
+    /// Creates an empty alias set that matches using the given options. The `truncation`
+    /// and `max_target_len` options are ignored, as for an [`Index`].
+    pub fn with_options(options: MatchOptions) -> Self {
+        AliasSet {
+            index: Index::with_options(options),
+            owners: Vec::new(),
+            candidates: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the options used by this alias set.
+    pub fn options(&self) -> &MatchOptions {
+        self.index.options()
+    }
+
+    /// Changes the options used by this alias set.
+    pub fn set_options(&mut self, options: MatchOptions) {
+        self.index.set_options(options);
+    }
+
+    /// Number of candidates in the set.
+    pub fn len(&self) -> usize {
+        self.candidates.len()
+    }
+
+    /// Returns true if the set has no candidates.
+    pub fn is_empty(&self) -> bool {
+        self.candidates.is_empty()
+    }
+
+    /// Adds an alias to the candidate with identifier `id`, adding the candidate if it is
+    /// not in the set. Returns the index of the alias among the aliases of the candidate.
+    pub fn add(&mut self, id: usize, alias: &str) -> usize {
+        let entry = self.index.add(alias);
+        let entries = self.candidates.entry(id).or_default();
+        entries.push(entry);
+        let alias = entries.len() - 1;
+        self.owners.push((id, alias));
+        alias
+    }
+
+    /// Returns the alias of a candidate with the given index, in the order the aliases were
+    /// added.
+    pub fn alias(&self, id: usize, alias: usize) -> Option<&str> {
+        let entry = *self.candidates.get(&id)?.get(alias)?;
+        self.index.get(entry)
+    }
+
+    /// Returns the aliases of a candidate, in the order they were added. Returns an empty
+    /// iterator if the candidate is not in the set.
+    pub fn aliases(&self, id: usize) -> impl Iterator<Item = &str> {
+        self.candidates
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| self.index.get(*entry))
+    }
+
+    /// Removes a candidate and all of its aliases from the set. Returns false if the
+    /// candidate is not in the set.
+    pub fn remove(&mut self, id: usize) -> bool {
+        let Some(entries) = self.candidates.remove(&id) else {
+            return false;
+        };
+        for entry in entries {
+            self.index.remove(entry);
+        }
+        true
+    }
+
+    /// Fuzzy match every alias of every candidate against a query string, returning each
+    /// candidate with at least one matching alias along with its best scoring alias, from
+    /// the best match to the worst. Candidates with the same score are ordered by
+    /// identifier, and aliases of a candidate with the same score by the order they were
+    /// added.
+    pub fn match_all(&mut self, query: &str) -> Vec<AliasMatch> {
+        let mut results: Vec<AliasMatch> = Vec::new();
+        let mut seen = BTreeMap::new();
+        for result in self.index.query(query, self.owners.len()) {
+            let (id, alias) = self.owners[result.item];
+            // Results are from best to worst, so the first alias found is the best one
+            seen.entry(id).or_insert_with(|| {
+                results.push(AliasMatch {
+                    id,
+                    alias,
+                    score: result.score,
+                });
+            });
+        }
+        results.sort_by(|a, b| b.score.cmp(&a.score).then(a.id.cmp(&b.id)));
+        results
+    }
+}
+
+impl Default for AliasSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{fuzzy_match, AliasMatch, AliasSet};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_alias_set() {
+        let mut set = AliasSet::new();
+        assert_eq!(set.add(2, "Befehlspalette anzeigen"), 0);
+        assert_eq!(set.add(2, "Show Command Palette"), 1);
+        assert_eq!(set.add(1, "Toggle Terminal"), 0);
+        assert_eq!(set.add(1, "Terminal umschalten"), 1);
+        assert_eq!(set.add(3, "Show All Commands"), 0);
+        assert_eq!(set.len(), 3);
+
+        // Every alias is scored, and the best one is reported
+        let matches = set.match_all("show com");
+        let ids: Vec<usize> = matches.iter().map(|result| result.id).collect();
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&2) && ids.contains(&3));
+        let palette = matches.iter().find(|result| result.id == 2).unwrap();
+        assert_eq!(
+            *palette,
+            AliasMatch {
+                id: 2,
+                alias: 1,
+                score: fuzzy_match("Show Command Palette", "show com").unwrap(),
+            }
+        );
+
+        // The start of the target scores higher, so the second alias is the best one
+        let matches = set.match_all("terminal");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].alias, 1);
+        assert_eq!(set.alias(1, 1), Some("Terminal umschalten"));
+
+        // Aliases with the same score are reported in the order they were added
+        assert_eq!(set.add(4, "Run Build"), 0);
+        assert_eq!(set.add(4, "Run Test"), 1);
+        let matches = set.match_all("run");
+        assert_eq!((matches[0].id, matches[0].alias), (4, 0));
+        set.remove(4);
+        assert_eq!(
+            set.aliases(2).collect::<Vec<_>>(),
+            ["Befehlspalette anzeigen", "Show Command Palette"]
+        );
+
+        // Removing a candidate removes all of its aliases
+        assert!(set.remove(2));
+        assert!(!set.remove(2));
+        assert!(set.match_all("palette").is_empty());
+        assert_eq!(set.aliases(2).count(), 0);
+        assert_eq!(set.len(), 2);
+    }
+}
@@ -0,0 +1,179 @@
+//! Finding several distinct alignments of a query within a target.
+
+use crate::matrix::PositionMask;
+use crate::{FuzzyMatcher, Match, ScoreModel};
+use alloc::vec::Vec;
+
+/// An alignment waiting to be returned, along with the restrictions of the part of the
+/// search space where it is the best alignment. Positions are indices of the loaded target
+/// units.
+struct Alternative {
+    result: Match,
+    units: Vec<usize>,
+    required: Vec<usize>,
+    excluded: Vec<usize>,
+}
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Fuzzy match a string against a query string, returning up to `count` alignments of
+    /// the query within the target that match different sets of positions, from the best
+    /// score to the worst. The first alignment is the one returned by
+    /// [`fuzzy_match_details`](Self::fuzzy_match_details). Returns an empty list if the
+    /// query does not match the target string.
+    ///
+    /// Each alignment after the first is the best one that leaves out a position matched
+    /// by one of the alignments before it, so the time taken grows with both `count` and
+    /// the length of the query. Alignments after the first are found without typos or
+    /// transpositions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut matcher = code_fuzzy_match::FuzzyMatcher::new();
+    /// let alignments = matcher.fuzzy_match_alternatives("src/test/test_main.rs", "test", 3);
+    /// assert_eq!(alignments.len(), 3);
+    /// assert_eq!(alignments[0].positions, vec![9, 10, 11, 12]);
+    /// assert_eq!(alignments[1].positions, vec![4, 5, 6, 7]);
+    /// assert!(alignments[1].score <= alignments[0].score);
+    /// ```
+    pub fn fuzzy_match_alternatives(
+        &mut self,
+        target: &str,
+        query: &str,
+        count: usize,
+    ) -> Vec<Match> {
+        let mut results = Vec::new();
+        if count == 0 {
+            return results;
+        }
+        self.load(target, query);
+        let Some(best) = self.loaded_details(false) else {
+            return results;
+        };
+        let mut pending = Vec::new();
+        pending.push(Alternative {
+            result: best,
+            units: self.matched_units(),
+            required: Vec::new(),
+            excluded: Vec::new(),
+        });
+
+        while results.len() < count {
+            // Take the best pending alignment, preferring the one found first when scores
+            // are equal
+            let Some(idx) = (0..pending.len())
+                .rev()
+                .max_by_key(|idx| pending[*idx].result.score)
+            else {
+                break;
+            };
+            let alternative = pending.remove(idx);
+
+            // Split the rest of its search space so that the alignments in each part match
+            // the positions of this alignment before some index, but not the position at
+            // that index. Every other alignment falls in exactly one of the parts.
+            for (idx, unit) in alternative.units.iter().enumerate() {
+                let mut required = alternative.required.clone();
+                for unit in &alternative.units[..idx] {
+                    if !required.contains(unit) {
+                        required.push(*unit);
+                    }
+                }
+                let mut excluded = alternative.excluded.clone();
+                excluded.push(*unit);
+                self.fill_alternative_mask(&required, &excluded);
+                if let Some(result) = self.loaded_details(true) {
+                    pending.push(Alternative {
+                        result,
+                        units: self.matched_units(),
+                        required,
+                        excluded,
+                    });
+                }
+            }
+
+            // Expanded target characters can give different alignments the same positions
+            if !results
+                .iter()
+                .any(|result: &Match| result.positions == alternative.result.positions)
+            {
+                results.push(alternative.result);
+            }
+        }
+        results
+    }
+
+    /// Returns the loaded target units of the recorded positions, in ascending order.
+    fn matched_units(&self) -> Vec<usize> {
+        let mut units: Vec<usize> = self
+            .matrix
+            .positions
+            .iter()
+            .map(|position| position - self.window_offset)
+            .collect();
+        units.sort_unstable();
+        units
+    }
+
+    /// Fills the position mask so that the loaded target units in `required` must be
+    /// matched, and those in `excluded` can't be.
+    fn fill_alternative_mask(&mut self, required: &[usize], excluded: &[usize]) {
+        let mask = &mut self.mask;
+        mask.flags.clear();
+        mask.flags.resize(
+            self.target_chars.len(),
+            PositionMask::START_ALLOWED | PositionMask::END_ALLOWED,
+        );
+        for unit in required {
+            mask.flags[*unit] |= PositionMask::REQUIRED;
+        }
+        for unit in excluded {
+            mask.flags[*unit] |= PositionMask::EXCLUDED;
+        }
+        mask.first_required = required.iter().min().copied();
+        mask.last_required = required.iter().max().copied();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FuzzyMatcher, MatchOptions};
+    use alloc::vec;
+
+    #[test]
+    fn test_match_alternatives() {
+        let mut matcher = FuzzyMatcher::new();
+        let target = "src/test/test_main.rs";
+        let alignments = matcher.fuzzy_match_alternatives(target, "test", 10);
+        assert_eq!(
+            alignments.first(),
+            matcher.fuzzy_match_details(target, "test").as_ref()
+        );
+        assert_eq!(alignments[1].positions, vec![4, 5, 6, 7]);
+        assert_eq!(alignments.len(), 7);
+        for (idx, alignment) in alignments.iter().enumerate() {
+            assert!(idx == 0 || alignment.score <= alignments[idx - 1].score);
+            assert!(alignments[..idx]
+                .iter()
+                .all(|other| other.positions != alignment.positions));
+        }
+
+        // A target with a single alignment has no alternatives
+        assert_eq!(matcher.fuzzy_match_alternatives("abc", "abc", 5).len(), 1);
+        assert!(matcher.fuzzy_match_alternatives("abc", "x", 5).is_empty());
+        assert!(matcher
+            .fuzzy_match_alternatives(target, "test", 0)
+            .is_empty());
+
+        // Every alignment is returned once, and gaps are penalized as usual
+        let mut matcher = FuzzyMatcher::with_options(MatchOptions {
+            gap_penalty: 1,
+            ..MatchOptions::default()
+        });
+        let alignments = matcher.fuzzy_match_alternatives("a_b a_b", "ab", 4);
+        assert_eq!(alignments.len(), 3);
+        assert_eq!(alignments[0].positions, vec![0, 2]);
+        assert_eq!(alignments[1].positions, vec![0, 6]);
+        assert!(alignments[1].score < alignments[0].score);
+    }
+}
@@ -0,0 +1,162 @@
+//! Matching of many targets at once, used by the batch APIs.
+
+#[cfg(feature = "batch-lanes")]
+mod lanes;
+
+#[cfg(feature = "batch-lanes")]
+pub(crate) use lanes::BatchBuffers;
+
+use crate::{FuzzyMatcher, Score, ScoreModel};
+use alloc::vec::Vec;
+
+/// Number of items matched between checks for cancellation, which are also grouped by
+/// length before scoring with the `batch-lanes` feature.
+pub(crate) const CHUNK_LEN: usize = 64;
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Fuzzy match a string taken from each item against a query string, returning the items
+    /// that match along with their scores, in their original order. With the `batch-lanes`
+    /// feature, targets are scored several at a time when the options allow it.
+    pub(crate) fn match_batch<T, S: Score>(
+        &mut self,
+        items: impl IntoIterator<Item = T>,
+        key: impl Fn(&T) -> &str,
+        query: &str,
+    ) -> Vec<(T, S)> {
+        self.match_batch_cancellable(items, key, query, || false)
+            .unwrap_or_default()
+    }
+
+    /// Same as [`match_batch`](Self::match_batch), but calls `should_cancel` before each
+    /// chunk of items and returns `None` as soon as it returns true.
+    pub(crate) fn match_batch_cancellable<T, S: Score>(
+        &mut self,
+        items: impl IntoIterator<Item = T>,
+        key: impl Fn(&T) -> &str,
+        query: &str,
+        mut should_cancel: impl FnMut() -> bool,
+    ) -> Option<Vec<(T, S)>> {
+        #[cfg(feature = "tracing")]
+        let trace = crate::trace::BatchTrace::start("match_batch", query);
+        #[cfg(feature = "tracing")]
+        let mut candidates = 0;
+        let query_mask = self.query_mask(query);
+        let mut matches = Vec::new();
+        let mut items = items.into_iter();
+        let mut chunk = Vec::with_capacity(CHUNK_LEN);
+        loop {
+            if should_cancel() {
+                #[cfg(feature = "tracing")]
+                trace.cancel(candidates);
+                return None;
+            }
+            chunk.extend(items.by_ref().take(CHUNK_LEN));
+            if chunk.is_empty() {
+                #[cfg(feature = "tracing")]
+                trace.finish(candidates, matches.iter().map(|(_, score)| *score));
+                return Some(matches);
+            }
+            #[cfg(feature = "tracing")]
+            {
+                candidates += chunk.len();
+            }
+
+            #[cfg(feature = "batch-lanes")]
+            if self.batch_supported() && !self.options.query_is_empty(query) {
+                self.score_chunk(&chunk, &key, query, &query_mask);
+                for (item, score) in chunk.drain(..).zip(self.batch.scores.iter()) {
+                    if let Some(score) = score {
+                        matches.push((item, score.into_score()));
+                    }
+                }
+                continue;
+            }
+
+            for item in chunk.drain(..) {
+                if let Some(score) = self.fuzzy_match_prefiltered(key(&item), query, &query_mask) {
+                    matches.push((item, score));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FuzzyMatcher, MatchOptions, ScoringProfile, Truncation};
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_match_batch() {
+        let mut targets: Vec<String> = [
+            "",
+            "a",
+            "src/lib.rs",
+            "src/batch.rs",
+            "The quick brown fox jumps over the lazy dog.",
+            "FuzzyMatcher::match_batch",
+            "Grüße aus Köln",
+            "lib",
+            "LIB",
+        ]
+        .iter()
+        .map(|target| String::from(*target))
+        .collect();
+        for idx in 0..100 {
+            targets.push(format!("src/module_{idx}/file{}.rs", "_x".repeat(idx % 7)));
+        }
+
+        let options = [
+            MatchOptions::default(),
+            MatchOptions {
+                gap_penalty: 2,
+                leading_gap_penalty: 1,
+                max_leading_gap_penalty: 10,
+                whole_word_bonus: 5,
+                contiguous_query_bonus: 7,
+                substring_bonus: 50,
+                length_normalization: 16,
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                require_word_start: true,
+                pin_exact_matches: true,
+                profile: ScoringProfile::FilePath,
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                truncation: Some(Truncation::Last(16)),
+                pin_exact_matches: true,
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                gap_penalty: usize::MAX,
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                leading_gap_penalty: usize::MAX,
+                ..MatchOptions::default()
+            },
+        ];
+        for options in options {
+            let mut matcher = FuzzyMatcher::with_options(options);
+            for query in [
+                "", "lib", "LIB", "a", "srs", "sm1f", "fox dog", "köln", "xyz",
+            ] {
+                let expected: Vec<(usize, usize)> = targets
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(idx, target)| Some((idx, matcher.fuzzy_match(target, query)?)))
+                    .collect();
+                let batch: Vec<(usize, usize)> = matcher
+                    .match_batch(targets.iter().enumerate(), |(_, target)| target, query)
+                    .into_iter()
+                    .map(|((idx, _), score)| (idx, score))
+                    .collect();
+                assert_eq!(batch, expected, "{query}");
+            }
+        }
+    }
+}
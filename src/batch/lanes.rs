@@ -0,0 +1,311 @@
+//! Scoring of several targets at once, used by the batch APIs with the `batch-lanes`
+//! feature.
+//!
+//! Targets of similar length are grouped into lanes, and the scoring matrix of every lane is
+//! computed in the same pass. The state of the matrix is stored as a struct of arrays, with
+//! the cells of every lane for a target index next to each other, so the loop over lanes
+//! has no dependencies between iterations and the loop overhead is shared by all lanes.
+//!
+//! As in the single target matrix, targets that the query can't be aligned with are dropped
+//! before scoring, and each row of a lane only covers the cells between the earliest and
+//! latest possible matches of its query unit. Cells outside of these bounds can never lead
+//! to a match, so the scores are the same as matching each target on its own.
+
+use crate::matrix::{
+    contains_query, is_word_start, normalize_length, score_cell, CharClass, MatrixInput, MatrixRow,
+    UNREACHABLE,
+};
+use crate::score::RawScore;
+use crate::{
+    char_match_kind, CharMask, FuzzyMatcher, MatchOptions, QueryMask, ScoreModel, ScoringProfile,
+};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// Number of targets scored in the same pass.
+const LANES: usize = 8;
+
+/// A target loaded into the batch buffers.
+struct BatchTarget {
+    /// Index of the target in the chunk.
+    idx: usize,
+    /// Range of the target in the character and class buffers.
+    range: Range<usize>,
+    /// Index in the row bounds buffer of the bounds of the first query unit.
+    rows: usize,
+    truncated: bool,
+}
+
+/// Scratch memory for scoring batches of targets.
+#[derive(Default)]
+pub(crate) struct BatchBuffers {
+    chars: Vec<char>,
+    classes: Vec<CharClass>,
+    targets: Vec<BatchTarget>,
+    /// Target indices of the cells of each row that can lead to a match, for each target
+    /// and query unit.
+    rows: Vec<Range<usize>>,
+    pub(super) scores: Vec<Option<RawScore>>,
+    lane_chars: Vec<char>,
+    prev_score: Vec<isize>,
+    score: Vec<isize>,
+    prev_seq_match_counts: Vec<usize>,
+    seq_match_counts: Vec<usize>,
+}
+
+impl BatchBuffers {
+    /// Scores each loaded target, placing the results in `scores`.
+    fn score_targets<M: ScoreModel>(
+        &mut self,
+        query_chars: &[char],
+        options: &MatchOptions,
+        model: &M,
+    ) {
+        // Targets of similar length share lanes, so that little work is spent on the
+        // padding after shorter targets.
+        let mut targets = core::mem::take(&mut self.targets);
+        self.rows.clear();
+        targets.retain_mut(|target| self.bound_rows(target, query_chars, options));
+        targets.sort_by_key(|target| target.range.len());
+        for group in targets.chunks(LANES) {
+            self.score_group(group, query_chars, options, model);
+        }
+        self.targets = targets;
+    }
+
+    /// Finds the bounds of each row of the matrix of a target, from the earliest possible
+    /// match of each query unit to the latest possible match of the next one, as the single
+    /// target matrix does. Returns false if the query can't be aligned with the target.
+    fn bound_rows(
+        &mut self,
+        target: &mut BatchTarget,
+        query_chars: &[char],
+        options: &MatchOptions,
+    ) -> bool {
+        let chars = &self.chars[target.range.clone()];
+        let classes = &self.classes[target.range.clone()];
+        let unit_match = |query_idx: usize, i: usize| {
+            (query_idx != 0 || !options.require_word_start || is_word_start(classes, i))
+                && char_match_kind(query_chars[query_idx], chars[i], options).is_some()
+        };
+
+        target.rows = self.rows.len();
+        let mut i = 0;
+        for query_idx in 0..query_chars.len() {
+            while i < chars.len() && !unit_match(query_idx, i) {
+                i += 1;
+            }
+            if i == chars.len() {
+                self.rows.truncate(target.rows);
+                return false;
+            }
+            self.rows.push(i..chars.len());
+            i += 1;
+        }
+
+        // The matches found above leave room for a latest possible match of each query unit
+        let mut end = chars.len();
+        for query_idx in (1..query_chars.len()).rev() {
+            end -= 1;
+            while !unit_match(query_idx, end) {
+                end -= 1;
+            }
+            self.rows[target.rows + query_idx - 1].end = end;
+        }
+        true
+    }
+
+    /// Scores up to [`LANES`] targets in a single pass over the matrix.
+    fn score_group<M: ScoreModel>(
+        &mut self,
+        group: &[BatchTarget],
+        query_chars: &[char],
+        options: &MatchOptions,
+        model: &M,
+    ) {
+        let query_len = query_chars.len();
+        let width = group.iter().map(|target| target.range.len()).max();
+        let width = match width {
+            Some(width) if width > 0 && query_len > 0 => width,
+            _ => return,
+        };
+
+        let classes = &self.classes;
+        let inputs: [Option<MatrixInput<M>>; LANES] = core::array::from_fn(|lane| {
+            group.get(lane).map(|target| {
+                MatrixInput::with_model(&classes[target.range.clone()], query_len, options, model)
+            })
+        });
+        let lens: [usize; LANES] =
+            core::array::from_fn(|lane| group.get(lane).map_or(0, |target| target.range.len()));
+
+        self.lane_chars.clear();
+        self.lane_chars.resize(width * LANES, '\0');
+        for (lane, target) in group.iter().enumerate() {
+            for (i, c) in self.chars[target.range.clone()].iter().enumerate() {
+                self.lane_chars[i * LANES + lane] = *c;
+            }
+        }
+        for buffer in [&mut self.prev_score, &mut self.score] {
+            buffer.clear();
+            buffer.resize(width * LANES, UNREACHABLE);
+        }
+        for buffer in [&mut self.prev_seq_match_counts, &mut self.seq_match_counts] {
+            buffer.clear();
+            buffer.resize(width * LANES, 0);
+        }
+
+        let mut prev_rows: [Range<usize>; LANES] = core::array::from_fn(|_| 0..0);
+        for (query_idx, query_char) in query_chars.iter().enumerate() {
+            let matrix_row = MatrixRow {
+                query_idx,
+                first: query_idx == 0,
+                last: query_idx + 1 == query_len,
+                keep_later: false,
+            };
+            let rows: [Range<usize>; LANES] = core::array::from_fn(|lane| {
+                group
+                    .get(lane)
+                    .map_or(0..0, |target| self.rows[target.rows + query_idx].clone())
+            });
+            let start = group.iter().zip(&rows).map(|(_, row)| row.start).min();
+            let end = group.iter().zip(&rows).map(|(_, row)| row.end).max();
+            for i in start.unwrap_or(0)..end.unwrap_or(0) {
+                for lane in 0..LANES {
+                    let cell = i * LANES + lane;
+                    let row = &rows[lane];
+                    let input = match &inputs[lane] {
+                        Some(input) if row.contains(&i) => input,
+                        _ => {
+                            self.score[cell] = UNREACHABLE;
+                            self.seq_match_counts[cell] = 0;
+                            continue;
+                        }
+                    };
+
+                    // Follows the rules of the single target matrix. Cells outside of the
+                    // bounds of their row are unreachable.
+                    let carried = if i == row.start {
+                        UNREACHABLE
+                    } else {
+                        self.score[cell - LANES]
+                    };
+                    let diagonal = || {
+                        if i == 0 || !prev_rows[lane].contains(&(i - 1)) {
+                            (UNREACHABLE, 0)
+                        } else {
+                            (
+                                self.prev_score[cell - LANES],
+                                self.prev_seq_match_counts[cell - LANES],
+                            )
+                        }
+                    };
+                    let unit_match =
+                        || char_match_kind(*query_char, self.lane_chars[cell], options);
+                    let cell_score =
+                        score_cell(input, matrix_row, i, carried, diagonal, unit_match);
+                    self.score[cell] = cell_score.score;
+                    self.seq_match_counts[cell] = cell_score.seq_match_count;
+                }
+            }
+            core::mem::swap(&mut self.prev_score, &mut self.score);
+            core::mem::swap(&mut self.prev_seq_match_counts, &mut self.seq_match_counts);
+            prev_rows = rows;
+        }
+
+        for (lane, target) in group.iter().enumerate() {
+            if lens[lane] == 0 {
+                continue;
+            }
+            let score = self.prev_score[(lens[lane] - 1) * LANES + lane];
+            if score == UNREACHABLE {
+                continue;
+            }
+            let score = normalize_length(options, score, lens[lane], query_len);
+            let target_chars = &self.chars[target.range.clone()];
+            let exact = options.pin_exact_matches
+                && !target.truncated
+                && target_chars.len() == query_len
+                && query_chars
+                    .iter()
+                    .zip(target_chars.iter())
+                    .all(|(q, t)| char_match_kind(*q, *t, options).is_some());
+            let substring = options.substring_bonus != 0
+                && contains_query(target_chars.len(), query_len, |query_idx, i| {
+                    char_match_kind(query_chars[query_idx], target_chars[i], options).is_some()
+                });
+            self.scores[target.idx] = Some(if exact {
+                RawScore::Exact
+            } else {
+                RawScore::Sum {
+                    sum: score,
+                    bonus: if substring {
+                        options.substring_bonus
+                    } else {
+                        0
+                    },
+                }
+            });
+        }
+    }
+}
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Returns true if the options of this matcher can be used with the batch matrix.
+    pub(super) fn batch_supported(&self) -> bool {
+        let options = &self.options;
+        !options.bidirectional
+            && !options.right_to_left
+            && options.max_typos == 0
+            && !options.transpositions
+            && !options.position_index
+            && !options.camel_humps
+            && options.max_gap.is_none()
+            && options.synonyms.is_empty()
+            && options.profile != ScoringProfile::VsCodeCompat
+    }
+
+    /// Scores a chunk of items, placing the score of each item in the batch buffers.
+    pub(super) fn score_chunk<T>(
+        &mut self,
+        chunk: &[T],
+        key: &impl Fn(&T) -> &str,
+        query: &str,
+        query_mask: &QueryMask,
+    ) {
+        self.batch.scores.clear();
+        self.batch.scores.resize(chunk.len(), None);
+        self.batch.chars.clear();
+        self.batch.classes.clear();
+        self.batch.targets.clear();
+        for (idx, item) in chunk.iter().enumerate() {
+            let target = key(item);
+            if !self.prefilter(query_mask, &CharMask::new(target)) {
+                continue;
+            }
+            self.load(target, query);
+            let start = self.batch.chars.len();
+            self.batch.chars.extend_from_slice(&self.target_chars);
+            self.batch.classes.extend_from_slice(&self.target_classes);
+            self.batch.targets.push(BatchTarget {
+                idx,
+                range: start..self.batch.chars.len(),
+                rows: 0,
+                truncated: self.truncated,
+            });
+        }
+        self.load_query(query);
+        #[cfg(feature = "stats")]
+        if !self.query_chars.is_empty() {
+            self.stats.matrix_runs += self.batch.targets.len();
+        }
+        self.batch
+            .score_targets(&self.query_chars, &self.options, &self.model);
+
+        // Every cell within the bounds of the rows of the batch matrix is computed
+        #[cfg(feature = "stats")]
+        if !self.query_chars.is_empty() {
+            self.stats.cells += self.batch.rows.iter().map(|row| row.len()).sum::<usize>();
+        }
+    }
+}
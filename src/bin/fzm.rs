@@ -0,0 +1,235 @@
+//! Filters lines read from standard input with a fuzzy query, printing the matching lines
+//! from best to worst, like the `--filter` mode of fzf.
+
+use code_fuzzy_match::{FuzzyMatcher, TieBreak};
+use std::io::{self, BufWriter, Read, Write};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: fzm [OPTIONS] QUERY
+
+Reads candidate lines from standard input and prints the lines that match QUERY, sorted
+from best to worst match.
+
+Options:
+      --print-score     Print the score of each match before the line
+      --top N           Print only the best N matches
+  -z, --null-delimited  Read and write NUL delimited items instead of lines
+      --positions       Print the character index of each matched character after the line
+      --json            Print each match as a line of JSON with its score, index, text and
+                        matched character ranges
+  -h, --help            Print this help
+";
+
+struct Args {
+    query: String,
+    print_score: bool,
+    top: Option<usize>,
+    null_delimited: bool,
+    positions: bool,
+    json: bool,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+        let mut query = None;
+        let mut print_score = false;
+        let mut top = None;
+        let mut null_delimited = false;
+        let mut positions = false;
+        let mut json = false;
+        let mut options_done = false;
+        while let Some(arg) = args.next() {
+            if options_done || !arg.starts_with('-') || arg == "-" {
+                if query.replace(arg).is_some() {
+                    return Err("expected a single query".into());
+                }
+                continue;
+            }
+            let (name, value) = match arg.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (arg.as_str(), None),
+            };
+            match name {
+                _ if value.is_some() && name != "--top" => {
+                    return Err(format!("option does not take a value: {name}"))
+                }
+                "--" => options_done = true,
+                "-h" | "--help" => return Ok(None),
+                "--print-score" => print_score = true,
+                "-z" | "--null-delimited" => null_delimited = true,
+                "--positions" => positions = true,
+                "--json" => json = true,
+                "--top" => {
+                    let value = value
+                        .or_else(|| args.next())
+                        .ok_or("--top requires a value")?;
+                    top = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("invalid value for --top: {value}"))?,
+                    );
+                }
+                _ => return Err(format!("unknown option: {arg}")),
+            }
+        }
+        Ok(Some(Args {
+            query: query.ok_or("missing query")?,
+            print_score,
+            top,
+            null_delimited,
+            positions,
+            json,
+        }))
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(error) => {
+            eprint!("fzm: {error}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        // Stop quietly when the reader of the output has gone away, such as `head`
+        Err(error) if error.kind() == io::ErrorKind::BrokenPipe => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("fzm: {error}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Filters standard input, returning whether anything matched.
+fn run(args: &Args) -> io::Result<bool> {
+    let mut input = Vec::new();
+    io::stdin().lock().read_to_end(&mut input)?;
+    let delimiter = if args.null_delimited { b'\0' } else { b'\n' };
+    if input.last() == Some(&delimiter) {
+        input.pop();
+    }
+    let candidates: Vec<String> = if input.is_empty() {
+        Vec::new()
+    } else {
+        input
+            .split(|byte| *byte == delimiter)
+            .map(|item| {
+                let item = if args.null_delimited {
+                    item
+                } else {
+                    item.strip_suffix(b"\r").unwrap_or(item)
+                };
+                String::from_utf8_lossy(item).into_owned()
+            })
+            .collect()
+    };
+
+    let mut matcher = FuzzyMatcher::new();
+    let ranked = matcher.rank(
+        candidates.iter().map(String::as_str),
+        &args.query,
+        TieBreak::DEFAULT,
+    );
+
+    let mut output = BufWriter::new(io::stdout().lock());
+    for ranked in ranked.iter().take(args.top.unwrap_or(usize::MAX)) {
+        if args.json {
+            let details = matcher.fuzzy_match_details(ranked.target, &args.query);
+            let ranges: Vec<String> = details
+                .map(|details| details.ranges())
+                .unwrap_or_default()
+                .iter()
+                .map(|range| format!("[{},{}]", range.start, range.end))
+                .collect();
+            writeln!(
+                output,
+                r#"{{"score":{},"index":{},"text":{},"ranges":[{}]}}"#,
+                ranked.score,
+                ranked.index,
+                json_string(ranked.target),
+                ranges.join(",")
+            )?;
+            continue;
+        }
+        if args.print_score {
+            write!(output, "{}\t", ranked.score)?;
+        }
+        output.write_all(ranked.target.as_bytes())?;
+        if args.positions {
+            let details = matcher.fuzzy_match_details(ranked.target, &args.query);
+            let positions = details.map(|details| details.positions).unwrap_or_default();
+            let positions: Vec<String> = positions.iter().map(usize::to_string).collect();
+            write!(output, "\t{}", positions.join(","))?;
+        }
+        output.write_all(&[delimiter])?;
+    }
+    output.flush()?;
+    Ok(!ranked.is_empty())
+}
+
+/// Quotes and escapes a string for JSON output.
+fn json_string(text: &str) -> String {
+    let mut result = String::with_capacity(text.len() + 2);
+    result.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if c < ' ' => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{json_string, Args};
+
+    fn parse(args: &[&str]) -> Result<Option<Args>, String> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = parse(&["--top", "5", "--print-score", "-z", "lib"])
+            .unwrap()
+            .unwrap();
+        assert_eq!(args.query, "lib");
+        assert_eq!(args.top, Some(5));
+        assert!(args.print_score && args.null_delimited && !args.positions);
+
+        let args = parse(&["--top=2", "--positions", "--", "-q"])
+            .unwrap()
+            .unwrap();
+        assert_eq!(args.query, "-q");
+        assert_eq!(args.top, Some(2));
+        assert!(args.positions);
+
+        assert!(parse(&["--help"]).unwrap().is_none());
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["a", "b"]).is_err());
+        assert!(parse(&["--top", "x", "a"]).is_err());
+        assert!(parse(&["--bogus", "a"]).is_err());
+        assert!(parse(&["--positions=1", "a"]).is_err());
+    }
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("src/lib.rs"), r#""src/lib.rs""#);
+        assert_eq!(json_string("a\"b\\c\td\u{1}é"), r#""a\"b\\c\td\u0001é""#);
+    }
+}
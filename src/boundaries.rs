@@ -0,0 +1,158 @@
+//! Word boundaries of a target, as seen by the matcher when awarding bonuses.
+
+use crate::matrix::{is_word_end, is_word_start, CharClass};
+use crate::{FuzzyMatcher, ScoreModel};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// Classification of each character of a target, as returned by
+/// [`FuzzyMatcher::analyze_boundaries`]. These are the word starts and separators that the
+/// matcher awards bonuses for, so code that abbreviates or highlights targets can use the
+/// same rules as the matcher.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Boundaries {
+    classes: Vec<CharClass>,
+}
+
+impl Boundaries {
+    /// Returns the class of each character of the target.
+    pub fn classes(&self) -> &[CharClass] {
+        &self.classes
+    }
+
+    /// Returns the number of characters in the target.
+    pub fn len(&self) -> usize {
+        self.classes.len()
+    }
+
+    /// Returns true if the target is empty.
+    pub fn is_empty(&self) -> bool {
+        self.classes.is_empty()
+    }
+
+    /// Returns true if the character at `idx` starts a word: it is the start of the target,
+    /// follows a separator, or is a camel case hump or other word start.
+    pub fn is_word_start(&self, idx: usize) -> bool {
+        is_word_start(&self.classes, idx)
+    }
+
+    /// Returns true if the character at `idx` ends a word: it is the end of the target, or
+    /// the next character is a separator or starts a new word.
+    pub fn is_word_end(&self, idx: usize) -> bool {
+        is_word_end(&self.classes, idx)
+    }
+
+    /// Returns true if the character at `idx` is a separator, including path separators.
+    pub fn is_separator(&self, idx: usize) -> bool {
+        matches!(
+            self.classes[idx],
+            CharClass::Separator | CharClass::PathSeparator
+        )
+    }
+
+    /// Returns the character index of each word start, in ascending order.
+    pub fn word_starts(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.classes.len()).filter(|idx| self.is_word_start(*idx))
+    }
+
+    /// Returns the range of character indices of each word, in order. Words are runs of
+    /// characters other than separators, split where a new word starts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let boundaries = code_fuzzy_match::analyze_boundaries("src/fuzzyMatch_v2.rs");
+    /// assert_eq!(boundaries.words(), vec![0..3, 4..9, 9..14, 15..17, 18..20]);
+    /// ```
+    pub fn words(&self) -> Vec<Range<usize>> {
+        let mut words = Vec::new();
+        let mut start = None;
+        for idx in 0..self.classes.len() {
+            if self.is_separator(idx) {
+                continue;
+            }
+            let word_start = *start.get_or_insert(idx);
+            if self.is_word_end(idx) {
+                words.push(word_start..idx + 1);
+                start = None;
+            }
+        }
+        words
+    }
+}
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Classifies each character of a target using the word start and separator rules of
+    /// the matcher's options, such as `path_separators` and `unicode_words`. The whole
+    /// target is classified, even if the options truncate targets when matching.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut matcher = code_fuzzy_match::FuzzyMatcher::new();
+    /// let boundaries = matcher.analyze_boundaries("openFile");
+    /// assert_eq!(boundaries.word_starts().collect::<Vec<_>>(), vec![0, 4]);
+    /// ```
+    pub fn analyze_boundaries(&mut self, target: &str) -> Boundaries {
+        self.classify_window(target);
+        Boundaries {
+            classes: self.target_classes.clone(),
+        }
+    }
+}
+
+/// Classifies each character of a target using the default options, as described in
+/// [`FuzzyMatcher::analyze_boundaries`].
+pub fn analyze_boundaries(target: &str) -> Boundaries {
+    let mut matcher = FuzzyMatcher::new();
+    matcher.analyze_boundaries(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::analyze_boundaries;
+    use crate::{CharClass, FuzzyMatcher, MatchOptions};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_analyze_boundaries() {
+        let boundaries = analyze_boundaries("my_crate::FooBar");
+        assert_eq!(boundaries.len(), 16);
+        assert_eq!(boundaries.classes()[2], CharClass::Separator);
+        assert_eq!(
+            boundaries.word_starts().collect::<Vec<_>>(),
+            vec![0, 3, 9, 10, 13]
+        );
+        assert_eq!(boundaries.words(), vec![0..2, 3..8, 10..13, 13..16]);
+        assert!(boundaries.is_word_end(7));
+        assert!(!boundaries.is_word_end(11));
+        assert!(analyze_boundaries("").is_empty());
+
+        // Word starts agree with the ones counted by matches
+        let mut matcher = FuzzyMatcher::new();
+        let result = matcher
+            .fuzzy_match_details("my_crate::FooBar", "mcfb")
+            .unwrap();
+        assert_eq!(
+            result.word_starts_hit,
+            result
+                .positions
+                .iter()
+                .filter(|idx| boundaries.is_word_start(**idx))
+                .count()
+        );
+
+        // Options that change word starts are applied, but truncation is not
+        let mut matcher = FuzzyMatcher::with_options(MatchOptions {
+            path_separators: vec!["::".into()],
+            digit_word_starts: true,
+            max_target_len: Some(4),
+            ..MatchOptions::default()
+        });
+        let boundaries = matcher.analyze_boundaries("my_crate::sha256");
+        assert!(boundaries.is_separator(9));
+        assert_eq!(boundaries.classes()[9], CharClass::PathSeparator);
+        assert_eq!(boundaries.words(), vec![0..2, 3..8, 10..13, 13..16]);
+    }
+}
@@ -0,0 +1,168 @@
+//! Matching of targets that are byte strings, which may not be valid UTF-8.
+
+#[cfg(feature = "std")]
+use crate::PathMatch;
+use crate::{FuzzyMatcher, ScoreModel};
+use alloc::string::String;
+#[cfg(feature = "std")]
+use std::{ffi::OsStr, path::Path};
+
+/// How byte strings that are not valid UTF-8 are decoded before matching, as used by
+/// [`FuzzyMatcher::fuzzy_match_bytes`]. Valid UTF-8 sequences are always decoded as UTF-8.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ByteDecoding {
+    /// Each invalid sequence is decoded as a single U+FFFD replacement character, in the
+    /// same way as [`String::from_utf8_lossy`].
+    #[default]
+    Lossy,
+    /// Each byte of an invalid sequence is decoded as the Latin-1 character with the same
+    /// value, so that legacy file names can still be matched by the characters they hold.
+    Latin1,
+}
+
+pub(crate) fn decode(bytes: &[u8], decoding: ByteDecoding, out: &mut String) {
+    out.clear();
+    for chunk in bytes.utf8_chunks() {
+        out.push_str(chunk.valid());
+        match decoding {
+            ByteDecoding::Lossy => {
+                if !chunk.invalid().is_empty() {
+                    out.push(char::REPLACEMENT_CHARACTER);
+                }
+            }
+            ByteDecoding::Latin1 => out.extend(chunk.invalid().iter().map(|b| char::from(*b))),
+        }
+    }
+}
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Calls `f` with the target decoded as configured by the `byte_decoding` option. Invalid
+    /// targets are decoded into a buffer that is kept between matches.
+    fn with_decoded<R>(&mut self, target: &[u8], f: impl FnOnce(&mut Self, &str) -> R) -> R {
+        if let Ok(target) = core::str::from_utf8(target) {
+            return f(self, target);
+        }
+        let mut decoded = core::mem::take(&mut self.decoded_target);
+        decode(target, self.options.byte_decoding, &mut decoded);
+        let result = f(self, &decoded);
+        self.decoded_target = decoded;
+        result
+    }
+
+    /// Fuzzy match a byte string against a query string. Targets that are not valid UTF-8
+    /// are decoded as configured by the `byte_decoding` option, and positions refer to the
+    /// characters of the decoded target. Returns `None` if the query does not match the
+    /// target.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use code_fuzzy_match::{ByteDecoding, FuzzyMatcher, MatchOptions};
+    ///
+    /// let mut matcher = FuzzyMatcher::new();
+    /// assert!(matcher.fuzzy_match_bytes(b"caf\xe9.txt", "caf.txt").is_some());
+    /// assert!(matcher.fuzzy_match_bytes(b"caf\xe9.txt", "caf\u{e9}").is_none());
+    ///
+    /// let mut matcher = FuzzyMatcher::with_options(MatchOptions {
+    ///     byte_decoding: ByteDecoding::Latin1,
+    ///     ..MatchOptions::default()
+    /// });
+    /// assert!(matcher.fuzzy_match_bytes(b"caf\xe9.txt", "caf\u{e9}").is_some());
+    /// ```
+    pub fn fuzzy_match_bytes(&mut self, target: &[u8], query: &str) -> Option<usize> {
+        self.with_decoded(target, |matcher, target| matcher.fuzzy_match(target, query))
+    }
+
+    /// Fuzzy match an OS string, such as a file name, against a query string. Strings that
+    /// are not valid Unicode are decoded as configured by the `byte_decoding` option. On
+    /// Windows, each unpaired surrogate is decoded as an invalid sequence of three bytes.
+    #[cfg(feature = "std")]
+    pub fn fuzzy_match_os_str(&mut self, target: &OsStr, query: &str) -> Option<usize> {
+        self.fuzzy_match_bytes(target.as_encoded_bytes(), query)
+    }
+
+    /// Fuzzy match a file path against a query string in two stages, in the same way as
+    /// [`fuzzy_match_path`](Self::fuzzy_match_path). Paths that are not valid Unicode are
+    /// decoded as by [`fuzzy_match_os_str`](Self::fuzzy_match_os_str).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use code_fuzzy_match::{FuzzyMatcher, PathMatchStage};
+    /// use std::path::PathBuf;
+    ///
+    /// let mut matcher = FuzzyMatcher::new();
+    /// let path = PathBuf::from("src").join("main.rs");
+    /// let result = matcher.fuzzy_match_os_path(&path, "main").unwrap();
+    /// assert_eq!(result.stage, PathMatchStage::Basename);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn fuzzy_match_os_path(
+        &mut self,
+        path: impl AsRef<Path>,
+        query: &str,
+    ) -> Option<PathMatch> {
+        let path = path.as_ref().as_os_str().as_encoded_bytes();
+        self.with_decoded(path, |matcher, path| matcher.fuzzy_match_path(path, query))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decode;
+    use crate::{ByteDecoding, FuzzyMatcher, MatchOptions};
+    use alloc::string::String;
+
+    #[test]
+    fn test_byte_targets() {
+        let mut decoded = String::new();
+        decode(b"a\xffb\xe2\x82c", ByteDecoding::Lossy, &mut decoded);
+        assert_eq!(decoded, "a\u{fffd}b\u{fffd}c");
+        decode(b"a\xffb\xe2\x82c", ByteDecoding::Latin1, &mut decoded);
+        assert_eq!(decoded, "a\u{ff}b\u{e2}\u{82}c");
+        decode("grüße".as_bytes(), ByteDecoding::Latin1, &mut decoded);
+        assert_eq!(decoded, "grüße");
+
+        // Valid targets score the same as string targets
+        let mut matcher = FuzzyMatcher::new();
+        assert_eq!(
+            matcher.fuzzy_match_bytes("src/grüße.rs".as_bytes(), "grß"),
+            matcher.fuzzy_match("src/grüße.rs", "grß")
+        );
+        assert_eq!(
+            matcher.fuzzy_match_bytes(b"src/gr\xfc\xdfe.rs", "gre"),
+            matcher.fuzzy_match("src/gr\u{fffd}\u{fffd}e.rs", "gre")
+        );
+        assert!(matcher
+            .fuzzy_match_bytes(b"src/gr\xfc\xdfe.rs", "grü")
+            .is_none());
+
+        let mut matcher = FuzzyMatcher::with_options(MatchOptions {
+            byte_decoding: ByteDecoding::Latin1,
+            ..MatchOptions::default()
+        });
+        assert_eq!(
+            matcher.fuzzy_match_bytes(b"src/gr\xfc\xdfe.rs", "grü"),
+            matcher.fuzzy_match("src/grüße.rs", "grü")
+        );
+    }
+
+    #[cfg(all(feature = "std", unix))]
+    #[test]
+    fn test_os_path() {
+        use crate::PathMatchStage;
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+        use std::path::Path;
+
+        let mut matcher = FuzzyMatcher::new();
+        let path = Path::new(OsStr::from_bytes(b"docs/r\xe9sum\xe9.txt"));
+        let result = matcher.fuzzy_match_os_path(path, "rsum").unwrap();
+        assert_eq!(result.stage, PathMatchStage::Basename);
+        assert_eq!(
+            matcher.fuzzy_match_os_str(path.as_os_str(), "docs/txt"),
+            matcher.fuzzy_match("docs/r\u{fffd}sum\u{fffd}.txt", "docs/txt")
+        );
+    }
+}
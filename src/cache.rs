@@ -0,0 +1,140 @@
+//! Cache of decoded targets, so that matching a stable list of candidates repeatedly does
+//! not decode and classify each target every time.
+
+use crate::matrix::CharClass;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Characters and classes of a target, as loaded by the matcher.
+pub(crate) struct CachedTarget {
+    pub(crate) chars: Vec<char>,
+    pub(crate) classes: Vec<CharClass>,
+    /// Index of the character that each loaded character was loaded from, if characters
+    /// were expanded or skipped.
+    pub(crate) origins: Vec<usize>,
+    /// Entries of the character index, if the `position_index` option is set.
+    pub(crate) index: Vec<(char, usize)>,
+    pub(crate) window_offset: usize,
+    pub(crate) truncated: bool,
+    last_used: u64,
+}
+
+/// Least recently used cache of decoded targets, keyed by the full target string.
+#[derive(Default)]
+pub(crate) struct TargetCache {
+    capacity: usize,
+    entries: BTreeMap<String, CachedTarget>,
+    recency: BTreeMap<u64, String>,
+    tick: u64,
+}
+
+impl TargetCache {
+    pub(crate) fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.entries.len() > capacity {
+            self.evict();
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+
+    /// Looks up a target, marking it as the most recently used entry.
+    pub(crate) fn get(&mut self, target: &str) -> Option<&CachedTarget> {
+        let entry = self.entries.get_mut(target)?;
+        self.tick += 1;
+        let key = self.recency.remove(&entry.last_used)?;
+        self.recency.insert(self.tick, key);
+        entry.last_used = self.tick;
+        Some(entry)
+    }
+
+    /// Adds a target, evicting the least recently used entry if the cache is full.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn insert(
+        &mut self,
+        target: &str,
+        chars: &[char],
+        classes: &[CharClass],
+        origins: &[usize],
+        index: &[(char, usize)],
+        window_offset: usize,
+        truncated: bool,
+    ) {
+        if self.capacity == 0 || self.entries.contains_key(target) {
+            return;
+        }
+        if self.entries.len() >= self.capacity {
+            self.evict();
+        }
+        self.tick += 1;
+        self.recency.insert(self.tick, target.into());
+        self.entries.insert(
+            target.into(),
+            CachedTarget {
+                chars: chars.to_vec(),
+                classes: classes.to_vec(),
+                origins: origins.to_vec(),
+                index: index.to_vec(),
+                window_offset,
+                truncated,
+                last_used: self.tick,
+            },
+        );
+    }
+
+    fn evict(&mut self) {
+        if let Some((_, key)) = self.recency.pop_first() {
+            self.entries.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FuzzyMatcher, MatchOptions};
+    use alloc::string::String;
+    use alloc::vec;
+
+    #[test]
+    fn test_target_cache() {
+        const TARGETS: &[&str] = &["src/lib.rs", "src::matrix::tolerant", "README.md"];
+        let mut uncached = FuzzyMatcher::new();
+        let mut cached = FuzzyMatcher::new();
+        cached.set_target_cache_capacity(2);
+        for _ in 0..2 {
+            for target in TARGETS {
+                assert_eq!(
+                    cached.fuzzy_match(target, "sr"),
+                    uncached.fuzzy_match(target, "sr")
+                );
+            }
+        }
+        assert_eq!(cached.target_cache.entries.len(), 2);
+
+        // Changing options invalidates cached targets
+        let options = MatchOptions {
+            path_separators: vec![String::from("::")],
+            ..MatchOptions::default()
+        };
+        uncached.set_options(options.clone());
+        cached.set_options(options);
+        assert_eq!(cached.target_cache.entries.len(), 0);
+        for _ in 0..2 {
+            assert_eq!(
+                cached.fuzzy_match(TARGETS[1], "mt"),
+                uncached.fuzzy_match(TARGETS[1], "mt")
+            );
+        }
+
+        cached.set_target_cache_capacity(0);
+        assert_eq!(cached.target_cache.entries.len(), 0);
+    }
+}
@@ -0,0 +1,223 @@
+//! Sets of candidates that grow while they are queried, such as the files of a project.
+
+use crate::{Index, MatchOptions, Scored};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Set of candidates for a picker that can be queried while it is still being filled, such
+/// as by a `FileWalker` discovering the files of a project. Candidates are preprocessed as
+/// they are added, as in an [`Index`].
+///
+/// Each candidate has a stable identifier, which is kept when the candidate is renamed and
+/// is not reused once the candidate is removed. Query results refer to candidates by
+/// identifier, so a filesystem watcher can keep the set in sync with
+/// [`insert`](CandidateSet::insert), [`remove`](CandidateSet::remove) and
+/// [`rename`](CandidateSet::rename) without invalidating results held by a user interface.
+///
+/// # Examples
+///
+/// ```
+/// use code_fuzzy_match::CandidateSet;
+///
+/// let mut set = CandidateSet::new();
+/// let lib = set.insert("src/lib.rs");
+/// set.insert("src/main.rs");
+/// assert_eq!(set.rename("src/lib.rs", "src/core.rs"), lib);
+/// assert_eq!(set.remove("src/main.rs"), Some(1));
+///
+/// let results = set.query("core", 10);
+/// assert_eq!(results.len(), 1);
+/// assert_eq!(results[0].item, lib);
+/// assert_eq!(set.get(lib), Some("src/core.rs"));
+/// ```
+pub struct CandidateSet {
+    index: Index,
+    ids: BTreeMap<String, usize>,
+}
+
+impl CandidateSet {
+    /// Creates an empty candidate set.
+    pub fn new() -> Self {
+        Self::with_options(MatchOptions::default())
+    }
+
+    /// Creates an empty candidate set that matches using the given options. The
+    /// `truncation` and `max_target_len` options are ignored, as for an [`Index`].
+    pub fn with_options(options: MatchOptions) -> Self {
+        CandidateSet {
+            index: Index::with_options(options),
+            ids: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the options used by this candidate set.
+    pub fn options(&self) -> &MatchOptions {
+        self.index.options()
+    }
+
+    /// Changes the options used by this candidate set.
+    pub fn set_options(&mut self, options: MatchOptions) {
+        self.index.set_options(options);
+    }
+
+    /// Number of candidates in the set.
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Returns true if the set has no candidates.
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Returns the candidate with the given identifier.
+    pub fn get(&self, id: usize) -> Option<&str> {
+        self.index.get(id)
+    }
+
+    /// Returns the identifier of a candidate, or `None` if it is not in the set.
+    pub fn id(&self, candidate: &str) -> Option<usize> {
+        self.ids.get(candidate).copied()
+    }
+
+    /// Adds a candidate to the set, returning its identifier. If the candidate is already
+    /// in the set, it is not added again and its existing identifier is returned.
+    pub fn insert(&mut self, candidate: &str) -> usize {
+        if let Some(id) = self.id(candidate) {
+            return id;
+        }
+        let id = self.index.add(candidate);
+        self.ids.insert(candidate.into(), id);
+        id
+    }
+
+    /// Removes a candidate from the set, returning its identifier, or `None` if it is not
+    /// in the set.
+    pub fn remove(&mut self, candidate: &str) -> Option<usize> {
+        let id = self.ids.remove(candidate)?;
+        self.index.remove(id);
+        Some(id)
+    }
+
+    /// Renames a candidate, keeping its identifier, and returns the identifier. If `to` is
+    /// already in the set, it is removed first. If `from` is not in the set, `to` is
+    /// inserted as with [`insert`](CandidateSet::insert).
+    pub fn rename(&mut self, from: &str, to: &str) -> usize {
+        if from == to {
+            return self.insert(to);
+        }
+        let Some(id) = self.ids.remove(from) else {
+            return self.insert(to);
+        };
+        self.remove(to);
+        self.index.replace(id, to);
+        self.ids.insert(to.into(), id);
+        id
+    }
+
+    /// Returns the identifier and text of each candidate, in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &str)> {
+        self.index
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| !entry.removed)
+            .map(|(id, entry)| (id, &self.index.text[entry.text.clone()]))
+    }
+
+    /// Fuzzy match every candidate against a query string, returning the
+    /// identifiers of the `k` best matching candidates from best to worst. Candidates with
+    /// the same score are ordered by identifier.
+    pub fn query(&mut self, query: &str, k: usize) -> Vec<Scored<usize>> {
+        self.index.query(query, k)
+    }
+}
+
+impl Default for CandidateSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CandidateSet, MatchOptions};
+    use alloc::string::String;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_candidate_updates() {
+        let mut set = CandidateSet::new();
+        let lib = set.insert("src/lib.rs");
+        let main = set.insert("src/main.rs");
+        let readme = set.insert("README.md");
+        assert_eq!(set.insert("src/lib.rs"), lib);
+        assert_eq!(set.len(), 3);
+
+        // Identifiers are kept when renaming, and not reused after removing
+        assert_eq!(set.rename("src/lib.rs", "src/matcher.rs"), lib);
+        assert_eq!(set.id("src/lib.rs"), None);
+        assert_eq!(set.id("src/matcher.rs"), Some(lib));
+        assert!(set.query("lib", 10).is_empty());
+        assert_eq!(set.query("matcher", 10)[0].item, lib);
+        assert_eq!(set.remove("README.md"), Some(readme));
+        assert_eq!(set.remove("README.md"), None);
+        let readme = set.insert("README.md");
+        assert_eq!(readme, 3);
+
+        // Renaming over an existing candidate replaces it
+        assert_eq!(set.rename("src/matcher.rs", "src/main.rs"), lib);
+        assert_eq!(set.get(main), None);
+        assert_eq!(set.rename("missing.rs", "new.rs"), 4);
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            [(lib, "src/main.rs"), (readme, "README.md"), (4, "new.rs")]
+        );
+
+        // Renamed candidates are kept when the options change
+        set.set_options(MatchOptions {
+            path_separators: vec![String::from("/")],
+            ..MatchOptions::default()
+        });
+        let results = set.query("main", 10);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].item, lib);
+    }
+
+    #[test]
+    fn test_dedup_targets() {
+        let candidates = [
+            "lib.rs",
+            "lib.py",
+            "src/lib.rs",
+            "src/lib.py",
+            "src/lib",
+            "Cargo.toml",
+            "lib-rs",
+        ];
+        let options = MatchOptions {
+            ignore_extension: true,
+            ignored_chars: String::from("-"),
+            ..MatchOptions::default()
+        };
+        let mut set = CandidateSet::with_options(options.clone());
+        let mut deduped = CandidateSet::with_options(MatchOptions {
+            dedup_targets: true,
+            ..options
+        });
+        for candidate in candidates {
+            set.insert(candidate);
+            deduped.insert(candidate);
+        }
+
+        // Candidates with the same target after normalization share a score
+        let results = deduped.query("lib", 10);
+        assert_eq!(results.len(), 6);
+        assert_eq!(results[0].score, results[1].score);
+        for query in ["lib", "srclib", "lib.py", "librs", "toml", ""] {
+            assert_eq!(deduped.query(query, 10), set.query(query, 10), "{query}");
+        }
+    }
+}
@@ -0,0 +1,162 @@
+//! Index of the positions of each character in a target, used to visit only the target
+//! characters that can match each query character.
+
+use crate::matrix::Candidates;
+use crate::prefilter::match_key;
+use crate::whitespace::SEPARATOR_CHARS;
+use crate::MatchOptions;
+use alloc::vec::Vec;
+
+/// Positions of the characters of a target, grouped by the key used to compare characters,
+/// along with scratch memory for the candidate positions of each query character.
+#[derive(Default)]
+pub(crate) struct CharIndex {
+    /// Key and position of each target character, sorted by key and then position.
+    pub(crate) entries: Vec<(char, usize)>,
+    candidate_positions: Vec<usize>,
+    candidate_starts: Vec<usize>,
+}
+
+impl CharIndex {
+    /// Indexes the characters of a target.
+    pub(crate) fn build(&mut self, target_chars: &[char]) {
+        self.entries.clear();
+        self.entries.extend(
+            target_chars
+                .iter()
+                .enumerate()
+                .map(|(idx, c)| (match_key(*c), idx)),
+        );
+        self.entries.sort_unstable();
+    }
+
+    /// Returns the positions of the target characters with the given key.
+    fn positions(&self, key: char) -> impl Iterator<Item = usize> + '_ {
+        let start = self.entries.partition_point(|(k, _)| *k < key);
+        let end = self.entries.partition_point(|(k, _)| *k <= key);
+        self.entries[start..end].iter().map(|(_, idx)| *idx)
+    }
+
+    /// Collects the positions in the target that each query character may match.
+    pub(crate) fn candidates(
+        &mut self,
+        query_chars: &[char],
+        options: &MatchOptions,
+    ) -> Candidates<'_> {
+        self.candidate_positions.clear();
+        self.candidate_starts.clear();
+        for query_char in query_chars {
+            let start = self.candidate_positions.len();
+            self.candidate_starts.push(start);
+
+            let key = match_key(*query_char);
+            let mut keys: Vec<char> = Vec::new();
+            keys.extend(options.case_locale.extra_key(*query_char));
+            if *query_char == ' ' && options.space_matches_separators {
+                keys.extend(
+                    SEPARATOR_CHARS
+                        .iter()
+                        .map(|c| match_key(*c))
+                        .filter(|k| *k != key),
+                );
+            }
+            for group in &options.equivalences {
+                if group.contains(*query_char) {
+                    keys.extend(group.chars().map(match_key).filter(|k| *k != key));
+                }
+            }
+
+            let mut positions = core::mem::take(&mut self.candidate_positions);
+            positions.extend(self.positions(key));
+            if !keys.is_empty() {
+                // Characters in a group of equivalences, with the case rules of another
+                // locale, or spaces matching separators may match characters with other keys
+                keys.sort_unstable();
+                keys.dedup();
+                for key in keys {
+                    positions.extend(self.positions(key));
+                }
+                positions[start..].sort_unstable();
+            }
+            self.candidate_positions = positions;
+        }
+        self.candidate_starts.push(self.candidate_positions.len());
+
+        Candidates {
+            positions: &self.candidate_positions,
+            starts: &self.candidate_starts,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FuzzyMatcher, MatchOptions, ScoringProfile};
+    use alloc::string::String;
+    use alloc::vec;
+
+    #[test]
+    fn test_position_index() {
+        const TARGETS: &[&str] = &[
+            "The quick brown fox jumps over the lazy dog.",
+            "src/matrix/sparse.rs",
+            "C:\\Windows\\System32\\drivers\\etc\\hosts",
+            "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaab",
+            "Grüße aus Köln",
+        ];
+        const QUERIES: &[&str] = &[
+            "fox",
+            "jmp the do",
+            "ee",
+            "srs",
+            "/etc/h",
+            "a",
+            "aab",
+            "ab",
+            "KÖLN",
+            "m-s",
+            "xyz",
+        ];
+
+        let options = [
+            MatchOptions::default(),
+            MatchOptions {
+                gap_penalty: 1,
+                leading_gap_penalty: 1,
+                whole_word_bonus: 5,
+                contiguous_query_bonus: 5,
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                bidirectional: true,
+                require_word_start: true,
+                profile: ScoringProfile::FilePath,
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                equivalences: vec![String::from("-_/")],
+                slash_equivalence: false,
+                pin_exact_matches: true,
+                ..MatchOptions::default()
+            },
+        ];
+        for options in options {
+            let mut dense = FuzzyMatcher::with_options(options.clone());
+            let mut indexed = FuzzyMatcher::with_options(MatchOptions {
+                position_index: true,
+                ..options
+            });
+            // Indexes restored from the target cache give the same results
+            indexed.set_target_cache_capacity(TARGETS.len());
+            for target in TARGETS.iter().chain(TARGETS) {
+                for query in QUERIES {
+                    assert_eq!(
+                        indexed.fuzzy_match_details(target, query),
+                        dense.fuzzy_match_details(target, query),
+                        "{target} {query}"
+                    );
+                }
+            }
+        }
+    }
+}
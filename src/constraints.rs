@@ -0,0 +1,216 @@
+//! Constraints on where in the target string a query is allowed to match.
+
+use crate::matrix::PositionMask;
+use crate::{FuzzyMatcher, ScoreModel};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// Constraints on the positions that a match is allowed to use. All positions are character
+/// indices into the target string. Constraints that are not set do not restrict the match.
+///
+/// Constraints are enforced while scoring rather than by filtering the result, so the best
+/// match that satisfies the constraints is found even if a better unconstrained match exists.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MatchConstraints {
+    /// Range of positions where the first character of the query must be matched.
+    pub start: Option<Range<usize>>,
+    /// Range of positions where the last character of the query must be matched.
+    pub end: Option<Range<usize>>,
+    /// Positions that must be matched by some character of the query.
+    pub required: Vec<usize>,
+    /// Positions that must not be matched by any character of the query.
+    pub excluded: Vec<usize>,
+}
+
+impl MatchConstraints {
+    /// Creates constraints that do not restrict the match.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates constraints requiring the entire match to fall within the given range of
+    /// positions, such as the current selection in an editor buffer.
+    pub fn within(range: Range<usize>) -> Self {
+        MatchConstraints {
+            start: Some(range.clone()),
+            end: Some(range),
+            ..Self::default()
+        }
+    }
+
+    /// Fills `mask` with the constraints for a window of `target_len` loaded characters
+    /// starting at character `offset` of the target. If characters were expanded or skipped,
+    /// `origins` holds the index in the window of the character that each loaded character
+    /// was loaded from. Returns false if the constraints can't be satisfied
+    /// within this window.
+    pub(crate) fn build_mask(
+        &self,
+        offset: usize,
+        origins: &[usize],
+        target_len: usize,
+        mask: &mut PositionMask,
+    ) -> bool {
+        mask.flags.clear();
+        mask.flags.resize(target_len, 0);
+        mask.first_required = None;
+        mask.last_required = None;
+
+        // Loaded characters for the character at a position of the target
+        let loaded = |idx: usize| match idx.checked_sub(offset) {
+            None => 0..0,
+            Some(idx) if origins.is_empty() => idx.min(target_len)..(idx + 1).min(target_len),
+            Some(idx) => {
+                origins.partition_point(|origin| *origin < idx)
+                    ..origins.partition_point(|origin| *origin <= idx)
+            }
+        };
+
+        for (window_idx, flags) in mask.flags.iter_mut().enumerate() {
+            let idx = offset + origins.get(window_idx).copied().unwrap_or(window_idx);
+            if self.start.as_ref().is_none_or(|start| start.contains(&idx)) {
+                *flags |= PositionMask::START_ALLOWED;
+            }
+            if self.end.as_ref().is_none_or(|end| end.contains(&idx)) {
+                *flags |= PositionMask::END_ALLOWED;
+            }
+        }
+
+        for idx in &self.excluded {
+            for flags in &mut mask.flags[loaded(*idx)] {
+                *flags |= PositionMask::EXCLUDED;
+            }
+        }
+
+        for idx in &self.required {
+            // An expanded character is required to be matched at its first loaded character
+            let Some(idx) = loaded(*idx).next() else {
+                return false;
+            };
+            mask.flags[idx] |= PositionMask::REQUIRED;
+            mask.first_required = Some(mask.first_required.map_or(idx, |first| first.min(idx)));
+            mask.last_required = Some(mask.last_required.map_or(idx, |last| last.max(idx)));
+        }
+
+        true
+    }
+}
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Fuzzy match a string against a query string, only considering matches that satisfy
+    /// the given constraints. Returns a score that is higher for a more confident match, or
+    /// `None` if there is no match that satisfies the constraints.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use code_fuzzy_match::{FuzzyMatcher, MatchConstraints};
+    ///
+    /// let mut matcher = FuzzyMatcher::new();
+    /// let target = "the quick brown fox";
+    ///
+    /// // Only search within "brown fox"
+    /// let constraints = MatchConstraints::within(10..19);
+    /// assert!(matcher.fuzzy_match_constrained(target, "fox", &constraints).is_some());
+    /// assert!(matcher.fuzzy_match_constrained(target, "quick", &constraints).is_none());
+    /// ```
+    pub fn fuzzy_match_constrained(
+        &mut self,
+        target: &str,
+        query: &str,
+        constraints: &MatchConstraints,
+    ) -> Option<usize> {
+        self.load(target, query);
+        if !constraints.build_mask(
+            self.window_offset,
+            &self.target_origins,
+            self.target_chars.len(),
+            &mut self.mask,
+        ) {
+            return None;
+        }
+        self.compute(false, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FuzzyMatcher, MatchConstraints, MatchOptions};
+    use alloc::vec;
+
+    #[test]
+    fn test_constraints() {
+        for bidirectional in [false, true] {
+            let mut matcher = FuzzyMatcher::with_options(MatchOptions {
+                bidirectional,
+                ..MatchOptions::default()
+            });
+            check_constraints(&mut matcher);
+        }
+    }
+
+    fn check_constraints(matcher: &mut FuzzyMatcher) {
+        const TARGET: &str = "The quick brown fox jumps over the lazy dog.";
+
+        // No constraints is the same as an unconstrained match
+        assert_eq!(
+            matcher.fuzzy_match_constrained(TARGET, "the dog", &MatchConstraints::new()),
+            matcher.fuzzy_match(TARGET, "the dog")
+        );
+
+        // Start of match anchored to the beginning of the target
+        let constraints = MatchConstraints {
+            start: Some(0..1),
+            ..MatchConstraints::default()
+        };
+        assert!(matcher
+            .fuzzy_match_constrained(TARGET, "the dog", &constraints)
+            .is_some());
+        assert!(matcher
+            .fuzzy_match_constrained(TARGET, "fox", &constraints)
+            .is_none());
+
+        // End of match anchored to the end of the target
+        let constraints = MatchConstraints {
+            end: Some(43..44),
+            ..MatchConstraints::default()
+        };
+        assert!(matcher
+            .fuzzy_match_constrained(TARGET, "dog.", &constraints)
+            .is_some());
+        assert!(matcher
+            .fuzzy_match_constrained(TARGET, "dog", &constraints)
+            .is_none());
+
+        // Required positions force a worse alignment: "o" must use the "o" in "over"
+        let unconstrained = matcher.fuzzy_match(TARGET, "fo");
+        let constraints = MatchConstraints {
+            required: vec![26],
+            ..MatchConstraints::default()
+        };
+        assert!(matcher
+            .fuzzy_match_constrained(TARGET, "fox", &constraints)
+            .is_none());
+        let constrained = matcher.fuzzy_match_constrained(TARGET, "fo", &constraints);
+        assert!(constrained.is_some());
+        assert!(constrained < unconstrained);
+
+        // Excluding the only "x" rules out the match entirely
+        let constraints = MatchConstraints {
+            excluded: vec![18],
+            ..MatchConstraints::default()
+        };
+        assert!(matcher
+            .fuzzy_match_constrained(TARGET, "fox", &constraints)
+            .is_none());
+
+        // Required positions outside of the target can't be satisfied
+        let constraints = MatchConstraints {
+            required: vec![100],
+            ..MatchConstraints::default()
+        };
+        assert!(matcher
+            .fuzzy_match_constrained(TARGET, "fox", &constraints)
+            .is_none());
+    }
+}
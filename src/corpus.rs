@@ -0,0 +1,264 @@
+//! Generator for synthetic candidate lists, for benchmarking.
+//!
+//! The generated corpora resemble the candidate lists that fuzzy matching is commonly used
+//! with: file paths in a source tree, identifiers in a code base, and command palette
+//! entries. Corpora are generated from a seed, so the same seed always produces the same
+//! candidates on every platform.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+const WORDS: &[&str] = &[
+    "account", "action", "address", "agent", "alloc", "async", "attr", "buffer", "build", "cache",
+    "channel", "client", "config", "context", "core", "data", "debug", "decode", "device",
+    "editor", "encode", "engine", "entry", "error", "event", "file", "filter", "format", "frame",
+    "handle", "handler", "header", "index", "input", "item", "key", "layout", "list", "loader",
+    "lock", "manager", "map", "match", "message", "model", "module", "node", "option", "output",
+    "packet", "panel", "parse", "parser", "path", "player", "plugin", "pool", "port", "query",
+    "queue", "reader", "record", "render", "request", "resource", "response", "result", "router",
+    "runtime", "scope", "search", "server", "session", "shader", "signal", "socket", "source",
+    "state", "status", "store", "stream", "string", "style", "symbol", "syntax", "table", "task",
+    "text", "thread", "token", "tree", "type", "user", "util", "value", "view", "widget", "window",
+    "worker", "writer",
+];
+
+const DIRECTORIES: &[&str] = &[
+    "src",
+    "lib",
+    "test",
+    "tests",
+    "docs",
+    "include",
+    "internal",
+    "pkg",
+    "cmd",
+    "api",
+    "common",
+    "vendor",
+    "third_party",
+    "tools",
+    "scripts",
+    "assets",
+    "examples",
+    "benches",
+];
+
+const EXTENSIONS: &[&str] = &[
+    "rs", "c", "h", "cpp", "hpp", "py", "js", "ts", "tsx", "go", "java", "md", "toml", "json",
+    "yaml",
+];
+
+const COMMAND_CATEGORIES: &[&str] = &[
+    "File",
+    "Edit",
+    "View",
+    "Go",
+    "Run",
+    "Terminal",
+    "Git",
+    "Debug",
+    "Search",
+    "Preferences",
+    "Window",
+    "Help",
+];
+
+const COMMAND_VERBS: &[&str] = &[
+    "Open",
+    "Close",
+    "Save",
+    "Toggle",
+    "Show",
+    "Hide",
+    "Focus",
+    "Select",
+    "Delete",
+    "Rename",
+    "Copy",
+    "Paste",
+    "Find",
+    "Replace",
+    "Reload",
+    "Reset",
+    "Start",
+    "Stop",
+    "Configure",
+    "Refresh",
+];
+
+/// Naming convention used for generated identifiers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IdentifierStyle {
+    /// Identifiers such as `read_config_file`, as in Rust and Python functions.
+    SnakeCase,
+    /// Identifiers such as `MAX_BUFFER_SIZE`, as used for constants.
+    ScreamingSnakeCase,
+    /// Identifiers such as `readConfigFile`, as in Java and JavaScript methods.
+    CamelCase,
+    /// Identifiers such as `ConfigFileReader`, as used for type names.
+    PascalCase,
+    /// Identifiers such as `config-file-reader`, as in CSS and command line flags.
+    KebabCase,
+}
+
+/// Seeded generator of synthetic candidate lists.
+///
+/// # Examples
+///
+/// ```
+/// use code_fuzzy_match::corpus::{CorpusGenerator, IdentifierStyle};
+///
+/// let paths = CorpusGenerator::new(42).file_paths(100);
+/// assert_eq!(paths.len(), 100);
+/// assert_eq!(paths, CorpusGenerator::new(42).file_paths(100));
+///
+/// let names = CorpusGenerator::new(42).identifiers(10, IdentifierStyle::SnakeCase);
+/// assert!(names.iter().all(|name| !name.contains(char::is_uppercase)));
+/// ```
+#[derive(Clone, Debug)]
+pub struct CorpusGenerator {
+    state: u64,
+}
+
+impl CorpusGenerator {
+    /// Creates a generator that produces candidates determined by `seed`.
+    pub fn new(seed: u64) -> Self {
+        CorpusGenerator { state: seed }
+    }
+
+    /// Returns the next pseudorandom number, using the SplitMix64 algorithm.
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a pseudorandom number in the inclusive range from `min` to `max`.
+    fn range(&mut self, min: usize, max: usize) -> usize {
+        min + (self.next_u64() % (max - min + 1) as u64) as usize
+    }
+
+    fn choose<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.range(0, items.len() - 1)]
+    }
+
+    /// Generates `count` relative file paths, such as `src/parser/token_reader.rs`.
+    pub fn file_paths(&mut self, count: usize) -> Vec<String> {
+        (0..count).map(|_| self.file_path()).collect()
+    }
+
+    fn file_path(&mut self) -> String {
+        let mut path = String::new();
+        let depth = self.range(0, 4);
+        for level in 0..depth {
+            if level == 0 {
+                path.push_str(self.choose(DIRECTORIES));
+            } else {
+                path.push_str(self.choose(WORDS));
+            }
+            path.push('/');
+        }
+        let style = if self.range(0, 3) == 0 {
+            IdentifierStyle::PascalCase
+        } else {
+            IdentifierStyle::SnakeCase
+        };
+        path.push_str(&self.identifier(style));
+        path.push('.');
+        path.push_str(self.choose(EXTENSIONS));
+        path
+    }
+
+    /// Generates `count` identifiers of one to four words in the given style.
+    pub fn identifiers(&mut self, count: usize, style: IdentifierStyle) -> Vec<String> {
+        (0..count).map(|_| self.identifier(style)).collect()
+    }
+
+    fn identifier(&mut self, style: IdentifierStyle) -> String {
+        let mut identifier = String::new();
+        let word_count = self.range(1, 4);
+        for word_idx in 0..word_count {
+            let word = self.choose(WORDS);
+            match style {
+                IdentifierStyle::SnakeCase | IdentifierStyle::ScreamingSnakeCase => {
+                    if word_idx > 0 {
+                        identifier.push('_');
+                    }
+                }
+                IdentifierStyle::KebabCase => {
+                    if word_idx > 0 {
+                        identifier.push('-');
+                    }
+                }
+                IdentifierStyle::CamelCase | IdentifierStyle::PascalCase => (),
+            }
+            for (char_idx, c) in word.chars().enumerate() {
+                let upper = match style {
+                    IdentifierStyle::ScreamingSnakeCase => true,
+                    IdentifierStyle::CamelCase => char_idx == 0 && word_idx > 0,
+                    IdentifierStyle::PascalCase => char_idx == 0,
+                    IdentifierStyle::SnakeCase | IdentifierStyle::KebabCase => false,
+                };
+                if upper {
+                    identifier.push(c.to_ascii_uppercase());
+                } else {
+                    identifier.push(c);
+                }
+            }
+        }
+        identifier
+    }
+
+    /// Generates `count` command palette entries, such as `Git: Toggle Stream Panel`.
+    pub fn commands(&mut self, count: usize) -> Vec<String> {
+        (0..count).map(|_| self.command()).collect()
+    }
+
+    fn command(&mut self) -> String {
+        let mut command = String::new();
+        command.push_str(self.choose(COMMAND_CATEGORIES));
+        command.push_str(": ");
+        command.push_str(self.choose(COMMAND_VERBS));
+        for _ in 0..self.range(1, 2) {
+            command.push(' ');
+            let word = self.choose(WORDS);
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                command.push(first.to_ascii_uppercase());
+                command.push_str(chars.as_str());
+            }
+        }
+        command
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CorpusGenerator, IdentifierStyle};
+
+    #[test]
+    fn test_corpus() {
+        // Same seed gives the same corpus, different seeds give different corpora
+        let paths = CorpusGenerator::new(1).file_paths(50);
+        assert_eq!(paths, CorpusGenerator::new(1).file_paths(50));
+        assert_ne!(paths, CorpusGenerator::new(2).file_paths(50));
+        assert!(paths.iter().all(|path| path.contains('.')));
+
+        let mut generator = CorpusGenerator::new(3);
+        for name in generator.identifiers(20, IdentifierStyle::ScreamingSnakeCase) {
+            assert!(name.chars().all(|c| c == '_' || c.is_ascii_uppercase()));
+        }
+        for name in generator.identifiers(20, IdentifierStyle::CamelCase) {
+            assert!(name.starts_with(|c: char| c.is_ascii_lowercase()));
+            assert!(name.chars().all(|c| c.is_ascii_alphabetic()));
+        }
+        for name in generator.identifiers(20, IdentifierStyle::KebabCase) {
+            assert!(name.chars().all(|c| c == '-' || c.is_ascii_lowercase()));
+        }
+        for command in generator.commands(20) {
+            assert!(command.contains(": "));
+        }
+    }
+}
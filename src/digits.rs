@@ -0,0 +1,67 @@
+//! Word starts at the boundaries between letters and digits, for the `digit_word_starts`
+//! option.
+
+use crate::matrix::CharClass;
+
+/// Returns true if `c` starts a word after `prev`: a digit after a letter, or a letter
+/// after a digit.
+pub(crate) fn is_boundary(prev: char, c: char) -> bool {
+    (prev.is_alphabetic() && c.is_numeric()) || (prev.is_numeric() && c.is_alphabetic())
+}
+
+/// Marks units of the target that start a run of digits after a letter, or a run of
+/// letters after a digit. `chars` yields the first character of each unit of the target in
+/// order, and `classes` holds the classification of each unit.
+pub(crate) fn mark_digit_boundaries(chars: impl Iterator<Item = char>, classes: &mut [CharClass]) {
+    let mut prev = None;
+    for (class, c) in classes.iter_mut().zip(chars) {
+        if *class == CharClass::Other && prev.is_some_and(|prev| is_boundary(prev, c)) {
+            *class = CharClass::WordStart;
+        }
+        prev = Some(c);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{fuzzy_match_in, FuzzyMatcher, MatchOptions, MatchScratch};
+
+    #[test]
+    fn test_digit_word_starts() {
+        let options = MatchOptions {
+            digit_word_starts: true,
+            ..MatchOptions::default()
+        };
+        let mut matcher = FuzzyMatcher::with_options(options.clone());
+        let result = matcher.fuzzy_match_details("sha256sum", "s2s").unwrap();
+        assert_eq!(result.positions, [0, 3, 6]);
+        assert_eq!(result.word_starts_hit, 3);
+        assert!(
+            matcher.fuzzy_match("utf8_len", "u8").unwrap()
+                > FuzzyMatcher::new().fuzzy_match("utf8_len", "u8").unwrap()
+        );
+        // Digits after a digit and camel case humps are unchanged
+        let result = matcher.fuzzy_match_details("md5Hash", "m5h").unwrap();
+        assert_eq!(result.word_starts_hit, 3);
+
+        let mut strict = FuzzyMatcher::with_options(MatchOptions {
+            require_word_start: true,
+            ..options.clone()
+        });
+        assert!(strict.fuzzy_match("sha256sum", "sum").is_some());
+        assert!(strict.fuzzy_match("sha256sum", "um").is_none());
+
+        // Streamed targets find the same boundaries across chunks
+        let mut streaming = FuzzyMatcher::with_options(options.clone());
+        streaming.begin("s2s");
+        streaming.feed("sha");
+        streaming.feed("256sum");
+        assert_eq!(streaming.finish(), matcher.fuzzy_match("sha256sum", "s2s"));
+
+        let mut scratch = MatchScratch::<16>::with_options(options);
+        assert_eq!(
+            fuzzy_match_in("sha256sum", "s2s", &mut scratch),
+            matcher.fuzzy_match("sha256sum", "s2s")
+        );
+    }
+}
@@ -0,0 +1,142 @@
+//! Edit distances between strings, for spell checking when fuzzy matching finds nothing.
+//!
+//! Fuzzy matching only finds targets that contain every character of the query in order,
+//! so a misspelled query can fail to match the target it was meant for. Edit distance counts
+//! the characters that must be inserted, deleted or substituted to turn one string into
+//! another, and can be used as a fallback to find targets close to such a query.
+//!
+//! Like [`FuzzyMatcher`](crate::FuzzyMatcher), an [`EditDistance`] keeps its memory between
+//! calls, so computing many distances doesn't allocate after the first few. Characters are
+//! compared exactly, so convert both strings to lowercase first for a case insensitive
+//! distance.
+
+use alloc::vec::Vec;
+
+/// Computes edit distances between strings, reusing memory between calls.
+///
+/// # Examples
+///
+/// ```
+/// use code_fuzzy_match::edit_distance::EditDistance;
+///
+/// let mut distance = EditDistance::new();
+/// assert_eq!(distance.levenshtein("kitten", "sitting"), 3);
+/// assert_eq!(distance.levenshtein("teh", "the"), 2);
+/// assert_eq!(distance.damerau_levenshtein("teh", "the"), 1);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct EditDistance {
+    a: Vec<char>,
+    b: Vec<char>,
+    /// Distances of the current row and the two rows before it, one after the other.
+    rows: Vec<usize>,
+}
+
+impl EditDistance {
+    /// Creates a new edit distance calculator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the Levenshtein distance between two strings: the number of characters that
+    /// must be inserted, deleted or substituted to turn one string into the other.
+    pub fn levenshtein(&mut self, a: &str, b: &str) -> usize {
+        self.compute(a, b, false)
+    }
+
+    /// Returns the Damerau-Levenshtein distance between two strings, which also counts
+    /// swapping two adjacent characters as a single edit. This is the optimal string
+    /// alignment distance, where no part of the string is edited more than once.
+    pub fn damerau_levenshtein(&mut self, a: &str, b: &str) -> usize {
+        self.compute(a, b, true)
+    }
+
+    /// Releases the memory held for computing distances.
+    pub fn shrink_to_fit(&mut self) {
+        *self = Self::new();
+    }
+
+    fn compute(&mut self, a: &str, b: &str, transpositions: bool) -> usize {
+        self.a.clear();
+        self.a.extend(a.chars());
+        self.b.clear();
+        self.b.extend(b.chars());
+        let (a, b) = (&self.a, &self.b);
+        if a.is_empty() || b.is_empty() {
+            return a.len().max(b.len());
+        }
+
+        let width = b.len() + 1;
+        self.rows.clear();
+        self.rows.resize(width * 3, 0);
+        let (mut before_prev, mut prev, mut current) = (0, width, width * 2);
+        for (j, distance) in self.rows[prev..prev + width].iter_mut().enumerate() {
+            *distance = j;
+        }
+
+        for i in 1..=a.len() {
+            self.rows[current] = i;
+            for j in 1..=b.len() {
+                let cost = usize::from(a[i - 1] != b[j - 1]);
+                let mut distance = (self.rows[prev + j] + 1)
+                    .min(self.rows[current + j - 1] + 1)
+                    .min(self.rows[prev + j - 1] + cost);
+                if transpositions && i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1]
+                {
+                    distance = distance.min(self.rows[before_prev + j - 2] + 1);
+                }
+                self.rows[current + j] = distance;
+            }
+            (before_prev, prev, current) = (prev, current, before_prev);
+        }
+        self.rows[prev + b.len()]
+    }
+}
+
+/// Returns the Levenshtein distance between two strings. When computing many distances, use
+/// [`EditDistance`] instead.
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    EditDistance::new().levenshtein(a, b)
+}
+
+/// Returns the Damerau-Levenshtein distance between two strings. When computing many
+/// distances, use [`EditDistance`] instead.
+pub fn damerau_levenshtein(a: &str, b: &str) -> usize {
+    EditDistance::new().damerau_levenshtein(a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{damerau_levenshtein, levenshtein, EditDistance};
+
+    #[test]
+    fn test_edit_distance() {
+        let cases = [
+            ("", "", 0, 0),
+            ("", "abc", 3, 3),
+            ("abc", "", 3, 3),
+            ("abc", "abc", 0, 0),
+            ("kitten", "sitting", 3, 3),
+            ("flaw", "lawn", 2, 2),
+            ("teh", "the", 2, 1),
+            ("ca", "abc", 3, 3),
+            ("abcdef", "badcfe", 4, 3),
+            ("Grüße", "Grusse", 3, 3),
+            ("ABC", "abc", 3, 3),
+        ];
+        let mut distance = EditDistance::new();
+        for (a, b, expected_levenshtein, expected_damerau) in cases {
+            assert_eq!(distance.levenshtein(a, b), expected_levenshtein, "{a} {b}");
+            assert_eq!(distance.levenshtein(b, a), expected_levenshtein, "{b} {a}");
+            assert_eq!(
+                distance.damerau_levenshtein(a, b),
+                expected_damerau,
+                "{a} {b}"
+            );
+            assert_eq!(levenshtein(a, b), expected_levenshtein);
+            assert_eq!(damerau_levenshtein(a, b), expected_damerau);
+        }
+        distance.shrink_to_fit();
+        assert_eq!(distance.damerau_levenshtein("instal", "install"), 1);
+    }
+}
@@ -0,0 +1,307 @@
+//! Breakdown of match scores into the bonuses that make them up, for tuning and debugging.
+
+use crate::matrix::{char_bonuses, leading_gap_penalty, normalize_length, signed, MatrixInput};
+use crate::{char_match_kind, FuzzyMatcher, ScoreModel};
+use alloc::vec::Vec;
+
+/// Bonuses awarded for a single matched character. The score of a match is the sum of the
+/// [`total`](Bonuses::total) of each matched character, less the
+/// [`penalty`](MatchExplanation::penalty) of the match, plus its
+/// [`substring_bonus`](MatchExplanation::substring_bonus).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bonuses {
+    /// Base score awarded for every matched character.
+    pub base: usize,
+    /// Bonus for continuing a run of sequentially matched characters.
+    pub sequential: usize,
+    /// Number of sequentially matched characters immediately before this one.
+    pub sequential_count: usize,
+    /// Bonus for matching with the same case as the query.
+    pub same_case: usize,
+    /// Bonus for matching the first character of the target.
+    pub start_of_target: usize,
+    /// Bonus for matching a path separator.
+    pub path_separator: usize,
+    /// Bonus for matching a separator such as a space or underscore.
+    pub separator: usize,
+    /// Bonus for matching the first character after a separator.
+    pub after_separator: usize,
+    /// Bonus for matching the start of a word, such as a capital letter in camel case.
+    pub word_start: usize,
+    /// Bonus for matching the last character of the target.
+    pub end_of_target: usize,
+    /// Bonus for completing a run of matched characters that covers an entire word.
+    pub whole_word: usize,
+    /// Bonus for matching the last character of a query that matched contiguously.
+    pub contiguous_query: usize,
+    /// Bonus for matching within the final path component, when using
+    /// [`ScoringProfile::FilePath`](crate::ScoringProfile::FilePath).
+    pub basename: usize,
+}
+
+impl Bonuses {
+    /// Returns the score contributed by this character, saturating instead of overflowing.
+    #[inline]
+    pub fn total(&self) -> usize {
+        self.base
+            .saturating_add(self.sequential)
+            .saturating_add(self.same_case)
+            .saturating_add(self.start_of_target)
+            .saturating_add(self.path_separator)
+            .saturating_add(self.separator)
+            .saturating_add(self.after_separator)
+            .saturating_add(self.word_start)
+            .saturating_add(self.end_of_target)
+            .saturating_add(self.whole_word)
+            .saturating_add(self.contiguous_query)
+            .saturating_add(self.basename)
+    }
+}
+
+/// A query character that was matched, and the bonuses it was awarded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MatchedChar {
+    /// Character index into the query string.
+    pub query_index: usize,
+    /// Character index into the target string.
+    pub target_index: usize,
+    /// Bonuses awarded for matching this character.
+    pub bonuses: Bonuses,
+}
+
+/// Breakdown of the score of a match.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MatchExplanation {
+    /// Score of the match, as returned by [`FuzzyMatcher::fuzzy_match`].
+    pub score: usize,
+    /// Each matched character, in target order.
+    pub chars: Vec<MatchedChar>,
+    /// Number of query characters that were skipped as typos.
+    pub typos: usize,
+    /// Number of the `typos` that were mistyped as the character of an adjacent key in the
+    /// target, as configured by the `keyboard_layout` option.
+    pub adjacent_key_typos: usize,
+    /// Number of pairs of query characters that were matched in swapped order.
+    pub transpositions: usize,
+    /// Number of target characters skipped between the first and last matched characters,
+    /// not counting target characters that query characters were mistyped as.
+    pub gaps: usize,
+    /// Total penalty subtracted for typos, transpositions, gaps, the position of the first
+    /// matched character, and the `length_normalization` option.
+    pub penalty: usize,
+    /// Bonus added to the score because the query occurs in the target as a substring, as
+    /// configured by the `substring_bonus` option.
+    pub substring_bonus: usize,
+}
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Fuzzy match a string against a query string, returning a breakdown of the bonuses
+    /// that make up the score. Returns `None` if the query does not match the target string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut matcher = code_fuzzy_match::FuzzyMatcher::new();
+    /// let explanation = matcher.fuzzy_match_explain("camelCase", "cC").unwrap();
+    /// assert_eq!(explanation.chars[0].bonuses.start_of_target, 8);
+    /// assert_eq!(explanation.chars[1].bonuses.word_start, 2);
+    /// assert_eq!(
+    ///     explanation.chars.iter().map(|c| c.bonuses.total()).sum::<usize>(),
+    ///     explanation.score
+    /// );
+    /// ```
+    pub fn fuzzy_match_explain(&mut self, target: &str, query: &str) -> Option<MatchExplanation> {
+        self.load(target, query);
+        let score = self.compute(true, false)?;
+
+        let positions = &self.matrix.positions;
+        let query_indices = &self.matrix.query_indices;
+        let mut order: Vec<usize> = (0..positions.len()).collect();
+        order.sort_unstable_by_key(|idx| positions[*idx]);
+
+        let input = MatrixInput::with_model(
+            &self.target_classes,
+            self.query_chars.len(),
+            &self.options,
+            &self.model,
+        );
+        let mut chars = Vec::with_capacity(order.len());
+        let mut seq_match_count = 0;
+        let mut last_position = None;
+        for idx in order {
+            let position = positions[idx];
+            let query_index = query_indices[idx];
+            let window_idx = position - self.window_offset;
+            if last_position.is_none_or(|last| last + 1 != position) {
+                seq_match_count = 0;
+            }
+            let kind = char_match_kind(
+                self.query_chars[query_index],
+                self.target_chars[window_idx],
+                &self.options,
+            )?;
+            chars.push(MatchedChar {
+                query_index,
+                target_index: self.target_index(position),
+                bonuses: char_bonuses(&input, window_idx, seq_match_count, kind),
+            });
+            seq_match_count += 1;
+            last_position = Some(position);
+        }
+
+        let typos = self.query_chars.len() - positions.len();
+        let near_misses = &self.matrix.near_misses;
+        let transpositions = positions
+            .windows(2)
+            .filter(|pair| pair[1] < pair[0])
+            .count();
+
+        // Mistyped target characters are not gaps
+        let mut consumed: Vec<usize> = positions
+            .iter()
+            .map(|position| position - self.window_offset)
+            .chain(near_misses.iter().copied())
+            .collect();
+        consumed.sort_unstable();
+        let first_position = consumed.first().copied().unwrap_or(0);
+        let gaps: usize = consumed.windows(2).map(|pair| pair[1] - pair[0] - 1).sum();
+        let penalty = [
+            (typos - near_misses.len()).saturating_mul(self.options.typo_penalty),
+            near_misses
+                .len()
+                .saturating_mul(self.options.adjacent_key_penalty),
+            transpositions.saturating_mul(self.options.transposition_penalty),
+            gaps.saturating_mul(self.options.gap_penalty),
+            leading_gap_penalty(&self.options, first_position),
+        ]
+        .into_iter()
+        .fold(0, usize::saturating_add);
+        let total = chars
+            .iter()
+            .map(|c| c.bonuses.total())
+            .fold(0, usize::saturating_add);
+        let sum = signed(total).saturating_sub(signed(penalty));
+        let normalized = normalize_length(
+            &self.options,
+            sum,
+            self.target_chars.len(),
+            self.query_chars.len(),
+        );
+        Some(MatchExplanation {
+            score,
+            chars,
+            typos,
+            adjacent_key_typos: near_misses.len(),
+            transpositions,
+            gaps,
+            penalty: penalty.saturating_add((sum - normalized) as usize),
+            substring_bonus: self.substring_bonus(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FuzzyMatcher, KeyboardLayout, MatchOptions};
+
+    #[test]
+    fn test_explain() {
+        const TARGET: &str = "The quick brown fox jumps over the lazy dog.";
+        for options in [
+            MatchOptions::default(),
+            MatchOptions {
+                bidirectional: true,
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                max_typos: 1,
+                transpositions: true,
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                gap_penalty: 1,
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                gap_penalty: 1,
+                bidirectional: true,
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                leading_gap_penalty: 1,
+                max_leading_gap_penalty: 12,
+                bidirectional: true,
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                whole_word_bonus: 10,
+                contiguous_query_bonus: 20,
+                max_typos: 1,
+                transpositions: true,
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                gap_penalty: 1,
+                leading_gap_penalty: 2,
+                max_typos: 1,
+                transpositions: true,
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                substring_bonus: 100,
+                gap_penalty: 1,
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                length_normalization: 8,
+                gap_penalty: 1,
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                max_typos: 2,
+                keyboard_layout: Some(KeyboardLayout::qwerty()),
+                gap_penalty: 1,
+                leading_gap_penalty: 2,
+                transpositions: true,
+                ..MatchOptions::default()
+            },
+        ] {
+            let mut matcher = FuzzyMatcher::with_options(options);
+            for query in [
+                "fox",
+                "The quick",
+                "jmp the do",
+                "xz",
+                "ee",
+                "qiuck",
+                "rhe quock",
+            ] {
+                let score = matcher.fuzzy_match(TARGET, query);
+                let explanation = matcher.fuzzy_match_explain(TARGET, query);
+                assert_eq!(explanation.as_ref().map(|e| e.score), score);
+                if let Some(explanation) = explanation {
+                    let total: usize = explanation.chars.iter().map(|c| c.bonuses.total()).sum();
+                    assert_eq!(
+                        total.saturating_sub(explanation.penalty).max(1)
+                            + explanation.substring_bonus,
+                        explanation.score,
+                        "query = {:?}",
+                        query
+                    );
+                }
+            }
+        }
+
+        let mut matcher = FuzzyMatcher::new();
+        let explanation = matcher
+            .fuzzy_match_explain("src/my_file.rs", "/mf")
+            .unwrap();
+        assert_eq!(explanation.chars[0].bonuses.path_separator, 5);
+        assert_eq!(explanation.chars[1].bonuses.sequential_count, 1);
+        assert_eq!(explanation.chars[2].bonuses.after_separator, 2);
+        assert_eq!(explanation.chars[2].bonuses.same_case, 1);
+    }
+}
@@ -0,0 +1,183 @@
+//! Ranking boosts learned from the candidates that were chosen for each query.
+
+use crate::{FuzzyMatcher, ScoreModel};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+
+/// Counts of the candidates chosen for each query, used to boost the ranking of candidates
+/// that are frequently chosen for the same query.
+///
+/// A selection is recorded for every prefix of the query, so that a candidate chosen after
+/// typing `open` is also boosted while typing `op`. Queries are compared case-insensitively.
+/// Each selection adds the [`weight`](Self::weight) to the score of the candidate when it
+/// matches the query.
+///
+/// The feedback of a matcher is kept in [`FuzzyMatcher::feedback`], and can be saved and
+/// restored with the `serde` feature so that it is carried across sessions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SelectionFeedback {
+    boosts: BTreeMap<String, BTreeMap<String, usize>>,
+    weight: usize,
+}
+
+impl SelectionFeedback {
+    /// Creates empty feedback with a weight of four.
+    pub fn new() -> Self {
+        Self::with_weight(4)
+    }
+
+    /// Creates empty feedback with the given weight.
+    pub fn with_weight(weight: usize) -> Self {
+        SelectionFeedback {
+            boosts: BTreeMap::new(),
+            weight,
+        }
+    }
+
+    /// Returns the amount each selection adds to the score of the chosen candidate.
+    pub fn weight(&self) -> usize {
+        self.weight
+    }
+
+    /// Changes the amount each selection adds to the score of the chosen candidate.
+    pub fn set_weight(&mut self, weight: usize) {
+        self.weight = weight;
+    }
+
+    /// Records that `candidate` was chosen from the results for `query`.
+    pub fn record(&mut self, query: &str, candidate: &str) {
+        let query = query.to_lowercase();
+        for (idx, ch) in query.char_indices() {
+            let count = self
+                .boosts
+                .entry(String::from(&query[..idx + ch.len_utf8()]))
+                .or_default()
+                .entry(String::from(candidate))
+                .or_default();
+            *count = count.saturating_add(1);
+        }
+    }
+
+    /// Returns the boost to the score of `candidate` when matched against `query`.
+    pub fn boost(&self, query: &str, candidate: &str) -> usize {
+        self.boosts_for(query)
+            .map_or(0, |boosts| self.candidate_boost(boosts, candidate))
+    }
+
+    pub(crate) fn boosts_for(&self, query: &str) -> Option<&BTreeMap<String, usize>> {
+        if self.boosts.is_empty() {
+            return None;
+        }
+        self.boosts.get(&query.to_lowercase())
+    }
+
+    pub(crate) fn candidate_boost(
+        &self,
+        boosts: &BTreeMap<String, usize>,
+        candidate: &str,
+    ) -> usize {
+        boosts
+            .get(candidate)
+            .map_or(0, |count| count.saturating_mul(self.weight))
+    }
+
+    /// Forgets every recorded selection.
+    pub fn clear(&mut self) {
+        self.boosts.clear();
+    }
+
+    /// Returns true if no selections have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.boosts.is_empty()
+    }
+}
+
+impl Default for SelectionFeedback {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Records that `candidate` was chosen from the results for `query`, so that it ranks
+    /// higher for the same query in [`rank_items`](Self::rank_items) and the other ranking
+    /// methods. Scores of individual matches, such as from
+    /// [`fuzzy_match`](Self::fuzzy_match), are not affected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut matcher = code_fuzzy_match::FuzzyMatcher::new();
+    /// let commands = ["Open File", "Open Folder"];
+    /// let results = matcher.rank_items(commands, |c| c, "open f", false);
+    /// assert_eq!(results[0].item, "Open File");
+    ///
+    /// matcher.record_selection("open fo", "Open Folder");
+    /// let results = matcher.rank_items(commands, |c| c, "open f", false);
+    /// assert_eq!(results[0].item, "Open Folder");
+    /// ```
+    pub fn record_selection(&mut self, query: &str, candidate: &str) {
+        self.feedback.record(query, candidate);
+    }
+
+    /// Returns the selection feedback used to boost rankings.
+    pub fn feedback(&self) -> &SelectionFeedback {
+        &self.feedback
+    }
+
+    /// Replaces the selection feedback used to boost rankings, such as with feedback saved
+    /// in a previous session.
+    pub fn set_feedback(&mut self, feedback: SelectionFeedback) {
+        self.feedback = feedback;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FuzzyMatcher, SelectionFeedback};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_selection_feedback() {
+        let mut feedback = SelectionFeedback::new();
+        assert!(feedback.is_empty());
+        feedback.record("Src", "src/lib.rs");
+        feedback.record("sr", "src/lib.rs");
+        assert_eq!(feedback.boost("s", "src/lib.rs"), 8);
+        assert_eq!(feedback.boost("SR", "src/lib.rs"), 8);
+        assert_eq!(feedback.boost("src", "src/lib.rs"), 4);
+        assert_eq!(feedback.boost("srcl", "src/lib.rs"), 0);
+        assert_eq!(feedback.boost("src", "src/main.rs"), 0);
+
+        // Boosts are applied by ranking methods but not to individual scores
+        let targets = ["src/main.rs", "src/lib.rs", "src/list.rs"];
+        let mut matcher = FuzzyMatcher::new();
+        let ranked = |matcher: &mut FuzzyMatcher, positions| -> Vec<(&str, usize)> {
+            matcher
+                .rank_items(targets, |t| t, "li", positions)
+                .into_iter()
+                .map(|result| (result.item, result.score))
+                .collect()
+        };
+        let lib = matcher.fuzzy_match("src/lib.rs", "li").unwrap();
+        let list = matcher.fuzzy_match("src/list.rs", "li").unwrap();
+        assert_eq!(
+            ranked(&mut matcher, false),
+            [("src/lib.rs", lib), ("src/list.rs", list)]
+        );
+        matcher.record_selection("lis", "src/list.rs");
+        for positions in [false, true] {
+            assert_eq!(
+                ranked(&mut matcher, positions),
+                [("src/list.rs", list + 4), ("src/lib.rs", lib)]
+            );
+        }
+        assert_eq!(matcher.fuzzy_match("src/list.rs", "li"), Some(list));
+
+        matcher.set_feedback(SelectionFeedback::with_weight(0));
+        assert!(matcher.feedback().is_empty());
+        feedback.clear();
+        assert!(feedback.is_empty());
+    }
+}
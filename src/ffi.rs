@@ -0,0 +1,308 @@
+//! C API for embedding the matcher in C and C++ programs.
+//!
+//! The declarations are in `include/code_fuzzy_match.h`. Build the crate as a C library with
+//! `cargo rustc --release --features ffi --crate-type cdylib` (or `staticlib`).
+//!
+//! Matchers are created with [`cfm_matcher_new`] and must be destroyed with
+//! [`cfm_matcher_free`]. A matcher must not be used from more than one thread at a time.
+//! Strings are passed as a pointer to UTF-8 bytes and a length in bytes, and do not need
+//! to be null terminated. Strings that are not valid UTF-8 never match. Targets held as
+//! UTF-16 can be matched with [`cfm_fuzzy_match_utf16`]. The library does not keep any
+//! pointer passed to it after a call returns.
+
+use crate::FuzzyMatcher;
+use alloc::boxed::Box;
+
+/// Converts a pointer and length from C into a string, returning `None` if the bytes are
+/// not valid UTF-8.
+///
+/// # Safety
+///
+/// `ptr` must point to `len` readable bytes, or `len` must be zero.
+unsafe fn str_from_raw<'a>(ptr: *const u8, len: usize) -> Option<&'a str> {
+    if len == 0 {
+        return Some("");
+    }
+    core::str::from_utf8(core::slice::from_raw_parts(ptr, len)).ok()
+}
+
+/// Creates a matcher with the default options. The matcher must be destroyed with
+/// [`cfm_matcher_free`].
+#[no_mangle]
+pub extern "C" fn cfm_matcher_new() -> *mut FuzzyMatcher {
+    Box::into_raw(Box::new(FuzzyMatcher::new()))
+}
+
+/// Destroys a matcher created with [`cfm_matcher_new`]. Passing a null pointer does nothing.
+///
+/// # Safety
+///
+/// `matcher` must be null or a matcher returned by [`cfm_matcher_new`] that has not already
+/// been destroyed.
+#[no_mangle]
+pub unsafe extern "C" fn cfm_matcher_free(matcher: *mut FuzzyMatcher) {
+    if !matcher.is_null() {
+        drop(Box::from_raw(matcher));
+    }
+}
+
+/// Fuzzy match a string against a query string. Returns true and writes the score to
+/// `score` if the query matches the target, or returns false if it does not.
+///
+/// # Safety
+///
+/// `matcher` must be a valid matcher. `target` and `query` must point to `target_len` and
+/// `query_len` readable bytes. `score` must be null or point to writable memory for a
+/// `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn cfm_fuzzy_match(
+    matcher: *mut FuzzyMatcher,
+    target: *const u8,
+    target_len: usize,
+    query: *const u8,
+    query_len: usize,
+    score: *mut usize,
+) -> bool {
+    let matcher = &mut *matcher;
+    let result = match (
+        str_from_raw(target, target_len),
+        str_from_raw(query, query_len),
+    ) {
+        (Some(target), Some(query)) => matcher.fuzzy_match(target, query),
+        _ => None,
+    };
+    match result {
+        Some(result) => {
+            if !score.is_null() {
+                *score = result;
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+/// Fuzzy match a string against a query string, also returning the character index in the
+/// target of each matched character. Returns true and writes the score to `score` if the
+/// query matches the target, or returns false if it does not.
+///
+/// Up to `positions_capacity` character indices are written to `positions`, in ascending
+/// order. The total number of matched characters is written to `positions_len`, which may
+/// be larger than the capacity, in which case the call can be repeated with a larger
+/// buffer. A buffer with room for one index per character of the query is always enough.
+///
+/// # Safety
+///
+/// `matcher` must be a valid matcher. `target` and `query` must point to `target_len` and
+/// `query_len` readable bytes. `positions` must point to writable memory for
+/// `positions_capacity` values of type `size_t`, or `positions_capacity` must be zero.
+/// `score` and `positions_len` must be null or point to writable memory for a `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn cfm_fuzzy_match_positions(
+    matcher: *mut FuzzyMatcher,
+    target: *const u8,
+    target_len: usize,
+    query: *const u8,
+    query_len: usize,
+    score: *mut usize,
+    positions: *mut usize,
+    positions_capacity: usize,
+    positions_len: *mut usize,
+) -> bool {
+    let matcher = &mut *matcher;
+    let result = match (
+        str_from_raw(target, target_len),
+        str_from_raw(query, query_len),
+    ) {
+        (Some(target), Some(query)) => matcher.fuzzy_match_details(target, query),
+        _ => None,
+    };
+    let result = match result {
+        Some(result) => result,
+        None => return false,
+    };
+    write_match(
+        result.score,
+        &result.positions,
+        score,
+        positions,
+        positions_capacity,
+        positions_len,
+    );
+    true
+}
+
+/// Fuzzy match a UTF-16 string against a query string, returning the offset in UTF-16 code
+/// units of each matched character in the target, such as for editors that store text as
+/// UTF-16. Unpaired surrogates in the target are matched as U+FFFD replacement characters.
+/// Otherwise the same as [`cfm_fuzzy_match_positions`].
+///
+/// # Safety
+///
+/// `matcher` must be a valid matcher. `target` must point to `target_len` readable UTF-16
+/// code units, and `query` must point to `query_len` readable bytes. `positions` must point
+/// to writable memory for `positions_capacity` values of type `size_t`, or
+/// `positions_capacity` must be zero. `score` and `positions_len` must be null or point to
+/// writable memory for a `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn cfm_fuzzy_match_utf16(
+    matcher: *mut FuzzyMatcher,
+    target: *const u16,
+    target_len: usize,
+    query: *const u8,
+    query_len: usize,
+    score: *mut usize,
+    positions: *mut usize,
+    positions_capacity: usize,
+    positions_len: *mut usize,
+) -> bool {
+    let matcher = &mut *matcher;
+    let target = if target_len == 0 {
+        &[]
+    } else {
+        core::slice::from_raw_parts(target, target_len)
+    };
+    let result = match str_from_raw(query, query_len) {
+        Some(query) => matcher.fuzzy_match_utf16(target, query),
+        None => None,
+    };
+    let result = match result {
+        Some(result) => result,
+        None => return false,
+    };
+    write_match(
+        result.score,
+        &result.positions,
+        score,
+        positions,
+        positions_capacity,
+        positions_len,
+    );
+    true
+}
+
+/// Writes the score and positions of a match to the output parameters of a C call.
+///
+/// # Safety
+///
+/// The output parameters must be valid as described by [`cfm_fuzzy_match_positions`].
+unsafe fn write_match(
+    result_score: usize,
+    result_positions: &[usize],
+    score: *mut usize,
+    positions: *mut usize,
+    positions_capacity: usize,
+    positions_len: *mut usize,
+) {
+    if !score.is_null() {
+        *score = result_score;
+    }
+    if !positions_len.is_null() {
+        *positions_len = result_positions.len();
+    }
+    let count = result_positions.len().min(positions_capacity);
+    if count > 0 {
+        core::slice::from_raw_parts_mut(positions, count)
+            .copy_from_slice(&result_positions[..count]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        cfm_fuzzy_match, cfm_fuzzy_match_positions, cfm_fuzzy_match_utf16, cfm_matcher_free,
+        cfm_matcher_new,
+    };
+    use crate::FuzzyMatcher;
+
+    #[test]
+    fn test_ffi() {
+        let target = "the quick brown fox";
+        let query = "bro fox";
+        let expected = FuzzyMatcher::new()
+            .fuzzy_match_details(target, query)
+            .unwrap();
+
+        unsafe {
+            let matcher = cfm_matcher_new();
+            let mut score = 0;
+            assert!(cfm_fuzzy_match(
+                matcher,
+                target.as_ptr(),
+                target.len(),
+                query.as_ptr(),
+                query.len(),
+                &mut score,
+            ));
+            assert_eq!(score, expected.score);
+            assert!(!cfm_fuzzy_match(
+                matcher,
+                target.as_ptr(),
+                target.len(),
+                "cat".as_ptr(),
+                3,
+                &mut score,
+            ));
+
+            // Invalid UTF-8 never matches
+            let invalid = [b'f', 0xff];
+            assert!(!cfm_fuzzy_match(
+                matcher,
+                invalid.as_ptr(),
+                invalid.len(),
+                query.as_ptr(),
+                1,
+                core::ptr::null_mut(),
+            ));
+
+            // Positions that don't fit are counted but not written
+            let mut positions = [usize::MAX; 4];
+            let mut positions_len = 0;
+            assert!(cfm_fuzzy_match_positions(
+                matcher,
+                target.as_ptr(),
+                target.len(),
+                query.as_ptr(),
+                query.len(),
+                &mut score,
+                positions.as_mut_ptr(),
+                positions.len(),
+                &mut positions_len,
+            ));
+            assert_eq!(positions_len, expected.positions.len());
+            assert_eq!(positions, expected.positions[..4]);
+
+            let mut positions = [usize::MAX; 8];
+            assert!(cfm_fuzzy_match_positions(
+                matcher,
+                target.as_ptr(),
+                target.len(),
+                query.as_ptr(),
+                query.len(),
+                core::ptr::null_mut(),
+                positions.as_mut_ptr(),
+                positions.len(),
+                &mut positions_len,
+            ));
+            assert_eq!(positions[..positions_len], expected.positions);
+
+            // UTF-16 positions are offsets in code units
+            let utf16: alloc::vec::Vec<u16> = "😀 fox".encode_utf16().collect();
+            assert!(cfm_fuzzy_match_utf16(
+                matcher,
+                utf16.as_ptr(),
+                utf16.len(),
+                "fx".as_ptr(),
+                2,
+                core::ptr::null_mut(),
+                positions.as_mut_ptr(),
+                positions.len(),
+                &mut positions_len,
+            ));
+            assert_eq!(positions[..positions_len], [3, 5]);
+
+            cfm_matcher_free(matcher);
+            cfm_matcher_free(core::ptr::null_mut());
+        }
+    }
+}
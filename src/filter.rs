@@ -0,0 +1,169 @@
+//! Filtering of iterators and collections by fuzzy matching.
+
+use crate::{FuzzyMatcher, QueryMask, ScoreModel};
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+/// Iterator returned by [`FuzzyFilterExt::fuzzy_filter`].
+pub struct FuzzyFilter<'m, 'q, I, F> {
+    iter: I,
+    matcher: &'m mut FuzzyMatcher,
+    query: &'q str,
+    query_mask: QueryMask,
+    key: F,
+}
+
+impl<I, F> Iterator for FuzzyFilter<'_, '_, I, F>
+where
+    I: Iterator,
+    F: FnMut(&I::Item) -> &str,
+{
+    type Item = (I::Item, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for item in self.iter.by_ref() {
+            let target = (self.key)(&item);
+            if let Some(score) =
+                self.matcher
+                    .fuzzy_match_prefiltered(target, self.query, &self.query_mask)
+            {
+                return Some((item, score));
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.iter.size_hint().1)
+    }
+}
+
+/// Extension trait for filtering any iterator by fuzzy matching.
+pub trait FuzzyFilterExt: Iterator + Sized {
+    /// Filters the items of this iterator, keeping only those where the string returned by
+    /// `key` matches the query. The resulting iterator yields each matching item along with
+    /// its score, in the original order. Items are matched as the iterator is consumed, so
+    /// the input is never collected into memory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use code_fuzzy_match::{FuzzyFilterExt, FuzzyMatcher};
+    ///
+    /// struct File {
+    ///     name: String,
+    /// }
+    ///
+    /// let files = vec![
+    ///     File { name: "main.rs".into() },
+    ///     File { name: "README.md".into() },
+    /// ];
+    /// let mut matcher = FuzzyMatcher::new();
+    /// let matches: Vec<_> = files
+    ///     .iter()
+    ///     .fuzzy_filter(&mut matcher, "rs", |file| &file.name)
+    ///     .map(|(file, _)| file.name.as_str())
+    ///     .collect();
+    /// assert_eq!(matches, ["main.rs"]);
+    /// ```
+    fn fuzzy_filter<'m, 'q, F>(
+        self,
+        matcher: &'m mut FuzzyMatcher,
+        query: &'q str,
+        key: F,
+    ) -> FuzzyFilter<'m, 'q, Self, F>
+    where
+        F: FnMut(&Self::Item) -> &str,
+    {
+        FuzzyFilter {
+            iter: self,
+            query_mask: matcher.query_mask(query),
+            matcher,
+            query,
+            key,
+        }
+    }
+}
+
+impl<I: Iterator> FuzzyFilterExt for I {}
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Removes the items whose key does not match the query, keeping the remaining items in
+    /// place. If `sort_by_score` is set, the remaining items are then sorted from best to
+    /// worst match, with items of equal score kept in their original order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut items = vec!["Cargo.toml", "src/lib.rs", "README.md", "lib.rs"];
+    /// let mut matcher = code_fuzzy_match::FuzzyMatcher::new();
+    /// matcher.retain_matches(&mut items, |item| item, "lib", true);
+    /// assert_eq!(items, ["lib.rs", "src/lib.rs"]);
+    /// ```
+    pub fn retain_matches<T>(
+        &mut self,
+        items: &mut Vec<T>,
+        key: impl Fn(&T) -> &str,
+        query: &str,
+        sort_by_score: bool,
+    ) {
+        let mut matches: Vec<(T, usize)> = self.match_batch(core::mem::take(items), &key, query);
+        if sort_by_score {
+            matches.sort_by_key(|(_, score)| Reverse(*score));
+        }
+        items.extend(matches.into_iter().map(|(item, _)| item));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FuzzyFilterExt, FuzzyMatcher};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_fuzzy_filter() {
+        let mut matcher = FuzzyMatcher::new();
+        let lines = "fn main() {\n    println!(\"hello\");\n}\n".lines();
+        let matches: Vec<_> = lines
+            .fuzzy_filter(&mut matcher, "print", |line| line)
+            .collect();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].0, "    println!(\"hello\");");
+        assert_eq!(
+            Some(matches[0].1),
+            FuzzyMatcher::new().fuzzy_match(matches[0].0, "print")
+        );
+
+        // Items are matched lazily
+        let mut matched = 0;
+        let first = (0..)
+            .map(|idx| {
+                matched += 1;
+                if idx == 2 {
+                    "needle"
+                } else {
+                    "hay"
+                }
+            })
+            .fuzzy_filter(&mut matcher, "needle", |item| item)
+            .next();
+        assert_eq!(first.map(|(item, _)| item), Some("needle"));
+        assert_eq!(matched, 3);
+    }
+
+    #[test]
+    fn test_retain_matches() {
+        let mut matcher = FuzzyMatcher::new();
+        let original = vec![("b", "foo_b"), ("x", "bar"), ("a", "foo_a"), ("f", "foo")];
+
+        let mut items = original.clone();
+        matcher.retain_matches(&mut items, |item| item.1, "foo", false);
+        assert_eq!(items, [("b", "foo_b"), ("a", "foo_a"), ("f", "foo")]);
+
+        // Equal scores keep their original order
+        let mut items = original.clone();
+        matcher.retain_matches(&mut items, |item| item.1, "foo", true);
+        assert_eq!(items, [("f", "foo"), ("b", "foo_b"), ("a", "foo_a")]);
+    }
+}
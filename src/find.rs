@@ -0,0 +1,108 @@
+//! Finding every match of a query within a large text, such as for fuzzy find in file.
+
+use crate::{FuzzyMatcher, ScoreModel};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// A match of a query within one line of a larger text, as found by
+/// [`FuzzyMatcher::find_all`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Occurrence {
+    /// Zero based index of the line that matched.
+    pub line: usize,
+    /// Score of the match against the line. Scores are comparable with those returned by
+    /// [`FuzzyMatcher::fuzzy_match`] for the line.
+    pub score: usize,
+    /// Byte ranges into the full text of each run of consecutive matched characters, in
+    /// order.
+    pub ranges: Vec<Range<usize>>,
+}
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Fuzzy match each line of a text against a query string, returning every line that
+    /// matches with a score of at least `min_score`, in the order they appear in the text.
+    /// Lines end with `\n` or `\r\n`, and the line ending is not part of the match. Options
+    /// such as `truncation` apply to each line, which limits the time taken by very long
+    /// lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut matcher = code_fuzzy_match::FuzzyMatcher::new();
+    /// let text = "fn main() {\n    read_config();\n}\n\nfn read_config() {}\n";
+    /// let found = matcher.find_all(text, "rconf", 1);
+    /// assert_eq!(found.len(), 2);
+    /// assert_eq!(found[1].line, 4);
+    /// assert_eq!(&text[found[1].ranges[0].clone()], "r");
+    /// assert_eq!(&text[found[1].ranges[1].clone()], "conf");
+    /// ```
+    pub fn find_all(&mut self, text: &str, query: &str, min_score: usize) -> Vec<Occurrence> {
+        let mut occurrences = Vec::new();
+        let mut line_start = 0;
+        for (line_idx, line) in text.split_inclusive('\n').enumerate() {
+            let start = line_start;
+            line_start += line.len();
+            let line = line.strip_suffix('\n').unwrap_or(line);
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            let Some(result) = self.fuzzy_match_details(line, query) else {
+                continue;
+            };
+            if result.score < min_score {
+                continue;
+            }
+
+            let mut ranges: Vec<Range<usize>> = Vec::new();
+            let mut positions = result.positions.iter().peekable();
+            for (char_idx, (byte_idx, c)) in line.char_indices().enumerate() {
+                if positions.next_if_eq(&&char_idx).is_none() {
+                    if positions.peek().is_none() {
+                        break;
+                    }
+                    continue;
+                }
+                let range = start + byte_idx..start + byte_idx + c.len_utf8();
+                match ranges.last_mut() {
+                    Some(last) if last.end == range.start => last.end = range.end,
+                    _ => ranges.push(range),
+                }
+            }
+            occurrences.push(Occurrence {
+                line: line_idx,
+                score: result.score,
+                ranges,
+            });
+        }
+        occurrences
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FuzzyMatcher;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_find_all() {
+        let text = "fn read_config() {\r\n    let config = read();\r\n}\r\n// Grüße conf\r\nrconf";
+        let mut matcher = FuzzyMatcher::new();
+        let found = matcher.find_all(text, "rconf", 1);
+        assert_eq!(found.iter().map(|o| o.line).collect::<Vec<_>>(), [0, 3, 4]);
+        assert_eq!(
+            Some(found[0].score),
+            matcher.fuzzy_match("fn read_config() {", "rconf")
+        );
+        assert_eq!(found[1].ranges.len(), 2);
+        assert_eq!(&text[found[1].ranges[0].clone()], "r");
+        assert_eq!(&text[found[1].ranges[1].clone()], "conf");
+        assert_eq!(found[2].ranges, vec![text.len() - 5..text.len()]);
+
+        // Weak matches are left out
+        let min_score = found[2].score;
+        let strong = matcher.find_all(text, "rconf", min_score);
+        assert!(strong.iter().all(|o| o.score >= min_score));
+        assert!(strong.len() < found.len());
+        assert!(matcher.find_all("", "rconf", 0).is_empty());
+    }
+}
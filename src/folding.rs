@@ -0,0 +1,92 @@
+//! Full Unicode case folding for the `full_case_folding` option.
+
+use alloc::vec::Vec;
+use caseless::Caseless;
+use unicode_normalization::UnicodeNormalization;
+
+/// Appends the characters that a character folds to, after compatibility normalization and
+/// full case folding. A character may fold to several characters, such as `'ß'` to `"ss"`
+/// and `'ﬁ'` to `"fi"`.
+pub(crate) fn fold(c: char, out: &mut Vec<char>) {
+    let start = out.len();
+    out.extend(core::iter::once(c).nfkc().default_case_fold().nfkc());
+    if out.len() == start {
+        out.push(c);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CandidateSet, FuzzyMatcher, MatchConstraints, MatchOptions, EXACT_MATCH_SCORE};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_full_case_folding() {
+        assert!(crate::fuzzy_match("Straße", "strasse").is_none());
+
+        let options = MatchOptions {
+            full_case_folding: true,
+            ..MatchOptions::default()
+        };
+        let mut matcher = FuzzyMatcher::with_options(options.clone());
+        matcher.set_target_cache_capacity(4);
+        for _ in 0..2 {
+            let result = matcher.fuzzy_match_details("Straße", "strasse").unwrap();
+            assert_eq!(result.positions, [0, 1, 2, 3, 4, 5]);
+        }
+        let result = matcher.fuzzy_match_details("STRASSE", "straße").unwrap();
+        assert_eq!(result.positions, [0, 1, 2, 3, 4, 5, 6]);
+        let result = matcher.fuzzy_match_details("my ﬁle", "file").unwrap();
+        assert_eq!(result.positions, [3, 4, 5]);
+        assert!(matcher.fuzzy_match("ＦＩＬＥ", "file").is_some());
+        let utf16: Vec<u16> = "große".encode_utf16().collect();
+        assert_eq!(
+            matcher
+                .fuzzy_match_utf16(&utf16, "grosse")
+                .unwrap()
+                .positions,
+            [0, 1, 2, 3, 4]
+        );
+
+        let explanation = matcher.fuzzy_match_explain("Straße", "strasse").unwrap();
+        let target_indices: Vec<usize> = explanation.chars.iter().map(|c| c.target_index).collect();
+        assert_eq!(target_indices, [0, 1, 2, 3, 4, 4, 5]);
+        assert_eq!(
+            Some(explanation.score),
+            matcher.fuzzy_match("Straße", "strasse")
+        );
+
+        // Constraints refer to characters of the target
+        let constraints = MatchConstraints {
+            excluded: vec![4],
+            ..MatchConstraints::default()
+        };
+        assert!(matcher
+            .fuzzy_match_constrained("Straße", "strasse", &constraints)
+            .is_none());
+        let constraints = MatchConstraints {
+            required: vec![4],
+            ..MatchConstraints::default()
+        };
+        assert!(matcher
+            .fuzzy_match_constrained("Straße", "ss", &constraints)
+            .is_some());
+
+        let mut set = CandidateSet::with_options(options.clone());
+        set.insert("Maßstab");
+        set.insert("Masse");
+        let results = set.query("massst", 10);
+        assert_eq!(results.len(), 1);
+        assert_eq!(set.get(results[0].item), Some("Maßstab"));
+
+        matcher.set_options(MatchOptions {
+            pin_exact_matches: true,
+            ..options
+        });
+        assert_eq!(
+            matcher.fuzzy_match("Straße", "STRASSE"),
+            Some(EXACT_MATCH_SCORE)
+        );
+    }
+}
@@ -0,0 +1,266 @@
+//! Ranking boosts for candidates that were selected frequently and recently.
+
+use crate::{FuzzyMatcher, ScoreModel, Scored};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Reverse;
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+/// Selections of a single candidate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct FrecencyEntry {
+    count: usize,
+    last_used: u64,
+}
+
+/// Tracks how often and how recently each candidate was selected, to boost the ranking of
+/// candidates that are used frequently and recently, such as in a command palette.
+///
+/// Candidates are identified by a key of type `K`, such as a command ID or a path. Times
+/// are given by the caller in seconds, from any fixed starting point such as the Unix
+/// epoch, so that the store works without a system clock and can be saved and loaded with
+/// the `serde` feature.
+///
+/// The frecency of a candidate is the number of times it was selected, weighted by how
+/// long ago it was last selected: 16 within the last hour, 8 within the last day, 2 within
+/// the last week, and 1 after that. It is multiplied by the [`weight`](Self::weight) and
+/// added to the fuzzy match score by [`boost`](Self::boost).
+///
+/// # Examples
+///
+/// ```
+/// use code_fuzzy_match::{Frecency, FuzzyMatcher};
+///
+/// let commands = ["Open File", "Open Folder", "Open Recent"];
+/// let mut frecency: Frecency<String> = Frecency::new();
+/// frecency.record("Open Recent".to_string(), 1_000);
+///
+/// let mut matcher = FuzzyMatcher::new();
+/// let results =
+///     matcher.rank_items_frecent(commands, |c| c, |c| *c, "open", &frecency, 2_000);
+/// assert_eq!(results[0].item, "Open Recent");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "K: serde::Serialize",
+        deserialize = "K: Ord + serde::Deserialize<'de>"
+    ))
+)]
+pub struct Frecency<K> {
+    entries: BTreeMap<K, FrecencyEntry>,
+    weight: usize,
+}
+
+impl<K: Ord> Frecency<K> {
+    /// Creates an empty store with a weight of one.
+    pub fn new() -> Self {
+        Self::with_weight(1)
+    }
+
+    /// Creates an empty store with the given weight. Higher weights let frecency outrank
+    /// larger differences in match quality.
+    pub fn with_weight(weight: usize) -> Self {
+        Frecency {
+            entries: BTreeMap::new(),
+            weight,
+        }
+    }
+
+    /// Returns the amount each point of frecency adds to the score of a match.
+    pub fn weight(&self) -> usize {
+        self.weight
+    }
+
+    /// Changes the amount each point of frecency adds to the score of a match.
+    pub fn set_weight(&mut self, weight: usize) {
+        self.weight = weight;
+    }
+
+    /// Records that the candidate was selected at time `now`, in seconds.
+    pub fn record(&mut self, id: K, now: u64) {
+        let entry = self.entries.entry(id).or_insert(FrecencyEntry {
+            count: 0,
+            last_used: now,
+        });
+        entry.count += 1;
+        entry.last_used = entry.last_used.max(now);
+    }
+
+    /// Returns the frecency of the candidate at time `now`, or zero if it was never
+    /// selected.
+    pub fn frecency<Q>(&self, id: &Q, now: u64) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let Some(entry) = self.entries.get(id) else {
+            return 0;
+        };
+        let age = now.saturating_sub(entry.last_used);
+        let recency = if age < HOUR {
+            16
+        } else if age < DAY {
+            8
+        } else if age < WEEK {
+            2
+        } else {
+            1
+        };
+        entry.count.saturating_mul(recency)
+    }
+
+    /// Adds the weighted frecency of the candidate at time `now` to a fuzzy match score.
+    pub fn boost<Q>(&self, score: usize, id: &Q, now: u64) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        score.saturating_add(self.frecency(id, now).saturating_mul(self.weight))
+    }
+
+    /// Forgets the selections of a candidate.
+    pub fn remove<Q>(&mut self, id: &Q)
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.entries.remove(id);
+    }
+
+    /// Forgets the candidates that were not selected within `max_age` seconds of `now`, to
+    /// keep the store from growing without bound.
+    pub fn prune(&mut self, now: u64, max_age: u64) {
+        self.entries
+            .retain(|_, entry| now.saturating_sub(entry.last_used) <= max_age);
+    }
+
+    /// Returns the number of candidates that have been selected.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if no candidates have been selected.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<K: Ord> Default for Frecency<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Fuzzy match a string taken from each item against a query string, returning the
+    /// items that match from best to worst after boosting each score with the frecency of
+    /// the item at time `now`, and with the boosts from selections recorded with
+    /// [`record_selection`](Self::record_selection). The `id` function returns the key of
+    /// the item in `frecency`. Items with the same boosted score are kept in their original
+    /// order.
+    pub fn rank_items_frecent<T, K, Q>(
+        &mut self,
+        items: impl IntoIterator<Item = T>,
+        key: impl Fn(&T) -> &str,
+        id: impl Fn(&T) -> &Q,
+        query: &str,
+        frecency: &Frecency<K>,
+        now: u64,
+    ) -> Vec<Scored<T>>
+    where
+        K: Ord + Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let matches: Vec<(T, usize)> = self.match_batch(items, &key, query);
+        if self.options.natural_order(query) {
+            return matches
+                .into_iter()
+                .map(|(item, score)| Scored {
+                    item,
+                    score,
+                    positions: None,
+                })
+                .collect();
+        }
+        let mut results: Vec<Scored<T>> = matches
+            .into_iter()
+            .map(|(item, score)| Scored {
+                score: frecency.boost(score, id(&item), now),
+                item,
+                positions: None,
+            })
+            .collect();
+        if let Some(boosts) = self.feedback.boosts_for(query) {
+            for result in &mut results {
+                let boost = self.feedback.candidate_boost(boosts, key(&result.item));
+                result.score = result.score.saturating_add(boost);
+            }
+        }
+        results.sort_by_key(|result| Reverse(result.score));
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DAY, HOUR, WEEK};
+    use crate::{fuzzy_match, Frecency, FuzzyMatcher};
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_frecency() {
+        let mut frecency: Frecency<String> = Frecency::new();
+        assert!(frecency.is_empty());
+        frecency.record("src/lib.rs".into(), 0);
+        frecency.record("src/lib.rs".into(), 10);
+        frecency.record("src/main.rs".into(), 0);
+        assert_eq!(frecency.len(), 2);
+
+        // Frecency decays with time since the last selection
+        assert_eq!(frecency.frecency("src/lib.rs", 10), 32);
+        assert_eq!(frecency.frecency("src/lib.rs", 10 + HOUR), 16);
+        assert_eq!(frecency.frecency("src/lib.rs", 10 + DAY), 4);
+        assert_eq!(frecency.frecency("src/lib.rs", 10 + WEEK), 2);
+        assert_eq!(frecency.frecency("src/other.rs", 10), 0);
+        frecency.set_weight(3);
+        assert_eq!(frecency.boost(5, "src/main.rs", HOUR), 5 + 3 * 8);
+        assert_eq!(frecency.boost(5, "src/other.rs", HOUR), 5);
+
+        // Frecency reorders matches of similar quality, but doesn't let poor matches win
+        let targets = ["src/main.rs", "src/lib.rs", "src/list.rs", "tests/ls.rs"];
+        let mut matcher = FuzzyMatcher::new();
+        let ranked = |frecency: &Frecency<String>, matcher: &mut FuzzyMatcher| -> Vec<&str> {
+            matcher
+                .rank_items_frecent(targets, |t| t, |t| *t, "li", frecency, WEEK)
+                .iter()
+                .map(|result| result.item)
+                .collect()
+        };
+        let mut recent = Frecency::new();
+        recent.record(String::from("src/list.rs"), WEEK - 1);
+        assert_eq!(
+            ranked(&Frecency::new(), &mut matcher),
+            ["src/lib.rs", "src/list.rs"]
+        );
+        assert_eq!(ranked(&recent, &mut matcher), ["src/list.rs", "src/lib.rs"]);
+        let results = matcher.rank_items_frecent(targets, |t| t, |t| *t, "li", &recent, WEEK);
+        assert_eq!(
+            results[0].score,
+            fuzzy_match("src/list.rs", "li").unwrap() + 16
+        );
+
+        frecency.prune(10 + DAY, DAY);
+        assert_eq!(frecency.len(), 1);
+        frecency.remove("src/lib.rs");
+        assert!(frecency.is_empty());
+    }
+}
@@ -0,0 +1,238 @@
+//! Matching at the level of extended grapheme clusters.
+//!
+//! Emoji with modifiers, flags, and combining sequences in complex scripts are made up of
+//! several `char`s. Matching them one `char` at a time can produce partial matches that split
+//! a cluster that the user sees as a single character. This mode segments both the target
+//! and the query into extended grapheme clusters and only matches whole clusters.
+
+use crate::matrix::{contains_query, CharClass, MatrixInput, UnitMatch};
+use crate::score::RawScore;
+use crate::{char_matches, FuzzyMatcher, MatchOptions, ScoreModel};
+use alloc::vec::Vec;
+use core::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Result of a grapheme cluster aware fuzzy match.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GraphemeMatch {
+    /// Score of the match. Scores are comparable with those returned by
+    /// [`FuzzyMatcher::fuzzy_match`].
+    pub score: usize,
+    /// Byte ranges into the target string of each grapheme cluster that was matched, in
+    /// order. There is one range for each grapheme cluster in the query, except for clusters
+    /// that were skipped as typos.
+    pub ranges: Vec<Range<usize>>,
+}
+
+/// Buffers holding the grapheme cluster segmentation of the current target and query.
+#[derive(Default)]
+pub(crate) struct GraphemeBuffers {
+    target_clusters: Vec<Range<usize>>,
+    query_clusters: Vec<Range<usize>>,
+}
+
+fn cluster_match_kind(
+    query_cluster: &str,
+    target_cluster: &str,
+    options: &MatchOptions,
+) -> Option<UnitMatch> {
+    if query_cluster == target_cluster {
+        return Some(UnitMatch::Exact);
+    }
+
+    // Clusters match if they have the same number of characters and each of the characters
+    // match using the same rules as the character based matcher.
+    let fold = |c| {
+        if options.kana_folding {
+            crate::kana::fold(c)
+        } else {
+            c
+        }
+    };
+    let mut query_chars = query_cluster.chars().map(fold);
+    let mut target_chars = target_cluster.chars().map(fold);
+    let mut exact = true;
+    loop {
+        match (query_chars.next(), target_chars.next()) {
+            (None, None) if exact => return Some(UnitMatch::Exact),
+            (None, None) => return Some(UnitMatch::Folded),
+            (Some(q), Some(t)) if q == t => (),
+            (Some(q), Some(t)) if char_matches(q, t, options) => exact = false,
+            _ => return None,
+        }
+    }
+}
+
+fn segment(text: &str, clusters: &mut Vec<Range<usize>>) {
+    clusters.clear();
+    clusters.extend(
+        text.grapheme_indices(true)
+            .map(|(start, cluster)| start..start + cluster.len()),
+    );
+}
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Fuzzy match a string against a query string, treating each extended grapheme cluster
+    /// as a single character. Returns the score along with the byte ranges of the matched
+    /// clusters in the target, or `None` if the query does not match the target string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut matcher = code_fuzzy_match::FuzzyMatcher::new();
+    /// let result = matcher.fuzzy_match_graphemes("thumbs 👍🏽 up", "👍🏽").unwrap();
+    /// assert_eq!(result.ranges, vec![7..15]);
+    ///
+    /// // The skin tone modifier is part of the cluster, so the bare emoji does not match.
+    /// assert!(matcher.fuzzy_match_graphemes("thumbs 👍🏽 up", "👍").is_none());
+    /// ```
+    pub fn fuzzy_match_graphemes(&mut self, target: &str, query: &str) -> Option<GraphemeMatch> {
+        let window = self.truncate(target);
+        let window_start = window.start;
+        let target = &target[window];
+
+        let buffers = &mut self.grapheme_buffers;
+        segment(target, &mut buffers.target_clusters);
+        segment(query, &mut buffers.query_clusters);
+
+        // Classify each cluster by its first character, which is the base character of
+        // the cluster.
+        self.target_classes.clear();
+        self.target_classes.extend(
+            buffers
+                .target_clusters
+                .iter()
+                .map(|range| CharClass::of(target[range.clone()].chars().next().unwrap_or(' '))),
+        );
+
+        crate::separators::mark_path_separators(
+            target,
+            buffers.target_clusters.iter().map(|range| range.start),
+            &mut self.target_classes,
+            &self.options.path_separators,
+        );
+
+        if self.options.digit_word_starts {
+            crate::digits::mark_digit_boundaries(
+                buffers
+                    .target_clusters
+                    .iter()
+                    .map(|range| target[range.clone()].chars().next().unwrap_or(' ')),
+                &mut self.target_classes,
+            );
+        }
+
+        #[cfg(feature = "unicode-words")]
+        if self.options.unicode_words {
+            crate::words::mark_word_starts(
+                target,
+                buffers.target_clusters.iter().map(|range| range.start),
+                &mut self.target_classes,
+            );
+        }
+
+        let target_clusters = &buffers.target_clusters;
+        let query_clusters = &buffers.query_clusters;
+        let options = &self.options;
+        let score = self.matrix.compute(
+            &MatrixInput {
+                record_positions: true,
+                ..MatrixInput::with_model(
+                    &self.target_classes,
+                    query_clusters.len(),
+                    &self.options,
+                    &self.model,
+                )
+            },
+            |query_idx, target_idx| {
+                cluster_match_kind(
+                    &query[query_clusters[query_idx].clone()],
+                    &target[target_clusters[target_idx].clone()],
+                    options,
+                )
+            },
+        )?;
+
+        let substring = options.substring_bonus != 0
+            && contains_query(
+                target_clusters.len(),
+                query_clusters.len(),
+                |query_idx, i| {
+                    cluster_match_kind(
+                        &query[query_clusters[query_idx].clone()],
+                        &target[target_clusters[i].clone()],
+                        options,
+                    )
+                    .is_some()
+                },
+            );
+        let score = RawScore::Sum {
+            sum: score,
+            bonus: if substring {
+                options.substring_bonus
+            } else {
+                0
+            },
+        }
+        .into_score();
+
+        Some(GraphemeMatch {
+            score,
+            ranges: self
+                .matrix
+                .positions
+                .iter()
+                .map(|idx| {
+                    let range = &target_clusters[*idx];
+                    range.start + window_start..range.end + window_start
+                })
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FuzzyMatcher;
+    use alloc::vec;
+
+    #[test]
+    fn test_grapheme_match() {
+        let mut matcher = FuzzyMatcher::new();
+
+        // Family emoji is a single cluster made of several characters joined by ZWJ
+        let target = "a👨‍👩‍👧b";
+        let result = matcher.fuzzy_match_graphemes(target, "a👨‍👩‍👧b").unwrap();
+        assert_eq!(result.ranges.len(), 3);
+        assert_eq!(&target[result.ranges[1].clone()], "👨‍👩‍👧");
+
+        // Partial clusters must not match
+        assert!(matcher.fuzzy_match_graphemes(target, "👨").is_none());
+
+        // Combining characters stay attached to their base character
+        let result = matcher
+            .fuzzy_match_graphemes("Cafe\u{301} au lait", "E\u{301}")
+            .unwrap();
+        assert_eq!(result.ranges, vec![3..6]);
+        assert!(matcher
+            .fuzzy_match_graphemes("Cafe\u{301}", "cafe")
+            .is_none());
+    }
+
+    #[test]
+    fn test_grapheme_score_matches_chars() {
+        // For targets without multi-character clusters, scores are the same as the
+        // character based matcher.
+        let mut matcher = FuzzyMatcher::new();
+        let target = "The quick brown fox jumps over the lazy dog.";
+        for query in ["fox", "The quick", "jmp the do", "xz"] {
+            assert_eq!(
+                matcher
+                    .fuzzy_match_graphemes(target, query)
+                    .map(|m| m.score),
+                matcher.fuzzy_match(target, query)
+            );
+        }
+    }
+}
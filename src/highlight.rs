@@ -0,0 +1,115 @@
+//! Highlighting of matched characters for display in terminals and web pages.
+
+use alloc::string::String;
+use core::fmt::Write;
+
+/// Calls `f` for each run of consecutive characters of `target`, with true for runs of
+/// characters at the given positions.
+fn for_each_run(target: &str, positions: &[usize], mut f: impl FnMut(&str, bool)) {
+    let mut positions = positions.iter().peekable();
+    let mut run_start = 0;
+    let mut run_matched = false;
+    for (char_idx, (byte_idx, _)) in target.char_indices().enumerate() {
+        let matched = positions.next_if_eq(&&char_idx).is_some();
+        if matched != run_matched {
+            f(&target[run_start..byte_idx], run_matched);
+            run_start = byte_idx;
+            run_matched = matched;
+        }
+    }
+    f(&target[run_start..], run_matched);
+}
+
+/// Returns `target` with the characters at the given positions wrapped in ANSI escape
+/// codes, for printing matches to a terminal. Positions are character indices in
+/// increasing order, such as the positions of a [`Match`](crate::Match). `style` holds the
+/// parameters of the escape code that starts each run of matched characters, such as
+/// `"1;31"` for bold red text, and each run ends with a reset.
+///
+/// # Examples
+///
+/// ```
+/// use code_fuzzy_match::highlight_ansi;
+///
+/// let mut matcher = code_fuzzy_match::FuzzyMatcher::new();
+/// let result = matcher.fuzzy_match_details("src/lib.rs", "slib").unwrap();
+/// assert_eq!(
+///     highlight_ansi("src/lib.rs", &result.positions, "1;31"),
+///     "\x1b[1;31ms\x1b[0mrc/\x1b[1;31mlib\x1b[0m.rs"
+/// );
+/// ```
+pub fn highlight_ansi(target: &str, positions: &[usize], style: &str) -> String {
+    let mut out = String::with_capacity(target.len());
+    for_each_run(target, positions, |run, matched| {
+        if !matched {
+            out.push_str(run);
+        } else if !run.is_empty() {
+            let _ = write!(out, "\x1b[{style}m{run}\x1b[0m");
+        }
+    });
+    out
+}
+
+/// Returns `target` escaped for HTML, with each run of characters at the given positions
+/// wrapped using a tag template. Positions are character indices in increasing order, such
+/// as the positions of a [`Match`](crate::Match). The escaped run of matched characters
+/// replaces the first `{}` in `template`, such as `"<mark>{}</mark>"`.
+///
+/// # Examples
+///
+/// ```
+/// use code_fuzzy_match::highlight_html;
+///
+/// assert_eq!(
+///     highlight_html("Vec<T>", &[0, 3, 4], "<b>{}</b>"),
+///     "<b>V</b>ec<b>&lt;T</b>&gt;"
+/// );
+/// ```
+pub fn highlight_html(target: &str, positions: &[usize], template: &str) -> String {
+    let (open, close) = template.split_once("{}").unwrap_or((template, ""));
+    let mut out = String::with_capacity(target.len());
+    for_each_run(target, positions, |run, matched| {
+        if run.is_empty() {
+            return;
+        }
+        if matched {
+            out.push_str(open);
+        }
+        for c in run.chars() {
+            match c {
+                '&' => out.push_str("&amp;"),
+                '<' => out.push_str("&lt;"),
+                '>' => out.push_str("&gt;"),
+                '"' => out.push_str("&quot;"),
+                '\'' => out.push_str("&#39;"),
+                _ => out.push(c),
+            }
+        }
+        if matched {
+            out.push_str(close);
+        }
+    });
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{highlight_ansi, highlight_html};
+
+    #[test]
+    fn test_highlight() {
+        assert_eq!(
+            highlight_ansi("Grüße", &[0, 2, 3], "4"),
+            "\x1b[4mG\x1b[0mr\x1b[4müß\x1b[0me"
+        );
+        assert_eq!(highlight_ansi("abc", &[], "1"), "abc");
+        assert_eq!(highlight_ansi("abc", &[0, 1, 2], "1"), "\x1b[1mabc\x1b[0m");
+        assert_eq!(highlight_ansi("", &[], "1"), "");
+
+        assert_eq!(
+            highlight_html("a&b \"c\"", &[0, 1, 5], "<mark class=\"m\">{}</mark>"),
+            "<mark class=\"m\">a&amp;</mark>b &quot;<mark class=\"m\">c</mark>&quot;"
+        );
+        assert_eq!(highlight_html("it's", &[3], "<b>"), "it&#39;<b>s");
+    }
+}
@@ -0,0 +1,624 @@
+//! Persistent index of preprocessed candidates for repeated top-k queries.
+
+use crate::matrix::CharClass;
+use crate::{CharMask, FuzzyMatcher, MatchOptions, Scored};
+use alloc::collections::{BTreeMap, BinaryHeap};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+use core::fmt;
+use core::ops::Range;
+
+/// Identifies the format of serialized indexes.
+const MAGIC: &[u8; 4] = b"CFMI";
+/// Version of the serialized format written by this version of the crate.
+const VERSION: u8 = 1;
+
+/// A candidate stored in an [`Index`].
+#[derive(Clone)]
+pub(crate) struct Entry {
+    /// Byte range of the candidate in the text buffer.
+    pub(crate) text: Range<usize>,
+    /// Range of the candidate in the class buffer, with one class per character.
+    pub(crate) classes: Range<usize>,
+    pub(crate) mask: CharMask,
+    pub(crate) removed: bool,
+}
+
+/// Error returned when loading a serialized [`Index`] fails.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IndexError {
+    /// The data is not a serialized index.
+    InvalidMagic,
+    /// The index was serialized with a newer, unsupported version of the format.
+    UnsupportedVersion(u8),
+    /// The data ended before the end of the index.
+    UnexpectedEnd,
+    /// The data is not valid for the format.
+    InvalidData,
+}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexError::InvalidMagic => write!(f, "not a serialized index"),
+            IndexError::UnsupportedVersion(version) => {
+                write!(f, "unsupported index format version {version}")
+            }
+            IndexError::UnexpectedEnd => write!(f, "unexpected end of index data"),
+            IndexError::InvalidData => write!(f, "invalid index data"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for IndexError {}
+
+/// Collection of candidates that are preprocessed once and queried many times, such as the
+/// identifiers of a large code base. Each candidate is stored along with the classification
+/// of its characters and a [`CharMask`], so queries skip decoding each candidate and reject
+/// most candidates without running the matcher.
+///
+/// Indexes can be saved with [`to_bytes`](Index::to_bytes) and loaded again with
+/// [`from_bytes`](Index::from_bytes) to avoid preprocessing the candidates on every run.
+///
+/// The `truncation` and `max_target_len` options are not supported by indexes and are
+/// ignored.
+///
+/// # Examples
+///
+/// ```
+/// use code_fuzzy_match::Index;
+///
+/// let mut index = Index::new();
+/// let lib = index.add("src/lib.rs");
+/// index.add("src/main.rs");
+/// index.add("Cargo.toml");
+///
+/// let bytes = index.to_bytes();
+/// let mut index = Index::from_bytes(&bytes).unwrap();
+/// let results = index.query("lib", 10);
+/// assert_eq!(results.len(), 1);
+/// assert_eq!(results[0].item, lib);
+/// assert_eq!(index.get(lib), Some("src/lib.rs"));
+/// ```
+pub struct Index {
+    matcher: FuzzyMatcher,
+    pub(crate) text: String,
+    pub(crate) classes: Vec<CharClass>,
+    pub(crate) entries: Vec<Entry>,
+}
+
+impl Index {
+    /// Creates an empty index.
+    pub fn new() -> Self {
+        Self::with_options(MatchOptions::default())
+    }
+
+    /// Creates an empty index that matches using the given options.
+    pub fn with_options(options: MatchOptions) -> Self {
+        Index {
+            matcher: FuzzyMatcher::with_options(MatchOptions {
+                truncation: None,
+                max_target_len: None,
+                ..options
+            }),
+            text: String::new(),
+            classes: Vec::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Returns the options used by this index.
+    pub fn options(&self) -> &MatchOptions {
+        self.matcher.options()
+    }
+
+    /// Changes the options used by this index. Candidates are preprocessed again if the
+    /// new options change how candidates are classified.
+    pub fn set_options(&mut self, options: MatchOptions) {
+        let reclassify = !self.same_classification(&options);
+        self.matcher.set_options(MatchOptions {
+            truncation: None,
+            max_target_len: None,
+            ..options
+        });
+        if reclassify {
+            let text = core::mem::take(&mut self.text);
+            let entries = core::mem::take(&mut self.entries);
+            self.classes.clear();
+            for entry in entries {
+                self.push(&text[entry.text], entry.removed);
+            }
+        }
+    }
+
+    /// Returns true if candidates are classified the same way with both sets of options.
+    fn same_classification(&self, options: &MatchOptions) -> bool {
+        #[cfg(feature = "unicode-words")]
+        if options.unicode_words != self.options().unicode_words {
+            return false;
+        }
+        options.digit_word_starts == self.options().digit_word_starts
+            && options.path_separators == self.options().path_separators
+    }
+
+    /// Number of candidates in the index, not counting removed candidates.
+    pub fn len(&self) -> usize {
+        self.entries.iter().filter(|entry| !entry.removed).count()
+    }
+
+    /// Returns true if the index has no candidates.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds a candidate to the index, returning its identifier. Identifiers are assigned in
+    /// increasing order and are preserved when the index is serialized.
+    pub fn add(&mut self, candidate: &str) -> usize {
+        self.push(candidate, false)
+    }
+
+    fn push(&mut self, candidate: &str, removed: bool) -> usize {
+        let candidate = if removed { "" } else { candidate };
+        let entry = self.entry(candidate, removed);
+        self.entries.push(entry);
+        self.entries.len() - 1
+    }
+
+    /// Replaces the text of a candidate, keeping its identifier. The old text is left in
+    /// the buffers until the index is serialized.
+    pub(crate) fn replace(&mut self, id: usize, candidate: &str) {
+        let entry = self.entry(candidate, false);
+        self.entries[id] = entry;
+    }
+
+    /// Appends a candidate to the buffers, returning its entry.
+    fn entry(&mut self, candidate: &str, removed: bool) -> Entry {
+        self.matcher.classify(candidate);
+        let text_start = self.text.len();
+        self.text.push_str(candidate);
+        let classes_start = self.classes.len();
+        self.classes.extend_from_slice(&self.matcher.target_classes);
+        Entry {
+            text: text_start..self.text.len(),
+            classes: classes_start..self.classes.len(),
+            mask: CharMask::new(candidate),
+            removed,
+        }
+    }
+
+    /// Removes a candidate from the index. Returns false if there is no candidate with the
+    /// given identifier. Identifiers of removed candidates are not reused.
+    pub fn remove(&mut self, id: usize) -> bool {
+        match self.entries.get_mut(id) {
+            Some(entry) if !entry.removed => {
+                entry.removed = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the candidate with the given identifier.
+    pub fn get(&self, id: usize) -> Option<&str> {
+        self.entries
+            .get(id)
+            .filter(|entry| !entry.removed)
+            .map(|entry| &self.text[entry.text.clone()])
+    }
+
+    /// Fuzzy match every candidate against a query string, returning the identifiers of the
+    /// `k` best matching candidates from best to worst. Candidates with the same score are
+    /// ordered by identifier.
+    pub fn query(&mut self, query: &str, k: usize) -> Vec<Scored<usize>> {
+        #[cfg(feature = "tracing")]
+        let trace = crate::trace::BatchTrace::start("index_query", query);
+        let query_mask = self.matcher.query_mask(query);
+        let mut best = TopMatches::new(k, self.entries.len());
+        // Score of each distinct loaded target, if targets are deduplicated
+        let mut scored: BTreeMap<Vec<(char, u8)>, Option<usize>> = BTreeMap::new();
+        for (id, entry) in self.entries.iter().enumerate() {
+            if entry.removed || !self.matcher.prefilter(&query_mask, &entry.mask) {
+                continue;
+            }
+            let target = &self.text[entry.text.clone()];
+            let classes = &self.classes[entry.classes.clone()];
+            self.matcher
+                .load_classified(target, classes.iter().copied(), query);
+            let score = if self.matcher.options.dedup_targets {
+                let loaded = self
+                    .matcher
+                    .target_chars
+                    .iter()
+                    .copied()
+                    .zip(
+                        self.matcher
+                            .target_classes
+                            .iter()
+                            .map(|c| class_to_byte(*c)),
+                    )
+                    .collect();
+                *scored
+                    .entry(loaded)
+                    .or_insert_with(|| self.matcher.compute(false, false))
+            } else {
+                self.matcher.compute(false, false)
+            };
+            if let Some(score) = score {
+                best.push(id, score);
+            }
+        }
+        let results = best.into_vec();
+        #[cfg(feature = "tracing")]
+        trace.finish(
+            self.entries.len(),
+            results.iter().map(|result| result.score),
+        );
+        results
+    }
+
+    /// Serializes the index to a compact byte format that can be loaded with
+    /// [`from_bytes`](Index::from_bytes). The options that affect how candidates are
+    /// classified are saved along with the candidates, but other options are not.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+
+        #[cfg(feature = "unicode-words")]
+        let flags = self.options().unicode_words as u8;
+        #[cfg(not(feature = "unicode-words"))]
+        let flags = 0;
+        out.push(flags | (self.options().digit_word_starts as u8) << 1);
+
+        let separators = &self.options().path_separators;
+        write_len(&mut out, separators.len());
+        for separator in separators {
+            write_len(&mut out, separator.len());
+            out.extend_from_slice(separator.as_bytes());
+        }
+
+        write_len(&mut out, self.entries.len());
+        for entry in &self.entries {
+            // Only the identifiers of removed candidates are kept
+            let (text, classes) = if entry.removed {
+                ("", &[][..])
+            } else {
+                (
+                    &self.text[entry.text.clone()],
+                    &self.classes[entry.classes.clone()],
+                )
+            };
+            out.push(entry.removed as u8);
+            write_len(&mut out, text.len());
+            out.extend_from_slice(text.as_bytes());
+            out.extend(classes.iter().map(|class| class_to_byte(*class)));
+            out.extend_from_slice(&entry.mask.ascii.to_le_bytes());
+            out.extend_from_slice(&entry.mask.non_ascii.to_le_bytes());
+        }
+        out
+    }
+
+    /// Loads an index serialized with [`to_bytes`](Index::to_bytes). The index uses the
+    /// default options, apart from the saved options that affect how candidates are
+    /// classified.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, IndexError> {
+        let mut reader = Reader { bytes };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(IndexError::InvalidMagic);
+        }
+        let version = reader.byte()?;
+        if version != VERSION {
+            return Err(IndexError::UnsupportedVersion(version));
+        }
+
+        let mut options = MatchOptions::default();
+        let flags = reader.byte()?;
+        #[cfg(feature = "unicode-words")]
+        {
+            options.unicode_words = flags & 1 != 0;
+        }
+        #[cfg(not(feature = "unicode-words"))]
+        if flags & 1 != 0 {
+            // Candidates were classified using word segmentation, which isn't available
+            return Err(IndexError::InvalidData);
+        }
+        options.digit_word_starts = flags & 2 != 0;
+        for _ in 0..reader.len()? {
+            let len = reader.len()?;
+            options.path_separators.push(reader.str(len)?.into());
+        }
+
+        let mut index = Index::with_options(options);
+        for _ in 0..reader.len()? {
+            let removed = match reader.byte()? {
+                0 => false,
+                1 => true,
+                _ => return Err(IndexError::InvalidData),
+            };
+            let len = reader.len()?;
+            let text = reader.str(len)?;
+
+            let text_start = index.text.len();
+            index.text.push_str(text);
+            let classes_start = index.classes.len();
+            for class in reader.take(text.chars().count())? {
+                index.classes.push(class_from_byte(*class)?);
+            }
+            let ascii = reader.u64()?;
+            let non_ascii = reader.u64()?;
+            index.entries.push(Entry {
+                text: text_start..index.text.len(),
+                classes: classes_start..index.classes.len(),
+                mask: CharMask { ascii, non_ascii },
+                removed,
+            });
+        }
+        if !reader.bytes.is_empty() {
+            return Err(IndexError::InvalidData);
+        }
+        Ok(index)
+    }
+
+    /// Writes the index to a writer in the format of [`to_bytes`](Index::to_bytes).
+    #[cfg(feature = "std")]
+    pub fn write_to(&self, mut writer: impl std::io::Write) -> std::io::Result<()> {
+        writer.write_all(&self.to_bytes())
+    }
+
+    /// Reads an index written by [`write_to`](Index::write_to).
+    #[cfg(feature = "std")]
+    pub fn read_from(mut reader: impl std::io::Read) -> std::io::Result<Self> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        Self::from_bytes(&bytes)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+    }
+}
+
+impl Default for Index {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Index {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.to_bytes())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Index {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct IndexVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for IndexVisitor {
+            type Value = Index;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a serialized index")
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, bytes: &[u8]) -> Result<Index, E> {
+                Index::from_bytes(bytes).map_err(E::custom)
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Index, A::Error> {
+                // Formats without native byte strings serialize bytes as a sequence
+                let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(byte) = seq.next_element()? {
+                    bytes.push(byte);
+                }
+                self.visit_bytes(&bytes)
+            }
+        }
+
+        deserializer.deserialize_bytes(IndexVisitor)
+    }
+}
+
+/// The best matches found by a query, keeping only the `k` best.
+pub(crate) struct TopMatches {
+    k: usize,
+    best: BinaryHeap<Reverse<Scored<usize>>>,
+}
+
+impl TopMatches {
+    /// Creates an empty set of matches for a query over `len` candidates.
+    pub(crate) fn new(k: usize, len: usize) -> Self {
+        TopMatches {
+            k,
+            best: BinaryHeap::with_capacity(k.min(len) + 1),
+        }
+    }
+
+    pub(crate) fn push(&mut self, id: usize, score: usize) {
+        if self.k == 0 {
+            return;
+        }
+        self.best.push(Reverse(Scored {
+            item: id,
+            score,
+            positions: None,
+        }));
+        if self.best.len() > self.k {
+            self.best.pop();
+        }
+    }
+
+    /// Returns the matches from best to worst.
+    pub(crate) fn into_vec(self) -> Vec<Scored<usize>> {
+        self.best
+            .into_sorted_vec()
+            .into_iter()
+            .map(|result| result.0)
+            .collect()
+    }
+}
+
+fn write_len(out: &mut Vec<u8>, len: usize) {
+    out.extend_from_slice(&(len as u64).to_le_bytes());
+}
+
+pub(crate) fn class_to_byte(class: CharClass) -> u8 {
+    match class {
+        CharClass::PathSeparator => 0,
+        CharClass::Separator => 1,
+        CharClass::Uppercase => 2,
+        CharClass::WordStart => 3,
+        CharClass::Other => 4,
+    }
+}
+
+pub(crate) fn class_from_byte(byte: u8) -> Result<CharClass, IndexError> {
+    match byte {
+        0 => Ok(CharClass::PathSeparator),
+        1 => Ok(CharClass::Separator),
+        2 => Ok(CharClass::Uppercase),
+        3 => Ok(CharClass::WordStart),
+        4 => Ok(CharClass::Other),
+        _ => Err(IndexError::InvalidData),
+    }
+}
+
+/// Reads values from serialized index data.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], IndexError> {
+        if self.bytes.len() < len {
+            return Err(IndexError::UnexpectedEnd);
+        }
+        let (value, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(value)
+    }
+
+    fn byte(&mut self) -> Result<u8, IndexError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u64(&mut self) -> Result<u64, IndexError> {
+        let bytes = self.take(8)?;
+        Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn len(&mut self) -> Result<usize, IndexError> {
+        usize::try_from(self.u64()?).map_err(|_| IndexError::InvalidData)
+    }
+
+    fn str(&mut self, len: usize) -> Result<&'a str, IndexError> {
+        core::str::from_utf8(self.take(len)?).map_err(|_| IndexError::InvalidData)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FuzzyMatcher, Index, IndexError, MappedIndex, MatchOptions};
+    use alloc::string::String;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_index() {
+        const CANDIDATES: &[&str] = &[
+            "FuzzyMatcher::fuzzy_match",
+            "MatchOptions",
+            "fuzzy_match_details",
+            "Index::query",
+            "rank_items",
+        ];
+        let options = MatchOptions {
+            path_separators: vec![String::from("::")],
+            ..MatchOptions::default()
+        };
+        let mut index = Index::with_options(options.clone());
+        for candidate in CANDIDATES {
+            index.add(candidate);
+        }
+
+        // Results are the same as matching each candidate
+        let mut matcher = FuzzyMatcher::with_options(options);
+        let mut expected: Vec<(usize, usize)> = CANDIDATES
+            .iter()
+            .enumerate()
+            .filter_map(|(id, c)| Some((id, matcher.fuzzy_match(c, "fzm")?)))
+            .collect();
+        expected.sort_by_key(|(id, score)| (usize::MAX - score, *id));
+        let results = index.query("fzm", 10);
+        assert_eq!(
+            results
+                .iter()
+                .map(|r| (r.item, r.score))
+                .collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(index.query("fzm", 1).len(), 1);
+        assert_eq!(index.query("fzm", 1)[0].item, expected[0].0);
+
+        assert!(index.remove(0));
+        assert!(!index.remove(0));
+        assert_eq!(index.len(), CANDIDATES.len() - 1);
+        assert!(index.query("fzm", 10).iter().all(|r| r.item != 0));
+
+        // Serialized indexes keep identifiers and classification options
+        let mut loaded = Index::from_bytes(&index.to_bytes()).unwrap();
+        assert_eq!(
+            loaded.options().path_separators,
+            index.options().path_separators
+        );
+        assert_eq!(loaded.get(0), None);
+        assert_eq!(loaded.get(3), Some("Index::query"));
+        assert_eq!(loaded.query("iq", 10), index.query("iq", 10));
+
+        assert_eq!(
+            Index::from_bytes(b"nope").err(),
+            Some(IndexError::InvalidMagic)
+        );
+        let bytes = index.to_bytes();
+        assert_eq!(
+            Index::from_bytes(&bytes[..bytes.len() - 1]).err(),
+            Some(IndexError::UnexpectedEnd)
+        );
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&index).unwrap();
+            let mut loaded: Index = serde_json::from_str(&json).unwrap();
+            assert_eq!(loaded.query("iq", 10), index.query("iq", 10));
+        }
+    }
+
+    #[test]
+    fn test_index_reclassify() {
+        let options = MatchOptions {
+            digit_word_starts: true,
+            ..MatchOptions::default()
+        };
+        let mut matcher = FuzzyMatcher::with_options(options.clone());
+        let expected = matcher.fuzzy_match("file2name", "fn");
+
+        // Changing an option that affects classification reclassifies existing candidates
+        let mut index = Index::new();
+        index.add("file2name");
+        index.set_options(options.clone());
+        assert_eq!(index.query("fn", 10)[0].score, expected.unwrap());
+
+        let mut loaded = Index::from_bytes(&index.to_bytes()).unwrap();
+        assert!(loaded.options().digit_word_starts);
+        assert_eq!(loaded.query("fn", 10), index.query("fn", 10));
+
+        let mut mapped = MappedIndex::new(index.to_mapped_bytes()).unwrap();
+        mapped.set_options(MatchOptions::default());
+        assert!(mapped.options().digit_word_starts);
+        assert_eq!(mapped.query("fn", 10), index.query("fn", 10));
+    }
+}
@@ -0,0 +1,96 @@
+//! Matching of queries as the initials of the words in the target.
+
+use crate::matrix::{is_word_start, CharClass, UnitMatch};
+use crate::{char_match_kind, FuzzyMatcher, ScoreModel};
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Match a query as the initials of words in the target, so that `"ofp"` matches
+    /// "Open File in Panel". Each query character must match the first character of a word,
+    /// in order, and words may be skipped. Returns a score that is higher for a more
+    /// confident match, or `None` if the query is not made up of initials of the target.
+    ///
+    /// Matching the initials of consecutive words scores higher than skipping words, and
+    /// matching the initials of every word in the target scores highest. These scores are
+    /// not comparable with those returned by [`fuzzy_match`](FuzzyMatcher::fuzzy_match).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut matcher = code_fuzzy_match::FuzzyMatcher::new();
+    /// let partial = matcher.match_initials("Open File in Panel", "ofp").unwrap();
+    /// let full = matcher.match_initials("Open File in Panel", "ofip").unwrap();
+    /// assert!(full > partial);
+    /// assert!(matcher.match_initials("Open File in Panel", "opf").is_none());
+    /// ```
+    pub fn match_initials(&mut self, target: &str, query: &str) -> Option<usize> {
+        self.load(target, query);
+        if self.query_chars.is_empty() {
+            return None;
+        }
+
+        let mut query_idx = 0;
+        let mut word_idx = 0;
+        let mut last_matched_word = None;
+        let mut score = 0;
+        for (target_idx, class) in self.target_classes.iter().enumerate() {
+            if matches!(class, CharClass::PathSeparator | CharClass::Separator)
+                || !is_word_start(&self.target_classes, target_idx)
+            {
+                continue;
+            }
+
+            // Match each query character against the earliest word that it can start.
+            // Matching greedily never misses a match, as any later word could be matched
+            // by the next query character instead.
+            if let Some(query_char) = self.query_chars.get(query_idx) {
+                if let Some(kind) =
+                    char_match_kind(*query_char, self.target_chars[target_idx], &self.options)
+                {
+                    score += 10;
+                    if kind == UnitMatch::Exact {
+                        score += 1;
+                    }
+                    if word_idx == 0 || last_matched_word.is_some_and(|last| last + 1 == word_idx) {
+                        // Initials of consecutive words, or of the first word
+                        score += 5;
+                    }
+                    last_matched_word = Some(word_idx);
+                    query_idx += 1;
+                }
+            }
+            word_idx += 1;
+        }
+
+        if query_idx < self.query_chars.len() {
+            return None;
+        }
+        if query_idx == word_idx {
+            // Every word in the target was matched
+            score += 10 * word_idx;
+        }
+        Some(score)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FuzzyMatcher;
+
+    #[test]
+    fn test_initials() {
+        let mut matcher = FuzzyMatcher::new();
+        const TARGET: &str = "Open File in Panel";
+        assert!(matcher.match_initials(TARGET, "ofp").is_some());
+        assert!(matcher.match_initials(TARGET, "OFIP") > matcher.match_initials(TARGET, "ofp"));
+        assert!(matcher.match_initials(TARGET, "OF") > matcher.match_initials(TARGET, "OP"));
+        assert!(matcher.match_initials(TARGET, "opn").is_none());
+        assert!(matcher.match_initials(TARGET, "").is_none());
+
+        // Camel case and separators start words
+        assert!(matcher
+            .match_initials("src/fuzzy_matcher/MatchOptions.rs", "sfmmor")
+            .is_some());
+        assert!(matcher.match_initials("readConfigFile", "rcf").is_some());
+        assert!(matcher.match_initials("readConfigFile", "rdc").is_none());
+    }
+}
@@ -0,0 +1,93 @@
+//! Folding of character widths and kana for the `kana_folding` option, so that Japanese
+//! text matches regardless of the input form it was written in.
+
+/// Folds a full-width ASCII character to its half-width form, and a katakana character to
+/// its hiragana form. Other characters are returned unchanged.
+pub(crate) fn fold(c: char) -> char {
+    let offset = match c {
+        '\u{ff01}'..='\u{ff5e}' => 0xfee0,
+        '\u{3000}' => return ' ',
+        '\u{30a1}'..='\u{30f6}' | '\u{30fd}' | '\u{30fe}' => 0x60,
+        _ => return c,
+    };
+    char::from_u32(c as u32 - offset).unwrap_or(c)
+}
+
+/// Folds each character in place.
+pub(crate) fn fold_chars(chars: &mut [char]) {
+    for c in chars {
+        *c = fold(*c);
+    }
+}
+
+/// Returns true if characters other than `c` and its case variants fold to the same
+/// character as `c`.
+pub(crate) fn has_variants(c: char) -> bool {
+    matches!(
+        fold(c),
+        ' '..='~' | '\u{3041}'..='\u{3096}' | '\u{309d}' | '\u{309e}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fold, has_variants};
+    use crate::{fuzzy_match_in, CandidateSet, FuzzyMatcher, MatchOptions, MatchScratch};
+
+    #[test]
+    fn test_kana_folding() {
+        assert_eq!(fold('ア'), 'あ');
+        assert_eq!(fold('ヶ'), 'ゖ');
+        assert_eq!(fold('ヾ'), 'ゞ');
+        assert_eq!(fold('Ａ'), 'A');
+        assert_eq!(fold('～'), '~');
+        assert_eq!(fold('\u{3000}'), ' ');
+        assert_eq!(fold('あ'), 'あ');
+        assert_eq!(fold('ー'), 'ー');
+        assert!(has_variants('a') && has_variants('カ') && has_variants('か'));
+        assert!(!has_variants('漢'));
+
+        let options = MatchOptions {
+            kana_folding: true,
+            ..MatchOptions::default()
+        };
+        let mut matcher = FuzzyMatcher::with_options(options.clone());
+        matcher.set_target_cache_capacity(4);
+        // The form used in the target does not change the score
+        let katakana = matcher.fuzzy_match("ファイルを開く", "ふぁいる").unwrap();
+        assert_eq!(
+            matcher.fuzzy_match("ふぁいるを開く", "ファイル"),
+            Some(katakana)
+        );
+        // Folded targets are kept in the cache
+        assert_eq!(
+            matcher.fuzzy_match("ファイルを開く", "ふぁいる"),
+            Some(katakana)
+        );
+        let result = matcher
+            .fuzzy_match_details("ＦｕｚｚｙＭａｔｃｈｅｒ", "fm")
+            .unwrap();
+        assert_eq!(result.positions, [0, 5]);
+        assert_eq!(
+            matcher.fuzzy_match("ＦｕｚｚｙＭａｔｃｈｅｒ", "fm"),
+            matcher.fuzzy_match("FuzzyMatcher", "fm")
+        );
+
+        let mut scratch = MatchScratch::<16>::with_options(options.clone());
+        assert_eq!(
+            fuzzy_match_in("ファイルを開く", "ふぁいる", &mut scratch),
+            Some(katakana)
+        );
+
+        let mut set = CandidateSet::with_options(options);
+        set.insert("ＲＥＡＤＭＥ");
+        set.insert("設定ファイル");
+        let results = set.query("readme", 10);
+        assert_eq!(results.len(), 1);
+        let results = set.query("ふぁいる", 10);
+        assert_eq!(set.get(results[0].item), Some("設定ファイル"));
+
+        let mut matcher = FuzzyMatcher::new();
+        assert!(matcher.fuzzy_match("ファイル", "ふぁいる").is_none());
+    }
+}
@@ -0,0 +1,152 @@
+//! Keyboard layouts for scoring typos on adjacent keys.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Physical arrangement of the keys of a keyboard, used by the `keyboard_layout` option to
+/// find characters on adjacent keys. Each row of keys is shifted to the right of the row
+/// above it, so a key touches the key on either side of it, the two keys above it at the
+/// same and next column, and the two keys below it at the previous and same column.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyboardLayout {
+    rows: Vec<String>,
+}
+
+impl KeyboardLayout {
+    /// Creates a layout from the unshifted characters of each row of keys, from top to
+    /// bottom, starting from the leftmost key of each row. Letters are compared ignoring
+    /// case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use code_fuzzy_match::KeyboardLayout;
+    ///
+    /// let layout = KeyboardLayout::from_rows(["qwe", "asd", "zxc"]);
+    /// assert!(layout.adjacent('w', 'S'));
+    /// assert!(!layout.adjacent('q', 'd'));
+    /// ```
+    pub fn from_rows<S: Into<String>>(rows: impl IntoIterator<Item = S>) -> Self {
+        KeyboardLayout {
+            rows: rows
+                .into_iter()
+                .map(|row| row.into().to_lowercase())
+                .collect(),
+        }
+    }
+
+    /// The US QWERTY layout.
+    pub fn qwerty() -> Self {
+        Self::from_rows(["1234567890-=", "qwertyuiop[]", "asdfghjkl;'", "zxcvbnm,./"])
+    }
+
+    /// The French AZERTY layout.
+    pub fn azerty() -> Self {
+        Self::from_rows(["&é\"'(-è_çà)=", "azertyuiop^$", "qsdfghjklmù", "wxcvbn,;:!"])
+    }
+
+    /// The US Dvorak layout.
+    pub fn dvorak() -> Self {
+        Self::from_rows(["1234567890[]", "',.pyfgcrl/=", "aoeuidhtns-", ";qjkxbmwvz"])
+    }
+
+    /// Returns the characters of each row of keys, from top to bottom.
+    pub(crate) fn rows(&self) -> &[String] {
+        &self.rows
+    }
+
+    /// Returns the row and column of the key for a character.
+    fn key(&self, c: char) -> Option<(usize, usize)> {
+        let c = c.to_lowercase().next().unwrap_or(c);
+        self.rows
+            .iter()
+            .enumerate()
+            .find_map(|(row, keys)| Some((row, keys.chars().position(|key| key == c)?)))
+    }
+
+    /// Returns true if the two characters are on different keys that touch each other.
+    pub fn adjacent(&self, a: char, b: char) -> bool {
+        let (Some((a_row, a_col)), Some((b_row, b_col))) = (self.key(a), self.key(b)) else {
+            return false;
+        };
+        match a_row as isize - b_row as isize {
+            0 => a_col.abs_diff(b_col) == 1,
+            // The row below is shifted right, so its keys touch the same and previous column
+            1 => b_col == a_col || b_col == a_col + 1,
+            -1 => a_col == b_col || a_col == b_col + 1,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FuzzyMatcher, KeyboardLayout, MatchOptions};
+
+    #[test]
+    fn test_keyboard_layout() {
+        let qwerty = KeyboardLayout::qwerty();
+        for (a, b) in [
+            ('g', 'h'),
+            ('g', 't'),
+            ('g', 'y'),
+            ('g', 'v'),
+            ('g', 'b'),
+            ('q', '2'),
+        ] {
+            assert!(qwerty.adjacent(a, b), "{a} {b}");
+            assert!(qwerty.adjacent(b, a), "{b} {a}");
+        }
+        for (a, b) in [('g', 'p'), ('g', 'r'), ('g', 'n'), ('g', 'g'), ('g', 'é')] {
+            assert!(!qwerty.adjacent(a, b), "{a} {b}");
+        }
+        assert!(qwerty.adjacent('G', 'h'));
+        assert!(KeyboardLayout::azerty().adjacent('a', 'z'));
+        assert!(KeyboardLayout::dvorak().adjacent('h', 't'));
+        assert!(KeyboardLayout::dvorak().adjacent('g', 'h'));
+        assert!(!KeyboardLayout::dvorak().adjacent('a', 'p'));
+    }
+
+    #[test]
+    fn test_adjacent_key_typos() {
+        let mut matcher = FuzzyMatcher::with_options(MatchOptions {
+            max_typos: 2,
+            keyboard_layout: Some(KeyboardLayout::qwerty()),
+            ..MatchOptions::default()
+        });
+        let adjacent = matcher.fuzzy_match("height", "geight").unwrap();
+        let distant = matcher.fuzzy_match("height", "peight").unwrap();
+        assert!(adjacent > distant);
+
+        // The mistyped character is not a matched position
+        let result = matcher.fuzzy_match_details("height", "heughp").unwrap();
+        assert_eq!(result.positions, [0, 1, 3, 4]);
+        let explanation = matcher.fuzzy_match_explain("height", "heughp").unwrap();
+        assert_eq!(explanation.typos, 2);
+        assert_eq!(explanation.adjacent_key_typos, 1);
+        assert_eq!(explanation.score, result.score);
+
+        // A huge penalty doesn't wrap around into a bonus
+        for adjacent_key_penalty in [0x8000_0000_0000_0000, usize::MAX] {
+            let mut matcher = FuzzyMatcher::with_options(MatchOptions {
+                adjacent_key_penalty,
+                ..matcher.options().clone()
+            });
+            assert_eq!(
+                matcher.fuzzy_match("height", "geight"),
+                matcher.fuzzy_match("height", "peight")
+            );
+        }
+
+        // Without a layout, both are ordinary typos
+        matcher.set_options(MatchOptions {
+            max_typos: 1,
+            ..MatchOptions::default()
+        });
+        assert_eq!(
+            matcher.fuzzy_match("height", "geight"),
+            matcher.fuzzy_match("height", "peight")
+        );
+    }
+}
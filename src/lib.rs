@@ -2,7 +2,8 @@
 //!
 //! The fuzzy matching algorithm used in this crate is optimized for use cases such as
 //! command palettes, quick file navigation, and code searching. It does not use Levenshtein
-//! distance, which is more suited to use cases like spell checking.
+//! distance, which is more suited to use cases like spell checking, though edit distances are
+//! available as a fallback with the `edit-distance` feature.
 //!
 //! The algorithm only allows matches where the characters in the query string are present and
 //! in the same order as the characters in the target string. All queries are substring queries,
@@ -11,8 +12,14 @@
 //! string, with words treated as they might appear in code (letters following a separator or
 //! in camel case are treated as a word). Sequential matches are also favored.
 //!
+//! Applications that need the exact matches and orderings of Visual Studio Code can use the
+//! [`ScoringProfile::VsCodeCompat`] scoring profile, which reproduces its `fuzzyScore` function.
+//!
 //! This crate provides a [`FuzzyMatcher`] struct for batch processing in addition to a
-//! [`fuzzy_match`] function for matching a single item.
+//! [`fuzzy_match`] function for matching a single item. Where the heap allocator can't be
+//! used, [`fuzzy_match_in`] matches using memory provided by the caller in a
+//! [`MatchScratch`]. The [`suggest`] function finds the candidates that mistyped input was
+//! likely meant to be, for "did you mean" messages.
 //!
 //! # Example usage
 //!
@@ -27,31 +34,288 @@
 //! let lower_score = matcher.fuzzy_match("Example string", "str");
 //! assert!(high_score.unwrap() > lower_score.unwrap());
 //! ```
+//!
+//! # Deterministic scores
+//!
+//! Scores are computed with integer arithmetic only, so the same target, query and
+//! configuration give identical scores on every platform, regardless of the width of
+//! `usize` or the byte order. [`FuzzyMatcher::score_signature`] returns a hash of the scoring
+//! parameters that is the same on every platform as well, so that systems matching on
+//! several machines can check with [`FuzzyMatcher::score_eq`] that both sides rank targets
+//! identically before merging their results.
+//!
+//! # Optional features
+//!
+//! * `case-folding`: Enables the `full_case_folding` option in [`MatchOptions`], which
+//!   compares characters using full Unicode case folding, so that characters that fold to
+//!   several characters match, such as `ß` and `ss`.
+//! * `cli`: Builds the `fzm` binary, which filters lines read from standard input with a
+//!   query given as an argument and prints the matches from best to worst, for use in shell
+//!   pipelines. Matches can also be printed as JSON lines for editor integrations. Implies
+//!   `std`.
+//! * `corpus`: Enables the [`corpus`] module, which generates synthetic candidate lists for
+//!   benchmarking.
+//! * `edit-distance`: Enables the [`edit_distance`] module, which computes Levenshtein and
+//!   Damerau-Levenshtein distances as a spell checking fallback when fuzzy matching finds
+//!   nothing.
+//! * `ffi`: Enables the [`ffi`] module, a C API for embedding the matcher in C and C++
+//!   programs. Implies `std`.
+//! * `gitignore`: Enables `FileWalker::set_gitignore`, which skips the files ignored by
+//!   `.gitignore` files when walking a directory tree. Implies `std`.
+//! * `graphemes`: Enables [`FuzzyMatcher::fuzzy_match_graphemes`], which matches whole
+//!   extended grapheme clusters instead of individual characters. Also allows
+//!   [`Truncation`] to find exact grapheme cluster boundaries.
+//! * `memmap`: Enables `MappedIndex::open`, which memory maps an index file. Implies `std`.
+//! * `phonetic`: Enables the [`phonetic`] module, which matches names by how they sound
+//!   using Soundex or Metaphone codes, for names whose spelling varies.
+//! * `pinyin`: Enables the [`pinyin`] module, which matches Chinese characters by their
+//!   pinyin readings from a table provided by the application, so that a query like
+//!   `"wenjian"` or `"wj"` matches `"文件"`.
+//! * `pyo3`: Adds Python bindings built with PyO3, for reproducing scores from Python: a
+//!   `code_fuzzy_match` module with `fuzzy_match`, `match_positions` and `rank` functions.
+//!   Ranking takes an optional limit and releases the GIL while matching. Implies `std`.
+//! * `ropey`: Enables [`FuzzyMatcher::fuzzy_match_rope`], which matches within a `ropey`
+//!   rope without copying it into a string, and reports positions as lines and columns
+//!   with [`Match::line_columns`].
+//! * `score-matrix`: Enables [`FuzzyMatcher::fuzzy_match_matrix`], which returns every cell
+//!   of the scoring matrix along with the chosen alignment, for rendering heatmaps when
+//!   tuning bonuses.
+//! * `serde`: Implements `Serialize` and `Deserialize` for [`Index`], for [`MatchOptions`]
+//!   and the other configuration types, for match results such as [`Match`], and for the
+//!   [`Frecency`] and [`SelectionFeedback`] stores used to boost rankings.
+//! * `simd`: Compares ASCII query characters against many target characters at once using
+//!   vector instructions on x86_64.
+//! * `stats`: Enables [`FuzzyMatcher::stats`], which counts the candidates checked and
+//!   rejected by prefilters, the runs of the scoring matrix and the cells computed, for
+//!   tuning prefilters and measuring performance.
+//! * `std`: Enables reading and writing an [`Index`] with `std::io`, implements
+//!   `std::error::Error` for [`IndexError`], enables matching `OsStr` and `Path` targets
+//!   with [`FuzzyMatcher::fuzzy_match_os_str`] and [`FuzzyMatcher::fuzzy_match_os_path`],
+//!   enables [`FuzzyMatcher::match_lines`] for filtering the lines of a reader in bounded
+//!   memory, enables `Budget::Time` for ranking within a time limit, enables
+//!   `MatcherWorker` for matching on a background thread, enables `SharedMatcher` for
+//!   matching from many threads, enables `FileWalker` for finding the files of a
+//!   directory tree to match in a [`CandidateSet`], and enables `highlight_ansi` and
+//!   `highlight_html` for highlighting matched characters in terminals and web pages.
+//! * `tracing`: Emits `tracing` spans and events for batch operations such as ranking,
+//!   filtering and index queries, with the query, the number of candidates and matches,
+//!   the top score and the time taken, so that latency can be attributed to matching.
+//!   Implies `std`.
+//! * `tui`: Enables the `picker` example, an interactive picker that filters a list as you
+//!   type and highlights the matched characters, using `MatcherWorker`. Implies `std`.
+//! * `unicode-words`: Enables the `unicode_words` option in [`MatchOptions`], which detects
+//!   word starts using Unicode word segmentation.
+//! * `wasm`: Exports `wasm-bindgen` bindings for using the matcher from JavaScript: a
+//!   `fuzzyMatch(target, query)` function, and a `FuzzyMatcher` class with `fuzzyMatch`,
+//!   `matchPositions` and `rank` methods. Positions and ranked results are returned as typed
+//!   arrays. Implies `std`.
 
 #![no_std]
 
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+use alloc::string::String;
 use alloc::vec::Vec;
+use core::ops::Range;
+
+mod aliases;
+mod alternatives;
+mod batch;
+mod boundaries;
+mod bytes;
+mod cache;
+mod candidates;
+mod char_index;
+mod constraints;
+#[cfg(feature = "corpus")]
+pub mod corpus;
+mod digits;
+#[cfg(feature = "edit-distance")]
+pub mod edit_distance;
+mod explain;
+mod feedback;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod filter;
+mod find;
+#[cfg(feature = "case-folding")]
+mod folding;
+mod frecency;
+#[cfg(feature = "graphemes")]
+mod graphemes;
+#[cfg(feature = "std")]
+mod highlight;
+mod index;
+mod initials;
+mod kana;
+mod keyboard;
+#[cfg(feature = "std")]
+mod lines;
+mod mapped;
+mod matrix;
+mod model;
+mod multi_field;
+mod options;
+mod path;
+#[cfg(feature = "phonetic")]
+pub mod phonetic;
+#[cfg(feature = "pinyin")]
+pub mod pinyin;
+mod position;
+mod prefilter;
+#[cfg(feature = "pyo3")]
+mod python;
+mod query;
+mod rank;
+mod result;
+#[cfg(feature = "ropey")]
+mod rope;
+mod score;
+#[cfg(feature = "score-matrix")]
+mod score_matrix;
+mod scored;
+mod scratch;
+mod separators;
+#[cfg(feature = "std")]
+mod shared;
+mod signature;
+#[cfg(feature = "simd")]
+mod simd;
+#[cfg(feature = "stats")]
+mod stats;
+mod stream;
+mod suggest;
+mod synonyms;
+#[cfg(feature = "tracing")]
+mod trace;
+mod truncate;
+mod utf16;
+mod vscode;
+#[cfg(feature = "std")]
+mod walk;
+#[cfg(feature = "wasm")]
+mod wasm;
+mod whitespace;
+#[cfg(feature = "unicode-words")]
+mod words;
+#[cfg(feature = "std")]
+mod worker;
+
+pub use aliases::{AliasMatch, AliasSet};
+pub use boundaries::{analyze_boundaries, Boundaries};
+pub use bytes::ByteDecoding;
+pub use candidates::CandidateSet;
+pub use constraints::MatchConstraints;
+pub use explain::{Bonuses, MatchExplanation, MatchedChar};
+pub use feedback::SelectionFeedback;
+pub use filter::{FuzzyFilter, FuzzyFilterExt};
+pub use find::Occurrence;
+pub use frecency::Frecency;
+#[cfg(feature = "graphemes")]
+pub use graphemes::GraphemeMatch;
+#[cfg(feature = "std")]
+pub use highlight::{highlight_ansi, highlight_html};
+pub use index::{Index, IndexError};
+pub use keyboard::KeyboardLayout;
+pub use mapped::MappedIndex;
+pub use matrix::CharClass;
+pub use model::{
+    ConstProfile, ConstScoreModel, CustomScoreModel, DefaultScoreModel, ScoreContext, ScoreModel,
+};
+pub use multi_field::{MultiFieldMatch, MultiFieldMatcher};
+pub use options::{CaseLocale, EmptyQueryPolicy, MatchOptions, ScoreVersion, ScoringProfile};
+pub use path::{PathMatch, PathMatchStage};
+pub use position::{LinePosition, PositionEncoding};
+pub use prefilter::{CharMask, QueryMask};
+pub use query::{Query, QueryAst};
+pub use rank::{sort_matches, Budget, RankCursor, RankedMatch, TieBreak};
+pub use result::Match;
+pub use score::Score;
+#[cfg(feature = "score-matrix")]
+pub use score_matrix::ScoreMatrix;
+pub use scored::{merge_topk, Scored};
+pub use scratch::{fuzzy_match_in, MatchScratch};
+#[cfg(feature = "std")]
+pub use shared::SharedMatcher;
+#[cfg(feature = "stats")]
+pub use stats::MatchStats;
+pub use suggest::{suggest, Suggestion};
+pub use truncate::{LongTargetPolicy, Truncation};
+pub use utf16::Utf16Match;
+#[cfg(feature = "std")]
+pub use walk::FileWalker;
+#[cfg(feature = "std")]
+pub use worker::{MatcherWorker, WorkerResults};
+
+use matrix::{Matrix, MatrixInput, PositionMask, UnitMatch};
+use score::RawScore;
+
+/// Score given to a query that is equal to the entire target when the `pin_exact_matches`
+/// option is set. This is higher than the score of any other match.
+pub const EXACT_MATCH_SCORE: usize = usize::MAX;
 
 /// Fuzzy matcher instance. Holds memory for the state of the fuzzy matcher so that
 /// large batches of queries can be processed with minimal allocations. When performing a
 /// large batch of fuzzy match queries, use a common instance of this struct to improve
 /// performance by avoiding extra allocations.
-pub struct FuzzyMatcher {
+///
+/// Matches are scored with the model `M`, which is the default scoring model unless the
+/// matcher is created with [`with_model`](Self::with_model).
+pub struct FuzzyMatcher<M = DefaultScoreModel> {
+    options: MatchOptions,
     target_chars: Vec<char>,
-    first_possible_match: Vec<usize>,
-    prev_seq_match_counts: Vec<usize>,
-    prev_score: Vec<usize>,
-    seq_match_counts: Vec<usize>,
-    score: Vec<usize>,
+    target_classes: Vec<CharClass>,
+    /// Index in the target window of the character that each loaded target character was
+    /// loaded from, or empty if no characters were expanded by full case folding or skipped
+    /// by the `ignored_chars` option.
+    target_origins: Vec<usize>,
+    query_chars: Vec<char>,
+    window_offset: usize,
+    truncated: bool,
+    matrix: Matrix,
+    mask: PositionMask,
+    #[cfg(feature = "graphemes")]
+    grapheme_buffers: graphemes::GraphemeBuffers,
+    target_cache: cache::TargetCache,
+    char_index: char_index::CharIndex,
+    #[cfg(feature = "simd")]
+    match_table: Vec<u8>,
+    #[cfg(feature = "batch-lanes")]
+    batch: batch::BatchBuffers,
+    vscode: vscode::VsCodeBuffers,
+    feedback: feedback::SelectionFeedback,
+    /// Target decoded from a byte string that is not valid UTF-8, or from characters that
+    /// need to be matched as a `&str`.
+    decoded_target: String,
+    stream: stream::StreamState,
+    /// Counters of the work done outside of the scoring matrix.
+    #[cfg(feature = "stats")]
+    stats: stats::MatchStats,
+    /// Target length that scratch memory was preallocated for.
+    reserved_target_len: usize,
+    model: M,
 }
 
-fn char_matches(query_char: char, target_char: char) -> bool {
+fn char_matches(query_char: char, target_char: char, options: &MatchOptions) -> bool {
+    if !options.equivalences.is_empty() && options.equivalent(query_char, target_char) {
+        return true;
+    }
+
+    if let Some(matches) = options.case_locale.chars_match(query_char, target_char) {
+        return matches;
+    }
+
     // Treat slashes and backslashes as the same character to be able to use as a path
     // matching function.
     match query_char {
-        '/' => matches!(target_char, '/' | '\\'),
-        '\\' => matches!(target_char, '/' | '\\'),
+        '/' | '\\' if options.slash_equivalence => matches!(target_char, '/' | '\\'),
+        ' ' if options.space_matches_separators => {
+            matches!(
+                CharClass::of(target_char),
+                CharClass::Separator | CharClass::PathSeparator
+            )
+        }
         _ => {
             // The `eq_ignore_ascii_case` function is *much* faster than a full
             // Unicode case-insensitive comparison, so if the target character is
@@ -68,19 +332,216 @@ fn char_matches(query_char: char, target_char: char) -> bool {
     }
 }
 
+fn char_match_kind(
+    query_char: char,
+    target_char: char,
+    options: &MatchOptions,
+) -> Option<UnitMatch> {
+    if query_char == target_char {
+        Some(UnitMatch::Exact)
+    } else if char_matches(query_char, target_char, options) {
+        Some(UnitMatch::Folded)
+    } else {
+        None
+    }
+}
+
 impl FuzzyMatcher {
     /// Creates a new instance of a fuzzy matcher.
     pub fn new() -> Self {
+        Self::with_options(MatchOptions::default())
+    }
+
+    /// Creates a new instance of a fuzzy matcher with the given options.
+    pub fn with_options(options: MatchOptions) -> Self {
+        Self::with_options_and_capacity(options, 0)
+    }
+
+    /// Creates a new instance of a fuzzy matcher with scratch memory preallocated for
+    /// targets of up to `max_target_len` characters. Longer targets can still be matched,
+    /// and memory grows as needed to match them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut matcher = code_fuzzy_match::FuzzyMatcher::with_capacity(256);
+    /// assert!(matcher.fuzzy_match("src/lib.rs", "lib").is_some());
+    /// ```
+    pub fn with_capacity(max_target_len: usize) -> Self {
+        Self::with_options_and_capacity(MatchOptions::default(), max_target_len)
+    }
+
+    /// Creates a new instance of a fuzzy matcher with the given options and with scratch
+    /// memory preallocated for targets of up to `max_target_len` characters.
+    pub fn with_options_and_capacity(options: MatchOptions, max_target_len: usize) -> Self {
+        Self::with_model_and_capacity(DefaultScoreModel, options, max_target_len)
+    }
+}
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Creates a new instance of a fuzzy matcher that scores matches with the given model.
+    /// Unlike the `score_model` option, the type of the model is known at compile time, so
+    /// its scoring is inlined into the matcher. A `score_model` set in the options still
+    /// takes precedence.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use code_fuzzy_match::{ConstProfile, ConstScoreModel, FuzzyMatcher, MatchOptions};
+    ///
+    /// /// Rewards the start of words more than the default profile.
+    /// struct WordStarts;
+    ///
+    /// impl ConstProfile for WordStarts {
+    ///     const WORD_START_BONUS: usize = 10;
+    /// }
+    ///
+    /// let mut default_matcher = FuzzyMatcher::new();
+    /// let mut matcher = FuzzyMatcher::with_model(
+    ///     ConstScoreModel::<WordStarts>::new(),
+    ///     MatchOptions::default(),
+    /// );
+    /// assert_eq!(default_matcher.fuzzy_match("getValue", "v"), Some(3));
+    /// assert_eq!(matcher.fuzzy_match("getValue", "v"), Some(11));
+    /// ```
+    pub fn with_model(model: M, options: MatchOptions) -> Self {
+        Self::with_model_and_capacity(model, options, 0)
+    }
+
+    /// Creates a new instance of a fuzzy matcher that scores matches with the given model,
+    /// with scratch memory preallocated for targets of up to `max_target_len` characters.
+    pub fn with_model_and_capacity(model: M, options: MatchOptions, max_target_len: usize) -> Self {
         FuzzyMatcher {
-            target_chars: Vec::new(),
-            first_possible_match: Vec::new(),
-            prev_seq_match_counts: Vec::new(),
-            prev_score: Vec::new(),
-            seq_match_counts: Vec::new(),
-            score: Vec::new(),
+            options,
+            target_chars: Vec::with_capacity(max_target_len),
+            target_classes: Vec::with_capacity(max_target_len),
+            target_origins: Vec::new(),
+            query_chars: Vec::new(),
+            window_offset: 0,
+            truncated: false,
+            matrix: Matrix::with_capacity(max_target_len),
+            mask: PositionMask::default(),
+            #[cfg(feature = "graphemes")]
+            grapheme_buffers: graphemes::GraphemeBuffers::default(),
+            target_cache: cache::TargetCache::default(),
+            char_index: char_index::CharIndex::default(),
+            #[cfg(feature = "simd")]
+            match_table: Vec::new(),
+            #[cfg(feature = "batch-lanes")]
+            batch: batch::BatchBuffers::default(),
+            vscode: vscode::VsCodeBuffers::default(),
+            feedback: feedback::SelectionFeedback::default(),
+            decoded_target: String::new(),
+            stream: stream::StreamState::default(),
+            #[cfg(feature = "stats")]
+            stats: stats::MatchStats::default(),
+            reserved_target_len: max_target_len,
+            model,
         }
     }
 
+    /// Releases scratch memory held after matching long targets, keeping only the memory
+    /// preallocated by [`with_capacity`](Self::with_capacity). The options, the target
+    /// cache and the selection feedback are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut matcher = code_fuzzy_match::FuzzyMatcher::new();
+    /// let long_target = "x".repeat(1_000_000);
+    /// assert!(matcher.fuzzy_match(&long_target, "xx").is_some());
+    /// matcher.shrink_to_fit();
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        let empty = FuzzyMatcher::with_capacity(self.reserved_target_len);
+        self.target_chars = empty.target_chars;
+        self.target_classes = empty.target_classes;
+        self.target_origins = empty.target_origins;
+        self.query_chars = empty.query_chars;
+        self.window_offset = 0;
+        self.truncated = false;
+        self.matrix = empty.matrix;
+        self.mask = empty.mask;
+        #[cfg(feature = "graphemes")]
+        {
+            self.grapheme_buffers = empty.grapheme_buffers;
+        }
+        self.char_index = empty.char_index;
+        #[cfg(feature = "simd")]
+        {
+            self.match_table = empty.match_table;
+        }
+        #[cfg(feature = "batch-lanes")]
+        {
+            self.batch = empty.batch;
+        }
+        self.vscode = empty.vscode;
+        self.decoded_target = empty.decoded_target;
+    }
+
+    /// Returns the scoring model used by this matcher.
+    pub fn model(&self) -> &M {
+        &self.model
+    }
+
+    /// Returns the options used by this matcher.
+    pub fn options(&self) -> &MatchOptions {
+        &self.options
+    }
+
+    /// Returns the version of the scoring rules used by this matcher, as set by the
+    /// `score_version` option.
+    pub fn score_version(&self) -> ScoreVersion {
+        self.options.score_version
+    }
+
+    /// Changes the options used by this matcher. This clears the target cache, as the
+    /// options affect how targets are decoded.
+    pub fn set_options(&mut self, options: MatchOptions) {
+        self.options = options;
+        self.target_cache.clear();
+    }
+
+    /// Returns the maximum number of targets kept in the target cache.
+    pub fn target_cache_capacity(&self) -> usize {
+        self.target_cache.capacity()
+    }
+
+    /// Sets the maximum number of targets kept in the target cache. When matching the same
+    /// targets repeatedly, such as rescoring a stable candidate list as the user types a
+    /// query, cached targets are not decoded again. The least recently used targets are
+    /// evicted when the cache is full. The cache is disabled by default, which is the same
+    /// as a capacity of zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut matcher = code_fuzzy_match::FuzzyMatcher::new();
+    /// matcher.set_target_cache_capacity(1024);
+    /// let candidates = ["src/lib.rs", "src/main.rs", "Cargo.toml"];
+    /// for query in ["s", "sr", "src"] {
+    ///     let matches = candidates
+    ///         .iter()
+    ///         .filter(|target| matcher.fuzzy_match(target, query).is_some())
+    ///         .count();
+    ///     assert_eq!(matches, 2);
+    /// }
+    /// ```
+    pub fn set_target_cache_capacity(&mut self, capacity: usize) {
+        self.target_cache.set_capacity(capacity);
+    }
+
+    /// Removes all targets from the target cache.
+    pub fn clear_target_cache(&mut self) {
+        self.target_cache.clear();
+    }
+
+    /// Returns true if the most recently matched target was truncated before matching,
+    /// as configured by the `max_target_len` and `truncation` options.
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+
     /// Fuzzy match a string against a query string. Returns a score that is higher for
     /// a more confident match, or `None` if the query does not match the target string.
     ///
@@ -98,201 +559,459 @@ impl FuzzyMatcher {
     /// assert!(high_score.unwrap() > lower_score.unwrap());
     /// ```
     pub fn fuzzy_match(&mut self, target: &str, query: &str) -> Option<usize> {
-        // Break the target string into a vector of characters, since we need to manage
-        // parallel vectors with information per character. Match query string characters
-        // along the way to perform an early exit if the query string definitely does not
-        // match, as well as computing the earliest possible index for each given query
-        // character.
+        self.load(target, query);
+        self.compute(false, false)
+    }
+
+    /// Fuzzy match a string against a query string, returning the score as the given
+    /// [`Score`] type. Use `isize` to keep scores that penalties take to zero or below,
+    /// which [`fuzzy_match`](Self::fuzzy_match) raises to one.
+    pub fn fuzzy_match_as<S: Score>(&mut self, target: &str, query: &str) -> Option<S> {
+        self.load(target, query);
+        self.compute(false, false)
+    }
+
+    /// Fuzzy match a target given as characters against a query string, so that text held
+    /// in ropes, gap buffers or other non-contiguous storage can be matched without first
+    /// copying it into a `&str`. Scores are the same as for the equivalent string target.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut matcher = code_fuzzy_match::FuzzyMatcher::new();
+    /// let chunks = ["the quick ", "brown fox"];
+    /// let target = chunks.iter().flat_map(|chunk| chunk.chars());
+    /// assert_eq!(
+    ///     matcher.fuzzy_match_chars(target, "bro fox"),
+    ///     matcher.fuzzy_match("the quick brown fox", "bro fox")
+    /// );
+    /// ```
+    pub fn fuzzy_match_chars(
+        &mut self,
+        target: impl IntoIterator<Item = char>,
+        query: &str,
+    ) -> Option<usize> {
+        self.load_chars(target.into_iter(), query);
+        self.compute(false, false)
+    }
+
+    /// Returns the byte range of the window of the target to match within, as configured
+    /// by the `max_target_len` and `truncation` options, and records whether the target was
+    /// truncated.
+    fn truncate(&mut self, target: &str) -> Range<usize> {
+        let window = truncate::target_window(target, &self.options);
+        self.truncated = window.len() < target.len();
+        self.window_offset = target[..window.start].chars().count();
+        window
+    }
+
+    /// Break the target and query strings into vectors of characters, since we need to
+    /// manage parallel vectors with information per character.
+    fn load(&mut self, target: &str, query: &str) {
+        self.load_query(query);
+
+        if let Some(cached) = self.target_cache.get(target) {
+            self.target_chars.clear();
+            self.target_chars.extend_from_slice(&cached.chars);
+            self.target_classes.clear();
+            self.target_classes.extend_from_slice(&cached.classes);
+            self.target_origins.clear();
+            self.target_origins.extend_from_slice(&cached.origins);
+            self.window_offset = cached.window_offset;
+            self.truncated = cached.truncated;
+            self.char_index.entries.clear();
+            self.char_index.entries.extend_from_slice(&cached.index);
+            self.strip_extension();
+            return;
+        }
+
+        self.classify(target);
+        self.expand_target();
+        if self.options.position_index {
+            self.char_index.build(&self.target_chars);
+        }
+        self.target_cache.insert(
+            target,
+            &self.target_chars,
+            &self.target_classes,
+            &self.target_origins,
+            &self.char_index.entries,
+            self.window_offset,
+            self.truncated,
+        );
+        self.strip_extension();
+    }
+
+    /// Decodes and classifies the characters of a target, truncated as set by the options.
+    /// Characters are not yet expanded or skipped, so each character of the target window
+    /// has a class.
+    fn classify(&mut self, target: &str) {
+        let window = self.truncate(target);
+        self.classify_window(&target[window]);
+    }
+
+    /// Decodes and classifies the characters of the window of a target to match within.
+    fn classify_window(&mut self, target: &str) {
         self.target_chars.clear();
         self.target_chars.extend(target.chars());
-        self.first_possible_match.clear();
-        let mut query_chars = query.chars();
-        let mut cur_query_char = query_chars.next();
-        for (target_idx, target_char) in target.chars().enumerate() {
-            if let Some(query_char) = cur_query_char {
-                if char_matches(query_char, target_char) {
-                    self.first_possible_match.push(target_idx);
-                    cur_query_char = query_chars.next();
-                }
+        if self.options.kana_folding {
+            kana::fold_chars(&mut self.target_chars);
+        }
+        self.target_classes.clear();
+        self.target_classes
+            .extend(self.target_chars.iter().map(|c| CharClass::of(*c)));
+        separators::mark_path_separators(
+            target,
+            target.char_indices().map(|(idx, _)| idx),
+            &mut self.target_classes,
+            &self.options.path_separators,
+        );
+        if self.options.digit_word_starts {
+            digits::mark_digit_boundaries(
+                self.target_chars.iter().copied(),
+                &mut self.target_classes,
+            );
+        }
+        #[cfg(feature = "unicode-words")]
+        if self.options.unicode_words {
+            words::mark_word_starts(
+                target,
+                target.char_indices().map(|(idx, _)| idx),
+                &mut self.target_classes,
+            );
+        }
+    }
+
+    /// Loads the query characters, folded as set by the options.
+    fn load_query(&mut self, query: &str) {
+        self.query_chars.clear();
+        self.query_chars
+            .extend(whitespace::query_chars(query, &self.options));
+        if self.options.kana_folding {
+            kana::fold_chars(&mut self.query_chars);
+        }
+        #[cfg(feature = "case-folding")]
+        if self.options.full_case_folding {
+            for c in core::mem::take(&mut self.query_chars) {
+                folding::fold(c, &mut self.query_chars);
             }
         }
+    }
 
-        // If we didn't consume all query characters, then the query is not a match.
-        if cur_query_char.is_some() {
-            return None;
+    /// Expands the loaded target characters that fold to several characters when the
+    /// `full_case_folding` option is set, and skips the characters in the `ignored_chars`
+    /// option, recording the character that each remaining character was loaded from.
+    fn expand_target(&mut self) {
+        self.target_origins.clear();
+        #[cfg(feature = "case-folding")]
+        let folding = self.options.full_case_folding;
+        #[cfg(not(feature = "case-folding"))]
+        let folding = false;
+        if !folding && self.options.ignored_chars.is_empty() {
+            return;
+        }
+
+        let chars = core::mem::take(&mut self.target_chars);
+        let classes = core::mem::take(&mut self.target_classes);
+        let mut after_separator = false;
+        for (idx, (c, class)) in chars.into_iter().zip(classes).enumerate() {
+            if self.options.ignored_chars.contains(c) {
+                after_separator |= matches!(class, CharClass::Separator | CharClass::PathSeparator);
+                continue;
+            }
+            let start = self.target_chars.len();
+            if !folding {
+                self.target_chars.push(c);
+            }
+            #[cfg(feature = "case-folding")]
+            if folding {
+                folding::fold(c, &mut self.target_chars);
+            }
+            // Only the first character of an expansion keeps the class of the original
+            // character, so that the expansion starts a word where the original did. A
+            // character after skipped separators still starts a word.
+            self.target_classes.push(match class {
+                CharClass::Other if after_separator => CharClass::WordStart,
+                _ => class,
+            });
+            self.target_classes.extend(
+                self.target_chars[start + 1..]
+                    .iter()
+                    .map(|c| CharClass::of(*c)),
+            );
+            self.target_origins.resize(self.target_chars.len(), idx);
+            after_separator = false;
         }
+    }
 
-        debug_assert_eq!(target.chars().count(), self.target_chars.len());
-        debug_assert_eq!(query.chars().count(), self.first_possible_match.len());
+    /// Leaves the file extension out of the loaded target when the `ignore_extension`
+    /// option is set and the query has no `.`. The extension starts at the last `.` of the
+    /// final path component, unless the component starts with it.
+    fn strip_extension(&mut self) {
+        if !self.options.ignore_extension || self.query_chars.contains(&'.') {
+            return;
+        }
+        let name_start = self
+            .target_classes
+            .iter()
+            .rposition(|class| *class == CharClass::PathSeparator)
+            .map_or(0, |idx| idx + 1);
+        let Some(dot) = self.target_chars[name_start..]
+            .iter()
+            .rposition(|c| *c == '.')
+            .filter(|dot| *dot > 0)
+        else {
+            return;
+        };
+        let len = name_start + dot;
+        self.target_chars.truncate(len);
+        self.target_classes.truncate(len);
+        self.target_origins.truncate(len);
+        self.char_index.entries.retain(|(_, idx)| *idx < len);
+    }
 
-        // Create vectors holding the score and sequential counts for two query characters.
-        // This algorithm implements a matrix-based method of fuzzy matching, but we don't
-        // need to hold the entire matrix in memory, just the current and previous rows.
-        self.prev_seq_match_counts.clear();
-        self.prev_score.clear();
-        self.prev_seq_match_counts
-            .resize(self.target_chars.len(), 0);
-        self.prev_score.resize(self.target_chars.len(), 0);
+    /// Returns the character index in the target of a recorded position, which differs
+    /// from the position when target characters were expanded.
+    fn target_index(&self, position: usize) -> usize {
+        match self.target_origins.get(position - self.window_offset) {
+            Some(origin) => self.window_offset + origin,
+            None => position,
+        }
+    }
 
-        self.seq_match_counts.clear();
-        self.score.clear();
-        self.seq_match_counts.resize(self.target_chars.len(), 0);
-        self.score.resize(self.target_chars.len(), 0);
+    /// Returns the character indices in the target of the recorded positions, in ascending
+    /// order.
+    fn matched_positions(&self) -> Vec<usize> {
+        let mut positions: Vec<usize> = self
+            .matrix
+            .positions
+            .iter()
+            .map(|position| self.target_index(*position))
+            .collect();
+        positions.sort_unstable();
+        positions.dedup();
+        positions
+    }
 
-        let mut first_possible_target_idx: usize = 0;
+    /// Loads a target given as characters, such as a target that isn't stored as a `&str`.
+    /// Targets are decoded directly into the matcher's buffers unless truncation or Unicode
+    /// word segmentation is needed, which only work on a `&str`. Targets are only cached when
+    /// decoded into a `&str`.
+    fn load_chars(&mut self, target: impl Iterator<Item = char>, query: &str) {
+        #[cfg(feature = "unicode-words")]
+        let needs_str = self.options.unicode_words;
+        #[cfg(not(feature = "unicode-words"))]
+        let needs_str = false;
+        if needs_str || self.options.truncation.is_some() || self.options.max_target_len.is_some() {
+            let mut decoded = core::mem::take(&mut self.decoded_target);
+            decoded.clear();
+            decoded.extend(target);
+            self.load(&decoded, query);
+            self.decoded_target = decoded;
+            return;
+        }
 
-        // Compute match scores for each query character in sequence
-        let mut first_query_char = true;
-        for (query_char, first_possible_match) in
-            query.chars().zip(self.first_possible_match.iter())
-        {
-            // If the starting point of the search is beyond the end of the target string,
-            // we can't have a match.
-            if first_possible_target_idx >= self.target_chars.len() {
-                return None;
-            }
+        self.load_query(query);
+        self.target_chars.clear();
+        self.target_chars.extend(target);
+        if self.options.kana_folding {
+            kana::fold_chars(&mut self.target_chars);
+        }
+        self.target_classes.clear();
+        self.target_classes
+            .extend(self.target_chars.iter().map(|c| CharClass::of(*c)));
+        separators::mark_path_separator_chars(
+            &self.target_chars,
+            &mut self.target_classes,
+            &self.options.path_separators,
+        );
+        if self.options.digit_word_starts {
+            digits::mark_digit_boundaries(
+                self.target_chars.iter().copied(),
+                &mut self.target_classes,
+            );
+        }
+        self.expand_target();
+        self.window_offset = 0;
+        self.truncated = false;
+        if self.options.position_index {
+            self.char_index.build(&self.target_chars);
+        }
+        self.strip_extension();
+    }
 
-            // If the initial scan saw that the first possible match for this query character
-            // is later in the string, use that instead.
-            first_possible_target_idx = first_possible_target_idx.max(*first_possible_match);
-
-            // Reset vector holding the score and sequential counts for this query character.
-            // This algorithm implements a matrix-based method of fuzzy matching, but we don't
-            // need to hold the entire matrix in memory, just the current and previous rows.
-            (&mut self.seq_match_counts[first_possible_target_idx..self.target_chars.len()])
-                .fill(0);
-            (&mut self.score[first_possible_target_idx..self.target_chars.len()]).fill(0);
-
-            let mut first_nonzero_score = None;
-
-            // Compute match scores for each target character in sequence, for this query character.
-            // Start at the character after the previous earliest character that had a score. Any
-            // character before that cannot have a score, so we don't need to check those.
-            for i in first_possible_target_idx..self.target_chars.len() {
-                // Get characters and the score for the previous character in the target
-                let target_char = self.target_chars[i];
-                let prev_target_score = if i == first_possible_target_idx {
-                    0
-                } else {
-                    self.score[i - 1]
-                };
-
-                // Previous score and sequential match count comes from the previous character
-                // in both the target and the query
-                let prev_query_score = if i == 0 { 0 } else { self.prev_score[i - 1] };
-                let seq_match_count = if i == 0 {
-                    0
-                } else {
-                    self.prev_seq_match_counts[i - 1]
-                };
-
-                if !first_query_char && prev_query_score == 0 {
-                    self.score[i] = prev_target_score;
-                    continue;
-                }
-
-                // Check to ensure the characters match at all.
-                if !char_matches(query_char, target_char) {
-                    // No match, use existing score and reset sequential count
-                    self.score[i] = prev_target_score;
-                    continue;
-                }
-
-                // Compute score for this character match. These bonuses are inspired by
-                // the algorithm used by Visual Studio Code.
-                let mut char_score = 1;
-
-                // Sequential match bonus
-                char_score += seq_match_count * 5;
-
-                if target_char == query_char {
-                    // Same case bonus
-                    char_score += 1;
-                }
-
-                if i == 0 {
-                    // Start of target bonus
-                    char_score += 8;
-                } else {
-                    if matches!(target_char, '/' | '\\') {
-                        // Path separator bonus
-                        char_score += 5;
-                    } else if matches!(target_char, '_' | '-' | '.' | ' ' | '\'' | '"' | ':') {
-                        // Separator bonus
-                        char_score += 4;
-                    } else if seq_match_count == 0 {
-                        if i > 0
-                            && matches!(
-                                self.target_chars[i - 1],
-                                '_' | '-' | '.' | ' ' | '\'' | '"' | ':'
-                            )
-                        {
-                            // Start of word after separator bonus
-                            char_score += 2;
-                        } else if target_char.is_ascii() {
-                            // It is faster to check for ASCII first and then use
-                            // `is_ascii_uppercase` than to always use `is_uppercase`.
-                            if target_char.is_ascii_uppercase() {
-                                // Start of word bonus
-                                char_score += 2;
-                            }
-                        } else if target_char.is_uppercase() {
-                            // Start of word bonus
-                            char_score += 2;
-                        }
-                    }
-                }
-
-                if i + 1 == self.target_chars.len() {
-                    // End of target bonus
-                    char_score += 2;
-                }
-
-                // Compute new score and check if it's improved
-                let new_score = prev_query_score + char_score;
-                if new_score >= prev_target_score {
-                    // Score is at least the previous score, keep sequential match going
-                    self.score[i] = new_score;
-                    self.seq_match_counts[i] = seq_match_count + 1;
-                    if first_nonzero_score.is_none() {
-                        first_nonzero_score = Some(i);
-                    }
-                } else {
-                    // Score is lower than the previous score, don't use this match
-                    self.score[i] = prev_target_score;
-                }
+    /// Loads a target that was already classified, such as a candidate stored in an
+    /// [`Index`]. The target is never truncated or cached.
+    fn load_classified(
+        &mut self,
+        target: &str,
+        classes: impl IntoIterator<Item = CharClass>,
+        query: &str,
+    ) {
+        self.load_query(query);
+        self.target_chars.clear();
+        self.target_chars.extend(target.chars());
+        if self.options.kana_folding {
+            kana::fold_chars(&mut self.target_chars);
+        }
+        self.target_classes.clear();
+        self.target_classes.extend(classes);
+        self.expand_target();
+        self.window_offset = 0;
+        self.truncated = false;
+        if self.options.position_index {
+            self.char_index.build(&self.target_chars);
+        }
+        self.strip_extension();
+    }
+
+    /// Runs the scoring matrix over the loaded target and query characters. If `masked` is
+    /// set, the position mask must have been filled for the loaded target. Recorded
+    /// positions are indices of the loaded characters offset by the start of the window in
+    /// the full target, which [`target_index`](Self::target_index) maps to characters of the
+    /// target.
+    fn compute<S: Score>(&mut self, record_positions: bool, masked: bool) -> Option<S> {
+        if self.query_chars.is_empty() {
+            return self.compute_empty(record_positions);
+        }
+        let score = if masked || self.options.synonyms.is_empty() {
+            self.compute_raw(record_positions, masked)
+        } else {
+            self.compute_synonyms(record_positions)
+        };
+        score.map(RawScore::into_score)
+    }
+
+    /// Returns the score of an empty query, as set by the `empty_query` option.
+    fn compute_empty<S: Score>(&mut self, record_positions: bool) -> Option<S> {
+        if self.options.empty_query == EmptyQueryPolicy::NoMatch {
+            return None;
+        }
+        if record_positions {
+            self.matrix.positions.clear();
+            self.matrix.query_indices.clear();
+            self.matrix.near_misses.clear();
+        }
+        Some(S::from_sum(0))
+    }
+
+    /// Runs the scoring matrix over the loaded target and query characters, as for
+    /// [`compute`](Self::compute), without trying synonyms of the query.
+    fn compute_raw(&mut self, record_positions: bool, masked: bool) -> Option<RawScore> {
+        if self.options.profile == ScoringProfile::VsCodeCompat && !masked {
+            #[cfg(feature = "stats")]
+            {
+                self.stats.matrix_runs += 1;
+            }
+            let positions = record_positions.then_some(&mut self.matrix.positions);
+            let score = self
+                .vscode
+                .compute(&self.target_chars, &self.query_chars, positions)?;
+            if record_positions {
+                self.matrix.query_indices.clear();
+                self.matrix
+                    .query_indices
+                    .extend(0..self.matrix.positions.len());
+                self.matrix.near_misses.clear();
             }
+            return Some(self.finish_compute(record_positions, score));
+        }
 
-            if let Some(first_nonzero_score) = first_nonzero_score {
-                // Start the next character's matching at the character following the one that
-                // first set a valid score.
-                first_possible_target_idx = first_nonzero_score + 1;
-
-                // Keep scores and sequential match information for this character in the query
-                // for lookup during the next character.
-                (&mut self.prev_score[first_nonzero_score..self.target_chars.len()])
-                    .copy_from_slice(&self.score[first_nonzero_score..self.target_chars.len()]);
-                (&mut self.prev_seq_match_counts[first_nonzero_score..self.target_chars.len()])
-                    .copy_from_slice(
-                        &self.seq_match_counts[first_nonzero_score..self.target_chars.len()],
-                    );
-                first_query_char = false;
-            } else {
-                // If the all scores are zero, we already know we don't have a match. Exit early
-                // in this case.
-                return None;
+        let target_chars = &self.target_chars;
+        let query_chars = &self.query_chars;
+        let options = &self.options;
+        let candidates = (options.position_index && !masked)
+            .then(|| self.char_index.candidates(query_chars, options));
+        let near_miss = |query_idx: usize, target_idx: usize| {
+            options.keyboard_layout.as_ref().is_some_and(|layout| {
+                layout.adjacent(query_chars[query_idx], target_chars[target_idx])
+            })
+        };
+        let input = MatrixInput {
+            record_positions,
+            mask: masked.then_some(&self.mask),
+            candidates: candidates.as_ref(),
+            near_miss: options
+                .keyboard_layout
+                .is_some()
+                .then_some(&near_miss as &dyn Fn(usize, usize) -> bool),
+            ..MatrixInput::with_model(
+                &self.target_classes,
+                query_chars.len(),
+                &self.options,
+                &self.model,
+            )
+        };
+
+        // Comparing the whole target at once is wasted work when the position index
+        // already limits which target characters are compared.
+        #[cfg(feature = "simd")]
+        if input.candidates.is_none()
+            && simd::fill_match_table(&mut self.match_table, query_chars, target_chars, options)
+        {
+            let table = &self.match_table;
+            let target_len = target_chars.len();
+            let score = self.matrix.compute(&input, |query_idx, target_idx| {
+                simd::unit_match(table[query_idx * target_len + target_idx])
+            })?;
+            return Some(self.finish_compute(record_positions, score));
+        }
+
+        let score = self.matrix.compute(&input, |query_idx, target_idx| {
+            char_match_kind(query_chars[query_idx], target_chars[target_idx], options)
+        })?;
+        Some(self.finish_compute(record_positions, score))
+    }
+
+    /// Adjusts the result of the scoring matrix for the window of the target, for
+    /// substring matches and for exact matches.
+    fn finish_compute(&mut self, record_positions: bool, sum: isize) -> RawScore {
+        if record_positions && self.window_offset != 0 {
+            for position in &mut self.matrix.positions {
+                *position += self.window_offset;
             }
         }
+        if self.options.pin_exact_matches && self.is_exact_match() {
+            return RawScore::Exact;
+        }
+        RawScore::Sum {
+            sum,
+            bonus: self.substring_bonus(),
+        }
+    }
 
-        // Final score will always be in the last slot of the final score vector
-        let score = *self.prev_score.last().unwrap_or(&0);
-        if score == 0 {
-            // Score of zero is not a match
-            None
+    /// Returns the `substring_bonus` option if the loaded query occurs in the loaded target
+    /// as a substring, or zero otherwise.
+    fn substring_bonus(&self) -> usize {
+        let (target_chars, query_chars) = (&self.target_chars, &self.query_chars);
+        let found = self.options.substring_bonus != 0
+            && matrix::contains_query(target_chars.len(), query_chars.len(), |query_idx, i| {
+                char_match_kind(query_chars[query_idx], target_chars[i], &self.options).is_some()
+            });
+        if found {
+            self.options.substring_bonus
         } else {
-            Some(score)
+            0
         }
     }
+
+    /// Returns true if the loaded query is equal to the entire loaded target, using the
+    /// same rules for comparing characters as the rest of the matcher.
+    fn is_exact_match(&self) -> bool {
+        !self.truncated
+            && self.query_chars.len() == self.target_chars.len()
+            && self
+                .query_chars
+                .iter()
+                .zip(self.target_chars.iter())
+                .all(|(q, t)| char_match_kind(*q, *t, &self.options).is_some())
+    }
+}
+
+impl<M: ScoreModel + Default> Default for FuzzyMatcher<M> {
+    fn default() -> Self {
+        Self::with_model(M::default(), MatchOptions::default())
+    }
 }
 
 /// Fuzzy match a string against a query string. Returns a score that is higher for
@@ -343,6 +1062,56 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_empty_query() {
+        use crate::{EmptyQueryPolicy, FuzzyMatcher, MatchOptions, TieBreak};
+
+        let targets = ["src/main.rs", "lib.rs", "Cargo.toml", ""];
+        let mut matcher = FuzzyMatcher::new();
+        assert_eq!(matcher.fuzzy_match("lib.rs", ""), None);
+        assert!(matcher.rank(targets, "", TieBreak::DEFAULT).is_empty());
+
+        // Every target matches with the lowest score, ranked as equal scores are
+        let mut matcher = FuzzyMatcher::with_options(MatchOptions {
+            empty_query: EmptyQueryPolicy::MatchAllZeroScore,
+            ..MatchOptions::default()
+        });
+        assert_eq!(matcher.fuzzy_match("lib.rs", ""), Some(1));
+        assert_eq!(matcher.fuzzy_match_as::<isize>("lib.rs", ""), Some(0));
+        assert!(matcher
+            .fuzzy_match_details("lib.rs", "")
+            .unwrap()
+            .positions
+            .is_empty());
+        let ranked = matcher.rank(targets, "", TieBreak::DEFAULT);
+        assert_eq!(
+            ranked.iter().map(|m| m.target).collect::<Vec<_>>(),
+            ["", "lib.rs", "Cargo.toml", "src/main.rs"]
+        );
+        assert_eq!(matcher.fuzzy_match("lib.rs", "x"), None);
+
+        // Every target matches in the order given, whichever API is used
+        let mut matcher = FuzzyMatcher::with_options(MatchOptions {
+            empty_query: EmptyQueryPolicy::MatchAllNaturalOrder,
+            ..MatchOptions::default()
+        });
+        let ranked = matcher.rank(targets, "", TieBreak::DEFAULT);
+        assert_eq!(ranked.iter().map(|m| m.target).collect::<Vec<_>>(), targets);
+        let ranked: Vec<crate::Scored<&str>> =
+            matcher.rank_items_boosted(targets, |t| t, |t| t.len(), "", false);
+        assert_eq!(ranked.iter().map(|m| m.item).collect::<Vec<_>>(), targets);
+        assert!(ranked.iter().all(|m| m.score == 1));
+        let mut index = crate::Index::with_options(matcher.options().clone());
+        for target in targets {
+            index.add(target);
+        }
+        let results = index.query("", 10);
+        assert_eq!(
+            results.iter().map(|m| m.item).collect::<Vec<_>>(),
+            [0, 1, 2, 3]
+        );
+    }
+
     #[test]
     fn test_ranking() {
         const TARGET: &str = "The quick brown fox jumps over the lazy dog.";
@@ -411,6 +1180,145 @@ mod tests {
         assert!(result.is_some());
         let result = crate::fuzzy_match("c:\\windows\\notepad.exe", "\\windows");
         assert!(result.is_some());
+
+        let mut matcher = crate::FuzzyMatcher::with_options(crate::MatchOptions {
+            slash_equivalence: false,
+            ..crate::MatchOptions::default()
+        });
+        assert!(matcher.fuzzy_match("/bin/ls", "/ls").is_some());
+        assert!(matcher.fuzzy_match("/bin/ls", "\\ls").is_none());
+        assert!(matcher.fuzzy_match("\\d+/", "\\d").is_some());
+        assert!(matcher.fuzzy_match("\\d+/", "/d").is_none());
+    }
+
+    #[test]
+    fn test_equivalences() {
+        let mut matcher = crate::FuzzyMatcher::with_options(crate::MatchOptions {
+            equivalences: alloc::vec!["-_".into()],
+            ..crate::MatchOptions::default()
+        });
+        assert!(crate::fuzzy_match("my-crate", "my_crate").is_none());
+        assert!(matcher.fuzzy_match("my-crate", "my_crate").is_some());
+        assert!(matcher.fuzzy_match("my_crate", "my-crate").is_some());
+        assert!(
+            matcher.fuzzy_match("my_crate", "my-crate")
+                < matcher.fuzzy_match("my_crate", "my_crate")
+        );
+        assert!(matcher.fuzzy_match("my.crate", "my-crate").is_none());
+    }
+
+    #[test]
+    fn test_ignored_chars() {
+        let title = "Run: Build & Test (release)";
+        let options = crate::MatchOptions {
+            ignored_chars: " :&()".into(),
+            ..crate::MatchOptions::default()
+        };
+        let mut matcher = crate::FuzzyMatcher::with_options(options.clone());
+        assert!(
+            matcher.fuzzy_match(title, "runbuildtest").unwrap()
+                > crate::fuzzy_match(title, "runbuildtest").unwrap()
+        );
+        let result = matcher.fuzzy_match_details(title, "runbuildtest").unwrap();
+        assert_eq!(result.positions, [0, 1, 2, 5, 6, 7, 8, 9, 13, 14, 15, 16]);
+        assert_eq!(result.longest_run, 5);
+        assert!(matcher.fuzzy_match(title, "run:").is_none());
+
+        // Skipped separators still start words
+        let result = matcher.fuzzy_match_details("run: build", "rb").unwrap();
+        assert_eq!(result.positions, [0, 5]);
+        assert_eq!(result.word_starts_hit, 2);
+
+        let mut set = crate::CandidateSet::with_options(crate::MatchOptions {
+            position_index: true,
+            ..options
+        });
+        set.insert(title);
+        set.insert("Run Tests");
+        let results = set.query("buildtest", 10);
+        assert_eq!(results.len(), 1);
+        assert_eq!(set.get(results[0].item), Some(title));
+    }
+
+    #[test]
+    fn test_ignore_extension() {
+        let options = crate::MatchOptions {
+            ignore_extension: true,
+            length_normalization: 8,
+            ..crate::MatchOptions::default()
+        };
+        let mut matcher = crate::FuzzyMatcher::with_options(crate::MatchOptions {
+            ignore_extension: false,
+            ..options.clone()
+        });
+        assert!(
+            matcher.fuzzy_match("src/main.rs", "main")
+                > matcher.fuzzy_match("src/main.toml", "main")
+        );
+        matcher.set_options(options.clone());
+        assert_eq!(
+            matcher.fuzzy_match("src/main.rs", "main"),
+            matcher.fuzzy_match("src/main.toml", "main")
+        );
+        assert!(matcher.fuzzy_match("src/main.rs", "mrs").is_none());
+        assert!(matcher.fuzzy_match("lib/.gitignore", "git").is_some());
+        assert!(matcher.fuzzy_match("archive.tar.gz", "tar").is_some());
+
+        // A query with a dot matches the extension
+        let result = matcher.fuzzy_match_details("src/main.rs", ".rs").unwrap();
+        assert_eq!(result.positions, [8, 9, 10]);
+        let constraints = crate::MatchConstraints {
+            end: Some(10..11),
+            ..crate::MatchConstraints::default()
+        };
+        assert!(matcher
+            .fuzzy_match_constrained("src/main.rs", "m.rs", &constraints)
+            .is_some());
+        assert!(matcher
+            .fuzzy_match_constrained("src/main.toml", "m.rs", &constraints)
+            .is_none());
+
+        let mut set = crate::CandidateSet::with_options(crate::MatchOptions {
+            position_index: true,
+            ..options
+        });
+        set.insert("src/main.rs");
+        set.insert("src/lib.rs");
+        assert_eq!(set.query("rs", 10).len(), 0);
+        assert_eq!(set.query(".rs", 10).len(), 2);
+    }
+
+    #[test]
+    fn test_case_locale() {
+        assert!(crate::fuzzy_match("ILIK", "ilik").is_some());
+        assert!(crate::fuzzy_match("ılık", "ILIK").is_none());
+
+        let options = crate::MatchOptions {
+            case_locale: crate::CaseLocale::Turkish,
+            ..crate::MatchOptions::default()
+        };
+        let mut matcher = crate::FuzzyMatcher::with_options(options.clone());
+        assert!(matcher.fuzzy_match("ILIK", "ilik").is_none());
+        assert!(matcher.fuzzy_match("ILIK", "ılık").is_some());
+        assert!(matcher.fuzzy_match("ılık", "ILIK").is_some());
+        assert!(matcher.fuzzy_match("İzmir", "izmir").is_some());
+        assert!(matcher.fuzzy_match("izmir", "İZMİR").is_some());
+        assert!(matcher.fuzzy_match("izmir", "IZMIR").is_none());
+        assert!(matcher.fuzzy_match("Kasım", "kasim").is_none());
+        assert!(matcher.fuzzy_match("KASIM", "kasım").is_some());
+        // Other letters still fold with the default rules
+        assert!(matcher.fuzzy_match("ŞEHİR", "şehir").is_some());
+
+        // The prefilter and position index agree with the matcher
+        let query_mask = matcher.query_mask("ılık");
+        assert!(query_mask.may_match(&crate::CharMask::new("ILIK")));
+        matcher.set_options(crate::MatchOptions {
+            position_index: true,
+            ..options
+        });
+        assert!(matcher.fuzzy_match("ILIK", "ılık").is_some());
+        assert!(matcher.fuzzy_match("ılık", "ILIK").is_some());
+        assert!(matcher.fuzzy_match("ILIK", "ilik").is_none());
     }
 
     #[test]
@@ -448,4 +1356,642 @@ mod tests {
             lower
         );
     }
+
+    #[test]
+    fn test_bidirectional() {
+        let mut forward = crate::FuzzyMatcher::new();
+        let mut bidirectional = crate::FuzzyMatcher::with_options(crate::MatchOptions {
+            bidirectional: true,
+            ..crate::MatchOptions::default()
+        });
+
+        // The forward pass prefers the earlier word starts, but the contiguous match at the
+        // end of the target is better.
+        let forward_score = forward.fuzzy_match("foo bar foobar", "foobar");
+        let bidirectional_score = bidirectional.fuzzy_match("foo bar foobar", "foobar");
+        assert!(
+            bidirectional_score > forward_score,
+            "bidirectional = {:?}, forward = {:?}",
+            bidirectional_score,
+            forward_score
+        );
+
+        // Never worse than the forward pass
+        const TARGET: &str = "The quick brown fox jumps over the lazy dog.";
+        for query in ["fox", "The quick", "jmp the do", "xz", "ee"] {
+            assert!(bidirectional.fuzzy_match(TARGET, query) >= forward.fuzzy_match(TARGET, query));
+        }
+    }
+
+    #[test]
+    fn test_right_to_left() {
+        let mut matcher = crate::FuzzyMatcher::with_options(crate::MatchOptions {
+            right_to_left: true,
+            ..crate::MatchOptions::default()
+        });
+
+        // The forward pass prefers the earlier word starts
+        let forward = crate::FuzzyMatcher::new()
+            .fuzzy_match_details("foo bar foobar", "foobar")
+            .unwrap();
+        let result = matcher
+            .fuzzy_match_details("foo bar foobar", "foobar")
+            .unwrap();
+        assert_eq!(forward.positions, [0, 1, 2, 4, 5, 6]);
+        assert_eq!(result.positions, [8, 9, 10, 11, 12, 13]);
+        assert!(result.score > forward.score);
+
+        // Later placements are favored over the start of target bonus
+        const TARGET: &str = "lib_ext/lib/lib.rs";
+        let result = matcher.fuzzy_match_details(TARGET, "lib").unwrap();
+        assert_eq!(result.positions, [12, 13, 14]);
+        assert!(Some(result.score) < crate::fuzzy_match(TARGET, "lib"));
+        let result = matcher.fuzzy_match_details(TARGET, "libr").unwrap();
+        assert_eq!(result.positions, [12, 13, 14, 16]);
+        assert_eq!(
+            matcher.fuzzy_match_explain(TARGET, "libr").unwrap().score,
+            result.score
+        );
+    }
+
+    #[test]
+    fn test_typos() {
+        let mut exact = crate::FuzzyMatcher::new();
+        let mut tolerant = crate::FuzzyMatcher::with_options(crate::MatchOptions {
+            max_typos: 1,
+            ..crate::MatchOptions::default()
+        });
+
+        // Swapped letters are a match with a typo
+        assert!(exact.fuzzy_match("quick brown fox", "qiuck").is_none());
+        let typo = tolerant.fuzzy_match("quick brown fox", "qiuck");
+        assert!(typo.is_some());
+        assert!(typo < tolerant.fuzzy_match("quick brown fox", "quick"));
+
+        // Queries without typos score the same as without typo tolerance
+        const TARGET: &str = "The quick brown fox jumps over the lazy dog.";
+        for query in ["fox", "The quick", "jmp the do", "xz", "ee"] {
+            assert_eq!(
+                tolerant.fuzzy_match(TARGET, query),
+                exact.fuzzy_match(TARGET, query)
+            );
+        }
+
+        // Only one typo is allowed
+        assert!(tolerant.fuzzy_match("quick brown fox", "qxiuck").is_none());
+
+        // Short queries can't have typos
+        assert!(tolerant.fuzzy_match("quick brown fox", "qz").is_none());
+
+        // Huge penalties don't wrap around into bonuses
+        for typo_penalty in [0x8000_0000_0000_0000, usize::MAX] {
+            let mut tolerant = crate::FuzzyMatcher::with_options(crate::MatchOptions {
+                max_typos: 1,
+                typo_penalty,
+                gap_penalty: usize::MAX,
+                ..crate::MatchOptions::default()
+            });
+            assert_eq!(tolerant.fuzzy_match("quick brown fox", "qxuick"), Some(1));
+            assert_eq!(tolerant.fuzzy_match("quick brown fox", "qbxf"), Some(1));
+            let explanation = tolerant
+                .fuzzy_match_explain("quick brown fox", "qxuick")
+                .unwrap();
+            assert_eq!(explanation.score, 1);
+            assert_eq!(
+                tolerant.fuzzy_match("quick brown fox", "quick"),
+                exact.fuzzy_match("quick brown fox", "quick")
+            );
+        }
+    }
+
+    #[test]
+    fn test_transpositions() {
+        let mut exact = crate::FuzzyMatcher::new();
+        let mut tolerant = crate::FuzzyMatcher::with_options(crate::MatchOptions {
+            transpositions: true,
+            ..crate::MatchOptions::default()
+        });
+
+        assert!(exact.fuzzy_match("the", "teh").is_none());
+        let transposed = tolerant.fuzzy_match("the", "teh");
+        assert!(transposed.is_some());
+        assert!(transposed < tolerant.fuzzy_match("the", "the"));
+
+        // Queries without transpositions score the same as without transposition tolerance
+        const TARGET: &str = "The quick brown fox jumps over the lazy dog.";
+        for query in ["fox", "The quick", "jmp the do", "xz", "ee"] {
+            assert_eq!(
+                tolerant.fuzzy_match(TARGET, query),
+                exact.fuzzy_match(TARGET, query)
+            );
+        }
+
+        // Characters must be adjacent in the target to be transposed
+        assert!(tolerant.fuzzy_match("t_h_e", "teh").is_none());
+
+        // Huge penalties don't wrap around into bonuses
+        for transposition_penalty in [0x8000_0000_0000_0000, usize::MAX] {
+            let mut tolerant = crate::FuzzyMatcher::with_options(crate::MatchOptions {
+                transpositions: true,
+                transposition_penalty,
+                ..crate::MatchOptions::default()
+            });
+            assert_eq!(tolerant.fuzzy_match("the", "teh"), Some(1));
+        }
+    }
+
+    #[test]
+    fn test_gap_penalty() {
+        let mut matcher = crate::FuzzyMatcher::with_options(crate::MatchOptions {
+            gap_penalty: 1,
+            ..crate::MatchOptions::default()
+        });
+
+        // Unrelated letters far apart are heavily penalized
+        const TARGET: &str = "The quick brown fox jumps over the lazy dog.";
+        let scattered = matcher.fuzzy_match(TARGET, "xz");
+        assert!(scattered < crate::fuzzy_match(TARGET, "xz"));
+        assert_eq!(scattered, Some(1));
+
+        // Compact matches are preferred over scattered ones
+        assert!(matcher.fuzzy_match("a_bc", "abc") > matcher.fuzzy_match("a___b___c", "abc"));
+        assert_eq!(
+            matcher.fuzzy_match("jump", "jump"),
+            crate::fuzzy_match("jump", "jump")
+        );
+
+        // Huge penalties don't wrap around into bonuses
+        const SPREAD: &str = "src/config/lib.rs xx";
+        assert!(crate::fuzzy_match(SPREAD, "cfgl") > Some(1));
+        for gap_penalty in [0x8000_0000_0000_0000, usize::MAX] {
+            let options = crate::MatchOptions {
+                gap_penalty,
+                ..crate::MatchOptions::default()
+            };
+            for options in [
+                options.clone(),
+                crate::MatchOptions {
+                    position_index: true,
+                    ..options.clone()
+                },
+                crate::MatchOptions {
+                    max_gap: Some(8),
+                    ..options.clone()
+                },
+            ] {
+                let mut matcher = crate::FuzzyMatcher::with_options(options);
+                assert_eq!(matcher.fuzzy_match(SPREAD, "cfgl"), Some(1));
+                assert_eq!(
+                    matcher.fuzzy_match("jump", "jump"),
+                    crate::fuzzy_match("jump", "jump")
+                );
+            }
+            let mut scratch = crate::MatchScratch::<64>::with_options(options.clone());
+            assert_eq!(crate::fuzzy_match_in(SPREAD, "cfgl", &mut scratch), Some(1));
+            let mut matcher = crate::FuzzyMatcher::with_options(options);
+            matcher.begin("cfgl");
+            matcher.feed(SPREAD);
+            assert_eq!(matcher.finish(), Some(1));
+        }
+    }
+
+    #[test]
+    fn test_leading_gap_penalty() {
+        let mut matcher = crate::FuzzyMatcher::with_options(crate::MatchOptions {
+            leading_gap_penalty: 1,
+            max_leading_gap_penalty: 10,
+            ..crate::MatchOptions::default()
+        });
+
+        // Without the penalty, these are scored the same
+        assert_eq!(
+            crate::fuzzy_match("a foo", "foo"),
+            crate::fuzzy_match("a long way to foo", "foo")
+        );
+        assert!(
+            matcher.fuzzy_match("a foo", "foo") > matcher.fuzzy_match("a long way to foo", "foo")
+        );
+
+        // The penalty is capped
+        assert_eq!(
+            matcher.fuzzy_match("a long way to foo", "foo"),
+            matcher.fuzzy_match("a much longer way to foo", "foo")
+        );
+
+        // Huge penalties don't wrap around into bonuses
+        for leading_gap_penalty in [isize::MAX as usize - 1, usize::MAX] {
+            let options = crate::MatchOptions {
+                leading_gap_penalty,
+                ..crate::MatchOptions::default()
+            };
+            for options in [
+                options.clone(),
+                crate::MatchOptions {
+                    position_index: true,
+                    ..options.clone()
+                },
+                crate::MatchOptions {
+                    max_typos: 1,
+                    ..options.clone()
+                },
+                crate::MatchOptions {
+                    max_gap: Some(8),
+                    ..options.clone()
+                },
+            ] {
+                let mut matcher = crate::FuzzyMatcher::with_options(options);
+                assert_eq!(matcher.fuzzy_match("a foo", "foo"), Some(1));
+                assert_eq!(
+                    matcher.fuzzy_match("foo", "foo"),
+                    crate::fuzzy_match("foo", "foo")
+                );
+            }
+            let mut scratch = crate::MatchScratch::<64>::with_options(options.clone());
+            assert_eq!(crate::fuzzy_match_in("a foo", "foo", &mut scratch), Some(1));
+            let mut matcher = crate::FuzzyMatcher::with_options(options);
+            matcher.begin("foo");
+            matcher.feed("a foo");
+            assert_eq!(matcher.finish(), Some(1));
+        }
+    }
+
+    #[test]
+    fn test_max_gap() {
+        let options = crate::MatchOptions {
+            max_gap: Some(3),
+            ..crate::MatchOptions::default()
+        };
+        let mut matcher = crate::FuzzyMatcher::with_options(options.clone());
+        const TARGET: &str = "The quick brown fox jumps over the lazy dog.";
+        assert!(crate::fuzzy_match(TARGET, "xz").is_some());
+        assert!(matcher.fuzzy_match(TARGET, "xz").is_none());
+        assert!(matcher.fuzzy_match(TARGET, "fxj").is_some());
+        assert!(matcher.fuzzy_match("f___o___x", "fox").is_some());
+        assert!(matcher.fuzzy_match("f____o___x", "fox").is_none());
+        assert_eq!(
+            matcher.fuzzy_match("src/lib.rs", "lib"),
+            crate::fuzzy_match("src/lib.rs", "lib")
+        );
+
+        // The best alignment within the limit is found even if a better one is too spread
+        // out
+        let result = matcher
+            .fuzzy_match_details("Abcd_____Efg_ae", "ae")
+            .unwrap();
+        assert_eq!(result.positions, [13, 14]);
+        assert_eq!(
+            matcher
+                .fuzzy_match_explain("Abcd_____Efg_ae", "ae")
+                .unwrap()
+                .score,
+            result.score
+        );
+        let constraints = crate::MatchConstraints {
+            required: alloc::vec![9],
+            ..crate::MatchConstraints::default()
+        };
+        assert!(matcher
+            .fuzzy_match_constrained("Abcd_____Efg_ae", "ae", &constraints)
+            .is_none());
+        assert!(matcher
+            .fuzzy_match_constrained("Abcd_____Efg_ae", "efa", &constraints)
+            .is_some());
+
+        let mut set = crate::CandidateSet::with_options(options);
+        set.insert(TARGET);
+        set.insert("xyz");
+        let results = set.query("xz", 10);
+        assert_eq!(results.len(), 1);
+        assert_eq!(set.get(results[0].item), Some("xyz"));
+    }
+
+    #[test]
+    fn test_whole_word_bonuses() {
+        let mut matcher = crate::FuzzyMatcher::with_options(crate::MatchOptions {
+            whole_word_bonus: 10,
+            contiguous_query_bonus: 20,
+            ..crate::MatchOptions::default()
+        });
+
+        // The bonuses widen the margin of a literal word match
+        const WORD: &str = "the lazy dog barks";
+        const SCATTERED: &str = "doing";
+        let margin = crate::fuzzy_match(WORD, "dog").unwrap() as isize
+            - crate::fuzzy_match(SCATTERED, "dog").unwrap() as isize;
+        let bonus_margin = matcher.fuzzy_match(WORD, "dog").unwrap() as isize
+            - matcher.fuzzy_match(SCATTERED, "dog").unwrap() as isize;
+        assert_eq!(bonus_margin, margin + 30);
+
+        // A word prefix is contiguous but not a whole word
+        assert_eq!(
+            matcher.fuzzy_match("doghouse", "dog"),
+            crate::fuzzy_match("doghouse", "dog").map(|score| score + 20)
+        );
+
+        // Huge bonuses saturate instead of wrapping around into penalties
+        for max_typos in [0, 1] {
+            let mut matcher = crate::FuzzyMatcher::with_options(crate::MatchOptions {
+                whole_word_bonus: usize::MAX,
+                contiguous_query_bonus: usize::MAX,
+                max_typos,
+                ..crate::MatchOptions::default()
+            });
+            let word = matcher.fuzzy_match(WORD, "dog");
+            assert!(word > crate::fuzzy_match(WORD, "dog"));
+            assert!(word > matcher.fuzzy_match(SCATTERED, "dog"));
+            let explanation = matcher.fuzzy_match_explain(WORD, "dog").unwrap();
+            assert_eq!(Some(explanation.score), word);
+            if max_typos == 0 {
+                let mut scratch =
+                    crate::MatchScratch::<64>::with_options(matcher.options().clone());
+                assert_eq!(crate::fuzzy_match_in(WORD, "dog", &mut scratch), word);
+            }
+        }
+    }
+
+    #[test]
+    fn test_substring_bonus() {
+        let mut matcher = crate::FuzzyMatcher::with_options(crate::MatchOptions {
+            substring_bonus: 100,
+            ..crate::MatchOptions::default()
+        });
+
+        // Word starts outrank a literal match in the middle of a word without the bonus
+        const SCATTERED: &str = "FooBar";
+        const LITERAL: &str = "xfbx";
+        assert!(crate::fuzzy_match(SCATTERED, "fb") > crate::fuzzy_match(LITERAL, "fb"));
+        assert!(matcher.fuzzy_match(SCATTERED, "fb") < matcher.fuzzy_match(LITERAL, "fb"));
+        assert_eq!(
+            matcher.fuzzy_match(LITERAL, "FB"),
+            crate::fuzzy_match(LITERAL, "FB").map(|score| score + 100)
+        );
+        assert_eq!(
+            matcher.fuzzy_match(SCATTERED, "fb"),
+            crate::fuzzy_match(SCATTERED, "fb")
+        );
+
+        // The bonus applies even when the best match is elsewhere in the target
+        let result = matcher.fuzzy_match_details("FooBar_fbx", "fb").unwrap();
+        assert_eq!(result.positions, alloc::vec![0, 3]);
+        assert_eq!(
+            Some(result.score),
+            crate::fuzzy_match("FooBar_fbx", "fb").map(|score| score + 100)
+        );
+    }
+
+    #[test]
+    fn test_length_normalization() {
+        const LONG: &str =
+            "Config/src/modules/settings/loader/very/deeply/nested/path/to/the/config.rs";
+        const SHORT: &str = "lib/my_config.rs";
+        let mut matcher = crate::FuzzyMatcher::with_options(crate::MatchOptions {
+            length_normalization: 16,
+            ..crate::MatchOptions::default()
+        });
+
+        // Long targets no longer outrank short ones with a similar match
+        assert!(crate::fuzzy_match(LONG, "config") > crate::fuzzy_match(SHORT, "config"));
+        assert!(matcher.fuzzy_match(LONG, "config") < matcher.fuzzy_match(SHORT, "config"));
+
+        // A target that is `length_normalization` characters longer than the query scores
+        // half as much, and targets as long as the query are not scaled
+        let target = "x".repeat(16) + "config";
+        assert_eq!(
+            matcher.fuzzy_match(&target, "config"),
+            crate::fuzzy_match(&target, "config").map(|score| score / 2)
+        );
+        assert_eq!(
+            matcher.fuzzy_match("config", "cfg"),
+            crate::fuzzy_match("config", "cfg").map(|score| score * 16 / 19)
+        );
+        assert_eq!(
+            matcher.fuzzy_match("Config", "config"),
+            crate::fuzzy_match("Config", "config")
+        );
+    }
+
+    #[test]
+    fn test_exact_match() {
+        let mut matcher = crate::FuzzyMatcher::with_options(crate::MatchOptions {
+            pin_exact_matches: true,
+            equivalences: alloc::vec!["-_".into()],
+            ..crate::MatchOptions::default()
+        });
+        assert_eq!(
+            matcher.fuzzy_match("Cargo.toml", "cargo.TOML"),
+            Some(crate::EXACT_MATCH_SCORE)
+        );
+        assert_eq!(
+            matcher.fuzzy_match("my-crate", "my_crate"),
+            Some(crate::EXACT_MATCH_SCORE)
+        );
+        assert!(matcher.fuzzy_match("Cargo.toml", "cargo.tom") < Some(crate::EXACT_MATCH_SCORE));
+        assert!(
+            matcher
+                .fuzzy_match_details("src\\lib.rs", "src/lib.rs")
+                .unwrap()
+                .exact_match
+        );
+        assert!(
+            !matcher
+                .fuzzy_match_details("src/lib.rs", "srclib.rs")
+                .unwrap()
+                .exact_match
+        );
+
+        // Truncated targets are never equal to the query
+        matcher.set_options(crate::MatchOptions {
+            pin_exact_matches: true,
+            truncation: Some(crate::Truncation::First(4)),
+            ..crate::MatchOptions::default()
+        });
+        assert!(matcher.fuzzy_match("main.rs", "main") < Some(crate::EXACT_MATCH_SCORE));
+    }
+
+    #[test]
+    fn test_require_word_start() {
+        for max_typos in [0, 1] {
+            let mut matcher = crate::FuzzyMatcher::with_options(crate::MatchOptions {
+                require_word_start: true,
+                max_typos,
+                ..crate::MatchOptions::default()
+            });
+            assert!(matcher.fuzzy_match("Open File", "of").is_some());
+            assert!(matcher.fuzzy_match("proof", "of").is_none());
+            assert!(matcher.fuzzy_match("src/openFile.rs", "of").is_some());
+            assert!(matcher.fuzzy_match("src/openFile.rs", "pf").is_none());
+        }
+    }
+
+    #[test]
+    fn test_camel_humps() {
+        for bidirectional in [false, true] {
+            let options = crate::MatchOptions {
+                camel_humps: true,
+                bidirectional,
+                ..crate::MatchOptions::default()
+            };
+            let mut matcher = crate::FuzzyMatcher::with_options(options.clone());
+            let result = matcher.fuzzy_match_details("FooBar", "FBar").unwrap();
+            assert_eq!(result.positions, [0, 3, 4, 5]);
+            assert!(matcher.fuzzy_match("FooBar", "oar").is_none());
+            assert!(matcher.fuzzy_match("FooBar", "fob").is_some());
+            assert!(matcher.fuzzy_match("FooBar", "fr").is_none());
+            assert!(matcher.fuzzy_match("src/openFile.rs", "opfirs").is_some());
+
+            // A match inside a word is found even when a better score was carried past
+            // the start of the word
+            let result = matcher.fuzzy_match_details("BoxBag", "bag").unwrap();
+            assert_eq!(result.positions, [3, 4, 5]);
+            assert_eq!(
+                matcher.fuzzy_match_explain("BoxBag", "bag").unwrap().score,
+                result.score
+            );
+
+            let mut set = crate::CandidateSet::with_options(crate::MatchOptions {
+                position_index: true,
+                ..options
+            });
+            set.insert("FooBar");
+            set.insert("FooterBarrier");
+            assert_eq!(set.query("foba", 10).len(), 2);
+            assert_eq!(set.query("oar", 10).len(), 0);
+        }
+    }
+
+    #[test]
+    fn test_file_path_profile() {
+        let mut general = crate::FuzzyMatcher::new();
+        let mut file_path = crate::FuzzyMatcher::with_options(crate::MatchOptions {
+            profile: crate::ScoringProfile::FilePath,
+            ..crate::MatchOptions::default()
+        });
+
+        // The start of target bonus favors the directory name, unless matches in the file
+        // name are boosted.
+        assert!(
+            general.fuzzy_match("mod/foo.rs", "mod") > general.fuzzy_match("foo/mod.rs", "mod")
+        );
+        assert!(
+            file_path.fuzzy_match("foo/mod.rs", "mod") > file_path.fuzzy_match("mod/foo.rs", "mod")
+        );
+
+        // Targets without a path separator are entirely a file name
+        assert!(file_path.fuzzy_match("module", "mod") > general.fuzzy_match("module", "mod"));
+    }
+
+    #[test]
+    fn test_long_target_tail() {
+        let mut matcher = crate::FuzzyMatcher::new();
+        let tail = "x".repeat(10_000);
+        for target in [
+            alloc::format!("src/main.rs {tail}"),
+            alloc::format!("{tail} src/main.rs"),
+            alloc::format!("main{tail}.rs"),
+        ] {
+            // The batch APIs compute the entire matrix for every target
+            let score = matcher.fuzzy_match(&target, "main.rs");
+            let ranked = matcher.rank([target.as_str()], "main.rs", &[]);
+            assert_eq!(score, ranked.first().map(|m| m.score));
+
+            let result = matcher.fuzzy_match_details(&target, "main.rs").unwrap();
+            let matched: alloc::string::String = result
+                .positions
+                .iter()
+                .map(|idx| &target[*idx..*idx + 1])
+                .collect();
+            assert_eq!(matched, "main.rs");
+        }
+    }
+
+    #[test]
+    fn test_char_targets() {
+        use crate::{MatchOptions, ScoringProfile, Truncation};
+        use alloc::string::ToString;
+        use alloc::vec;
+
+        let options = [
+            MatchOptions::default(),
+            MatchOptions {
+                path_separators: vec!["::".to_string()],
+                position_index: true,
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                pin_exact_matches: true,
+                substring_bonus: 100,
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                truncation: Some(Truncation::Last(16)),
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                profile: ScoringProfile::VsCodeCompat,
+                ..MatchOptions::default()
+            },
+        ];
+        for options in options {
+            let mut matcher = crate::FuzzyMatcher::with_options(options);
+            for (target, query) in [
+                ("std::collections::HashMap", "::hash"),
+                ("The quick brown fox", "bro fox"),
+                ("src/lib.rs", "src/lib.rs"),
+                ("src/lib.rs", "main"),
+            ] {
+                assert_eq!(
+                    matcher.fuzzy_match_chars(target.chars(), query),
+                    matcher.fuzzy_match(target, query),
+                    "{target}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_shrink_to_fit() {
+        let mut matcher = crate::FuzzyMatcher::with_capacity(64);
+        assert!(matcher.target_chars.capacity() >= 64);
+        let score = matcher.fuzzy_match("src/lib.rs", "lib");
+
+        let long_target = "x".repeat(100_000);
+        assert!(matcher.fuzzy_match(&long_target, "xx").is_some());
+        assert!(matcher.target_chars.capacity() >= 100_000);
+
+        matcher.shrink_to_fit();
+        assert!(matcher.target_chars.capacity() < 100_000);
+        assert!(matcher.target_chars.capacity() >= 64);
+        assert_eq!(matcher.fuzzy_match("src/lib.rs", "lib"), score);
+    }
+
+    #[test]
+    fn test_score_version() {
+        // Scores of each version must never change. Add a new version instead.
+        let mut matcher = crate::FuzzyMatcher::with_options(crate::MatchOptions {
+            score_version: crate::ScoreVersion::V1,
+            ..crate::MatchOptions::default()
+        });
+        assert_eq!(matcher.score_version(), crate::ScoreVersion::V1);
+        for (target, query, score) in [
+            ("src/lib.rs", "lib", 21),
+            ("The quick brown fox", "bro fox", 67),
+            ("camelCase", "cC", 14),
+            ("FuzzyMatcher::match_batch", "fmb", 17),
+            ("Grüße aus Köln", "köln", 41),
+            ("src/module_12/lib.rs", "m12l", 15),
+        ] {
+            assert_eq!(matcher.fuzzy_match(target, query), Some(score), "{target}");
+        }
+
+        assert_eq!(
+            crate::FuzzyMatcher::new().score_version(),
+            crate::ScoreVersion::LATEST
+        );
+        assert_eq!(crate::ScoreVersion::V1.number(), 1);
+        assert_eq!(
+            crate::ScoreVersion::from_number(1),
+            Some(crate::ScoreVersion::V1)
+        );
+        assert_eq!(crate::ScoreVersion::from_number(0), None);
+    }
 }
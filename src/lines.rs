@@ -0,0 +1,123 @@
+//! Streaming matching of the lines of a reader, for inputs too large to hold in memory.
+
+use crate::{FuzzyMatcher, ScoreModel, Scored};
+use alloc::collections::BinaryHeap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+use std::io::{self, BufRead};
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Fuzzy match each line read from `reader` against a query string, returning the `k`
+    /// best matches from best to worst. Each item is the zero based index of the line and
+    /// its text. Lines are scored as they are read, and only the `k` best lines are kept, so
+    /// inputs of any size can be filtered in bounded memory.
+    ///
+    /// Lines end with `\n` or `\r\n`, and the line ending is not part of the match. Lines
+    /// that are not valid UTF-8 are decoded as configured by the `byte_decoding` option.
+    /// Lines with the same score are ranked in the order they were read. Returns an error if
+    /// reading fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let log = "INFO started\nWARN disk low\nERROR disk full\nINFO stopped\n";
+    /// let mut matcher = code_fuzzy_match::FuzzyMatcher::new();
+    /// let best = matcher.match_lines(log.as_bytes(), "disk", 1).unwrap();
+    /// assert_eq!(best.len(), 1);
+    /// assert_eq!(best[0].item, (1, "WARN disk low".to_string()));
+    /// ```
+    pub fn match_lines(
+        &mut self,
+        mut reader: impl BufRead,
+        query: &str,
+        k: usize,
+    ) -> io::Result<Vec<Scored<(usize, String)>>> {
+        if k == 0 {
+            return Ok(Vec::new());
+        }
+        let mut best: BinaryHeap<Reverse<Scored<(usize, String)>>> = BinaryHeap::new();
+
+        let mut buf = Vec::new();
+        let mut line_idx = 0;
+        loop {
+            buf.clear();
+            if reader.read_until(b'\n', &mut buf)? == 0 {
+                break;
+            }
+            let line = buf.strip_suffix(b"\n").unwrap_or(&buf);
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            if let Some(score) = self.fuzzy_match_bytes(line, query) {
+                // Later lines lose ties, so a full set only changes for a higher score
+                let full = best.len() == k;
+                if !full || best.peek().is_some_and(|worst| score > worst.0.score) {
+                    let mut text = String::new();
+                    crate::bytes::decode(line, self.options.byte_decoding, &mut text);
+                    best.push(Reverse(Scored {
+                        item: (line_idx, text),
+                        score,
+                        positions: None,
+                    }));
+                    if full {
+                        best.pop();
+                    }
+                }
+            }
+            line_idx += 1;
+        }
+
+        Ok(best
+            .into_sorted_vec()
+            .into_iter()
+            .map(|result| result.0)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FuzzyMatcher;
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use std::io::BufReader;
+
+    #[test]
+    fn test_match_lines() {
+        let mut input = Vec::new();
+        for idx in 0..1000 {
+            input.extend_from_slice(format!("line {idx}: src/module_{idx}.rs\r\n").as_bytes());
+        }
+        input.extend_from_slice(b"caf\xe9 module_42.rs");
+        let text = String::from_utf8_lossy(&input).into_owned();
+
+        let mut matcher = FuzzyMatcher::new();
+        let reader = BufReader::with_capacity(64, input.as_slice());
+        let best = matcher.match_lines(reader, "mod42", 5).unwrap();
+
+        // The same as ranking every line, keeping earlier lines first on ties
+        let mut expected: Vec<(usize, String, usize)> = text
+            .lines()
+            .enumerate()
+            .filter_map(|(idx, line)| {
+                Some((idx, String::from(line), matcher.fuzzy_match(line, "mod42")?))
+            })
+            .collect();
+        expected.sort_by_key(|(idx, _, score)| (core::cmp::Reverse(*score), *idx));
+        expected.truncate(5);
+        assert_eq!(
+            best.into_iter()
+                .map(|result| (result.item.0, result.item.1, result.score))
+                .collect::<Vec<_>>(),
+            expected
+        );
+        assert!(expected
+            .iter()
+            .any(|(_, line, _)| line.starts_with("caf\u{fffd}")));
+
+        assert!(matcher
+            .match_lines(input.as_slice(), "mod42", 0)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,426 @@
+//! Read-only index format that can be queried in place, such as from a memory-mapped file.
+//!
+//! All fields are little endian, and every section starts on an 8 byte boundary. The file
+//! begins with a 64 byte header:
+//!
+//! | Offset | Size | Field                                               |
+//! |--------|------|-----------------------------------------------------|
+//! | 0      | 8    | Magic `CFMIMAP\0`                                   |
+//! | 8      | 4    | Format version                                      |
+//! | 12     | 4    | Flags, see below                                    |
+//! | 16     | 8    | Number of entries                                   |
+//! | 24     | 8    | Offset of the entry table                           |
+//! | 32     | 8    | Offset of the class section                         |
+//! | 40     | 8    | Offset of the text section                          |
+//! | 48     | 8    | Offset of the path separator section                |
+//! | 56     | 8    | Number of path separators                           |
+//!
+//! Bit 0 of the flags is set if `unicode_words` was enabled, and bit 1 if
+//! `digit_word_starts` was enabled.
+//!
+//! Each entry of the entry table is 40 bytes: the offset of the candidate in the text
+//! section (8), the offset of its classes in the class section (8), its length in bytes
+//! (4), flags with bit 0 set if the candidate was removed (4), and its [`CharMask`] (16).
+//! The class section holds one byte per character of each candidate, and the text section
+//! holds the UTF-8 text of each candidate. Each path separator is stored as its length (8)
+//! followed by its text, padded to 8 bytes.
+
+use crate::index::{class_from_byte, class_to_byte, TopMatches};
+use crate::matrix::CharClass;
+use crate::{CharMask, FuzzyMatcher, Index, IndexError, MatchOptions, Scored};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+const MAGIC: &[u8; 8] = b"CFMIMAP\0";
+const VERSION: u32 = 1;
+const HEADER_LEN: usize = 64;
+const ENTRY_LEN: usize = 40;
+
+/// A candidate as stored in the entry table.
+struct Record {
+    text: Range<usize>,
+    classes: usize,
+    removed: bool,
+    mask: CharMask,
+}
+
+/// An index in the read-only format written by [`Index::to_mapped_bytes`], queried in
+/// place without loading the candidates into memory. Opening an index only reads its
+/// header, so startup time does not depend on the number of candidates.
+///
+/// The index can hold its bytes in any container, such as a `Vec<u8>`, a byte slice, or a
+/// memory-mapped file opened with [`MappedIndex::open`]. Candidates are checked as they are
+/// read, and candidates with invalid data are skipped instead of causing a panic.
+///
+/// # Examples
+///
+/// ```
+/// use code_fuzzy_match::{Index, MappedIndex};
+///
+/// let mut index = Index::new();
+/// index.add("src/lib.rs");
+/// let main = index.add("src/main.rs");
+///
+/// let bytes = index.to_mapped_bytes();
+/// let mut mapped = MappedIndex::new(bytes.as_slice()).unwrap();
+/// let results = mapped.query("main", 10);
+/// assert_eq!(results[0].item, main);
+/// assert_eq!(mapped.get(main), Some("src/main.rs"));
+/// ```
+pub struct MappedIndex<B> {
+    bytes: B,
+    matcher: FuzzyMatcher,
+    entry_count: usize,
+    entries_offset: usize,
+    classes_offset: usize,
+    text_offset: usize,
+}
+
+impl<B: AsRef<[u8]>> MappedIndex<B> {
+    /// Opens an index from bytes written by [`Index::to_mapped_bytes`]. Only the header is
+    /// read and checked.
+    pub fn new(bytes: B) -> Result<Self, IndexError> {
+        let data = bytes.as_ref();
+        if data.len() < HEADER_LEN {
+            return Err(IndexError::UnexpectedEnd);
+        }
+        if &data[..8] != MAGIC {
+            return Err(IndexError::InvalidMagic);
+        }
+        let version = read_u32(data, 8).ok_or(IndexError::UnexpectedEnd)?;
+        if version != VERSION {
+            return Err(IndexError::UnsupportedVersion(
+                version.try_into().unwrap_or(u8::MAX),
+            ));
+        }
+        let flags = read_u32(data, 12).ok_or(IndexError::UnexpectedEnd)?;
+        let field = |offset| read_len(data, offset).ok_or(IndexError::InvalidData);
+        let entry_count = field(16)?;
+        let entries_offset = field(24)?;
+        let classes_offset = field(32)?;
+        let text_offset = field(40)?;
+        let mut separators_offset = field(48)?;
+        let separator_count = field(56)?;
+
+        let entries_end = entry_count
+            .checked_mul(ENTRY_LEN)
+            .and_then(|len| len.checked_add(entries_offset));
+        if entries_end.is_none_or(|end| end > data.len())
+            || classes_offset > data.len()
+            || text_offset > data.len()
+        {
+            return Err(IndexError::UnexpectedEnd);
+        }
+
+        let mut options = MatchOptions::default();
+        #[cfg(feature = "unicode-words")]
+        {
+            options.unicode_words = flags & 1 != 0;
+        }
+        #[cfg(not(feature = "unicode-words"))]
+        if flags & 1 != 0 {
+            // Candidates were classified using word segmentation, which isn't available
+            return Err(IndexError::InvalidData);
+        }
+        options.digit_word_starts = flags & 2 != 0;
+        for _ in 0..separator_count {
+            let len = read_len(data, separators_offset).ok_or(IndexError::UnexpectedEnd)?;
+            let start = separators_offset + 8;
+            let text = start
+                .checked_add(len)
+                .and_then(|end| data.get(start..end))
+                .ok_or(IndexError::UnexpectedEnd)?;
+            let text = core::str::from_utf8(text).map_err(|_| IndexError::InvalidData)?;
+            options.path_separators.push(text.into());
+            separators_offset = align(start + len);
+        }
+
+        Ok(MappedIndex {
+            bytes,
+            matcher: FuzzyMatcher::with_options(options),
+            entry_count,
+            entries_offset,
+            classes_offset,
+            text_offset,
+        })
+    }
+
+    /// Returns the options used by this index.
+    pub fn options(&self) -> &MatchOptions {
+        self.matcher.options()
+    }
+
+    /// Changes the options used by this index. Options that affect how candidates are
+    /// classified, and the `truncation` and `max_target_len` options, are kept from the
+    /// saved index.
+    pub fn set_options(&mut self, options: MatchOptions) {
+        let current = self.matcher.options();
+        self.matcher.set_options(MatchOptions {
+            path_separators: current.path_separators.clone(),
+            #[cfg(feature = "unicode-words")]
+            unicode_words: current.unicode_words,
+            digit_word_starts: current.digit_word_starts,
+            truncation: None,
+            max_target_len: None,
+            ..options
+        });
+    }
+
+    /// Number of entries in the index, including removed candidates.
+    pub fn entry_count(&self) -> usize {
+        self.entry_count
+    }
+
+    /// Returns the candidate with the given identifier.
+    pub fn get(&self, id: usize) -> Option<&str> {
+        let data = self.bytes.as_ref();
+        let record = self.record(id)?;
+        if record.removed {
+            return None;
+        }
+        core::str::from_utf8(data.get(record.text)?).ok()
+    }
+
+    /// Fuzzy match every candidate against a query string, returning the identifiers of the
+    /// `k` best matching candidates from best to worst. Candidates with the same score are
+    /// ordered by identifier.
+    pub fn query(&mut self, query: &str, k: usize) -> Vec<Scored<usize>> {
+        #[cfg(feature = "tracing")]
+        let trace = crate::trace::BatchTrace::start("index_query", query);
+        let query_mask = self.matcher.query_mask(query);
+        let mut best = TopMatches::new(k, self.entry_count);
+        let data = self.bytes.as_ref();
+        for id in 0..self.entry_count {
+            let record = match self.record(id) {
+                Some(record)
+                    if !record.removed && self.matcher.prefilter(&query_mask, &record.mask) =>
+                {
+                    record
+                }
+                _ => continue,
+            };
+            let Some(target) = data
+                .get(record.text)
+                .and_then(|text| core::str::from_utf8(text).ok())
+            else {
+                continue;
+            };
+            let Some(classes) = record
+                .classes
+                .checked_add(target.chars().count())
+                .and_then(|end| data.get(record.classes..end))
+            else {
+                continue;
+            };
+
+            // Invalid classes only affect the score of the candidate
+            let classes = classes
+                .iter()
+                .map(|class| class_from_byte(*class).unwrap_or(CharClass::Other));
+            self.matcher.load_classified(target, classes, query);
+            if let Some(score) = self.matcher.compute(false, false) {
+                best.push(id, score);
+            }
+        }
+        let results = best.into_vec();
+        #[cfg(feature = "tracing")]
+        trace.finish(self.entry_count, results.iter().map(|result| result.score));
+        results
+    }
+
+    /// Reads an entry of the entry table, returning `None` if it is out of bounds.
+    fn record(&self, id: usize) -> Option<Record> {
+        if id >= self.entry_count {
+            return None;
+        }
+        let data = self.bytes.as_ref();
+        let offset = self.entries_offset + id * ENTRY_LEN;
+        let text_start = self.text_offset.checked_add(read_len(data, offset)?)?;
+        let classes = self
+            .classes_offset
+            .checked_add(read_len(data, offset + 8)?)?;
+        let text_len = read_u32(data, offset + 16)? as usize;
+        let flags = read_u32(data, offset + 20)?;
+        Some(Record {
+            text: text_start..text_start.checked_add(text_len)?,
+            classes,
+            removed: flags & 1 != 0,
+            mask: CharMask {
+                ascii: read_u64(data, offset + 24)?,
+                non_ascii: read_u64(data, offset + 32)?,
+            },
+        })
+    }
+}
+
+#[cfg(feature = "memmap")]
+impl MappedIndex<memmap2::Mmap> {
+    /// Memory maps an index file written by [`Index::write_mapped`].
+    ///
+    /// The file must not be modified while it is mapped. Modifying a mapped file is
+    /// undefined behavior.
+    pub fn open(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: The caller is responsible for not modifying the file while it is mapped,
+        // as documented above.
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+        Self::new(mmap).map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+    }
+}
+
+impl Index {
+    /// Serializes the index to the read-only format used by [`MappedIndex`], which can be
+    /// queried without loading it. Identifiers of candidates are preserved.
+    pub fn to_mapped_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HEADER_LEN);
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&VERSION.to_le_bytes());
+        #[cfg(feature = "unicode-words")]
+        let flags = self.options().unicode_words as u32;
+        #[cfg(not(feature = "unicode-words"))]
+        let flags = 0u32;
+        let flags = flags | (self.options().digit_word_starts as u32) << 1;
+        out.extend_from_slice(&flags.to_le_bytes());
+        // Section offsets are filled in once they are known
+        out.resize(HEADER_LEN, 0);
+
+        let separators_offset = out.len();
+        let separators = &self.options().path_separators;
+        for separator in separators {
+            write_u64(&mut out, separator.len());
+            out.extend_from_slice(separator.as_bytes());
+            out.resize(align(out.len()), 0);
+        }
+
+        let entries_offset = out.len();
+        for entry in &self.entries {
+            let (text, classes) = if entry.removed {
+                (0..0, 0..0)
+            } else {
+                (entry.text.clone(), entry.classes.clone())
+            };
+            write_u64(&mut out, text.start);
+            write_u64(&mut out, classes.start);
+            out.extend_from_slice(&(text.len() as u32).to_le_bytes());
+            out.extend_from_slice(&(entry.removed as u32).to_le_bytes());
+            out.extend_from_slice(&entry.mask.ascii.to_le_bytes());
+            out.extend_from_slice(&entry.mask.non_ascii.to_le_bytes());
+        }
+
+        let classes_offset = out.len();
+        out.extend(self.classes.iter().map(|class| class_to_byte(*class)));
+        out.resize(align(out.len()), 0);
+
+        let text_offset = out.len();
+        out.extend_from_slice(self.text.as_bytes());
+
+        for (offset, value) in [
+            (16, self.entries.len()),
+            (24, entries_offset),
+            (32, classes_offset),
+            (40, text_offset),
+            (48, separators_offset),
+            (56, separators.len()),
+        ] {
+            out[offset..offset + 8].copy_from_slice(&(value as u64).to_le_bytes());
+        }
+        out
+    }
+
+    /// Writes the index to a writer in the format of
+    /// [`to_mapped_bytes`](Index::to_mapped_bytes).
+    #[cfg(feature = "std")]
+    pub fn write_mapped(&self, mut writer: impl std::io::Write) -> std::io::Result<()> {
+        writer.write_all(&self.to_mapped_bytes())
+    }
+}
+
+/// Rounds an offset up to the next 8 byte boundary.
+fn align(offset: usize) -> usize {
+    offset.div_ceil(8) * 8
+}
+
+fn write_u64(out: &mut Vec<u8>, value: usize) {
+    out.extend_from_slice(&(value as u64).to_le_bytes());
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes(bytes.try_into().ok()?))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    let bytes = data.get(offset..offset.checked_add(8)?)?;
+    Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
+
+fn read_len(data: &[u8], offset: usize) -> Option<usize> {
+    usize::try_from(read_u64(data, offset)?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Index, IndexError, MappedIndex, MatchOptions};
+    use alloc::string::String;
+    use alloc::vec;
+
+    #[test]
+    fn test_mapped_index() {
+        let mut index = Index::with_options(MatchOptions {
+            path_separators: vec![String::from("::")],
+            ..MatchOptions::default()
+        });
+        for candidate in ["std::io::Read", "std::fs::File", "alloc::vec::Vec", "Grüße"] {
+            index.add(candidate);
+        }
+        index.remove(1);
+
+        let bytes = index.to_mapped_bytes();
+        assert_eq!(bytes.len() % 8, 0);
+        let mut mapped = MappedIndex::new(bytes.as_slice()).unwrap();
+        assert_eq!(
+            mapped.options().path_separators,
+            index.options().path_separators
+        );
+        assert_eq!(mapped.entry_count(), 4);
+        assert_eq!(mapped.get(0), Some("std::io::Read"));
+        assert_eq!(mapped.get(1), None);
+        assert_eq!(mapped.get(4), None);
+        for query in ["sir", "f", "vec", "GRÜ", "xyz"] {
+            assert_eq!(mapped.query(query, 10), index.query(query, 10));
+        }
+        assert_eq!(mapped.query("s", 1), index.query("s", 1));
+
+        assert_eq!(
+            MappedIndex::new(&bytes[..32]).err(),
+            Some(IndexError::UnexpectedEnd)
+        );
+        assert_eq!(
+            MappedIndex::new(index.to_bytes()).err(),
+            Some(IndexError::InvalidMagic)
+        );
+
+        // Corrupt candidates are skipped rather than causing a panic
+        let mut corrupt = bytes.clone();
+        let text_offset = u64::from_le_bytes(corrupt[40..48].try_into().unwrap()) as usize;
+        corrupt.truncate(text_offset + 4);
+        let mut mapped = MappedIndex::new(corrupt).unwrap();
+        assert_eq!(mapped.get(2), None);
+        assert!(mapped.query("vec", 10).is_empty());
+    }
+
+    #[cfg(feature = "memmap")]
+    #[test]
+    fn test_open_mapped_index() {
+        let mut index = Index::new();
+        let id = index.add("src/mapped.rs");
+        let path = std::env::temp_dir().join("code_fuzzy_match_test_open_mapped_index");
+        index
+            .write_mapped(std::fs::File::create(&path).unwrap())
+            .unwrap();
+        let mut mapped = MappedIndex::open(&path).unwrap();
+        assert_eq!(mapped.query("map", 1)[0].item, id);
+        drop(mapped);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,917 @@
+//! Matrix-based scoring engine shared by all of the matching modes.
+//!
+//! The engine is agnostic to what a "unit" of the target and query strings is. Callers
+//! classify each target unit ahead of time and provide a comparison function, which allows
+//! the same scoring to be used for characters, grapheme clusters, and other segmentations.
+
+mod gapped;
+mod sparse;
+mod tolerant;
+
+use crate::{Bonuses, DefaultScoreModel, MatchOptions, ScoreContext, ScoreModel, ScoringProfile};
+use alloc::vec::Vec;
+use gapped::GappedBuffers;
+use sparse::SparseBuffers;
+use tolerant::TolerantBuffers;
+
+pub(crate) use sparse::Candidates;
+
+/// Classification of a unit of the target string, used to decide which positional bonuses
+/// apply when a query character is matched against it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CharClass {
+    /// A path separator: `/`, `\`, or a sequence in the `path_separators` option.
+    PathSeparator,
+    /// A separator between words, such as a space, underscore or period.
+    Separator,
+    /// An uppercase letter.
+    Uppercase,
+    /// Start of a word found by word segmentation that isn't covered by the other classes.
+    WordStart,
+    /// Any other character.
+    Other,
+}
+
+impl CharClass {
+    pub(crate) fn of(c: char) -> Self {
+        if matches!(c, '/' | '\\') {
+            CharClass::PathSeparator
+        } else if matches!(c, '_' | '-' | '.' | ' ' | '\'' | '"' | ':') {
+            CharClass::Separator
+        } else if c.is_ascii() {
+            // It is faster to check for ASCII first and then use `is_ascii_uppercase`
+            // than to always use `is_uppercase`.
+            if c.is_ascii_uppercase() {
+                CharClass::Uppercase
+            } else {
+                CharClass::Other
+            }
+        } else if c.is_uppercase() {
+            CharClass::Uppercase
+        } else {
+            CharClass::Other
+        }
+    }
+}
+
+/// Result of comparing a query unit against a target unit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum UnitMatch {
+    /// The units match, ignoring case.
+    Folded,
+    /// The units match exactly, including case.
+    Exact,
+}
+
+/// Returns the context given to the scoring model for matching a query unit at target
+/// index `i`, given the number of sequential matches immediately preceding it.
+#[inline]
+fn score_context<'a, M: ScoreModel + ?Sized>(
+    input: &MatrixInput<'a, M>,
+    i: usize,
+    seq_match_count: usize,
+    kind: UnitMatch,
+) -> ScoreContext<'a> {
+    ScoreContext {
+        classes: input.classes,
+        index: i,
+        sequential_count: seq_match_count,
+        exact_case: kind == UnitMatch::Exact,
+        query_len: input.query_len,
+        basename_start: input.basename_start,
+        options: input.options,
+    }
+}
+
+/// Computes the bonuses for matching a query unit at target index `i`, given the number of
+/// sequential matches immediately preceding it. A scoring model set in the options takes
+/// precedence over the model of the input.
+#[inline]
+pub(crate) fn char_bonuses<M: ScoreModel + ?Sized>(
+    input: &MatrixInput<M>,
+    i: usize,
+    seq_match_count: usize,
+    kind: UnitMatch,
+) -> Bonuses {
+    let context = score_context(input, i, seq_match_count, kind);
+    match &input.options.score_model {
+        Some(model) => model.model().bonuses(&context),
+        None => input.model.bonuses(&context),
+    }
+}
+
+/// Computes the score for matching a query unit at target index `i`, given the number of
+/// sequential matches immediately preceding it.
+#[inline]
+pub(crate) fn char_score<M: ScoreModel + ?Sized>(
+    input: &MatrixInput<M>,
+    i: usize,
+    seq_match_count: usize,
+    kind: UnitMatch,
+) -> usize {
+    let context = score_context(input, i, seq_match_count, kind);
+    match &input.options.score_model {
+        Some(model) => model.model().score(&context),
+        None => input.model.score(&context),
+    }
+}
+
+/// Returns true if the target unit at index `i` starts a word: it is the start of the
+/// target, follows a separator, or is a camel case hump.
+pub(crate) fn is_word_start(classes: &[CharClass], i: usize) -> bool {
+    i == 0
+        || matches!(
+            classes[i - 1],
+            CharClass::PathSeparator | CharClass::Separator
+        )
+        || matches!(classes[i], CharClass::Uppercase | CharClass::WordStart)
+}
+
+/// Returns true if the query occurs as a run of consecutive units in the target, comparing
+/// units with `unit_matches`, which is given a query index and a target index.
+pub(crate) fn contains_query(
+    target_len: usize,
+    query_len: usize,
+    unit_matches: impl Fn(usize, usize) -> bool,
+) -> bool {
+    query_len != 0
+        && query_len <= target_len
+        && (0..=target_len - query_len)
+            .any(|start| (0..query_len).all(|query_idx| unit_matches(query_idx, start + query_idx)))
+}
+
+/// Returns true if the target unit at index `i` ends a word: it is the end of the target,
+/// or the next unit is a separator or starts a new word.
+pub(crate) fn is_word_end(classes: &[CharClass], i: usize) -> bool {
+    i + 1 == classes.len()
+        || matches!(
+            classes[i + 1],
+            CharClass::PathSeparator
+                | CharClass::Separator
+                | CharClass::Uppercase
+                | CharClass::WordStart
+        )
+}
+
+/// Per-position restrictions on which target units may be matched, used to implement
+/// constraints on a match by masking transitions through the matrix.
+#[derive(Default)]
+pub(crate) struct PositionMask {
+    pub(crate) flags: Vec<u8>,
+    pub(crate) first_required: Option<usize>,
+    pub(crate) last_required: Option<usize>,
+}
+
+impl PositionMask {
+    /// The first query unit may be matched at this position.
+    pub(crate) const START_ALLOWED: u8 = 1;
+    /// The last query unit may be matched at this position.
+    pub(crate) const END_ALLOWED: u8 = 2;
+    /// No query unit may be matched at this position.
+    pub(crate) const EXCLUDED: u8 = 4;
+    /// Some query unit must be matched at this position.
+    pub(crate) const REQUIRED: u8 = 8;
+
+    /// Returns true if a match of the first and/or last query unit is allowed at
+    /// `target_idx`. When matching in `reverse`, the last query unit is matched first.
+    fn allows_match(
+        &self,
+        target_idx: usize,
+        first_query_unit: bool,
+        last_query_unit: bool,
+        reverse: bool,
+    ) -> bool {
+        let flags = self.flags[target_idx];
+        if flags & Self::EXCLUDED != 0 {
+            return false;
+        }
+        if first_query_unit && flags & Self::START_ALLOWED == 0 {
+            return false;
+        }
+        if last_query_unit && flags & Self::END_ALLOWED == 0 {
+            return false;
+        }
+
+        // Nothing has been matched before the query unit that is matched first, so a
+        // required position before it would be skipped.
+        if reverse {
+            !last_query_unit
+                || self
+                    .last_required
+                    .is_none_or(|required| required <= target_idx)
+        } else {
+            !first_query_unit
+                || self
+                    .first_required
+                    .is_none_or(|required| required >= target_idx)
+        }
+    }
+
+    /// Returns true if the target unit at `target_idx` can be left unmatched by the query
+    /// units matched so far.
+    fn allows_skip(&self, target_idx: usize) -> bool {
+        self.flags[target_idx] & Self::REQUIRED == 0
+    }
+}
+
+/// Inputs describing a single run of the scoring matrix.
+pub(crate) struct MatrixInput<'a, M: ?Sized = DefaultScoreModel> {
+    /// Classification of each unit of the target.
+    pub(crate) classes: &'a [CharClass],
+    /// Number of units in the query.
+    pub(crate) query_len: usize,
+    /// If set, the target indices of the matched units are placed in
+    /// [`Matrix::positions`] when the query matches, in query order. Query units that were
+    /// skipped as typos do not have a position, and the positions of transposed query units
+    /// are not in increasing order.
+    pub(crate) record_positions: bool,
+    /// If given, must have one entry per target unit. Matches that violate the mask are not
+    /// considered.
+    pub(crate) mask: Option<&'a PositionMask>,
+    /// Options of the matcher performing the match.
+    pub(crate) options: &'a MatchOptions,
+    /// Index of the first target unit in the final path component, if the scoring profile
+    /// gives a bonus to matches there.
+    pub(crate) basename_start: Option<usize>,
+    /// If given, only these target units are considered for each query unit, and cells of
+    /// the matrix without a candidate are not visited. Only used for unmasked matches
+    /// without typos or transpositions, when traversing the target from start to end.
+    pub(crate) candidates: Option<&'a Candidates<'a>>,
+    /// If given, returns true if the query unit at the first index may be mistyped as the
+    /// target unit at the second index, as configured by the `keyboard_layout` option. Only
+    /// used when matching with typos.
+    pub(crate) near_miss: Option<&'a dyn Fn(usize, usize) -> bool>,
+    /// Scoring model used when the options don't set one.
+    pub(crate) model: &'a M,
+}
+
+impl<'a> MatrixInput<'a> {
+    /// Creates the input for an unconstrained match that does not record positions, scored
+    /// with the default model.
+    pub(crate) fn new(
+        classes: &'a [CharClass],
+        query_len: usize,
+        options: &'a MatchOptions,
+    ) -> Self {
+        Self::with_model(classes, query_len, options, &DefaultScoreModel)
+    }
+}
+
+impl<'a, M: ScoreModel + ?Sized> MatrixInput<'a, M> {
+    /// Creates the input for an unconstrained match that does not record positions, scored
+    /// with the given model.
+    pub(crate) fn with_model(
+        classes: &'a [CharClass],
+        query_len: usize,
+        options: &'a MatchOptions,
+        model: &'a M,
+    ) -> Self {
+        let basename_start = match options.profile {
+            ScoringProfile::General | ScoringProfile::VsCodeCompat => None,
+            ScoringProfile::FilePath => Some(
+                classes
+                    .iter()
+                    .rposition(|class| *class == CharClass::PathSeparator)
+                    .map_or(0, |idx| idx + 1),
+            ),
+        };
+        MatrixInput {
+            classes,
+            query_len,
+            record_positions: false,
+            mask: None,
+            options,
+            basename_start,
+            candidates: None,
+            near_miss: None,
+            model,
+        }
+    }
+}
+
+/// Score of a cell in the matrix that can't be reached by any alignment of the query.
+pub(crate) const UNREACHABLE: isize = isize::MIN;
+
+/// Converts a penalty or bonus from the options to a signed score. Values above
+/// `isize::MAX` are clamped, so that they can't wrap around to the opposite sign.
+pub(crate) fn signed(value: usize) -> isize {
+    value.min(isize::MAX as usize) as isize
+}
+
+/// Subtracts a penalty from the score of a reachable cell. The result saturates just above
+/// `UNREACHABLE`, so that large penalties can't overflow or make the cell unreachable.
+pub(crate) fn penalize(score: isize, penalty: isize) -> isize {
+    score.saturating_sub(penalty).max(UNREACHABLE + 1)
+}
+
+/// Row of the scoring matrix being computed, in the direction the target is traversed.
+#[derive(Clone, Copy)]
+pub(crate) struct MatrixRow {
+    /// Index of the query unit of the row in the original query.
+    pub(crate) query_idx: usize,
+    /// Set for the first row traversed, where a match starts afresh instead of continuing a
+    /// match of the previous query unit.
+    pub(crate) first: bool,
+    /// Set for the last row traversed, which carries its score to the end of the target
+    /// without gap penalties.
+    pub(crate) last: bool,
+    /// Keep the carried score when a match ties with it, so that traversing from end to
+    /// start favors the later placement.
+    pub(crate) keep_later: bool,
+}
+
+/// Score and sequential match count of a cell of the scoring matrix.
+#[derive(Clone, Copy)]
+pub(crate) struct CellScore {
+    pub(crate) score: isize,
+    /// Zero unless the query unit is matched with the target unit of the cell.
+    pub(crate) seq_match_count: usize,
+    /// Score and sequential match count of matching the units, if they match, even when the
+    /// carried score is kept instead.
+    pub(crate) matched: Option<(isize, usize)>,
+}
+
+/// Computes a cell of the scoring matrix at target unit `target_idx`, in the original
+/// orientation of the target. `carried` is the score of the previous cell of the row, or
+/// `UNREACHABLE` if the target unit can't be skipped. `diagonal` gives the score and
+/// sequential match count of the previous unit in both the target and the query, and
+/// `unit_match` compares the units. Both are only called when needed.
+#[inline]
+pub(crate) fn score_cell<M: ScoreModel + ?Sized>(
+    input: &MatrixInput<M>,
+    row: MatrixRow,
+    target_idx: usize,
+    carried: isize,
+    diagonal: impl FnOnce() -> (isize, usize),
+    unit_match: impl FnOnce() -> Option<UnitMatch>,
+) -> CellScore {
+    let options = input.options;
+
+    // Skipping a target unit between two matched units is penalized as a gap in the match
+    let carried = if carried == UNREACHABLE || row.last {
+        carried
+    } else {
+        penalize(carried, signed(options.gap_penalty))
+    };
+    let (prev_score, seq_match_count) = if row.first { (0, 0) } else { diagonal() };
+
+    let matched = if prev_score == UNREACHABLE
+        || (row.query_idx == 0
+            && options.require_word_start
+            && !is_word_start(input.classes, target_idx))
+    {
+        None
+    } else {
+        unit_match().map(|kind| {
+            let mut score = prev_score.saturating_add(signed(char_score(
+                input,
+                target_idx,
+                seq_match_count,
+                kind,
+            )));
+            if row.query_idx == 0 {
+                score = penalize(score, signed(leading_gap_penalty(options, target_idx)));
+            }
+            (score, seq_match_count + 1)
+        })
+    };
+
+    match matched {
+        Some((score, seq_match_count))
+            if score > carried || (score == carried && !row.keep_later) =>
+        {
+            CellScore {
+                score,
+                seq_match_count,
+                matched,
+            }
+        }
+        _ => CellScore {
+            score: carried,
+            seq_match_count: 0,
+            matched,
+        },
+    }
+}
+
+/// Scratch memory for the scoring matrix. Only the current and previous rows of the matrix
+/// are held in memory, unless match positions are requested, in which case a single flag
+/// per cell is kept to record which cells were matches.
+#[derive(Default)]
+pub(crate) struct Matrix {
+    first_possible_match: Vec<usize>,
+    last_possible_match: Vec<usize>,
+    prev_seq_match_counts: Vec<usize>,
+    prev_score: Vec<isize>,
+    seq_match_counts: Vec<usize>,
+    score: Vec<isize>,
+    /// Score and sequential count of matching the query unit at each target unit, whether
+    /// or not it is the best score of the cell. Only kept for the `camel_humps` option.
+    prev_match_seq_counts: Vec<usize>,
+    prev_match_score: Vec<isize>,
+    match_seq_counts: Vec<usize>,
+    match_score: Vec<isize>,
+    trace: Vec<bool>,
+    pub(crate) positions: Vec<usize>,
+    /// Index of the query unit matched at each of the `positions`.
+    pub(crate) query_indices: Vec<usize>,
+    /// Target indices of the units that query units were mistyped as, relative to the
+    /// start of the matched window. Only recorded along with the `positions`.
+    pub(crate) near_misses: Vec<usize>,
+    alt_positions: Vec<usize>,
+    tolerant: TolerantBuffers,
+    sparse: SparseBuffers,
+    gapped: GappedBuffers,
+    /// If set, the score of every cell is recorded here row by row, for visualizing the
+    /// matrix. Cells that weren't computed are `UNREACHABLE`.
+    #[cfg(feature = "score-matrix")]
+    pub(crate) recorded_scores: Option<Vec<isize>>,
+    /// Runs of the matrix and cells compared, for the `stats` feature.
+    #[cfg(feature = "stats")]
+    pub(crate) stats: crate::MatchStats,
+}
+
+impl Matrix {
+    /// Creates scratch memory with room for the rows of targets of up to `target_len`
+    /// units. Recorded matches and the other matrices still grow as needed.
+    pub(crate) fn with_capacity(target_len: usize) -> Self {
+        Matrix {
+            prev_seq_match_counts: Vec::with_capacity(target_len),
+            prev_score: Vec::with_capacity(target_len),
+            seq_match_counts: Vec::with_capacity(target_len),
+            score: Vec::with_capacity(target_len),
+            ..Matrix::default()
+        }
+    }
+
+    /// Scores a query against a target. The `unit_match` function compares the query unit
+    /// at the first index to the target unit at the second index. The score is the sum of
+    /// the character scores and penalties, which may be zero or negative.
+    pub(crate) fn compute<M: ScoreModel + ?Sized>(
+        &mut self,
+        input: &MatrixInput<M>,
+        unit_match: impl Fn(usize, usize) -> Option<UnitMatch>,
+    ) -> Option<isize> {
+        #[cfg(feature = "stats")]
+        let cells = core::cell::Cell::new(0);
+        #[cfg(feature = "stats")]
+        let unit_match = |query_idx, target_idx| {
+            cells.set(cells.get() + 1);
+            unit_match(query_idx, target_idx)
+        };
+        let sum = self.compute_alignment(input, unit_match);
+        #[cfg(feature = "stats")]
+        {
+            self.stats.matrix_runs += 1;
+            self.stats.cells += cells.get();
+        }
+        let sum = sum?;
+        Some(normalize_length(
+            input.options,
+            sum,
+            input.classes.len(),
+            input.query_len,
+        ))
+    }
+
+    /// Returns true if the scores of every cell are being recorded, which always uses the
+    /// exact matrix from start to end.
+    fn recording(&self) -> bool {
+        #[cfg(feature = "score-matrix")]
+        return self.recorded_scores.is_some();
+        #[cfg(not(feature = "score-matrix"))]
+        false
+    }
+
+    /// Returns whether each cell of the exact matrix, row by row, was given its score by
+    /// matching the query unit with the target unit. Only filled when positions are
+    /// recorded.
+    #[cfg(feature = "score-matrix")]
+    pub(crate) fn trace(&self) -> &[bool] {
+        &self.trace
+    }
+
+    /// Finds the best alignment of the query in the target and returns its score, before
+    /// normalizing for the length of the target.
+    fn compute_alignment<M: ScoreModel + ?Sized>(
+        &mut self,
+        input: &MatrixInput<M>,
+        unit_match: impl Fn(usize, usize) -> Option<UnitMatch>,
+    ) -> Option<isize> {
+        self.near_misses.clear();
+
+        // At least half of the query must be matched for the query to be considered a match
+        // with typos, otherwise short queries would match nearly anything.
+        let max_typos = input
+            .options
+            .max_typos
+            .min(input.query_len.saturating_sub(1) / 2);
+        let transpositions = input.options.transpositions && input.query_len > 1;
+        // A gap limit that every alignment fits within doesn't need the gapped matrix
+        let max_gap = input
+            .options
+            .max_gap
+            .filter(|max_gap| max_gap.saturating_add(1) < input.classes.len());
+        let recording = self.recording();
+        if (max_typos > 0 || transpositions)
+            && input.mask.is_none()
+            && !input.options.camel_humps
+            && max_gap.is_none()
+            && !recording
+        {
+            return self.compute_tolerant(input, max_typos, transpositions, &unit_match);
+        }
+
+        // Without typos, the first query unit is always the first unit to be matched
+        let unit_match = |query_idx: usize, target_idx: usize| {
+            if query_idx == 0
+                && input.options.require_word_start
+                && !is_word_start(input.classes, target_idx)
+            {
+                None
+            } else {
+                unit_match(query_idx, target_idx)
+            }
+        };
+
+        if let Some(max_gap) = max_gap.filter(|_| !recording) {
+            return self.compute_gapped(input, max_gap, &unit_match);
+        }
+
+        if !input.options.bidirectional || input.options.camel_humps || recording {
+            let reverse = input.options.right_to_left && !input.options.camel_humps && !recording;
+            if reverse {
+                return self.compute_directed::<true, _>(
+                    input,
+                    input.record_positions,
+                    &unit_match,
+                );
+            }
+            return self.compute_directed::<false, _>(input, input.record_positions, &unit_match);
+        }
+
+        // The matrix favors the earliest placement of each query unit when choosing between
+        // alignments. Find the best alignment when favoring the latest placements as well,
+        // and keep whichever one is better under the normal scoring rules.
+        let reverse_score = self
+            .compute_directed::<true, _>(input, true, &unit_match)
+            .map(|_| score_alignment(input, &self.positions, unit_match));
+        core::mem::swap(&mut self.positions, &mut self.alt_positions);
+        let forward_score =
+            self.compute_directed::<false, _>(input, input.record_positions, &unit_match);
+        if reverse_score > forward_score {
+            core::mem::swap(&mut self.positions, &mut self.alt_positions);
+            reverse_score
+        } else {
+            forward_score
+        }
+    }
+
+    /// Runs the scoring matrix in one direction. When `REVERSE` is set, the query and target
+    /// are traversed from end to start, but bonuses are still computed using the original
+    /// orientation of the target. Positions are always reported in the original orientation.
+    fn compute_directed<const REVERSE: bool, M: ScoreModel + ?Sized>(
+        &mut self,
+        input: &MatrixInput<M>,
+        record_positions: bool,
+        unit_match: &impl Fn(usize, usize) -> Option<UnitMatch>,
+    ) -> Option<isize> {
+        let classes = input.classes;
+        let query_len = input.query_len;
+        let target_len = classes.len();
+        let humps = input.options.camel_humps;
+        // When matching right to left, ties keep the later placement carried over from the
+        // end of the target
+        let keep_later = REVERSE && input.options.right_to_left && !input.options.bidirectional;
+
+        if !REVERSE && input.mask.is_none() && !humps && !self.recording() {
+            if let Some(candidates) = input.candidates {
+                return self.compute_sparse(input, record_positions, candidates, unit_match);
+            }
+        }
+
+        // Map indices in the direction of traversal to indices in the original strings
+        let query_unit = |idx: usize| if REVERSE { query_len - 1 - idx } else { idx };
+        let target_unit = |idx: usize| if REVERSE { target_len - 1 - idx } else { idx };
+
+        // Match query units against the target to perform an early exit if the query
+        // definitely does not match, as well as computing the earliest possible index for
+        // each given query unit.
+        self.first_possible_match.clear();
+        for target_idx in 0..target_len {
+            let query_idx = self.first_possible_match.len();
+            if query_idx >= query_len {
+                break;
+            }
+            if unit_match(query_unit(query_idx), target_unit(target_idx)).is_some() {
+                self.first_possible_match.push(target_idx);
+            }
+        }
+
+        // If we didn't consume all query units, then the query is not a match.
+        if self.first_possible_match.len() < query_len {
+            return None;
+        }
+
+        // Scan from the end of the target as well to find the latest possible index for each
+        // query unit. Cells of a row after the last possible match of the next query unit are
+        // never read, so each row only needs to be computed up to that point.
+        self.last_possible_match.clear();
+        for target_idx in (0..target_len).rev() {
+            let matched = self.last_possible_match.len();
+            if matched >= query_len {
+                break;
+            }
+            if unit_match(query_unit(query_len - 1 - matched), target_unit(target_idx)).is_some() {
+                self.last_possible_match.push(target_idx);
+            }
+        }
+        self.last_possible_match.reverse();
+
+        // Create vectors holding the score and sequential counts for two query characters.
+        // This algorithm implements a matrix-based method of fuzzy matching, but we don't
+        // need to hold the entire matrix in memory, just the current and previous rows.
+        self.prev_seq_match_counts.clear();
+        self.prev_score.clear();
+        self.prev_seq_match_counts.resize(target_len, 0);
+        self.prev_score.resize(target_len, UNREACHABLE);
+
+        self.seq_match_counts.clear();
+        self.score.clear();
+        self.seq_match_counts.resize(target_len, 0);
+        self.score.resize(target_len, UNREACHABLE);
+
+        if humps {
+            self.prev_match_seq_counts.clear();
+            self.prev_match_score.clear();
+            self.prev_match_seq_counts.resize(target_len, 0);
+            self.prev_match_score.resize(target_len, UNREACHABLE);
+            self.match_seq_counts.clear();
+            self.match_score.clear();
+            self.match_seq_counts.resize(target_len, 0);
+            self.match_score.resize(target_len, UNREACHABLE);
+        }
+
+        if record_positions {
+            self.trace.clear();
+            self.trace.resize(query_len * target_len, false);
+        }
+        #[cfg(feature = "score-matrix")]
+        if let Some(recorded) = &mut self.recorded_scores {
+            recorded.clear();
+            recorded.resize(query_len * target_len, UNREACHABLE);
+        }
+
+        let mut first_possible_target_idx: usize = 0;
+
+        // Compute match scores for each query character in sequence
+        for query_idx in 0..query_len {
+            let first_query_char = query_idx == 0;
+            let last_query_char = query_idx + 1 == query_len;
+            let orig_query_idx = query_unit(query_idx);
+            let row = MatrixRow {
+                query_idx: orig_query_idx,
+                first: first_query_char,
+                last: last_query_char,
+                keep_later,
+            };
+
+            // If the initial scan saw that the first possible match for this query character
+            // is later in the string, use that instead.
+            first_possible_target_idx =
+                first_possible_target_idx.max(self.first_possible_match[query_idx]);
+
+            // The last row carries its score to the end of the target, where the final score
+            // is read. Other rows end before the last possible match of the next query unit.
+            let row_end = if last_query_char {
+                target_len
+            } else {
+                self.last_possible_match[query_idx + 1]
+            };
+
+            // If the starting point of the search is beyond the end of the row, there is no
+            // room left for the rest of the query and we can't have a match.
+            if first_possible_target_idx >= row_end {
+                return None;
+            }
+
+            // Reset vector holding the score and sequential counts for this query character.
+            self.seq_match_counts[first_possible_target_idx..row_end].fill(0);
+            self.score[first_possible_target_idx..row_end].fill(UNREACHABLE);
+            if humps {
+                self.match_score[first_possible_target_idx..row_end].fill(UNREACHABLE);
+            }
+
+            let mut first_scored = None;
+
+            // Compute match scores for each target character in sequence, for this query character.
+            // Start at the character after the previous earliest character that had a score. Any
+            // character before that cannot have a score, so we don't need to check those.
+            for i in first_possible_target_idx..row_end {
+                let orig_target_idx = target_unit(i);
+
+                // If this character must be matched, the score can't be carried over from a
+                // state where it was skipped.
+                let carried = if i == first_possible_target_idx
+                    || input
+                        .mask
+                        .is_some_and(|mask| !mask.allows_skip(orig_target_idx))
+                {
+                    UNREACHABLE
+                } else {
+                    self.score[i - 1]
+                };
+
+                // Inside a word, camel humps matching only allows continuing a match of the
+                // previous query character, even if a better score was carried past it.
+                let inside_hump = humps && !is_word_start(classes, orig_target_idx);
+                let diagonal = || {
+                    if i == 0 {
+                        (UNREACHABLE, 0)
+                    } else if inside_hump {
+                        (
+                            self.prev_match_score[i - 1],
+                            self.prev_match_seq_counts[i - 1],
+                        )
+                    } else {
+                        (self.prev_score[i - 1], self.prev_seq_match_counts[i - 1])
+                    }
+                };
+                let unit_match = || {
+                    if (first_query_char && inside_hump)
+                        || input.mask.is_some_and(|mask| {
+                            !mask.allows_match(
+                                orig_target_idx,
+                                orig_query_idx == 0,
+                                orig_query_idx + 1 == query_len,
+                                REVERSE,
+                            )
+                        })
+                    {
+                        None
+                    } else {
+                        unit_match(orig_query_idx, orig_target_idx)
+                    }
+                };
+                let cell = score_cell(input, row, orig_target_idx, carried, diagonal, unit_match);
+
+                self.score[i] = cell.score;
+                self.seq_match_counts[i] = cell.seq_match_count;
+                if humps {
+                    if let Some((score, seq_match_count)) = cell.matched {
+                        self.match_score[i] = score;
+                        self.match_seq_counts[i] = seq_match_count;
+                    }
+                }
+                if cell.seq_match_count > 0 {
+                    if first_scored.is_none() {
+                        first_scored = Some(i);
+                    }
+                    if record_positions {
+                        self.trace[query_idx * target_len + i] = true;
+                    }
+                }
+            }
+
+            #[cfg(feature = "score-matrix")]
+            if let Some(recorded) = &mut self.recorded_scores {
+                let row = query_idx * target_len;
+                recorded[row + first_possible_target_idx..row + row_end]
+                    .copy_from_slice(&self.score[first_possible_target_idx..row_end]);
+            }
+
+            if let Some(first_scored) = first_scored {
+                // Start the next character's matching at the character following the one that
+                // first set a valid score.
+                first_possible_target_idx = first_scored + 1;
+
+                // Keep scores and sequential match information for this character in the query
+                // for lookup during the next character.
+                self.prev_score[first_scored..row_end]
+                    .copy_from_slice(&self.score[first_scored..row_end]);
+                self.prev_seq_match_counts[first_scored..row_end]
+                    .copy_from_slice(&self.seq_match_counts[first_scored..row_end]);
+                if humps {
+                    self.prev_match_score[first_scored..row_end]
+                        .copy_from_slice(&self.match_score[first_scored..row_end]);
+                    self.prev_match_seq_counts[first_scored..row_end]
+                        .copy_from_slice(&self.match_seq_counts[first_scored..row_end]);
+                }
+            } else {
+                // If no cell has a score, we already know we don't have a match. Exit early
+                // in this case.
+                return None;
+            }
+        }
+
+        // Final score will always be in the last slot of the final score vector
+        let score = *self.prev_score.last().unwrap_or(&UNREACHABLE);
+        if score == UNREACHABLE {
+            return None;
+        }
+
+        if record_positions {
+            self.backtrack(query_len, target_len, humps.then_some(classes));
+            if REVERSE {
+                self.positions.reverse();
+                for position in &mut self.positions {
+                    *position = target_len - 1 - *position;
+                }
+            }
+        }
+
+        Some(score)
+    }
+
+    /// Walks the recorded matrix backwards from the final cell to find the target index
+    /// that each query unit was matched against. If `humps` holds the classes of the
+    /// target, a query unit matched inside a word continues the match of the previous
+    /// query unit, as with the `camel_humps` option.
+    fn backtrack(&mut self, query_len: usize, target_len: usize, humps: Option<&[CharClass]>) {
+        self.positions.clear();
+        let mut target_idx = target_len;
+        let mut continues_match = false;
+        for query_idx in (0..query_len).rev() {
+            if continues_match {
+                target_idx -= 1;
+            } else {
+                // A cell without a match carried its score from the previous target unit,
+                // so keep moving left until the cell that produced the score is found.
+                loop {
+                    target_idx -= 1;
+                    if self.trace[query_idx * target_len + target_idx] {
+                        break;
+                    }
+                }
+            }
+            self.positions.push(target_idx);
+            continues_match = humps.is_some_and(|classes| !is_word_start(classes, target_idx));
+        }
+        self.positions.reverse();
+        self.query_indices.clear();
+        self.query_indices.extend(0..query_len);
+    }
+}
+
+/// Computes the score of matching each query unit at the given target positions, using the
+/// same rules as the scoring matrix.
+pub(crate) fn score_alignment<M: ScoreModel + ?Sized>(
+    input: &MatrixInput<M>,
+    positions: &[usize],
+    unit_match: impl Fn(usize, usize) -> Option<UnitMatch>,
+) -> isize {
+    let mut score: isize = 0;
+    let mut seq_match_count = 0;
+    for (query_idx, target_idx) in positions.iter().enumerate() {
+        if query_idx > 0 && positions[query_idx - 1] + 1 == *target_idx {
+            seq_match_count += 1;
+        } else {
+            seq_match_count = 0;
+        }
+        let kind = unit_match(query_idx, *target_idx).unwrap_or(UnitMatch::Folded);
+        score = score.saturating_add(signed(char_score(
+            input,
+            *target_idx,
+            seq_match_count,
+            kind,
+        )));
+    }
+    if let Some(first) = positions.first() {
+        score = penalize(score, signed(leading_gap_penalty(input.options, *first)));
+    }
+    penalize(score, signed(gap_penalty(input, positions)))
+}
+
+/// Applies the `length_normalization` option to the sum of the character scores and
+/// penalties of a match.
+pub(crate) fn normalize_length(
+    options: &MatchOptions,
+    sum: isize,
+    target_len: usize,
+    query_len: usize,
+) -> isize {
+    let scale = options.length_normalization;
+    if scale == 0 || sum <= 0 {
+        return sum;
+    }
+    let extra = target_len.saturating_sub(query_len);
+    (sum as u128 * scale as u128 / (scale as u128 + extra as u128)) as isize
+}
+
+/// Computes the penalty for a match whose first matched unit is at target index
+/// `first_position`.
+pub(crate) fn leading_gap_penalty(options: &MatchOptions, first_position: usize) -> usize {
+    first_position
+        .saturating_mul(options.leading_gap_penalty)
+        .min(options.max_leading_gap_penalty)
+}
+
+/// Computes the total gap penalty for the given target positions, which must be in
+/// increasing order.
+fn gap_penalty<M: ScoreModel + ?Sized>(input: &MatrixInput<M>, positions: &[usize]) -> usize {
+    let gaps: usize = positions
+        .windows(2)
+        .map(|pair| pair[1].saturating_sub(pair[0] + 1))
+        .sum();
+    gaps.saturating_mul(input.options.gap_penalty)
+}
@@ -0,0 +1,173 @@
+//! Scoring matrix that limits the gap between consecutive matched units.
+//!
+//! With the `max_gap` option, a query unit can only follow a match of the previous query
+//! unit within a window of target units before it, so the best score of a row can't be
+//! carried over from one target unit to the next as in the exact matrix. Each row instead
+//! holds the score of matching the query unit at each target unit, and each match looks
+//! back over the window in the previous row. Rows without any match end the search early,
+//! so long targets where the query is spread out are rejected quickly.
+
+use super::{
+    char_score, is_word_start, leading_gap_penalty, penalize, signed, Matrix, MatrixInput,
+    UnitMatch, UNREACHABLE,
+};
+use crate::ScoreModel;
+use alloc::vec::Vec;
+
+/// A cell of a row, holding the score of matching the query unit with the target unit, or
+/// `UNREACHABLE` if it can't be matched there.
+#[derive(Clone, Copy)]
+struct Cell {
+    score: isize,
+    seq_match_count: usize,
+}
+
+const UNMATCHED: Cell = Cell {
+    score: UNREACHABLE,
+    seq_match_count: 0,
+};
+
+/// Scratch memory for the gapped matrix. `from` holds the target index where the previous
+/// query unit was matched for each cell, when positions are recorded.
+#[derive(Default)]
+pub(super) struct GappedBuffers {
+    prev: Vec<Cell>,
+    current: Vec<Cell>,
+    from: Vec<usize>,
+}
+
+impl Matrix {
+    /// Scores a query against a target, skipping at most `max_gap` target units between
+    /// consecutive matched units. Does not support typos, transpositions or reverse
+    /// traversal.
+    pub(super) fn compute_gapped<M: ScoreModel + ?Sized>(
+        &mut self,
+        input: &MatrixInput<M>,
+        max_gap: usize,
+        unit_match: &impl Fn(usize, usize) -> Option<UnitMatch>,
+    ) -> Option<isize> {
+        let classes = input.classes;
+        let query_len = input.query_len;
+        let target_len = classes.len();
+        let gap_penalty = signed(input.options.gap_penalty);
+        let humps = input.options.camel_humps;
+        let buffers = &mut self.gapped;
+
+        buffers.prev.clear();
+        buffers.prev.resize(target_len, UNMATCHED);
+        if input.record_positions {
+            buffers.from.clear();
+            buffers.from.resize(query_len * target_len, 0);
+        }
+
+        for query_idx in 0..query_len {
+            buffers.current.clear();
+            buffers.current.resize(target_len, UNMATCHED);
+            let mut matched = false;
+
+            for i in 0..target_len {
+                if input.mask.is_some_and(|mask| {
+                    !mask.allows_match(i, query_idx == 0, query_idx + 1 == query_len, false)
+                }) {
+                    continue;
+                }
+                let Some(kind) = unit_match(query_idx, i) else {
+                    continue;
+                };
+                let inside_word = humps && !is_word_start(classes, i);
+
+                let (prev_score, seq_match_count, from) = if query_idx == 0 {
+                    if inside_word {
+                        continue;
+                    }
+                    (-signed(leading_gap_penalty(input.options, i)), 0, i)
+                } else {
+                    // Find the best match of the previous query unit within the window,
+                    // preferring the closest one when scores are equal. Inside a word,
+                    // camel humps matching only allows continuing the previous match.
+                    let lowest = if inside_word {
+                        i.saturating_sub(1)
+                    } else {
+                        i.saturating_sub(max_gap + 1)
+                    };
+                    let mut best: Option<(isize, usize, usize)> = None;
+                    for j in (lowest..i).rev() {
+                        let cell = buffers.prev[j];
+                        if cell.score != UNREACHABLE {
+                            let skipped = i - 1 - j;
+                            let score =
+                                penalize(cell.score, gap_penalty.saturating_mul(skipped as isize));
+                            let seq_match_count = if skipped == 0 {
+                                cell.seq_match_count
+                            } else {
+                                0
+                            };
+                            if best.is_none_or(|(best_score, _, _)| score > best_score) {
+                                best = Some((score, seq_match_count, j));
+                            }
+                        }
+                        // Matching further back would skip this unit, which must be matched
+                        if input.mask.is_some_and(|mask| !mask.allows_skip(j)) {
+                            break;
+                        }
+                    }
+                    match best {
+                        Some(best) => best,
+                        None => continue,
+                    }
+                };
+
+                buffers.current[i] = Cell {
+                    score: prev_score.saturating_add(signed(char_score(
+                        input,
+                        i,
+                        seq_match_count,
+                        kind,
+                    ))),
+                    seq_match_count: seq_match_count + 1,
+                };
+                if input.record_positions {
+                    buffers.from[query_idx * target_len + i] = from;
+                }
+                matched = true;
+            }
+
+            // If no cell has a match, the rest of the query can't be matched
+            if !matched {
+                return None;
+            }
+            core::mem::swap(&mut buffers.prev, &mut buffers.current);
+        }
+
+        // Pick the best match of the last query unit, preferring the latest one when scores
+        // are equal as the exact matrix does. Units after it that must be matched can't be
+        // skipped.
+        let mut best: Option<(isize, usize)> = None;
+        for (i, cell) in buffers.prev.iter().enumerate() {
+            if cell.score == UNREACHABLE
+                || input
+                    .mask
+                    .is_some_and(|mask| mask.last_required.is_some_and(|required| required > i))
+            {
+                continue;
+            }
+            if best.is_none_or(|(best_score, _)| cell.score >= best_score) {
+                best = Some((cell.score, i));
+            }
+        }
+        let (score, mut target_idx) = best?;
+
+        if input.record_positions {
+            self.positions.clear();
+            for query_idx in (0..query_len).rev() {
+                self.positions.push(target_idx);
+                target_idx = buffers.from[query_idx * target_len + target_idx];
+            }
+            self.positions.reverse();
+            self.query_indices.clear();
+            self.query_indices.extend(0..query_len);
+        }
+
+        Some(score)
+    }
+}
@@ -0,0 +1,167 @@
+//! Scoring matrix that only visits the cells where a query unit can match.
+//!
+//! Between two matches in a row of the matrix, the score is carried over from the previous
+//! target unit, losing the gap penalty at each step. The score of those cells can therefore
+//! be computed from the last match in the row instead of being stored. For long targets
+//! where each query unit only matches a few target units, this avoids visiting most of the
+//! matrix. The results are identical to the full matrix.
+
+use super::{
+    char_score, leading_gap_penalty, penalize, signed, Matrix, MatrixInput, UnitMatch, UNREACHABLE,
+};
+use crate::ScoreModel;
+use alloc::vec::Vec;
+
+/// Target positions that each query unit may match, in increasing order. Positions that
+/// don't actually match are allowed, as each candidate is still compared.
+pub(crate) struct Candidates<'a> {
+    /// Candidate positions for every query unit, one after another.
+    pub(crate) positions: &'a [usize],
+    /// Index into `positions` of the first candidate of each query unit, followed by the
+    /// total number of candidates.
+    pub(crate) starts: &'a [usize],
+}
+
+/// A cell of the matrix where the query unit was matched with the target unit.
+#[derive(Clone, Copy)]
+struct Cell {
+    position: usize,
+    score: isize,
+    seq_match_count: usize,
+}
+
+/// Scratch memory for the sparse matrix, holding the matched cells of every row.
+#[derive(Default)]
+pub(super) struct SparseBuffers {
+    cells: Vec<Cell>,
+    row_starts: Vec<usize>,
+}
+
+/// Returns the score and sequential match count at target index `target_idx` of a row,
+/// given the matched cells of the row. Cells after the last match carry its score.
+fn value_at(row: &[Cell], target_idx: usize, gap_penalty: isize) -> (isize, usize) {
+    let idx = row.partition_point(|cell| cell.position <= target_idx);
+    match idx.checked_sub(1).map(|idx| row[idx]) {
+        None => (UNREACHABLE, 0),
+        Some(cell) if cell.position == target_idx => (cell.score, cell.seq_match_count),
+        Some(cell) => (
+            penalize(
+                cell.score,
+                gap_penalty.saturating_mul((target_idx - cell.position) as isize),
+            ),
+            0,
+        ),
+    }
+}
+
+impl Matrix {
+    /// Scores a query against a target, visiting only the given candidate positions of
+    /// each query unit. Does not support position masks or reverse traversal.
+    pub(super) fn compute_sparse<M: ScoreModel + ?Sized>(
+        &mut self,
+        input: &MatrixInput<M>,
+        record_positions: bool,
+        candidates: &Candidates,
+        unit_match: &impl Fn(usize, usize) -> Option<UnitMatch>,
+    ) -> Option<isize> {
+        let query_len = input.query_len;
+        let target_len = input.classes.len();
+        let gap_penalty = signed(input.options.gap_penalty);
+        let buffers = &mut self.sparse;
+        buffers.cells.clear();
+        buffers.row_starts.clear();
+
+        let mut prev_row_start = 0;
+        for query_idx in 0..query_len {
+            let first_query_char = query_idx == 0;
+            let last_query_char = query_idx + 1 == query_len;
+            let row_start = buffers.cells.len();
+            buffers.row_starts.push(row_start);
+
+            let row_candidates = &candidates.positions
+                [candidates.starts[query_idx]..candidates.starts[query_idx + 1]];
+            for &i in row_candidates {
+                // Score and sequential match count from the previous unit in both the
+                // target and the query
+                let (prev_query_score, seq_match_count) = if first_query_char {
+                    (0, 0)
+                } else if i == 0 {
+                    (UNREACHABLE, 0)
+                } else {
+                    value_at(
+                        &buffers.cells[prev_row_start..row_start],
+                        i - 1,
+                        gap_penalty,
+                    )
+                };
+                if prev_query_score == UNREACHABLE {
+                    continue;
+                }
+
+                let kind = match unit_match(query_idx, i) {
+                    Some(kind) => kind,
+                    None => continue,
+                };
+
+                // Score carried over from the last match in this row
+                let prev_target_score = match buffers.cells[row_start..].last() {
+                    None => UNREACHABLE,
+                    Some(cell) if last_query_char => cell.score,
+                    Some(cell) => penalize(
+                        cell.score,
+                        gap_penalty.saturating_mul((i - cell.position) as isize),
+                    ),
+                };
+
+                let mut new_score = prev_query_score.saturating_add(signed(char_score(
+                    input,
+                    i,
+                    seq_match_count,
+                    kind,
+                )));
+                if first_query_char {
+                    new_score = penalize(new_score, signed(leading_gap_penalty(input.options, i)));
+                }
+                if new_score >= prev_target_score {
+                    buffers.cells.push(Cell {
+                        position: i,
+                        score: new_score,
+                        seq_match_count: seq_match_count + 1,
+                    });
+                }
+            }
+
+            if buffers.cells.len() == row_start {
+                return None;
+            }
+            prev_row_start = row_start;
+        }
+
+        // The last row carries the score of its last match to the end of the target without
+        // a gap penalty
+        let score = buffers.cells.last()?.score;
+        if score == UNREACHABLE {
+            return None;
+        }
+
+        if record_positions {
+            // A cell is only stored when it was a match, so the cell that produced the score
+            // of each row is the last match before the match in the next row.
+            self.positions.clear();
+            let mut target_idx = target_len;
+            buffers.row_starts.push(buffers.cells.len());
+            for query_idx in (0..query_len).rev() {
+                let row = &buffers.cells
+                    [buffers.row_starts[query_idx]..buffers.row_starts[query_idx + 1]];
+                let idx = row.partition_point(|cell| cell.position < target_idx);
+                target_idx = row[idx - 1].position;
+                self.positions.push(target_idx);
+            }
+            self.positions.reverse();
+            self.query_indices.clear();
+            self.query_indices.extend(0..query_len);
+        }
+
+        Some(score)
+    }
+}
@@ -0,0 +1,270 @@
+//! Scoring matrix that tolerates typos and transposed characters in the query.
+//!
+//! This is a more general and slower form of the scoring matrix that keeps a separate layer
+//! of the matrix for each number of query characters that were skipped. It does not perform
+//! the early exits of the exact matrix, as a query can match without all of its characters
+//! being present in the target, or without its characters being in order.
+
+use super::{
+    char_score, is_word_start, leading_gap_penalty, signed, Matrix, MatrixInput, UnitMatch,
+};
+use crate::ScoreModel;
+use alloc::vec::Vec;
+
+/// The best score at a cell was carried over from the previous target unit.
+const TRACE_CARRY: u8 = 0;
+/// The best score at a cell came from matching the query unit with the target unit.
+const TRACE_MATCH: u8 = 1;
+/// The best score at a cell came from skipping the query unit as a typo.
+const TRACE_SKIP: u8 = 2;
+/// The best score at a cell came from matching the query unit and the one before it with
+/// the previous two target units in swapped order.
+const TRACE_TRANSPOSE: u8 = 3;
+/// The best score at a cell came from mistyping the query unit as the target unit.
+const TRACE_NEAR_MISS: u8 = 4;
+
+/// One row of the typo tolerant matrix. Each row holds one layer per allowed typo, and each
+/// layer has a column before the first target unit for states where no target units have
+/// been consumed.
+#[derive(Default)]
+struct Row {
+    score: Vec<Option<isize>>,
+    seq_match_counts: Vec<usize>,
+}
+
+impl Row {
+    fn reset(&mut self, len: usize) {
+        self.score.clear();
+        self.score.resize(len, None);
+        self.seq_match_counts.clear();
+        self.seq_match_counts.resize(len, 0);
+    }
+}
+
+/// Scratch memory for the typo tolerant matrix. Transpositions need to look back two query
+/// units, so the two rows before the current one are kept.
+#[derive(Default)]
+pub(super) struct TolerantBuffers {
+    prev_prev: Row,
+    prev: Row,
+    current: Row,
+    trace: Vec<u8>,
+}
+
+impl Matrix {
+    /// Scores a query against a target, allowing up to `max_typos` query units to be left
+    /// unmatched, and adjacent query units to be swapped if `transpositions` is set. Each
+    /// typo or transposition subtracts its penalty from the score.
+    pub(super) fn compute_tolerant<M: ScoreModel + ?Sized>(
+        &mut self,
+        input: &MatrixInput<M>,
+        max_typos: usize,
+        transpositions: bool,
+        unit_match: &impl Fn(usize, usize) -> Option<UnitMatch>,
+    ) -> Option<isize> {
+        let classes = input.classes;
+        let query_len = input.query_len;
+        let target_len = classes.len();
+        let layers = max_typos + 1;
+        let width = target_len + 1;
+        let typo_penalty = signed(input.options.typo_penalty);
+        let adjacent_key_penalty = signed(input.options.adjacent_key_penalty);
+        let transposition_penalty = signed(input.options.transposition_penalty);
+        let gap_penalty = signed(input.options.gap_penalty);
+        let require_word_start = input.options.require_word_start;
+        let buffers = &mut self.tolerant;
+
+        // Before any query units are processed, every column of the layer without typos is
+        // a valid state, with only the penalty for starting the match after that column.
+        buffers.prev.reset(layers * width);
+        for (col, score) in buffers.prev.score[..width].iter_mut().enumerate() {
+            *score = Some(-signed(leading_gap_penalty(input.options, col)));
+        }
+        buffers.prev_prev.reset(layers * width);
+
+        if input.record_positions {
+            buffers.trace.clear();
+            buffers
+                .trace
+                .resize((query_len + 1) * layers * width, TRACE_CARRY);
+        }
+
+        for query_idx in 0..query_len {
+            buffers.current.reset(layers * width);
+            let (prev_prev, prev, current) =
+                (&buffers.prev_prev, &buffers.prev, &mut buffers.current);
+
+            for typos in 0..layers {
+                let layer = typos * width;
+                for col in 0..width {
+                    let mut score = None;
+                    let mut seq_match_count = 0;
+                    let mut trace = TRACE_CARRY;
+
+                    if col > 0 {
+                        // Carry the score over from the previous target unit, penalizing the
+                        // gap unless all query units have been matched. States where every
+                        // query unit so far was skipped have no gap, and are only reached by
+                        // skipping from the same column so that the leading gap is penalized.
+                        if typos <= query_idx {
+                            score = current.score[layer + col - 1];
+                            if query_idx + 1 < query_len {
+                                score = score.map(|score| score.saturating_sub(gap_penalty));
+                            }
+                        }
+
+                        // Match the query unit against the target unit at this column. If
+                        // every query unit so far was skipped, this is the first matched unit.
+                        let allowed = !require_word_start
+                            || typos < query_idx
+                            || is_word_start(classes, col - 1);
+                        if let (true, Some(prev_query_score), Some(kind)) = (
+                            allowed,
+                            prev.score[layer + col - 1],
+                            unit_match(query_idx, col - 1),
+                        ) {
+                            let prev_seq_match_count = prev.seq_match_counts[layer + col - 1];
+                            let new_score = prev_query_score.saturating_add(signed(char_score(
+                                input,
+                                col - 1,
+                                prev_seq_match_count,
+                                kind,
+                            )));
+                            if score.is_none_or(|score| new_score >= score) {
+                                score = Some(new_score);
+                                seq_match_count = prev_seq_match_count + 1;
+                                trace = TRACE_MATCH;
+                            }
+                        }
+                    }
+
+                    if transpositions && query_idx > 0 && col > 1 {
+                        // Match this query unit and the previous one against the previous
+                        // two target units in swapped order
+                        let allowed = !require_word_start
+                            || typos + 1 < query_idx
+                            || is_word_start(classes, col - 2);
+                        if let (true, Some(prev_query_score), Some(first_kind), Some(second_kind)) = (
+                            allowed,
+                            prev_prev.score[layer + col - 2],
+                            unit_match(query_idx, col - 2),
+                            unit_match(query_idx - 1, col - 1),
+                        ) {
+                            let prev_seq_match_count = prev_prev.seq_match_counts[layer + col - 2];
+                            let new_score = prev_query_score
+                                .saturating_add(signed(char_score(
+                                    input,
+                                    col - 2,
+                                    prev_seq_match_count,
+                                    first_kind,
+                                )))
+                                .saturating_add(signed(char_score(
+                                    input,
+                                    col - 1,
+                                    prev_seq_match_count + 1,
+                                    second_kind,
+                                )))
+                                .saturating_sub(transposition_penalty);
+                            if score.is_none_or(|score| new_score > score) {
+                                score = Some(new_score);
+                                seq_match_count = prev_seq_match_count + 2;
+                                trace = TRACE_TRANSPOSE;
+                            }
+                        }
+                    }
+
+                    if typos > 0 {
+                        // Skip the query unit as a typo, without consuming a target unit. A
+                        // stray character in the query does not break a sequential match.
+                        if let Some(skipped_score) = prev.score[layer - width + col] {
+                            let new_score = skipped_score.saturating_sub(typo_penalty);
+                            if score.is_none_or(|score| new_score > score) {
+                                score = Some(new_score);
+                                seq_match_count = prev.seq_match_counts[layer - width + col];
+                                trace = TRACE_SKIP;
+                            }
+                        }
+
+                        // Consume the target unit as a mistyped form of the query unit. It
+                        // earns no bonuses and breaks a sequential match, but is not a gap.
+                        if let Some(near_miss) = input.near_miss.filter(|_| col > 0) {
+                            if let Some(mistyped_score) = prev.score[layer - width + col - 1] {
+                                let new_score = mistyped_score.saturating_sub(adjacent_key_penalty);
+                                if score.is_none_or(|score| new_score > score)
+                                    && near_miss(query_idx, col - 1)
+                                {
+                                    score = Some(new_score);
+                                    seq_match_count = 0;
+                                    trace = TRACE_NEAR_MISS;
+                                }
+                            }
+                        }
+                    }
+
+                    current.score[layer + col] = score;
+                    current.seq_match_counts[layer + col] = seq_match_count;
+                    if input.record_positions {
+                        buffers.trace[((query_idx + 1) * layers + typos) * width + col] = trace;
+                    }
+                }
+            }
+
+            core::mem::swap(&mut buffers.prev_prev, &mut buffers.prev);
+            core::mem::swap(&mut buffers.prev, &mut buffers.current);
+        }
+
+        // Pick the number of typos that gives the best score after penalties, preferring
+        // fewer typos when scores are equal.
+        let mut best: Option<(isize, usize)> = None;
+        for typos in 0..layers {
+            if let Some(score) = buffers.prev.score[typos * width + target_len] {
+                if best.is_none_or(|(best_score, _)| score > best_score) {
+                    best = Some((score, typos));
+                }
+            }
+        }
+        let (score, typos) = best?;
+
+        if input.record_positions {
+            self.positions.clear();
+            self.query_indices.clear();
+            self.near_misses.clear();
+            let (mut row, mut typos, mut col) = (query_len, typos, target_len);
+            while row > 0 {
+                match buffers.trace[(row * layers + typos) * width + col] {
+                    TRACE_MATCH => {
+                        self.positions.push(col - 1);
+                        self.query_indices.push(row - 1);
+                        row -= 1;
+                        col -= 1;
+                    }
+                    TRACE_TRANSPOSE => {
+                        // Positions are built in reverse query order
+                        self.positions.push(col - 2);
+                        self.query_indices.push(row - 1);
+                        self.positions.push(col - 1);
+                        self.query_indices.push(row - 2);
+                        row -= 2;
+                        col -= 2;
+                    }
+                    TRACE_SKIP => {
+                        row -= 1;
+                        typos -= 1;
+                    }
+                    TRACE_NEAR_MISS => {
+                        self.near_misses.push(col - 1);
+                        row -= 1;
+                        typos -= 1;
+                        col -= 1;
+                    }
+                    _ => col -= 1,
+                }
+            }
+            self.positions.reverse();
+            self.query_indices.reverse();
+            self.near_misses.reverse();
+        }
+
+        Some(score)
+    }
+}
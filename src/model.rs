@@ -0,0 +1,430 @@
+//! Pluggable models for the score awarded to each matched character.
+
+use crate::matrix::{is_word_end, is_word_start, CharClass};
+use crate::{Bonuses, MatchOptions};
+use alloc::sync::Arc;
+use core::fmt;
+use core::marker::PhantomData;
+
+/// Where a query character was matched in the target, given to a [`ScoreModel`] to score the
+/// match.
+#[derive(Clone, Copy, Debug)]
+pub struct ScoreContext<'a> {
+    /// Classification of each character of the target. When the target was truncated, only
+    /// the characters of the window being matched are included. When the target is fed in
+    /// chunks with [`FuzzyMatcher::feed`](crate::FuzzyMatcher::feed), only the characters
+    /// from up to the length of the query before the matched character to the character
+    /// after it are included.
+    pub classes: &'a [CharClass],
+    /// Index into `classes` of the matched target character.
+    pub index: usize,
+    /// Number of sequentially matched characters immediately before this one. When matching
+    /// the target from end to start with the `bidirectional` option, this counts the
+    /// sequential matches immediately after this one instead.
+    pub sequential_count: usize,
+    /// True if the characters match exactly, including case.
+    pub exact_case: bool,
+    /// Number of characters in the query.
+    pub query_len: usize,
+    /// Index into `classes` of the first character of the final path component, when using
+    /// [`ScoringProfile::FilePath`](crate::ScoringProfile::FilePath).
+    pub basename_start: Option<usize>,
+    /// Options of the matcher performing the match.
+    pub options: &'a MatchOptions,
+}
+
+impl ScoreContext<'_> {
+    /// Returns true if the matched character starts a word: it is the start of the target,
+    /// follows a separator, or is a camel case hump.
+    pub fn is_word_start(&self) -> bool {
+        is_word_start(self.classes, self.index)
+    }
+
+    /// Returns true if the matched character ends a word: it is the end of the target, or
+    /// the next character is a separator or starts a new word.
+    pub fn is_word_end(&self) -> bool {
+        is_word_end(self.classes, self.index)
+    }
+
+    /// Returns true if the matched character is within the final path component, when using
+    /// [`ScoringProfile::FilePath`](crate::ScoringProfile::FilePath).
+    pub fn in_basename(&self) -> bool {
+        self.basename_start.is_some_and(|start| self.index >= start)
+    }
+}
+
+/// Computes the score awarded for each matched character. Every matching mode, fast path and
+/// batch API uses the model set in the `score_model` option, so alternative scoring schemes
+/// can reuse the rest of the matcher. Penalties for gaps, typos and transpositions are
+/// applied by the matcher on top of the model's scores.
+///
+/// # Examples
+///
+/// ```
+/// use code_fuzzy_match::{CustomScoreModel, FuzzyMatcher, MatchOptions, ScoreContext, ScoreModel};
+///
+/// /// Only rewards sequential matches.
+/// struct Sequential;
+///
+/// impl ScoreModel for Sequential {
+///     fn score(&self, context: &ScoreContext) -> usize {
+///         1 + context.sequential_count * 10
+///     }
+/// }
+///
+/// let mut matcher = FuzzyMatcher::with_options(MatchOptions {
+///     score_model: Some(CustomScoreModel::new(Sequential)),
+///     ..MatchOptions::default()
+/// });
+/// assert_eq!(matcher.fuzzy_match("abc", "abc"), Some(33));
+/// assert_eq!(matcher.fuzzy_match("a_b_c", "abc"), Some(3));
+/// ```
+pub trait ScoreModel: Send + Sync {
+    /// Returns the score awarded for matching a query character at the given position.
+    fn score(&self, context: &ScoreContext) -> usize;
+
+    /// Returns the score awarded for matching a query character at the given position,
+    /// broken down into bonuses, for
+    /// [`FuzzyMatcher::fuzzy_match_explain`](crate::FuzzyMatcher::fuzzy_match_explain). The
+    /// [`total`](Bonuses::total) must be equal to [`score`](Self::score). By default the
+    /// whole score is reported as the base score.
+    fn bonuses(&self, context: &ScoreContext) -> Bonuses {
+        Bonuses {
+            base: self.score(context),
+            sequential_count: context.sequential_count,
+            ..Bonuses::default()
+        }
+    }
+}
+
+/// The scoring model used when no custom model is set, inspired by the algorithm used by
+/// Visual Studio Code. Uses the bonuses of [`ConstProfile`] without overriding any of them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DefaultScoreModel;
+
+impl ConstProfile for DefaultScoreModel {}
+
+impl ScoreModel for DefaultScoreModel {
+    #[inline]
+    fn score(&self, context: &ScoreContext) -> usize {
+        profile_bonuses::<Self>(context).total()
+    }
+
+    #[inline]
+    fn bonuses(&self, context: &ScoreContext) -> Bonuses {
+        profile_bonuses::<Self>(context)
+    }
+}
+
+/// Bonuses of the default scoring model, resolved at compile time. Override any of the
+/// constants and use the profile with [`ConstScoreModel`] to tune the default model
+/// without the cost of calling a [`CustomScoreModel`] for every matched character. The
+/// whole word and contiguous query bonuses are taken from [`MatchOptions`].
+pub trait ConstProfile {
+    /// Score for every matched character.
+    const BASE_SCORE: usize = 1;
+    /// Bonus for each sequentially matched character immediately before a match.
+    const SEQUENTIAL_BONUS: usize = 5;
+    /// Bonus for a match with the same case as the query.
+    const SAME_CASE_BONUS: usize = 1;
+    /// Bonus for matching the first character of the target.
+    const START_OF_TARGET_BONUS: usize = 8;
+    /// Bonus for matching a path separator.
+    const PATH_SEPARATOR_BONUS: usize = 5;
+    /// Bonus for matching a separator other than a path separator.
+    const SEPARATOR_BONUS: usize = 4;
+    /// Bonus for matching the start of a word after a separator.
+    const AFTER_SEPARATOR_BONUS: usize = 2;
+    /// Bonus for matching the start of a word in camel case or an uppercase character.
+    const WORD_START_BONUS: usize = 2;
+    /// Bonus for matching the last character of the target.
+    const END_OF_TARGET_BONUS: usize = 2;
+    /// Bonus for matching within the final path component, when using
+    /// [`ScoringProfile::FilePath`](crate::ScoringProfile::FilePath).
+    const BASENAME_BONUS: usize = 5;
+}
+
+/// A scoring model using the bonuses of a [`ConstProfile`], for use with
+/// [`FuzzyMatcher::with_model`](crate::FuzzyMatcher::with_model). Each profile is a separate
+/// type, so the bonuses are constants in the compiled matcher.
+pub struct ConstScoreModel<P>(PhantomData<fn() -> P>);
+
+impl<P: ConstProfile> ConstScoreModel<P> {
+    /// Creates the scoring model for the profile `P`.
+    pub const fn new() -> Self {
+        ConstScoreModel(PhantomData)
+    }
+}
+
+impl<P: ConstProfile> ScoreModel for ConstScoreModel<P> {
+    #[inline]
+    fn score(&self, context: &ScoreContext) -> usize {
+        profile_bonuses::<P>(context).total()
+    }
+
+    #[inline]
+    fn bonuses(&self, context: &ScoreContext) -> Bonuses {
+        profile_bonuses::<P>(context)
+    }
+}
+
+impl<P: ConstProfile> Default for ConstScoreModel<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P> Clone for ConstScoreModel<P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P> Copy for ConstScoreModel<P> {}
+
+impl<P> fmt::Debug for ConstScoreModel<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ConstScoreModel")
+    }
+}
+
+/// Computes the bonuses for a match using the constants of the profile `P`.
+#[inline(always)]
+fn profile_bonuses<P: ConstProfile + ?Sized>(context: &ScoreContext) -> Bonuses {
+    let classes = context.classes;
+    let i = context.index;
+    let seq_match_count = context.sequential_count;
+    let options = context.options;
+    let mut bonuses = Bonuses {
+        base: P::BASE_SCORE,
+        sequential_count: seq_match_count,
+        ..Bonuses::default()
+    };
+
+    // Sequential match bonus
+    bonuses.sequential = seq_match_count * P::SEQUENTIAL_BONUS;
+
+    if context.exact_case {
+        // Same case bonus
+        bonuses.same_case = P::SAME_CASE_BONUS;
+    }
+
+    if i == 0 {
+        // Start of target bonus
+        bonuses.start_of_target = P::START_OF_TARGET_BONUS;
+    } else if classes[i] == CharClass::PathSeparator {
+        // Path separator bonus
+        bonuses.path_separator = P::PATH_SEPARATOR_BONUS;
+    } else if classes[i] == CharClass::Separator {
+        // Separator bonus
+        bonuses.separator = P::SEPARATOR_BONUS;
+    } else if seq_match_count == 0 {
+        if classes[i - 1] == CharClass::Separator {
+            // Start of word after separator bonus
+            bonuses.after_separator = P::AFTER_SEPARATOR_BONUS;
+        } else if matches!(classes[i], CharClass::Uppercase | CharClass::WordStart) {
+            // Start of word bonus
+            bonuses.word_start = P::WORD_START_BONUS;
+        }
+    }
+
+    if i + 1 == classes.len() {
+        // End of target bonus
+        bonuses.end_of_target = P::END_OF_TARGET_BONUS;
+    }
+
+    if options.whole_word_bonus != 0 && is_word_end(classes, i) {
+        // Entire word matched bonus. When traversing in reverse, sequential matches are
+        // counted in the other direction and the run may not start in the target.
+        let run_start = i.checked_sub(seq_match_count);
+        if run_start.is_some_and(|start| {
+            !matches!(
+                classes[start],
+                CharClass::PathSeparator | CharClass::Separator
+            ) && is_word_start(classes, start)
+        }) {
+            bonuses.whole_word = options.whole_word_bonus;
+        }
+    }
+
+    if seq_match_count + 1 == context.query_len {
+        // Entire query matched contiguously bonus
+        bonuses.contiguous_query = options.contiguous_query_bonus;
+    }
+
+    if context.in_basename() {
+        // Final path component bonus
+        bonuses.basename = P::BASENAME_BONUS;
+    }
+
+    bonuses
+}
+
+/// A custom [`ScoreModel`] to use in the `score_model` option of [`MatchOptions`]. Options
+/// holding a custom model are only equal if they hold the same instance of the model, and
+/// the model is not serialized.
+#[derive(Clone)]
+pub struct CustomScoreModel(Arc<dyn ScoreModel>);
+
+impl CustomScoreModel {
+    /// Wraps a scoring model for use in [`MatchOptions`].
+    pub fn new(model: impl ScoreModel + 'static) -> Self {
+        CustomScoreModel(Arc::new(model))
+    }
+
+    /// Returns the wrapped scoring model.
+    pub fn model(&self) -> &dyn ScoreModel {
+        &*self.0
+    }
+}
+
+impl From<Arc<dyn ScoreModel>> for CustomScoreModel {
+    fn from(model: Arc<dyn ScoreModel>) -> Self {
+        CustomScoreModel(model)
+    }
+}
+
+impl PartialEq for CustomScoreModel {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for CustomScoreModel {}
+
+impl fmt::Debug for CustomScoreModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CustomScoreModel")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fuzzy_match_in, ConstProfile, ConstScoreModel, CustomScoreModel, DefaultScoreModel,
+        FuzzyMatcher, MatchOptions, MatchScratch, ScoreContext, ScoreModel, ScoringProfile,
+        TieBreak,
+    };
+    use alloc::vec::Vec;
+
+    /// Rewards word starts heavily, and ignores everything else.
+    struct WordStarts;
+
+    impl ScoreModel for WordStarts {
+        fn score(&self, context: &ScoreContext) -> usize {
+            if context.is_word_start() {
+                100
+            } else {
+                1
+            }
+        }
+    }
+
+    #[test]
+    fn test_score_model() {
+        let targets = ["src/main.rs", "src/matrix.rs", "MatrixInput", "commit"];
+        let default_model = MatchOptions {
+            score_model: Some(CustomScoreModel::new(DefaultScoreModel)),
+            ..MatchOptions::default()
+        };
+        let mut default_matcher = FuzzyMatcher::with_options(default_model);
+        for target in targets {
+            assert_eq!(
+                default_matcher.fuzzy_match(target, "mi"),
+                crate::fuzzy_match(target, "mi")
+            );
+        }
+
+        let options = MatchOptions {
+            score_model: Some(CustomScoreModel::new(WordStarts)),
+            gap_penalty: 1,
+            ..MatchOptions::default()
+        };
+        assert_ne!(options, MatchOptions::default());
+        assert_eq!(options.clone(), options);
+
+        // The model is used by every matching path
+        let mut matcher = FuzzyMatcher::with_options(options.clone());
+        let mut scratch = MatchScratch::<32>::with_options(options);
+        assert_eq!(matcher.fuzzy_match("MatrixInput", "mi"), Some(200 - 5));
+        assert_eq!(matcher.fuzzy_match("commit", "mi"), Some(2));
+        for target in targets {
+            let score = matcher.fuzzy_match(target, "mi");
+            assert_eq!(fuzzy_match_in(target, "mi", &mut scratch), score);
+            let explanation = matcher.fuzzy_match_explain(target, "mi");
+            assert_eq!(explanation.map(|explanation| explanation.score), score);
+        }
+        let ranked: Vec<&str> = matcher
+            .rank(targets, "mi", TieBreak::DEFAULT)
+            .iter()
+            .map(|ranked| ranked.target)
+            .collect();
+        assert_eq!(
+            ranked,
+            ["MatrixInput", "src/main.rs", "src/matrix.rs", "commit"]
+        );
+    }
+
+    /// Ignores case and rewards path components.
+    struct Paths;
+
+    impl ConstProfile for Paths {
+        const SAME_CASE_BONUS: usize = 0;
+        const PATH_SEPARATOR_BONUS: usize = 20;
+        const BASENAME_BONUS: usize = 0;
+    }
+
+    #[test]
+    fn test_const_profile() {
+        let targets = ["src/main.rs", "src/matrix.rs", "MatrixInput", "Src/Main.rs"];
+        for options in [
+            MatchOptions::default(),
+            MatchOptions {
+                profile: ScoringProfile::FilePath,
+                whole_word_bonus: 5,
+                ..MatchOptions::default()
+            },
+        ] {
+            let mut default_matcher = FuzzyMatcher::with_options(options.clone());
+            let mut const_matcher =
+                FuzzyMatcher::with_model(ConstScoreModel::<DefaultScoreModel>::new(), options);
+            for target in targets {
+                for query in ["mi", "s/m", "SRC"] {
+                    assert_eq!(
+                        const_matcher.fuzzy_match(target, query),
+                        default_matcher.fuzzy_match(target, query)
+                    );
+                }
+            }
+        }
+
+        // The profile is used by every matching path
+        let options = MatchOptions {
+            gap_penalty: 1,
+            ..MatchOptions::default()
+        };
+        let mut matcher = FuzzyMatcher::with_model(ConstScoreModel::<Paths>::default(), options);
+        assert_eq!(
+            matcher.fuzzy_match("src/main.rs", "src"),
+            Some(1 + 8 + 6 + 11)
+        );
+        assert_eq!(
+            matcher.fuzzy_match("src/main.rs", "src"),
+            matcher.fuzzy_match("SRC/main.rs", "src")
+        );
+        assert_eq!(matcher.fuzzy_match("a/b", "/"), Some(21));
+        for target in targets {
+            let score = matcher.fuzzy_match(target, "s/m");
+            let explanation = matcher.fuzzy_match_explain(target, "s/m");
+            assert_eq!(explanation.map(|explanation| explanation.score), score);
+        }
+        let ranked: Vec<&str> = matcher
+            .rank(["src_main.rs", "src/main.rs"], "s/m", TieBreak::DEFAULT)
+            .iter()
+            .map(|ranked| ranked.target)
+            .collect();
+        assert_eq!(ranked, ["src/main.rs"]);
+        matcher.shrink_to_fit();
+        assert_eq!(matcher.fuzzy_match("a/b", "/"), Some(21));
+    }
+}
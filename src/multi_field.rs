@@ -0,0 +1,123 @@
+//! Matching of items made up of several weighted text fields.
+
+use crate::{FuzzyMatcher, Match};
+use alloc::vec::Vec;
+
+/// Result of matching a query against the fields of an item.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MultiFieldMatch {
+    /// Sum of the score of each matching field multiplied by the weight of that field.
+    pub score: usize,
+    /// Result of matching each field, in the order the fields were given. Fields that did not
+    /// match the query are `None`.
+    pub fields: Vec<Option<Match>>,
+}
+
+/// Fuzzy matcher for items with several text fields, such as the title, category, and
+/// keywords of a command palette entry. Each field is given a weight when the matcher is
+/// created, and an item matches if the query matches any of its fields.
+///
+/// # Examples
+///
+/// ```
+/// use code_fuzzy_match::MultiFieldMatcher;
+///
+/// // Titles are worth three times as much as categories
+/// let mut matcher = MultiFieldMatcher::new(&[3, 1]);
+/// let title = matcher.match_fields(&["Open File", "File"], "open").unwrap();
+/// let category = matcher.match_fields(&["Save All", "Open Editors"], "open").unwrap();
+/// assert!(title.score > category.score);
+/// assert!(category.fields[0].is_none());
+/// ```
+pub struct MultiFieldMatcher {
+    matcher: FuzzyMatcher,
+    weights: Vec<usize>,
+}
+
+impl MultiFieldMatcher {
+    /// Creates a matcher for items with one field per entry of `weights`.
+    pub fn new(weights: &[usize]) -> Self {
+        Self::with_matcher(FuzzyMatcher::new(), weights)
+    }
+
+    /// Creates a matcher for items with one field per entry of `weights`, using the given
+    /// matcher, and therefore its options, to match each field.
+    pub fn with_matcher(matcher: FuzzyMatcher, weights: &[usize]) -> Self {
+        MultiFieldMatcher {
+            matcher,
+            weights: weights.to_vec(),
+        }
+    }
+
+    /// Returns the weight of each field.
+    pub fn weights(&self) -> &[usize] {
+        &self.weights
+    }
+
+    /// Returns the matcher used to match each field.
+    pub fn matcher(&mut self) -> &mut FuzzyMatcher {
+        &mut self.matcher
+    }
+
+    /// Fuzzy match the fields of an item against a query string. Returns the combined
+    /// weighted score along with the result for each field, or `None` if the query does not
+    /// match any of the fields.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of fields is not the same as the number of weights.
+    pub fn match_fields(&mut self, fields: &[&str], query: &str) -> Option<MultiFieldMatch> {
+        assert_eq!(
+            fields.len(),
+            self.weights.len(),
+            "number of fields must match the number of weights"
+        );
+
+        let mut score = 0;
+        let mut any_match = false;
+        let mut results = Vec::with_capacity(fields.len());
+        for (field, weight) in fields.iter().zip(self.weights.iter()) {
+            let result = self.matcher.fuzzy_match_details(field, query);
+            if let Some(result) = &result {
+                score += result.score.saturating_mul(*weight);
+                any_match = true;
+            }
+            results.push(result);
+        }
+
+        any_match.then_some(MultiFieldMatch {
+            score,
+            fields: results,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{fuzzy_match, MultiFieldMatcher};
+    use alloc::vec;
+
+    #[test]
+    fn test_multi_field() {
+        let mut matcher = MultiFieldMatcher::new(&[4, 2, 1]);
+        let fields = ["Open Terminal", "View", "console shell"];
+
+        let result = matcher.match_fields(&fields, "term").unwrap();
+        assert_eq!(
+            Some(result.score),
+            fuzzy_match(fields[0], "term").map(|score| score * 4)
+        );
+        assert_eq!(
+            result.fields[0].as_ref().unwrap().positions,
+            vec![5, 6, 7, 8]
+        );
+        assert!(result.fields[1].is_none());
+
+        // Scores of every matching field are combined
+        let result = matcher.match_fields(&fields, "e").unwrap();
+        assert_eq!(result.fields.iter().filter(|f| f.is_some()).count(), 3);
+
+        assert!(matcher.match_fields(&fields, "xyz").is_none());
+    }
+}
@@ -0,0 +1,458 @@
+//! Options controlling the behavior of a [`FuzzyMatcher`](crate::FuzzyMatcher).
+
+use crate::whitespace;
+use crate::{ByteDecoding, CustomScoreModel, KeyboardLayout, LongTargetPolicy, Truncation};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Options controlling how a [`FuzzyMatcher`](crate::FuzzyMatcher) matches and scores
+/// strings. The default options match the behavior of [`fuzzy_match`](crate::fuzzy_match).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct MatchOptions {
+    /// Also search for the best alignment of the query while traversing the target from end
+    /// to start, and keep it if it scores higher. This reduces the bias towards the earliest
+    /// possible placement of each query character, which can otherwise miss a better match
+    /// near the end of long targets. This roughly doubles the cost of a match.
+    pub bidirectional: bool,
+    /// Traverse the target from end to start when searching for the best alignment of the
+    /// query, favoring the latest placement of each query character the way the default
+    /// traversal favors the earliest one. For long paths, this aligns the query with the file
+    /// name and its parent directory rather than an earlier directory with the same name,
+    /// even when that scores lower than a match at the start of the target. Scores follow
+    /// the same rules in either direction, and positions are reported in target order. Has
+    /// no effect with `bidirectional`, which keeps the better of both directions, with
+    /// `camel_humps` or `max_gap`, or when matching with typos or transpositions.
+    pub right_to_left: bool,
+    /// Maximum number of query characters that may be left unmatched, so that a query with
+    /// a mistyped character can still match. Fewer than half of the query characters may be
+    /// unmatched regardless of this setting. Typo tolerance is not applied to constrained
+    /// matches, and matching with typos does not use the `bidirectional` option.
+    pub max_typos: usize,
+    /// Amount subtracted from the score for each unmatched query character when
+    /// `max_typos` is nonzero. Matches are never given a score below one.
+    pub typo_penalty: usize,
+    /// Layout of the keyboard used to type queries. When set and `max_typos` is nonzero, a
+    /// query character can be mistyped as the character of an adjacent key in the target,
+    /// such as `g` typed as `h` on a QWERTY keyboard. The mistyped target character is not
+    /// reported as a matched position and earns no bonuses, but is not penalized as a gap.
+    /// Only used when matching characters, not grapheme clusters.
+    pub keyboard_layout: Option<KeyboardLayout>,
+    /// Amount subtracted from the score for each query character mistyped as an adjacent
+    /// key, in place of `typo_penalty`, when `keyboard_layout` is set. Each mistyped
+    /// character counts as a typo towards `max_typos`.
+    pub adjacent_key_penalty: usize,
+    /// Allow two adjacent query characters to match the target in swapped order, so that
+    /// `"teh"` matches `"the"`. Transpositions are not applied to constrained matches, and
+    /// matching with transpositions does not use the `bidirectional` option.
+    pub transpositions: bool,
+    /// Amount subtracted from the score for each pair of swapped query characters when
+    /// `transpositions` is set. Matches are never given a score below one.
+    pub transposition_penalty: usize,
+    /// Amount subtracted from the score for each target character that is skipped between
+    /// two matched characters, so that scattered matches score lower than compact ones.
+    /// Matches are never given a score below one.
+    pub gap_penalty: usize,
+    /// Amount subtracted from the score for each target character before the first matched
+    /// character, so that matches near the start of the target rank higher. Matches are
+    /// never given a score below one.
+    pub leading_gap_penalty: usize,
+    /// Upper limit on the total penalty from `leading_gap_penalty`, so that matches far
+    /// into a long target are not penalized without bound.
+    pub max_leading_gap_penalty: usize,
+    /// Maximum number of target characters skipped between two consecutive matched
+    /// characters. Matches that need a larger gap are rejected, so that a short query
+    /// doesn't match a few scattered characters of a long sentence, and long targets are
+    /// rejected sooner. Typos, transpositions and the reverse pass of `bidirectional`
+    /// matching are not used while the limit applies. The
+    /// [`VsCodeCompat`](ScoringProfile::VsCodeCompat) profile ignores it, and
+    /// [`MatchScratch`](crate::MatchScratch) doesn't support it.
+    pub max_gap: Option<usize>,
+    /// Bonus added when a run of consecutively matched characters covers an entire word of
+    /// the target, so that `"dog"` matching the word "dog" clearly outranks scattered
+    /// matches.
+    pub whole_word_bonus: usize,
+    /// Bonus added when every character of the query is matched consecutively, such as when
+    /// the query is a substring of the target.
+    pub contiguous_query_bonus: usize,
+    /// Bonus added to the score when the query occurs in the target as a substring,
+    /// comparing characters using the same rules as the rest of the match, so case
+    /// differences are ignored. Unlike `contiguous_query_bonus`, this is awarded even when
+    /// the best scoring match is scattered, so that literal matches clearly outrank
+    /// scattered ones. Matched positions are those of the best scoring match.
+    pub substring_bonus: usize,
+    /// Scales down the scores of targets that are much longer than the query, so that long
+    /// targets don't rank above short ones just by having more places to earn bonuses. When
+    /// nonzero, scores are multiplied by `length_normalization / (length_normalization +
+    /// extra)`, where `extra` is the number of target characters beyond the length of the
+    /// query, so a target this many characters longer than the query scores half as much.
+    /// Scores at or below zero are not scaled, and the `substring_bonus` is added after
+    /// scaling.
+    pub length_normalization: usize,
+    /// Give a query that is equal to the entire target a score of
+    /// [`EXACT_MATCH_SCORE`](crate::EXACT_MATCH_SCORE), so that exact matches sort above all
+    /// other matches. Characters are compared using the same rules as the rest of the match,
+    /// so case differences and equivalent characters are still an exact match.
+    pub pin_exact_matches: bool,
+    /// Only allow matches where the first matched character is at the start of a word in
+    /// the target: the start of the target, after a separator, or a camel case hump. This
+    /// lets `"of"` match "Open File" but not "proof".
+    pub require_word_start: bool,
+    /// Only allow each query character to match at the start of a word in the target, as
+    /// for `require_word_start`, or right after the character matched by the previous query
+    /// character, like camel hump navigation in IDEs. This lets `"FBar"` match "FooBar" but
+    /// not `"oar"`. Typos, transpositions and the reverse pass of `bidirectional` matching
+    /// are not used with this option. The
+    /// [`VsCodeCompat`](ScoringProfile::VsCodeCompat) profile ignores it, and
+    /// [`MatchScratch`](crate::MatchScratch) doesn't support it.
+    pub camel_humps: bool,
+    /// Treat the boundary between letters and digits as the start of a word, so that the
+    /// first digit after a letter and the first letter after a digit receive the word start
+    /// bonus, as in `sha256sum` or `utf8_len`.
+    pub digit_word_starts: bool,
+    /// Only match within a window of very long targets. Use
+    /// [`FuzzyMatcher::truncated`](crate::FuzzyMatcher::truncated) to check if the most
+    /// recent target was truncated.
+    pub truncation: Option<Truncation>,
+    /// Maximum number of characters in a target. Longer targets are handled according to
+    /// `long_target_policy`, so that a single huge target can't stall matching.
+    pub max_target_len: Option<usize>,
+    /// How targets longer than `max_target_len` are handled. Truncated targets are reported
+    /// in the same way as targets truncated by the `truncation` option.
+    pub long_target_policy: LongTargetPolicy,
+    /// How an empty query is matched. By default an empty query matches nothing, and user
+    /// interfaces that list every target before anything is typed can choose to match every
+    /// target instead.
+    pub empty_query: EmptyQueryPolicy,
+    /// Index the positions of each character in the target, so that the matcher only
+    /// visits the target characters that can match each query character. This is faster
+    /// for long targets where the query characters are rare, and slower for short targets.
+    /// Combine with [`FuzzyMatcher::set_target_cache_capacity`](crate::FuzzyMatcher::set_target_cache_capacity)
+    /// to keep the index of each target between matches. The index is not used for
+    /// constrained matches, matches with typos or transpositions, or the reverse pass of
+    /// `bidirectional` matching. Scores are the same with or without the index.
+    pub position_index: bool,
+    /// Score each distinct target only once per query in an [`Index`](crate::Index) or
+    /// [`CandidateSet`](crate::CandidateSet), giving the same score to every candidate with
+    /// that target. Targets are the same when their characters and word boundaries are
+    /// identical after folding, ignored characters and extensions are applied, so
+    /// re-exported symbols and candidates that only differ in skipped characters are
+    /// scored once. This saves work on candidate lists with many duplicates, at the cost
+    /// of keeping each distinct target for the duration of a query. Scores are the same
+    /// with or without deduplication.
+    pub dedup_targets: bool,
+    /// Sequences of characters that are treated as path separators in addition to `/` and
+    /// `\`, such as `::` for Rust symbol paths. Every character of a matching sequence
+    /// receives the path separator bonus.
+    pub path_separators: Vec<String>,
+    /// Allow `/` and `\` to match each other, so that queries can use either path separator.
+    /// Disable this when matching strings that are not paths, such as regular expressions.
+    pub slash_equivalence: bool,
+    /// Remove whitespace from the start and end of the query, so that stray spaces in a
+    /// pasted query don't prevent it from matching.
+    pub trim_query: bool,
+    /// Replace each run of whitespace in the query with a single space.
+    pub collapse_query_spaces: bool,
+    /// Allow a space in the query to match any separator in the target, such as `_`, `-`,
+    /// `.` or `/`, so that a query of `open file` matches `open_file`. Spaces still match
+    /// each other exactly. Not used by the [`VsCodeCompat`](ScoringProfile::VsCodeCompat)
+    /// profile or grapheme matching.
+    pub space_matches_separators: bool,
+    /// Groups of characters that match each other, each given as a string holding the
+    /// characters of the group. For example, `"-_"` allows a query of `my_crate` to match
+    /// `my-crate`. Characters in a group are compared exactly, without case folding.
+    pub equivalences: Vec<String>,
+    /// Pairs of words that match each other in queries, such as `("rm", "remove")` or
+    /// `("cfg", "config")`. A space separated word of the query that is equal to either word
+    /// of a pair, ignoring case, is also tried as the other word, so that either form
+    /// matches targets containing the other. The best scoring form of the query is kept, and
+    /// matches using a synonym are penalized by `synonym_penalty`. Constrained matches and
+    /// grapheme matching ignore synonyms, and [`MatchScratch`](crate::MatchScratch) doesn't
+    /// support them.
+    pub synonyms: Vec<(String, String)>,
+    /// Amount subtracted from the score of a match that uses a word from `synonyms` in place
+    /// of the word in the query, so that direct matches rank above matches through a
+    /// synonym. Exact matches pinned by `pin_exact_matches` are not penalized.
+    pub synonym_penalty: usize,
+    /// Characters of the target that are skipped when matching, given as a string holding
+    /// the characters, such as `" :&()"` to make spaces and punctuation transparent when
+    /// matching titles like `"Run: Build & Test (release)"` with `"runbuildtest"`. A
+    /// character after a skipped separator is still treated as the start of a word, and
+    /// matched positions refer to the characters of the target. Grapheme matching doesn't
+    /// skip characters, and [`MatchScratch`](crate::MatchScratch) doesn't support them.
+    pub ignored_chars: String,
+    /// Leave the file extension of the target out of matching and scoring unless the query
+    /// contains a `.`, so that a query like `main` scores the same against `main.rs` and
+    /// `main.toml`. The extension starts at the last `.` of the final path component,
+    /// unless the component starts with it, as in `.gitignore`. A query such as `main.rs`
+    /// or `.rs` matches against the whole target, so the extension can still be anchored
+    /// with an `end` constraint. Streaming matches and grapheme matching keep the extension.
+    pub ignore_extension: bool,
+    /// Fold full-width ASCII characters to their half-width forms and katakana to hiragana
+    /// in both the query and the target, so that Japanese text matches regardless of the
+    /// input form used in the target. Folding is done once when a target is loaded, and
+    /// folded targets are kept in the target cache.
+    pub kana_folding: bool,
+    /// Language whose rules are used to compare letters ignoring case. The
+    /// [`VsCodeCompat`](ScoringProfile::VsCodeCompat) profile always uses the default rules.
+    pub case_locale: CaseLocale,
+    /// Compare characters using full Unicode case folding after compatibility normalization
+    /// (NFKC), so that a character that folds to several characters matches them on either
+    /// side of the comparison, such as `ß` and `ss`, or `ﬁ` and `fi`. A character of the
+    /// target is matched if any of the characters it folds to is matched. Grapheme matching
+    /// compares characters without folding them, and [`MatchScratch`](crate::MatchScratch)
+    /// doesn't support this option.
+    #[cfg(feature = "case-folding")]
+    pub full_case_folding: bool,
+    /// How targets that are not valid UTF-8 are decoded by
+    /// [`FuzzyMatcher::fuzzy_match_bytes`](crate::FuzzyMatcher::fuzzy_match_bytes).
+    pub byte_decoding: ByteDecoding,
+    /// Adjusts scoring for the kind of strings being matched.
+    pub profile: ScoringProfile,
+    /// Scoring model that computes the score awarded for each matched character, in place
+    /// of [`DefaultScoreModel`](crate::DefaultScoreModel). Custom models are not serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub score_model: Option<CustomScoreModel>,
+    /// Version of the scoring rules. Defaults to [`ScoreVersion::LATEST`]. Set this to a
+    /// specific version when scores are persisted or compared against stored thresholds, so
+    /// that scores don't change when the crate is updated.
+    pub score_version: ScoreVersion,
+    /// Detect the start of words using Unicode word segmentation (UAX #29) in addition to
+    /// the built in separator and camel case rules. This allows text in scripts without
+    /// case or separators between words, such as Japanese, to receive word start bonuses.
+    #[cfg(feature = "unicode-words")]
+    pub unicode_words: bool,
+}
+
+impl Default for MatchOptions {
+    fn default() -> Self {
+        MatchOptions {
+            bidirectional: false,
+            right_to_left: false,
+            max_typos: 0,
+            typo_penalty: 10,
+            keyboard_layout: None,
+            adjacent_key_penalty: 5,
+            transpositions: false,
+            transposition_penalty: 5,
+            gap_penalty: 0,
+            leading_gap_penalty: 0,
+            max_leading_gap_penalty: usize::MAX,
+            max_gap: None,
+            whole_word_bonus: 0,
+            contiguous_query_bonus: 0,
+            substring_bonus: 0,
+            length_normalization: 0,
+            pin_exact_matches: false,
+            require_word_start: false,
+            camel_humps: false,
+            digit_word_starts: false,
+            truncation: None,
+            max_target_len: None,
+            long_target_policy: LongTargetPolicy::Reject,
+            empty_query: EmptyQueryPolicy::NoMatch,
+            position_index: false,
+            dedup_targets: false,
+            path_separators: Vec::new(),
+            slash_equivalence: true,
+            trim_query: false,
+            collapse_query_spaces: false,
+            space_matches_separators: false,
+            equivalences: Vec::new(),
+            synonyms: Vec::new(),
+            synonym_penalty: 5,
+            ignored_chars: String::new(),
+            ignore_extension: false,
+            kana_folding: false,
+            case_locale: CaseLocale::Default,
+            #[cfg(feature = "case-folding")]
+            full_case_folding: false,
+            byte_decoding: ByteDecoding::Lossy,
+            profile: ScoringProfile::General,
+            score_model: None,
+            score_version: ScoreVersion::LATEST,
+            #[cfg(feature = "unicode-words")]
+            unicode_words: false,
+        }
+    }
+}
+
+impl MatchOptions {
+    /// Returns true if the two characters are in the same group of `equivalences`.
+    pub(crate) fn equivalent(&self, a: char, b: char) -> bool {
+        self.equivalences
+            .iter()
+            .any(|group| group.contains(a) && group.contains(b))
+    }
+
+    /// Returns true if the results for `query` are kept in the order of the targets, as set
+    /// by [`EmptyQueryPolicy::MatchAllNaturalOrder`].
+    pub(crate) fn natural_order(&self, query: &str) -> bool {
+        self.empty_query == EmptyQueryPolicy::MatchAllNaturalOrder && self.query_is_empty(query)
+    }
+
+    /// Returns true if `query` has no characters once its whitespace is handled as set by
+    /// the options.
+    pub(crate) fn query_is_empty(&self, query: &str) -> bool {
+        whitespace::query_chars(query, self).next().is_none()
+    }
+}
+
+/// How an empty query is matched, set with the `empty_query` option.
+///
+/// # Examples
+///
+/// ```
+/// use code_fuzzy_match::{EmptyQueryPolicy, FuzzyMatcher, MatchOptions, TieBreak};
+///
+/// let targets = ["src/main.rs", "lib.rs", "Cargo.toml"];
+/// let mut matcher = FuzzyMatcher::new();
+/// assert_eq!(matcher.fuzzy_match("lib.rs", ""), None);
+///
+/// let mut matcher = FuzzyMatcher::with_options(MatchOptions {
+///     empty_query: EmptyQueryPolicy::MatchAllNaturalOrder,
+///     ..MatchOptions::default()
+/// });
+/// let ranked = matcher.rank(targets, "", TieBreak::DEFAULT);
+/// assert_eq!(ranked.iter().map(|m| m.target).collect::<Vec<_>>(), targets);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EmptyQueryPolicy {
+    /// An empty query matches no target.
+    #[default]
+    NoMatch,
+    /// An empty query matches every target with a score of zero, which is raised to one
+    /// when scores are returned as `usize`, and without matched positions. Ranking APIs
+    /// order the matches as they would any matches with equal scores, so tie breaks,
+    /// frecency and other boosts still apply.
+    MatchAllZeroScore,
+    /// An empty query matches every target as with [`MatchAllZeroScore`](Self::MatchAllZeroScore),
+    /// and ranking APIs return the matches in the order the targets were given, without
+    /// applying tie breaks or boosts.
+    MatchAllNaturalOrder,
+}
+
+/// Language whose rules are used to compare letters ignoring case, set with the
+/// `case_locale` option.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CaseLocale {
+    /// Default Unicode case rules, which are correct for most languages.
+    #[default]
+    Default,
+    /// Case rules of Turkish and Azerbaijani, where the dotted and dotless `i` are different
+    /// letters. `I` is the uppercase form of `ı`, and `İ` is the uppercase form of `i`.
+    Turkish,
+}
+
+impl CaseLocale {
+    /// Returns whether two characters match ignoring case, or `None` if the characters are
+    /// compared using the default rules.
+    pub(crate) fn chars_match(self, a: char, b: char) -> Option<bool> {
+        match self {
+            CaseLocale::Default => None,
+            CaseLocale::Turkish => {
+                let fold = |c| match c {
+                    'I' | 'ı' => Some('ı'),
+                    'İ' | 'i' => Some('i'),
+                    _ => None,
+                };
+                match (fold(a), fold(b)) {
+                    (None, None) => None,
+                    (a, b) => Some(a == b),
+                }
+            }
+        }
+    }
+
+    /// Returns the key of the characters that match `c` in this locale but not with the
+    /// default rules, if they have a different key than `c`.
+    pub(crate) fn extra_key(self, c: char) -> Option<char> {
+        match (self, c) {
+            (CaseLocale::Turkish, 'ı') => Some('i'),
+            (CaseLocale::Turkish, 'I') => Some('ı'),
+            _ => None,
+        }
+    }
+}
+
+/// Adjustments to scoring for specific kinds of target strings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScoringProfile {
+    /// Scoring suitable for any kind of string, such as commands and symbol names.
+    #[default]
+    General,
+    /// Scoring for file paths. Characters matched within the final path component are given
+    /// an extra bonus, so that matches in the file name are preferred over matches scattered
+    /// across directory names.
+    FilePath,
+    /// Scoring that reproduces the `fuzzyScore` function of Visual Studio Code, for editors
+    /// migrating from its implementation that need the same matches and orderings. Like
+    /// `fuzzyScore`, the first query character must match at the start of the target or of
+    /// a word, characters are compared ignoring case, and only the first 128 characters of
+    /// the target and query are considered. Scores are offset from those of `fuzzyScore`,
+    /// which can be negative, so only their order is the same.
+    ///
+    /// The other options that affect scoring and alignment, such as penalties, typos and
+    /// custom scoring models, are not used, but `substring_bonus` and `pin_exact_matches`
+    /// still adjust the final score. Constrained matches and grapheme matching use the
+    /// [`General`](Self::General) profile instead, [`MatchScratch`](crate::MatchScratch)
+    /// doesn't support this profile, and the bonuses reported by
+    /// [`fuzzy_match_explain`](crate::FuzzyMatcher::fuzzy_match_explain) are those of the
+    /// `General` profile.
+    VsCodeCompat,
+}
+
+/// Version of the scoring rules. Scores are only guaranteed to stay the same between
+/// releases of the crate for the same version. When the scoring rules change, a new version
+/// is added and the earlier versions keep scoring as they did before.
+///
+/// # Examples
+///
+/// ```
+/// use code_fuzzy_match::{FuzzyMatcher, MatchOptions, ScoreVersion};
+///
+/// let mut matcher = FuzzyMatcher::with_options(MatchOptions {
+///     score_version: ScoreVersion::V1,
+///     ..MatchOptions::default()
+/// });
+/// assert_eq!(matcher.score_version(), ScoreVersion::V1);
+/// assert_eq!(matcher.fuzzy_match("src/lib.rs", "lib"), Some(21));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum ScoreVersion {
+    /// The scoring rules of the first release with versioned scoring.
+    V1,
+}
+
+impl ScoreVersion {
+    /// The most recent version of the scoring rules, used by default.
+    pub const LATEST: ScoreVersion = ScoreVersion::V1;
+
+    /// Returns the number of the version, for storing alongside persisted scores.
+    pub fn number(self) -> u32 {
+        match self {
+            ScoreVersion::V1 => 1,
+        }
+    }
+
+    /// Returns the version with the given number, or `None` if this release of the crate
+    /// doesn't know it.
+    pub fn from_number(number: u32) -> Option<Self> {
+        match number {
+            1 => Some(ScoreVersion::V1),
+            _ => None,
+        }
+    }
+}
+
+impl Default for ScoreVersion {
+    fn default() -> Self {
+        Self::LATEST
+    }
+}
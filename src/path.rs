@@ -0,0 +1,104 @@
+//! Two stage matching of file paths, preferring matches within the file name.
+
+use crate::{FuzzyMatcher, ScoreModel};
+
+/// The part of a path that a query was matched against.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PathMatchStage {
+    /// The query only matched when using the full path.
+    FullPath,
+    /// The query matched the final component of the path.
+    Basename,
+}
+
+/// Result of matching a query against a file path.
+///
+/// Results are ordered by stage first, so any match against the basename is greater than
+/// every match that needed the full path, and then by score within the same stage.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PathMatch {
+    /// The part of the path that the query matched.
+    pub stage: PathMatchStage,
+    /// Score of the match against that part of the path.
+    pub score: usize,
+}
+
+/// Returns the final component of a path. Both slashes and backslashes are treated as path
+/// separators.
+fn basename(path: &str) -> &str {
+    match path.rfind(['/', '\\']) {
+        Some(idx) => &path[idx + 1..],
+        None => path,
+    }
+}
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Fuzzy match a file path against a query string. The query is first matched against
+    /// the final component of the path, and only if that fails is it matched against the full
+    /// path. Returns `None` if the query does not match the full path either.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use code_fuzzy_match::{FuzzyMatcher, PathMatchStage};
+    ///
+    /// let mut matcher = FuzzyMatcher::new();
+    /// let result = matcher.fuzzy_match_path("src/matrix/tolerant.rs", "tol").unwrap();
+    /// assert_eq!(result.stage, PathMatchStage::Basename);
+    ///
+    /// let result = matcher.fuzzy_match_path("src/matrix/tolerant.rs", "mat/tol").unwrap();
+    /// assert_eq!(result.stage, PathMatchStage::FullPath);
+    ///
+    /// // Basename matches rank above full path matches regardless of score
+    /// assert!(
+    ///     matcher.fuzzy_match_path("src/matrix/tolerant.rs", "rs")
+    ///         > matcher.fuzzy_match_path("src/matrix/tolerant.rs", "src/matrix")
+    /// );
+    /// ```
+    pub fn fuzzy_match_path(&mut self, path: &str, query: &str) -> Option<PathMatch> {
+        let name = basename(path);
+        if name.len() < path.len() {
+            if let Some(score) = self.fuzzy_match(name, query) {
+                return Some(PathMatch {
+                    stage: PathMatchStage::Basename,
+                    score,
+                });
+            }
+        }
+        let score = self.fuzzy_match(path, query)?;
+        Some(PathMatch {
+            stage: if name.len() == path.len() {
+                PathMatchStage::Basename
+            } else {
+                PathMatchStage::FullPath
+            },
+            score,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FuzzyMatcher, PathMatchStage};
+
+    #[test]
+    fn test_path_stages() {
+        let mut matcher = FuzzyMatcher::new();
+
+        let result = matcher
+            .fuzzy_match_path("c:\\src\\main.rs", "main")
+            .unwrap();
+        assert_eq!(result.stage, PathMatchStage::Basename);
+        assert_eq!(Some(result.score), matcher.fuzzy_match("main.rs", "main"));
+
+        // Paths without separators are entirely a basename
+        let result = matcher.fuzzy_match_path("README.md", "read").unwrap();
+        assert_eq!(result.stage, PathMatchStage::Basename);
+
+        let result = matcher.fuzzy_match_path("src/main.rs", "srcmain").unwrap();
+        assert_eq!(result.stage, PathMatchStage::FullPath);
+        assert!(matcher.fuzzy_match_path("src/main.rs", "lib").is_none());
+    }
+}
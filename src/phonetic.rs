@@ -0,0 +1,370 @@
+//! Phonetic matching, for names of people and products whose spelling varies.
+//!
+//! Fuzzy matching only finds targets that contain every character of the query in order,
+//! so it can't match `"steven"` to `"Stephen"`. Phonetic algorithms reduce words to codes
+//! that are the same for words that sound alike, so names can be matched by how they sound
+//! rather than how they are spelled. Both algorithms are designed for English names, and
+//! only consider the ASCII letters of each word.
+//!
+//! Like [`FuzzyMatcher`](crate::FuzzyMatcher), a [`PhoneticMatcher`] keeps its memory
+//! between calls, so matching many targets doesn't allocate after the first few.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Algorithm used to reduce words to phonetic codes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PhoneticAlgorithm {
+    /// American Soundex, which keeps the first letter and encodes up to three of the
+    /// following consonants as digits. Soundex is simple and forgiving, but often gives
+    /// the same code to names that sound different.
+    Soundex,
+    /// The original Metaphone algorithm, which applies English pronunciation rules to the
+    /// whole word. Metaphone is more precise than Soundex.
+    #[default]
+    Metaphone,
+}
+
+impl PhoneticAlgorithm {
+    /// Returns the phonetic code of a word using this algorithm.
+    pub fn encode(&self, word: &str) -> String {
+        match self {
+            PhoneticAlgorithm::Soundex => soundex(word),
+            PhoneticAlgorithm::Metaphone => metaphone(word),
+        }
+    }
+}
+
+/// Returns the uppercase ASCII letters of a word.
+fn letters(word: &str) -> Vec<u8> {
+    word.bytes()
+        .filter(u8::is_ascii_alphabetic)
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Returns the American Soundex code of a word: its first letter followed by three
+/// digits, or an empty string if the word has no ASCII letters.
+///
+/// # Examples
+///
+/// ```
+/// use code_fuzzy_match::phonetic::soundex;
+///
+/// assert_eq!(soundex("Robert"), "R163");
+/// assert_eq!(soundex("Rupert"), "R163");
+/// assert_eq!(soundex("Lee"), "L000");
+/// ```
+pub fn soundex(word: &str) -> String {
+    fn digit(c: u8) -> Option<u8> {
+        match c {
+            b'B' | b'F' | b'P' | b'V' => Some(b'1'),
+            b'C' | b'G' | b'J' | b'K' | b'Q' | b'S' | b'X' | b'Z' => Some(b'2'),
+            b'D' | b'T' => Some(b'3'),
+            b'L' => Some(b'4'),
+            b'M' | b'N' => Some(b'5'),
+            b'R' => Some(b'6'),
+            _ => None,
+        }
+    }
+
+    let letters = letters(word);
+    let Some(&first) = letters.first() else {
+        return String::new();
+    };
+    let mut code = String::with_capacity(4);
+    code.push(first as char);
+    let mut last = digit(first);
+    for &c in &letters[1..] {
+        if code.len() == 4 {
+            break;
+        }
+        match digit(c) {
+            Some(d) if Some(d) != last => {
+                code.push(d as char);
+                last = Some(d);
+            }
+            Some(_) => (),
+            // H and W don't separate consonants with the same code, but vowels do
+            None if c == b'H' || c == b'W' => (),
+            None => last = None,
+        }
+    }
+    while code.len() < 4 {
+        code.push('0');
+    }
+    code
+}
+
+/// Returns the Metaphone code of a word, or an empty string if the word has no ASCII
+/// letters. The code uses `0` for the "th" sound and `X` for the "sh" sound.
+///
+/// # Examples
+///
+/// ```
+/// use code_fuzzy_match::phonetic::metaphone;
+///
+/// assert_eq!(metaphone("Stephen"), "STFN");
+/// assert_eq!(metaphone("Steven"), "STFN");
+/// assert_eq!(metaphone("Knight"), "NT");
+/// ```
+pub fn metaphone(word: &str) -> String {
+    let letters = letters(word);
+    let at = |idx: usize| letters.get(idx).copied().unwrap_or(0);
+    let is_vowel = |c: u8| matches!(c, b'A' | b'E' | b'I' | b'O' | b'U');
+    let is_front_vowel = |c: u8| matches!(c, b'E' | b'I' | b'Y');
+
+    let mut code = String::new();
+    let mut start = 0;
+    match (at(0), at(1)) {
+        (b'A', b'E') | (b'G' | b'K' | b'P', b'N') | (b'W', b'R') => start = 1,
+        (b'X', _) => {
+            code.push('S');
+            start = 1;
+        }
+        (b'W', b'H') => {
+            code.push('W');
+            start = 2;
+        }
+        _ => (),
+    }
+
+    for idx in start..letters.len() {
+        let (prev, c, next) = (idx.checked_sub(1).map_or(0, &at), at(idx), at(idx + 1));
+        // Doubled letters are only encoded once, except for C
+        if c == prev && c != b'C' {
+            continue;
+        }
+        match c {
+            b'A' | b'E' | b'I' | b'O' | b'U' => {
+                // Vowels are only encoded at the start of the code
+                if code.is_empty() {
+                    code.push(c as char);
+                }
+            }
+            b'B' => {
+                if !(prev == b'M' && next == 0) {
+                    code.push('B');
+                }
+            }
+            b'C' => {
+                if next == b'I' && at(idx + 2) == b'A' {
+                    code.push('X');
+                } else if next == b'H' {
+                    code.push(if prev == b'S' { 'K' } else { 'X' });
+                } else if is_front_vowel(next) {
+                    if prev != b'S' {
+                        code.push('S');
+                    }
+                } else {
+                    code.push('K');
+                }
+            }
+            b'D' => {
+                if next == b'G' && is_front_vowel(at(idx + 2)) {
+                    code.push('J');
+                } else {
+                    code.push('T');
+                }
+            }
+            b'G' => {
+                let silent = (next == b'H' && at(idx + 2) != 0 && !is_vowel(at(idx + 2)))
+                    || (next == b'N' && (at(idx + 2) == 0 || &letters[idx + 2..] == b"ED"))
+                    || (prev == b'D' && is_front_vowel(next));
+                if !silent {
+                    code.push(if is_front_vowel(next) { 'J' } else { 'K' });
+                }
+            }
+            b'H' => {
+                // Silent after consonants it changes the sound of, and between a vowel and
+                // a consonant
+                let after_consonant = matches!(prev, b'C' | b'G' | b'P' | b'S' | b'T');
+                if !after_consonant && (!is_vowel(prev) || is_vowel(next)) {
+                    code.push('H');
+                }
+            }
+            b'K' => {
+                if prev != b'C' {
+                    code.push('K');
+                }
+            }
+            b'P' => code.push(if next == b'H' { 'F' } else { 'P' }),
+            b'Q' => code.push('K'),
+            b'S' => {
+                if next == b'H' || (next == b'I' && matches!(at(idx + 2), b'O' | b'A')) {
+                    code.push('X');
+                } else {
+                    code.push('S');
+                }
+            }
+            b'T' => {
+                if next == b'I' && matches!(at(idx + 2), b'O' | b'A') {
+                    code.push('X');
+                } else if next == b'H' {
+                    code.push('0');
+                } else if !(next == b'C' && at(idx + 2) == b'H') {
+                    code.push('T');
+                }
+            }
+            b'V' => code.push('F'),
+            b'W' | b'Y' => {
+                if is_vowel(next) {
+                    code.push(c as char);
+                }
+            }
+            b'X' => code.push_str("KS"),
+            b'Z' => code.push('S'),
+            _ => code.push(c as char),
+        }
+    }
+    code
+}
+
+/// Matches targets against queries by how their words sound, reusing memory between
+/// calls.
+///
+/// # Examples
+///
+/// ```
+/// use code_fuzzy_match::phonetic::{PhoneticAlgorithm, PhoneticMatcher};
+///
+/// let mut matcher = PhoneticMatcher::new(PhoneticAlgorithm::Metaphone);
+/// assert!(matcher.phonetic_match("Stephen King", "steven king").is_some());
+/// assert!(matcher.phonetic_match("Stephen King", "steve king").is_none());
+/// assert!(
+///     matcher.phonetic_match("Stephen King", "stephen").unwrap()
+///         > matcher.phonetic_match("Stephen King", "steven").unwrap()
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PhoneticMatcher {
+    algorithm: PhoneticAlgorithm,
+    target_codes: Vec<String>,
+}
+
+impl PhoneticMatcher {
+    /// Creates a phonetic matcher using the given algorithm.
+    pub fn new(algorithm: PhoneticAlgorithm) -> Self {
+        PhoneticMatcher {
+            algorithm,
+            target_codes: Vec::new(),
+        }
+    }
+
+    /// Returns the algorithm used by this matcher.
+    pub fn algorithm(&self) -> PhoneticAlgorithm {
+        self.algorithm
+    }
+
+    /// Matches a target against a query by the sound of their words. Words are runs of
+    /// letters and digits. Each word of the query must sound like a word of the target,
+    /// with the words in the same order, though other words of the target may appear
+    /// between them. Words without any ASCII letters, such as numbers, must be equal.
+    ///
+    /// Returns `None` if the query doesn't match the target. Otherwise the score is the
+    /// number of characters in the words of the query, with the words that are also spelled
+    /// the same in the target, ignoring case, counted twice. Scores are not comparable with
+    /// those of [`FuzzyMatcher`](crate::FuzzyMatcher).
+    pub fn phonetic_match(&mut self, target: &str, query: &str) -> Option<usize> {
+        let target_words: Vec<&str> = words(target).collect();
+        self.target_codes.clear();
+        for word in &target_words {
+            self.target_codes.push(self.algorithm.encode(word));
+        }
+
+        let mut score = 0;
+        let mut next_target = 0;
+        let mut query_words = words(query).peekable();
+        query_words.peek()?;
+        for query_word in query_words {
+            let query_code = self.algorithm.encode(query_word);
+            let found = (next_target..target_words.len()).find(|idx| {
+                if query_code.is_empty() {
+                    target_words[*idx].eq_ignore_ascii_case(query_word)
+                } else {
+                    self.target_codes[*idx] == query_code
+                }
+            })?;
+            let len = query_word.chars().count();
+            score += if target_words[found].to_lowercase() == query_word.to_lowercase() {
+                len * 2
+            } else {
+                len
+            };
+            next_target = found + 1;
+        }
+        Some(score)
+    }
+
+    /// Releases the memory held for matching.
+    pub fn shrink_to_fit(&mut self) {
+        self.target_codes = Vec::new();
+    }
+}
+
+/// Returns the words of a string, which are runs of letters and digits.
+fn words(s: &str) -> impl Iterator<Item = &str> {
+    s.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{metaphone, soundex, PhoneticAlgorithm, PhoneticMatcher};
+
+    #[test]
+    fn test_phonetic() {
+        for (word, expected) in [
+            ("Robert", "R163"),
+            ("Rupert", "R163"),
+            ("Ashcraft", "A261"),
+            ("Tymczak", "T522"),
+            ("Pfister", "P236"),
+            ("Honeyman", "H555"),
+            ("Lee", "L000"),
+            ("123", ""),
+        ] {
+            assert_eq!(soundex(word), expected, "{word}");
+        }
+
+        for (word, expected) in [
+            ("Stephen", "STFN"),
+            ("Steven", "STFN"),
+            ("Knight", "NT"),
+            ("Night", "NT"),
+            ("Thomas", "0MS"),
+            ("Schmidt", "SKMTT"),
+            ("Xavier", "SFR"),
+            ("Whitney", "WTN"),
+            ("Caitlin", "KTLN"),
+            ("Katelyn", "KTLN"),
+            ("Judge", "JJ"),
+            ("Philip", "FLP"),
+            ("Phillip", "FLP"),
+            ("Gnome", "NM"),
+            ("Aerial", "ERL"),
+            ("Catherine", "K0RN"),
+            ("Kathryn", "K0RN"),
+            ("", ""),
+        ] {
+            assert_eq!(metaphone(word), expected, "{word}");
+        }
+
+        let mut matcher = PhoneticMatcher::new(PhoneticAlgorithm::Soundex);
+        assert_eq!(matcher.algorithm(), PhoneticAlgorithm::Soundex);
+        assert_eq!(
+            matcher.phonetic_match("Robert Smith", "rupert smyth"),
+            Some(11)
+        );
+        assert_eq!(matcher.phonetic_match("Robert Smith", "smith"), Some(10));
+        assert_eq!(matcher.phonetic_match("Robert Smith", "smith robert"), None);
+        assert_eq!(matcher.phonetic_match("Robert Smith 2", "robert 3"), None);
+        assert_eq!(
+            matcher.phonetic_match("Robert Smith 2", "robert 2"),
+            Some(14)
+        );
+        assert_eq!(matcher.phonetic_match("Robert Smith", ""), None);
+        matcher.shrink_to_fit();
+    }
+}
@@ -0,0 +1,366 @@
+//! Pinyin matching, for finding Chinese candidates with a Latin query.
+//!
+//! Typing Chinese requires an input method, so users of pickers often search for Chinese
+//! names by their pinyin readings instead, such as `"wenjian"`, or just the initials `"wj"`,
+//! for `"文件"`. A [`PinyinMatcher`] matches each character of the target that has readings
+//! in a [`PinyinTable`] by a prefix of one of its readings, and every other character by
+//! itself. Characters with several readings, such as `"行"` (`xing` and `hang`), match using
+//! any of them.
+//!
+//! The crate does not include pinyin data. Tables are built by the application, usually from
+//! a data set such as the `kMandarin` field of the Unicode Han Database.
+
+use crate::matrix::{is_word_start, CharClass};
+use crate::Match;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Readings of characters, used by a [`PinyinMatcher`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PinyinTable {
+    readings: BTreeMap<char, Vec<String>>,
+}
+
+impl PinyinTable {
+    /// Creates an empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses a table from lines that each hold a character followed by its readings,
+    /// separated by whitespace. Empty lines and lines starting with `#` are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use code_fuzzy_match::pinyin::PinyinTable;
+    ///
+    /// let table = PinyinTable::parse("# Readings\n文 wén\n行 xíng háng\n");
+    /// assert_eq!(table.readings('文'), ["wen"]);
+    /// assert_eq!(table.readings('行'), ["xing", "hang"]);
+    /// ```
+    pub fn parse(text: &str) -> Self {
+        let mut table = Self::new();
+        for line in text.lines() {
+            let mut fields = line.split_whitespace();
+            let Some(c) = fields.next().filter(|c| !c.starts_with('#')) else {
+                continue;
+            };
+            let mut chars = c.chars();
+            if let (Some(c), None) = (chars.next(), chars.next()) {
+                for reading in fields {
+                    table.insert(c, reading);
+                }
+            }
+        }
+        table
+    }
+
+    /// Adds a reading of a character. Tone marks and tone numbers are removed, and `ü` is
+    /// written as `v`, as with most input methods. Readings that the character already has
+    /// are ignored.
+    pub fn insert(&mut self, c: char, reading: &str) {
+        let reading = plain(reading);
+        if reading.is_empty() {
+            return;
+        }
+        let readings = self.readings.entry(c).or_default();
+        if !readings.contains(&reading) {
+            readings.push(reading);
+        }
+    }
+
+    /// Returns the readings of a character, in the order they were added.
+    pub fn readings(&self, c: char) -> &[String] {
+        self.readings.get(&c).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the number of characters with readings.
+    pub fn len(&self) -> usize {
+        self.readings.len()
+    }
+
+    /// Returns true if no character has readings.
+    pub fn is_empty(&self) -> bool {
+        self.readings.is_empty()
+    }
+}
+
+/// Returns a reading in lowercase ASCII letters, without tones.
+fn plain(reading: &str) -> String {
+    reading
+        .chars()
+        .flat_map(char::to_lowercase)
+        .filter_map(|c| {
+            Some(match c {
+                'a'..='z' => c,
+                'ā' | 'á' | 'ǎ' | 'à' => 'a',
+                'ē' | 'é' | 'ě' | 'è' => 'e',
+                'ī' | 'í' | 'ǐ' | 'ì' => 'i',
+                'ō' | 'ó' | 'ǒ' | 'ò' => 'o',
+                'ū' | 'ú' | 'ǔ' | 'ù' => 'u',
+                'ü' | 'ǖ' | 'ǘ' | 'ǚ' | 'ǜ' => 'v',
+                'ń' | 'ň' | 'ǹ' => 'n',
+                'ḿ' => 'm',
+                _ => return None,
+            })
+        })
+        .collect()
+}
+
+/// Returns true if a query character matches a target character, ignoring case.
+fn chars_match(query_char: char, target_char: char) -> bool {
+    query_char == target_char || query_char.to_lowercase().eq(target_char.to_lowercase())
+}
+
+/// Matches targets containing Chinese characters against Latin queries using their pinyin
+/// readings. Like [`FuzzyMatcher`](crate::FuzzyMatcher), a matcher keeps its memory between
+/// calls, so matching many targets doesn't allocate after the first few.
+///
+/// # Examples
+///
+/// ```
+/// use code_fuzzy_match::pinyin::{PinyinMatcher, PinyinTable};
+///
+/// let table = PinyinTable::parse("文 wén\n件 jiàn\n夹 jiā\n");
+/// let mut matcher = PinyinMatcher::new(table);
+/// let result = matcher.pinyin_match_details("src/文件夹", "wenjj").unwrap();
+/// assert_eq!(result.positions, [4, 5, 6]);
+/// assert!(matcher.pinyin_match("src/文件夹", "srcwj").is_some());
+/// assert!(matcher.pinyin_match("src/文件夹", "wenxian").is_none());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PinyinMatcher {
+    table: PinyinTable,
+    target_chars: Vec<char>,
+    query_chars: Vec<char>,
+    /// Best score after each target character for each number of matched query
+    /// characters, or `None` if the query characters can't be matched.
+    any: Vec<Option<usize>>,
+    /// Best score after each target character for each number of matched query
+    /// characters, for matches where the target character is matched.
+    ended: Vec<Option<usize>>,
+    /// For each cell of `ended`, the number of query characters matched before the target
+    /// character, and whether the previous target character is matched.
+    from: Vec<(usize, bool)>,
+}
+
+impl PinyinMatcher {
+    /// Creates a matcher using the readings of the given table.
+    pub fn new(table: PinyinTable) -> Self {
+        PinyinMatcher {
+            table,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the table of readings used by this matcher.
+    pub fn table(&self) -> &PinyinTable {
+        &self.table
+    }
+
+    /// Matches a target against a query, with each character of the target that has
+    /// readings matching a prefix of one of its readings, and every other character matching
+    /// itself, ignoring case. Every character of the query must be matched, in order, and
+    /// characters of the target may be skipped.
+    ///
+    /// Returns `None` if the query doesn't match the target. Otherwise the score is the
+    /// number of characters in the query, with the characters of whole readings and of
+    /// characters matching themselves counted twice, plus one for each matched character of
+    /// the target that follows another matched character. Scores are not comparable with
+    /// those of [`FuzzyMatcher`](crate::FuzzyMatcher).
+    pub fn pinyin_match(&mut self, target: &str, query: &str) -> Option<usize> {
+        self.compute(target, query)
+    }
+
+    /// Matches a target against a query like [`pinyin_match`](Self::pinyin_match),
+    /// returning the positions of the matched characters of the target along with the
+    /// score. Characters with readings are counted as the start of a word.
+    pub fn pinyin_match_details(&mut self, target: &str, query: &str) -> Option<Match> {
+        let score = self.compute(target, query)?;
+        let width = self.query_chars.len() + 1;
+        let mut positions = Vec::new();
+        let mut exact_case = true;
+        let mut query_idx = self.query_chars.len();
+        let mut matched = false;
+        for target_idx in (0..self.target_chars.len()).rev() {
+            if query_idx == 0 {
+                break;
+            }
+            let idx = (target_idx + 1) * width + query_idx;
+            if !matched && self.ended[idx] != self.any[idx] {
+                continue;
+            }
+            let (prev_query_idx, prev_matched) = self.from[idx];
+            let target_char = self.target_chars[target_idx];
+            if query_idx - prev_query_idx == 1 && self.table.readings(target_char).is_empty() {
+                exact_case &= self.query_chars[prev_query_idx] == target_char;
+            }
+            positions.push(target_idx);
+            query_idx = prev_query_idx;
+            matched = prev_matched;
+        }
+        positions.reverse();
+
+        let classes: Vec<CharClass> = self
+            .target_chars
+            .iter()
+            .map(|c| CharClass::of(*c))
+            .collect();
+        let word_starts_hit = positions
+            .iter()
+            .filter(|idx| {
+                is_word_start(&classes, **idx)
+                    || !self.table.readings(self.target_chars[**idx]).is_empty()
+            })
+            .count();
+        let mut longest_run = 0;
+        let mut run = 0;
+        for (i, position) in positions.iter().enumerate() {
+            run = if i > 0 && positions[i - 1] + 1 == *position {
+                run + 1
+            } else {
+                1
+            };
+            longest_run = longest_run.max(run);
+        }
+        let exact_match = self.query_chars.len() == self.target_chars.len()
+            && self
+                .query_chars
+                .iter()
+                .zip(self.target_chars.iter())
+                .all(|(q, t)| chars_match(*q, *t));
+        Some(Match {
+            score,
+            positions,
+            longest_run,
+            word_starts_hit,
+            exact_case,
+            exact_match,
+            truncated: false,
+        })
+    }
+
+    fn compute(&mut self, target: &str, query: &str) -> Option<usize> {
+        let PinyinMatcher {
+            table,
+            target_chars,
+            query_chars,
+            any,
+            ended,
+            from,
+        } = self;
+        target_chars.clear();
+        target_chars.extend(target.chars());
+        query_chars.clear();
+        query_chars.extend(query.chars());
+        if query_chars.is_empty() {
+            return None;
+        }
+
+        let width = query_chars.len() + 1;
+        let cells = (target_chars.len() + 1) * width;
+        any.clear();
+        any.resize(cells, None);
+        ended.clear();
+        ended.resize(cells, None);
+        from.clear();
+        from.resize(cells, (0, false));
+        any[0] = Some(0);
+
+        for (target_idx, target_char) in target_chars.iter().enumerate() {
+            let row = target_idx * width;
+            let next_row = row + width;
+            for query_idx in 0..query_chars.len() {
+                let Some(start) = any[row + query_idx] else {
+                    continue;
+                };
+                // Continuing a run of matched characters is worth one more point
+                let prev_matched = ended[row + query_idx] == Some(start);
+                let base = start + prev_matched as usize;
+                let mut extend = |len: usize, gain: usize| {
+                    let idx = next_row + query_idx + len;
+                    if ended[idx] < Some(base + gain) {
+                        ended[idx] = Some(base + gain);
+                        from[idx] = (query_idx, prev_matched);
+                    }
+                };
+
+                if chars_match(query_chars[query_idx], *target_char) {
+                    extend(1, 2);
+                }
+                for reading in table.readings(*target_char) {
+                    let common = reading
+                        .bytes()
+                        .zip(query_chars[query_idx..].iter())
+                        .take_while(|(r, q)| q.to_ascii_lowercase() == *r as char)
+                        .count();
+                    for len in 1..=common {
+                        extend(len, if len == reading.len() { len * 2 } else { len });
+                    }
+                }
+            }
+            for query_idx in 0..width {
+                any[next_row + query_idx] = any[row + query_idx].max(ended[next_row + query_idx]);
+            }
+        }
+        any[cells - 1]
+    }
+
+    /// Releases the memory held for matching.
+    pub fn shrink_to_fit(&mut self) {
+        self.target_chars = Vec::new();
+        self.query_chars = Vec::new();
+        self.any = Vec::new();
+        self.ended = Vec::new();
+        self.from = Vec::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PinyinMatcher, PinyinTable};
+
+    #[test]
+    fn test_pinyin() {
+        let mut table = PinyinTable::parse("文 wén\n件 jiàn jian\n行 xíng háng\n\n# 银 yin\n");
+        table.insert('银', "yin2");
+        table.insert('绿', "LǛ");
+        table.insert('绿', "");
+        assert_eq!(table.len(), 5);
+        assert_eq!(table.readings('件'), ["jian"]);
+        assert_eq!(table.readings('绿'), ["lv"]);
+        assert!(table.readings('a').is_empty());
+
+        let mut matcher = PinyinMatcher::new(table);
+        assert_eq!(matcher.pinyin_match("文件", "wenjian"), Some(15));
+        assert_eq!(matcher.pinyin_match("文件", "wj"), Some(3));
+        assert_eq!(matcher.pinyin_match("文件", "wenjia"), Some(10));
+        assert_eq!(matcher.pinyin_match("文件", "wenjianx"), None);
+        assert_eq!(matcher.pinyin_match("文件", "jianwen"), None);
+        assert_eq!(matcher.pinyin_match("文件", ""), None);
+
+        // Any reading of a character can be used
+        assert_eq!(matcher.pinyin_match("银行", "yinhang"), Some(15));
+        assert_eq!(matcher.pinyin_match("银行", "yinxing"), Some(15));
+        assert_eq!(matcher.pinyin_match("银行", "yh"), Some(3));
+
+        let result = matcher
+            .pinyin_match_details("Src/文件.rs", "srcwj")
+            .unwrap();
+        assert_eq!(result.positions, [0, 1, 2, 4, 5]);
+        assert_eq!(result.score, 11);
+        assert_eq!(result.longest_run, 3);
+        assert_eq!(result.word_starts_hit, 3);
+        assert!(!result.exact_case);
+        assert!(!result.exact_match);
+
+        let result = matcher.pinyin_match_details("文件", "文件").unwrap();
+        assert_eq!(result.positions, [0, 1]);
+        assert!(result.exact_case && result.exact_match);
+        matcher.shrink_to_fit();
+        assert_eq!(matcher.pinyin_match("绿文件", "lvwj"), Some(8));
+    }
+}
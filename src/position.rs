@@ -0,0 +1,149 @@
+//! Conversion of matched positions to lines and columns, as used by the Language Server
+//! Protocol.
+
+use crate::Match;
+use alloc::vec::Vec;
+
+/// Unit in which the columns of a [`LinePosition`] are counted. These are the position
+/// encodings of the Language Server Protocol.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PositionEncoding {
+    /// Columns are counted in bytes of UTF-8.
+    Utf8,
+    /// Columns are counted in UTF-16 code units. This is the default encoding of the
+    /// Language Server Protocol.
+    #[default]
+    Utf16,
+    /// Columns are counted in characters.
+    Utf32,
+}
+
+/// A zero based line and column in a target string, which can be used as a `Position` in
+/// Language Server Protocol responses.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinePosition {
+    /// Zero based line number.
+    pub line: usize,
+    /// Zero based column within the line, counted in the units of the [`PositionEncoding`].
+    pub character: usize,
+}
+
+impl Match {
+    /// Returns the line and column of each matched position in `target`, which must be the
+    /// string that was matched. Lines end with `\n`, `\r\n` or `\r`, and columns are counted
+    /// in the units given by `encoding`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use code_fuzzy_match::{LinePosition, PositionEncoding};
+    ///
+    /// let mut matcher = code_fuzzy_match::FuzzyMatcher::new();
+    /// let target = "// 😀\r\nfn main() {}";
+    /// let result = matcher.fuzzy_match_details(target, "main").unwrap();
+    /// let positions = result.line_positions(target, PositionEncoding::Utf16);
+    /// assert_eq!(positions[0], LinePosition { line: 1, character: 3 });
+    /// ```
+    pub fn line_positions(&self, target: &str, encoding: PositionEncoding) -> Vec<LinePosition> {
+        let mut result = Vec::with_capacity(self.positions.len());
+        let mut positions = self.positions.iter().peekable();
+        let mut current = LinePosition::default();
+        let mut chars = target.chars().enumerate().peekable();
+        while let Some((char_idx, c)) = chars.next() {
+            if positions.peek().is_none() {
+                break;
+            }
+            if positions.next_if_eq(&&char_idx).is_some() {
+                result.push(current);
+            }
+            match c {
+                '\n' => (),
+                '\r' if chars.next_if(|(_, next)| *next == '\n').is_none() => (),
+                '\r' => {
+                    // The line ending is the line feed after the carriage return
+                    if positions.next_if_eq(&&(char_idx + 1)).is_some() {
+                        result.push(current);
+                    }
+                }
+                _ => {
+                    current.character += match encoding {
+                        PositionEncoding::Utf8 => c.len_utf8(),
+                        PositionEncoding::Utf16 => c.len_utf16(),
+                        PositionEncoding::Utf32 => 1,
+                    };
+                    continue;
+                }
+            }
+            current.line += 1;
+            current.character = 0;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FuzzyMatcher, LinePosition, Match, PositionEncoding};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_line_positions() {
+        let target = "use 𝔸;\r\nfn æ()\rlet x\n\nend";
+        let result = Match {
+            score: 0,
+            positions: vec![5, 7, 8, 11, 15, 19, 24],
+            longest_run: 1,
+            word_starts_hit: 0,
+            exact_case: true,
+            exact_match: false,
+            truncated: false,
+        };
+        // Only the columns on the first line depend on the encoding
+        for (encoding, columns) in [
+            (PositionEncoding::Utf8, [8, 9]),
+            (PositionEncoding::Utf16, [6, 7]),
+            (PositionEncoding::Utf32, [5, 6]),
+        ] {
+            let positions: Vec<(usize, usize)> = result
+                .line_positions(target, encoding)
+                .iter()
+                .map(|position| (position.line, position.character))
+                .collect();
+            assert_eq!(
+                positions,
+                [
+                    (0, columns[0]),
+                    (0, columns[1]),
+                    (1, 0),
+                    (1, 3),
+                    (2, 0),
+                    (2, 4),
+                    (4, 2)
+                ]
+            );
+        }
+
+        let mut matcher = FuzzyMatcher::new();
+        let result = matcher.fuzzy_match_details(target, "end").unwrap();
+        assert_eq!(
+            result.line_positions(target, PositionEncoding::default()),
+            [
+                LinePosition {
+                    line: 4,
+                    character: 0
+                },
+                LinePosition {
+                    line: 4,
+                    character: 1
+                },
+                LinePosition {
+                    line: 4,
+                    character: 2
+                }
+            ]
+        );
+    }
+}
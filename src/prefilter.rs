@@ -0,0 +1,256 @@
+//! Quick rejection of targets that do not contain the characters of a query.
+
+use crate::{synonyms, whitespace, FuzzyMatcher, Score, ScoreModel};
+use alloc::vec::Vec;
+
+/// Returns the key of a character for grouping characters that may match. Characters that
+/// can match each other, other than through `equivalences`, always have the same key.
+/// Slashes are folded together regardless of the `slash_equivalence` option.
+pub(crate) fn match_key(c: char) -> char {
+    if c == '\\' {
+        '/'
+    } else if c.is_ascii() {
+        c.to_ascii_lowercase()
+    } else {
+        c.to_lowercase().next().unwrap_or(c)
+    }
+}
+
+/// Set of the characters present in a target string, used to quickly reject targets that
+/// cannot match a query before running the full matcher. Characters are case folded, and
+/// ASCII characters other than letters and digits share bits, as do non-ASCII characters,
+/// so a mask may report characters that are not in the target but never misses one.
+///
+/// Masks are cheap to store, so they can be computed once for each candidate in a large
+/// list and checked with [`QueryMask::may_match`] for every query.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CharMask {
+    pub(crate) ascii: u64,
+    pub(crate) non_ascii: u64,
+}
+
+impl CharMask {
+    /// Computes the mask of the characters in a target string.
+    pub fn new(target: &str) -> Self {
+        target.chars().fold(CharMask::default(), |mask, c| {
+            mask.union(CharMask::of_char(c))
+        })
+    }
+
+    fn of_char(c: char) -> Self {
+        let key = match_key(c);
+        if key.is_ascii() {
+            let bit = match key {
+                'a'..='z' => key as u32 - 'a' as u32,
+                '0'..='9' => 26 + key as u32 - '0' as u32,
+                _ => 36 + key as u32 % 28,
+            };
+            CharMask {
+                ascii: 1 << bit,
+                non_ascii: 0,
+            }
+        } else {
+            CharMask {
+                ascii: 0,
+                non_ascii: 1 << (key as u32 % 64),
+            }
+        }
+    }
+
+    fn union(self, other: CharMask) -> CharMask {
+        CharMask {
+            ascii: self.ascii | other.ascii,
+            non_ascii: self.non_ascii | other.non_ascii,
+        }
+    }
+
+    fn contains(&self, other: &CharMask) -> bool {
+        other.ascii & !self.ascii == 0 && other.non_ascii & !self.non_ascii == 0
+    }
+}
+
+/// Characters required by a query, created by [`FuzzyMatcher::query_mask`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryMask {
+    required: CharMask,
+    chars: Vec<CharMask>,
+    max_missing: usize,
+}
+
+impl QueryMask {
+    /// Returns false if a target with the given mask cannot match the query. Targets for
+    /// which this returns true may still fail to match.
+    pub fn may_match(&self, target: &CharMask) -> bool {
+        if self.max_missing == 0 {
+            return target.contains(&self.required);
+        }
+        // With typos allowed, some query characters may be missing from the target
+        self.chars
+            .iter()
+            .filter(|mask| !target.contains(mask))
+            .count()
+            <= self.max_missing
+    }
+}
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Returns the characters that a target must contain to match the query with the
+    /// options of this matcher. Use this along with a [`CharMask`] of each candidate to
+    /// reject most candidates in a large list without running the full matcher.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use code_fuzzy_match::{CharMask, FuzzyMatcher};
+    ///
+    /// let candidates = ["src/lib.rs", "src/main.rs", "Cargo.toml"];
+    /// let masks: Vec<CharMask> = candidates.iter().map(|target| CharMask::new(target)).collect();
+    ///
+    /// let mut matcher = FuzzyMatcher::new();
+    /// let query_mask = matcher.query_mask("main");
+    /// let matches: Vec<&str> = candidates
+    ///     .iter()
+    ///     .zip(masks.iter())
+    ///     .filter(|(_, mask)| query_mask.may_match(mask))
+    ///     .filter(|(target, _)| matcher.fuzzy_match(target, "main").is_some())
+    ///     .map(|(target, _)| *target)
+    ///     .collect();
+    /// assert_eq!(matches, ["src/main.rs"]);
+    /// ```
+    pub fn query_mask(&self, query: &str) -> QueryMask {
+        // Characters may fold to characters that are not in the query, so nothing is
+        // required when characters are expanded.
+        #[cfg(feature = "case-folding")]
+        if self.options.full_case_folding {
+            return QueryMask {
+                required: CharMask::default(),
+                chars: Vec::new(),
+                max_missing: 0,
+            };
+        }
+
+        // A synonym of a word of the query may hold other characters
+        if !self.options.synonyms.is_empty() {
+            let query_chars: Vec<char> = whitespace::query_chars(query, &self.options).collect();
+            if !synonyms::expand(&query_chars, &self.options.synonyms).is_empty() {
+                return QueryMask {
+                    required: CharMask::default(),
+                    chars: Vec::new(),
+                    max_missing: 0,
+                };
+            }
+        }
+
+        // Characters in a group of equivalences can match other characters, so they are
+        // not required to be present. The same goes for characters with other widths or
+        // kana forms when those are folded, for characters with other case rules in the
+        // case locale, and for spaces that match separators.
+        let chars: Vec<CharMask> = whitespace::query_chars(query, &self.options)
+            .filter(|c| {
+                !(self
+                    .options
+                    .equivalences
+                    .iter()
+                    .any(|group| group.contains(*c))
+                    || (self.options.kana_folding && crate::kana::has_variants(*c))
+                    || self.options.case_locale.extra_key(*c).is_some()
+                    || (self.options.space_matches_separators && *c == ' '))
+            })
+            .map(CharMask::of_char)
+            .collect();
+        QueryMask {
+            required: chars
+                .iter()
+                .fold(CharMask::default(), |mask, c| mask.union(*c)),
+            chars,
+            max_missing: self.options.max_typos,
+        }
+    }
+
+    /// Fuzzy match a string against a query string, first rejecting targets that do not
+    /// contain the characters in `query_mask`.
+    pub(crate) fn fuzzy_match_prefiltered<S: Score>(
+        &mut self,
+        target: &str,
+        query: &str,
+        query_mask: &QueryMask,
+    ) -> Option<S> {
+        if !self.prefilter(query_mask, &CharMask::new(target)) {
+            return None;
+        }
+        self.fuzzy_match_as(target, query)
+    }
+
+    /// Returns false if a target with the characters in `target_mask` can't match the query
+    /// of `query_mask`, counting the candidate when the `stats` feature is enabled.
+    pub(crate) fn prefilter(&mut self, query_mask: &QueryMask, target_mask: &CharMask) -> bool {
+        let may_match = query_mask.may_match(target_mask);
+        #[cfg(feature = "stats")]
+        {
+            self.stats.candidates += 1;
+            if !may_match {
+                self.stats.prefiltered += 1;
+            }
+        }
+        may_match
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CharMask, FuzzyMatcher, MatchOptions};
+    use alloc::string::String;
+    use alloc::vec;
+
+    #[test]
+    fn test_prefilter() {
+        const TARGETS: &[&str] = &[
+            "src/lib.rs",
+            "C:\\Windows\\notepad.exe",
+            "The quick brown fox",
+            "Grüße aus Köln",
+            "μετά",
+        ];
+        const QUERIES: &[&str] = &[
+            "lib", "LIB", "/win", "xyz", "quikc", "GRÜSSE", "KÖLN", "ΜΕΤΆ", "teh", "qick",
+        ];
+
+        let options = [
+            MatchOptions::default(),
+            MatchOptions {
+                max_typos: 1,
+                transpositions: true,
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                equivalences: vec![String::from("xq")],
+                ..MatchOptions::default()
+            },
+        ];
+        for options in options {
+            let mut matcher = FuzzyMatcher::with_options(options);
+            for query in QUERIES {
+                let query_mask = matcher.query_mask(query);
+                for target in TARGETS {
+                    // The prefilter never rejects a target that matches
+                    if matcher.fuzzy_match(target, query).is_some() {
+                        assert!(query_mask.may_match(&CharMask::new(target)));
+                    }
+                }
+            }
+        }
+
+        let mut matcher = FuzzyMatcher::new();
+        let mask = CharMask::new("src/lib.rs");
+        assert!(matcher.query_mask("SLR").may_match(&mask));
+        assert!(!matcher.query_mask("xyz").may_match(&mask));
+        assert!(!matcher.query_mask("lib.rsx").may_match(&mask));
+
+        matcher.set_options(MatchOptions {
+            max_typos: 1,
+            ..MatchOptions::default()
+        });
+        assert!(matcher.query_mask("lib.rsx").may_match(&mask));
+        assert!(!matcher.query_mask("lib.rsxz").may_match(&mask));
+    }
+}
@@ -0,0 +1,118 @@
+//! Python bindings, generated with PyO3.
+//!
+//! Build the extension module with `maturin build --features pyo3,pyo3/extension-module`,
+//! which produces a Python module named `code_fuzzy_match`. Match positions are character
+//! indices, so they can be used to index Python strings directly.
+
+use crate::{FuzzyMatcher, TieBreak};
+use alloc::string::String;
+use alloc::vec::Vec;
+use pyo3::prelude::*;
+
+/// Fuzzy match a string against a query string. Returns the score, or `None` if the query
+/// does not match the target string.
+#[pyfunction]
+fn fuzzy_match(target: &str, query: &str) -> Option<usize> {
+    crate::fuzzy_match(target, query)
+}
+
+/// Fuzzy match a string against a query string, returning the score and the index in the
+/// target of each matched character, or `None` if the query does not match the target
+/// string.
+#[pyfunction]
+fn match_positions(target: &str, query: &str) -> Option<(usize, Vec<usize>)> {
+    let result = FuzzyMatcher::new().fuzzy_match_details(target, query)?;
+    Some((result.score, result.positions))
+}
+
+/// Fuzzy match each of the targets against a query string, returning the index and score
+/// of the targets that match from best to worst, with ties broken as in the Rust API. Only
+/// the best `limit` matches are returned if a limit is given. The GIL is released while
+/// matching, so other Python threads can run during large batches.
+#[pyfunction]
+#[pyo3(signature = (targets, query, limit = None))]
+fn rank(
+    py: Python<'_>,
+    targets: Vec<String>,
+    query: &str,
+    limit: Option<usize>,
+) -> Vec<(usize, usize)> {
+    py.allow_threads(|| {
+        let ranked =
+            FuzzyMatcher::new().rank(targets.iter().map(String::as_str), query, TieBreak::DEFAULT);
+        ranked
+            .iter()
+            .take(limit.unwrap_or(usize::MAX))
+            .map(|ranked| (ranked.index, ranked.score))
+            .collect()
+    })
+}
+
+#[pymodule]
+fn code_fuzzy_match(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(fuzzy_match, module)?)?;
+    module.add_function(wrap_pyfunction!(match_positions, module)?)?;
+    module.add_function(wrap_pyfunction!(rank, module)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FuzzyMatcher, TieBreak};
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use pyo3::prelude::*;
+    use pyo3::types::IntoPyDict;
+
+    #[test]
+    fn test_python() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let module = PyModule::new_bound(py, "code_fuzzy_match").unwrap();
+            super::code_fuzzy_match(&module).unwrap();
+
+            let score: Option<usize> = module
+                .call_method1("fuzzy_match", ("src/lib.rs", "lib"))
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(score, crate::fuzzy_match("src/lib.rs", "lib"));
+            assert!(module
+                .call_method1("fuzzy_match", ("src/lib.rs", "xyz"))
+                .unwrap()
+                .is_none());
+
+            let expected = FuzzyMatcher::new()
+                .fuzzy_match_details("a😀b", "ab")
+                .unwrap();
+            let positions: (usize, Vec<usize>) = module
+                .call_method1("match_positions", ("a😀b", "ab"))
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(positions, (expected.score, Vec::from([0, 2])));
+
+            let targets: Vec<String> = (0..100).map(|idx| format!("file_{idx}.rs")).collect();
+            let expected: Vec<(usize, usize)> = FuzzyMatcher::new()
+                .rank(targets.iter().map(String::as_str), "f1", TieBreak::DEFAULT)
+                .iter()
+                .map(|ranked| (ranked.index, ranked.score))
+                .collect();
+            let ranked: Vec<(usize, usize)> = module
+                .call_method1("rank", (targets.clone(), "f1"))
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(ranked, expected);
+
+            let kwargs = [("limit", 3)].into_py_dict_bound(py);
+            let ranked: Vec<(usize, usize)> = module
+                .call_method("rank", (targets, "f1"), Some(&kwargs))
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(ranked, expected[..3]);
+        });
+    }
+}
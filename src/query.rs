@@ -0,0 +1,414 @@
+//! Search syntax with several terms, alternatives and operators, for pickers that offer the
+//! query syntax of fzf.
+
+use crate::{char_match_kind, FuzzyMatcher, ScoreModel};
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// A query parsed from the search syntax of fzf, matched with
+/// [`FuzzyMatcher::fuzzy_match_query`].
+///
+/// Terms are separated by whitespace, and a target matches when every term matches. A
+/// term holds one or more alternatives separated by `|`, and matches when any of its
+/// alternatives matches, scored by the best matching alternative. Alternatives may also be
+/// separated by a `|` surrounded by whitespace, as in `src | test`.
+///
+/// An alternative is fuzzy matched unless it starts with an operator:
+///
+/// * `'text` matches targets that contain `text`.
+/// * `^text` matches targets that start with `text`.
+/// * `!text` matches targets that don't contain `text`, and `!^text` matches targets that
+///   don't start with it.
+///
+/// A backslash escapes the next character, so that `\|` and `\ ` match a literal `|` and
+/// space, `\\` matches a backslash, and `\!`, `\'` and `\^` at the start of an alternative
+/// are matched as text. Operators without any text are ignored.
+///
+/// Queries can also be built from a [`QueryAst`], such as from the filters selected in a
+/// user interface, without writing them in this syntax.
+///
+/// # Examples
+///
+/// ```
+/// use code_fuzzy_match::{FuzzyMatcher, Query};
+///
+/// let query = Query::parse("assets png|jpg|gif !thumb");
+/// let mut matcher = FuzzyMatcher::new();
+/// assert!(matcher.fuzzy_match_query("assets/logo.png", &query).is_some());
+/// assert!(matcher.fuzzy_match_query("assets/photo.jpg", &query).is_some());
+/// assert!(matcher.fuzzy_match_query("assets/style.css", &query).is_none());
+/// assert!(matcher.fuzzy_match_query("assets/thumb.png", &query).is_none());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Query {
+    ast: QueryAst,
+}
+
+/// Structure of a [`Query`]. Text is compared as the matcher compares characters, so case
+/// is ignored and the options of the matcher apply.
+///
+/// # Examples
+///
+/// ```
+/// use code_fuzzy_match::{FuzzyMatcher, Query, QueryAst};
+///
+/// // The filters of a user interface, without escaping their text
+/// let query = Query::from(QueryAst::And(vec![
+///     QueryAst::Term("main".into()),
+///     QueryAst::Not(Box::new(QueryAst::Exact("test | bench".into()))),
+/// ]));
+/// assert_eq!(query, Query::parse("main !test\\ \\|\\ bench"));
+///
+/// let mut matcher = FuzzyMatcher::new();
+/// assert!(matcher.fuzzy_match_query("src/main.rs", &query).is_some());
+/// assert!(matcher.fuzzy_match_query("test | bench/main.rs", &query).is_none());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum QueryAst {
+    /// Matches targets that fuzzy match the text, with the score of the fuzzy match.
+    Term(String),
+    /// Matches targets that contain the text, with the score of a fuzzy match of the text.
+    Exact(String),
+    /// Matches targets that start with the text, with the score of a fuzzy match of the
+    /// text.
+    PrefixAnchor(String),
+    /// Matches targets that the inner query doesn't match, without adding to the score.
+    Not(Box<QueryAst>),
+    /// Matches targets that any of the inner queries matches, with the best score among
+    /// them. Matches nothing if empty.
+    Or(Vec<QueryAst>),
+    /// Matches targets that all of the inner queries match, with the sum of their scores.
+    /// Matches every target if empty, except as the whole query, where it is matched as an
+    /// empty query as set by the `empty_query` option.
+    And(Vec<QueryAst>),
+}
+
+impl Query {
+    /// Parses a query written in the search syntax described in [`Query`].
+    pub fn parse(query: &str) -> Self {
+        let mut terms: Vec<Vec<QueryAst>> = Vec::new();
+        let mut word = Word::default();
+        // Set after a `|`, so that the next word is an alternative of the last term
+        let mut alternative = false;
+        let mut flush = |word: &mut Word, alternative: &mut bool| {
+            let Some(node) = core::mem::take(word).into_ast() else {
+                return;
+            };
+            match terms.last_mut() {
+                Some(term) if *alternative => term.push(node),
+                _ => terms.push(alloc::vec![node]),
+            }
+            *alternative = false;
+        };
+
+        let mut chars = query.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => word.text.push(chars.next().unwrap_or('\\')),
+                '|' => {
+                    flush(&mut word, &mut alternative);
+                    alternative = true;
+                }
+                c if c.is_whitespace() => flush(&mut word, &mut alternative),
+                '!' if word.text.is_empty() && !(word.negate || word.exact || word.prefix) => {
+                    word.negate = true;
+                }
+                '\'' if word.text.is_empty() && !(word.exact || word.prefix) => word.exact = true,
+                '^' if word.text.is_empty() && !(word.exact || word.prefix) => word.prefix = true,
+                c => word.text.push(c),
+            }
+        }
+        flush(&mut word, &mut alternative);
+
+        let terms = terms
+            .into_iter()
+            .map(|mut term| {
+                if term.len() == 1 {
+                    term.remove(0)
+                } else {
+                    QueryAst::Or(term)
+                }
+            })
+            .collect();
+        Query {
+            ast: QueryAst::And(terms),
+        }
+    }
+
+    /// Returns the structure of the query.
+    pub fn ast(&self) -> &QueryAst {
+        &self.ast
+    }
+
+    /// Returns true if the query has no terms.
+    pub fn is_empty(&self) -> bool {
+        matches!(&self.ast, QueryAst::And(terms) if terms.is_empty())
+    }
+}
+
+impl Default for Query {
+    fn default() -> Self {
+        Query {
+            ast: QueryAst::And(Vec::new()),
+        }
+    }
+}
+
+impl From<QueryAst> for Query {
+    fn from(ast: QueryAst) -> Self {
+        Query { ast }
+    }
+}
+
+/// A word of a query being parsed, along with the operators before it.
+#[derive(Default)]
+struct Word {
+    text: String,
+    negate: bool,
+    exact: bool,
+    prefix: bool,
+}
+
+impl Word {
+    /// Returns the node for the word, or `None` if it has no text. Negated words are
+    /// matched exactly, as in fzf.
+    fn into_ast(self) -> Option<QueryAst> {
+        if self.text.is_empty() {
+            return None;
+        }
+        let node = if self.prefix {
+            QueryAst::PrefixAnchor(self.text)
+        } else if self.exact || self.negate {
+            QueryAst::Exact(self.text)
+        } else {
+            QueryAst::Term(self.text)
+        };
+        Some(if self.negate {
+            QueryAst::Not(Box::new(node))
+        } else {
+            node
+        })
+    }
+}
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Fuzzy match a string against a query parsed with [`Query::parse`] or built from a
+    /// [`QueryAst`]. Returns the score of the query as described by [`QueryAst`], raised to
+    /// one so that every match has a positive score, or `None` if the query does not match.
+    /// A query without terms is matched as an empty query, as set by the `empty_query`
+    /// option.
+    pub fn fuzzy_match_query(&mut self, target: &str, query: &Query) -> Option<usize> {
+        if query.is_empty() {
+            return self.fuzzy_match(target, "");
+        }
+        self.match_ast(target, &query.ast).map(|score| score.max(1))
+    }
+
+    /// Returns the score of a node of a query, or `None` if it does not match the target.
+    fn match_ast(&mut self, target: &str, ast: &QueryAst) -> Option<usize> {
+        match ast {
+            QueryAst::Term(text) => self.fuzzy_match(target, text),
+            QueryAst::Exact(text) => {
+                if !self.contains_text(target, text, false) {
+                    return None;
+                }
+                self.fuzzy_match(target, text)
+            }
+            QueryAst::PrefixAnchor(text) => {
+                if !self.contains_text(target, text, true) {
+                    return None;
+                }
+                self.fuzzy_match(target, text)
+            }
+            QueryAst::Not(inner) => match self.match_ast(target, inner) {
+                Some(_) => None,
+                None => Some(0),
+            },
+            QueryAst::Or(alternatives) => alternatives
+                .iter()
+                .filter_map(|alternative| self.match_ast(target, alternative))
+                .max(),
+            QueryAst::And(terms) => {
+                let mut score: usize = 0;
+                for term in terms {
+                    score = score.saturating_add(self.match_ast(target, term)?);
+                }
+                Some(score)
+            }
+        }
+    }
+
+    /// Returns true if `target` contains `text`, or starts with it if `anchored` is set,
+    /// comparing characters as the matcher does. The target and text are loaded as for a
+    /// fuzzy match, so truncation, ignored characters and the whitespace options apply.
+    fn contains_text(&mut self, target: &str, text: &str, anchored: bool) -> bool {
+        self.load(target, text);
+        let (text, target) = (&self.query_chars, &self.target_chars);
+        if text.len() > target.len() {
+            return false;
+        }
+        let starts = if anchored {
+            // The start of a target truncated at the front is not matched
+            0..usize::from(self.window_offset == 0)
+        } else {
+            0..target.len() - text.len() + 1
+        };
+        starts.into_iter().any(|start| {
+            text.iter()
+                .zip(&target[start..])
+                .all(|(q, t)| char_match_kind(*q, *t, &self.options).is_some())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FuzzyMatcher, MatchOptions, Query, QueryAst, Truncation};
+    use alloc::boxed::Box;
+    use alloc::vec;
+
+    #[test]
+    fn test_query_alternatives() {
+        let term = |text: &str| QueryAst::Term(text.into());
+        let parse = |query: &str| Query::parse(query).ast().clone();
+        assert_eq!(
+            parse("png|jpg|gif"),
+            QueryAst::And(vec![QueryAst::Or(vec![
+                term("png"),
+                term("jpg"),
+                term("gif")
+            ])])
+        );
+        assert_eq!(
+            parse(" src  png | jpg "),
+            QueryAst::And(vec![
+                term("src"),
+                QueryAst::Or(vec![term("png"), term("jpg")])
+            ])
+        );
+        assert_eq!(
+            parse("a\\|b c\\ d \\\\"),
+            QueryAst::And(vec![term("a|b"), term("c d"), term("\\")])
+        );
+        assert_eq!(
+            parse("|png||jpg|"),
+            QueryAst::And(vec![QueryAst::Or(vec![term("png"), term("jpg")])])
+        );
+        assert!(Query::parse("  | ! ").is_empty());
+
+        // Terms are scored by their best alternative and summed
+        let mut matcher = FuzzyMatcher::new();
+        let target = "assets/icons/logo.png";
+        let png = matcher.fuzzy_match(target, "png").unwrap();
+        let icons = matcher.fuzzy_match(target, "icons").unwrap();
+        assert_eq!(
+            matcher.fuzzy_match_query(target, &Query::parse("jpg|png")),
+            Some(png)
+        );
+        assert_eq!(
+            matcher.fuzzy_match_query(target, &Query::parse("png|icons")),
+            Some(png.max(icons))
+        );
+        assert_eq!(
+            matcher.fuzzy_match_query(target, &Query::parse("icons png|jpg")),
+            Some(icons + png)
+        );
+        assert!(matcher
+            .fuzzy_match_query(target, &Query::parse("fonts png"))
+            .is_none());
+        assert!(matcher
+            .fuzzy_match_query("a|b", &Query::parse("a\\|b"))
+            .is_some());
+        assert!(matcher
+            .fuzzy_match_query(target, &Query::default())
+            .is_none());
+    }
+
+    #[test]
+    fn test_query_ast() {
+        let exact = |text: &str| QueryAst::Exact(text.into());
+        let prefix = |text: &str| QueryAst::PrefixAnchor(text.into());
+        let not = |ast: QueryAst| QueryAst::Not(Box::new(ast));
+        assert_eq!(
+            Query::parse("'core ^src !test !^vendor \\!bang"),
+            Query::from(QueryAst::And(vec![
+                exact("core"),
+                prefix("src"),
+                not(exact("test")),
+                not(prefix("vendor")),
+                QueryAst::Term("!bang".into()),
+            ]))
+        );
+
+        let mut matcher = FuzzyMatcher::new();
+        let query = |ast: QueryAst| Query::from(ast);
+        let target = "src/core/Matcher.rs";
+        let fuzzy = matcher.fuzzy_match(target, "scm").unwrap();
+        assert_eq!(
+            matcher.fuzzy_match_query(target, &query(QueryAst::Term("scm".into()))),
+            Some(fuzzy)
+        );
+        assert!(matcher
+            .fuzzy_match_query(target, &query(exact("scm")))
+            .is_none());
+        assert!(matcher
+            .fuzzy_match_query(target, &query(exact("MATCHER")))
+            .is_some());
+        assert!(matcher
+            .fuzzy_match_query(target, &query(prefix("SRC/")))
+            .is_some());
+        assert!(matcher
+            .fuzzy_match_query(target, &query(prefix("core")))
+            .is_none());
+
+        // Negations match without adding to the score
+        assert_eq!(
+            matcher.fuzzy_match_query(target, &query(not(exact("test")))),
+            Some(1)
+        );
+        assert_eq!(
+            matcher.fuzzy_match_query(
+                target,
+                &query(QueryAst::And(vec![
+                    QueryAst::Term("scm".into()),
+                    not(prefix("vendor"))
+                ]))
+            ),
+            Some(fuzzy)
+        );
+        assert!(matcher
+            .fuzzy_match_query(target, &query(not(exact("core"))))
+            .is_none());
+        assert!(matcher
+            .fuzzy_match_query(target, &query(QueryAst::Or(vec![])))
+            .is_none());
+        assert!(matcher
+            .fuzzy_match_query(target, &query(QueryAst::And(vec![])))
+            .is_none());
+        assert_eq!(
+            matcher.fuzzy_match_query(target, &query(QueryAst::Or(vec![QueryAst::And(vec![])]))),
+            Some(1)
+        );
+
+        // Exact text is found in the target as the fuzzy match sees it
+        let mut matcher = FuzzyMatcher::with_options(MatchOptions {
+            ignored_chars: "-".into(),
+            trim_query: true,
+            truncation: Some(Truncation::Last(15)),
+            ..MatchOptions::default()
+        });
+        let target = "vendor/my-crate/lib.rs";
+        assert!(matcher
+            .fuzzy_match_query(target, &query(exact(" mycrate ")))
+            .is_some());
+        assert!(matcher
+            .fuzzy_match_query(target, &query(prefix("my")))
+            .is_none());
+        assert!(matcher
+            .fuzzy_match_query(target, &query(exact("vendor")))
+            .is_none());
+    }
+}
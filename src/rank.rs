@@ -0,0 +1,519 @@
+//! Ranking of batches of targets with deterministic ordering of equal scores.
+
+use crate::batch::CHUNK_LEN;
+use crate::{FuzzyMatcher, ScoreModel};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+/// Secondary sort key used to order matches that have the same score.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TieBreak {
+    /// Targets with fewer characters are ranked first.
+    ShorterTarget,
+    /// Targets are ranked in lexicographic order.
+    Lexicographic,
+}
+
+impl TieBreak {
+    /// Tie breaking used when none is specified: shorter targets first, then lexicographic
+    /// order.
+    pub const DEFAULT: &'static [TieBreak] = &[TieBreak::ShorterTarget, TieBreak::Lexicographic];
+
+    fn compare(&self, a: &str, b: &str) -> Ordering {
+        match self {
+            TieBreak::ShorterTarget => a.chars().count().cmp(&b.chars().count()),
+            TieBreak::Lexicographic => a.cmp(b),
+        }
+    }
+}
+
+/// A target that matched a query in a batch. Targets are identified by their index in the
+/// batch, or by an identifier chosen by the caller when ranked with
+/// [`FuzzyMatcher::rank_ids`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RankedMatch<'a, I = usize> {
+    /// The target string that matched.
+    pub target: &'a str,
+    /// Index of the target in the batch, or its identifier.
+    pub index: I,
+    /// Score of the match.
+    pub score: usize,
+}
+
+/// Limit on the amount of work done by a call to
+/// [`FuzzyMatcher::rank_with_budget`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Budget {
+    /// Match at most this many targets.
+    Candidates(usize),
+    /// Stop matching once this much time has passed. Targets are matched in small groups
+    /// between checks of the time, and at least one group is matched on every call.
+    #[cfg(feature = "std")]
+    Time(std::time::Duration),
+}
+
+/// Position of an incremental ranking of a batch of targets, along with the matches found
+/// so far. Start a new cursor when the query or the targets change.
+#[derive(Clone, Debug, Default)]
+pub struct RankCursor {
+    next: usize,
+    /// Index and score of each match found so far.
+    matches: Vec<(usize, usize)>,
+    complete: bool,
+}
+
+impl RankCursor {
+    /// Creates a cursor at the start of a batch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of targets that have been matched.
+    pub fn position(&self) -> usize {
+        self.next
+    }
+
+    /// Returns true if every target in the batch has been matched.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+}
+
+/// Sorts matches from highest to lowest score. Matches with the same score are ordered by
+/// each of the `tie_breaks` in turn, and then by their index in the batch or identifier, so
+/// the order is the same on every run.
+pub fn sort_matches<I: Ord>(matches: &mut [RankedMatch<I>], tie_breaks: &[TieBreak]) {
+    matches.sort_unstable_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| {
+                tie_breaks
+                    .iter()
+                    .map(|tie_break| tie_break.compare(a.target, b.target))
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            })
+            .then_with(|| a.index.cmp(&b.index))
+    });
+}
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Fuzzy match each of the targets against a query string, returning the targets that
+    /// match from best to worst. Matches with the same score are ordered using `tie_breaks`,
+    /// as described in [`sort_matches`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use code_fuzzy_match::{FuzzyMatcher, TieBreak};
+    ///
+    /// let mut matcher = FuzzyMatcher::new();
+    /// let targets = ["src/lib.rs", "lib", "Cargo.toml", "lib2"];
+    /// let ranked = matcher.rank(targets, "lib", TieBreak::DEFAULT);
+    /// let ranked: Vec<&str> = ranked.iter().map(|m| m.target).collect();
+    /// assert_eq!(ranked, ["lib", "lib2", "src/lib.rs"]);
+    /// ```
+    pub fn rank<'a>(
+        &mut self,
+        targets: impl IntoIterator<Item = &'a str>,
+        query: &str,
+        tie_breaks: &[TieBreak],
+    ) -> Vec<RankedMatch<'a>> {
+        self.rank_cancellable(targets, query, tie_breaks, || false)
+            .unwrap_or_default()
+    }
+
+    /// Same as [`rank`](Self::rank), but calls `should_cancel` periodically while matching
+    /// and stops early, returning `None`, as soon as it returns true. This allows a stale
+    /// query to be abandoned when the user has typed another character.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use code_fuzzy_match::{FuzzyMatcher, TieBreak};
+    /// use core::sync::atomic::{AtomicBool, Ordering};
+    ///
+    /// let stale = AtomicBool::new(false);
+    /// let mut matcher = FuzzyMatcher::new();
+    /// let targets = ["src/lib.rs", "lib", "Cargo.toml"];
+    /// let ranked = matcher.rank_cancellable(targets, "lib", TieBreak::DEFAULT, || {
+    ///     stale.load(Ordering::Relaxed)
+    /// });
+    /// assert_eq!(ranked.map(|ranked| ranked.len()), Some(2));
+    ///
+    /// stale.store(true, Ordering::Relaxed);
+    /// let ranked = matcher.rank_cancellable(targets, "lib", TieBreak::DEFAULT, || {
+    ///     stale.load(Ordering::Relaxed)
+    /// });
+    /// assert!(ranked.is_none());
+    /// ```
+    pub fn rank_cancellable<'a>(
+        &mut self,
+        targets: impl IntoIterator<Item = &'a str>,
+        query: &str,
+        tie_breaks: &[TieBreak],
+        should_cancel: impl FnMut() -> bool,
+    ) -> Option<Vec<RankedMatch<'a>>> {
+        self.rank_ids_cancellable(
+            targets.into_iter().enumerate(),
+            query,
+            tie_breaks,
+            should_cancel,
+        )
+    }
+
+    /// Same as [`rank`](Self::rank), but each target comes with an identifier chosen by the
+    /// caller, which is returned in place of its index in the batch. Identifiers stay valid
+    /// when targets are added or removed between queries, so a user interface can keep track
+    /// of the selected match while the list of targets changes. Matches with the same score
+    /// and tie breaks are ordered by identifier.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use code_fuzzy_match::{FuzzyMatcher, TieBreak};
+    ///
+    /// let mut matcher = FuzzyMatcher::new();
+    /// let targets = [(17u64, "src/lib.rs"), (3, "Cargo.toml"), (42, "lib")];
+    /// let ranked = matcher.rank_ids(targets, "lib", TieBreak::DEFAULT);
+    /// let ids: Vec<u64> = ranked.iter().map(|m| m.index).collect();
+    /// assert_eq!(ids, [42, 17]);
+    /// ```
+    pub fn rank_ids<'a, I: Ord>(
+        &mut self,
+        targets: impl IntoIterator<Item = (I, &'a str)>,
+        query: &str,
+        tie_breaks: &[TieBreak],
+    ) -> Vec<RankedMatch<'a, I>> {
+        self.rank_ids_cancellable(targets, query, tie_breaks, || false)
+            .unwrap_or_default()
+    }
+
+    /// Same as [`rank_ids`](Self::rank_ids), but calls `should_cancel` periodically while
+    /// matching and stops early, returning `None`, as soon as it returns true.
+    pub fn rank_ids_cancellable<'a, I: Ord>(
+        &mut self,
+        targets: impl IntoIterator<Item = (I, &'a str)>,
+        query: &str,
+        tie_breaks: &[TieBreak],
+        should_cancel: impl FnMut() -> bool,
+    ) -> Option<Vec<RankedMatch<'a, I>>> {
+        let mut matches: Vec<RankedMatch<'a, I>> = self
+            .match_batch_cancellable(targets, |(_, target)| target, query, should_cancel)?
+            .into_iter()
+            .map(|((index, target), score)| RankedMatch {
+                target,
+                index,
+                score,
+            })
+            .collect();
+        if !self.options.natural_order(query) {
+            sort_matches(&mut matches, tie_breaks);
+        }
+        Some(matches)
+    }
+
+    /// Fuzzy match as many of the targets as the budget allows against a query string,
+    /// continuing from the position of `cursor`. Returns every match found so far from
+    /// best to worst, ordered as in [`rank`](Self::rank). Call again with the same targets,
+    /// query and cursor, such as on the next frame of a user interface, until the cursor
+    /// is complete.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use code_fuzzy_match::{Budget, FuzzyMatcher, RankCursor, TieBreak};
+    ///
+    /// let targets = ["src/lib.rs", "lib", "Cargo.toml", "lib2"];
+    /// let mut matcher = FuzzyMatcher::new();
+    /// let mut cursor = RankCursor::new();
+    /// let ranked = matcher.rank_with_budget(
+    ///     &targets,
+    ///     "lib",
+    ///     TieBreak::DEFAULT,
+    ///     Budget::Candidates(2),
+    ///     &mut cursor,
+    /// );
+    /// assert_eq!(ranked.iter().map(|m| m.target).collect::<Vec<_>>(), ["lib", "src/lib.rs"]);
+    /// assert!(!cursor.is_complete());
+    ///
+    /// let ranked = matcher.rank_with_budget(
+    ///     &targets,
+    ///     "lib",
+    ///     TieBreak::DEFAULT,
+    ///     Budget::Candidates(2),
+    ///     &mut cursor,
+    /// );
+    /// assert_eq!(ranked.len(), 3);
+    /// assert!(cursor.is_complete());
+    /// ```
+    pub fn rank_with_budget<'a, S: AsRef<str>>(
+        &mut self,
+        targets: &'a [S],
+        query: &str,
+        tie_breaks: &[TieBreak],
+        budget: Budget,
+        cursor: &mut RankCursor,
+    ) -> Vec<RankedMatch<'a>> {
+        #[cfg(feature = "std")]
+        let start = std::time::Instant::now();
+        let mut matched = 0;
+        while cursor.next < targets.len() {
+            let chunk_len = match budget {
+                Budget::Candidates(count) => CHUNK_LEN.min(count - matched),
+                #[cfg(feature = "std")]
+                Budget::Time(duration) => {
+                    if matched > 0 && start.elapsed() >= duration {
+                        break;
+                    }
+                    CHUNK_LEN
+                }
+            };
+            if chunk_len == 0 {
+                break;
+            }
+
+            let offset = cursor.next;
+            let end = targets.len().min(offset + chunk_len);
+            let matches = self.match_batch(
+                targets[offset..end].iter().enumerate(),
+                |(_, target)| target.as_ref(),
+                query,
+            );
+            cursor.matches.extend(
+                matches
+                    .into_iter()
+                    .map(|((index, _), score)| (offset + index, score)),
+            );
+            matched += end - offset;
+            cursor.next = end;
+        }
+        cursor.complete = cursor.next >= targets.len();
+
+        let mut matches: Vec<RankedMatch<'a>> = cursor
+            .matches
+            .iter()
+            .map(|(index, score)| RankedMatch {
+                target: targets[*index].as_ref(),
+                index: *index,
+                score: *score,
+            })
+            .collect();
+        if self.options.natural_order(query) {
+            matches.sort_unstable_by_key(|m| m.index);
+        } else {
+            sort_matches(&mut matches, tie_breaks);
+        }
+        matches
+    }
+
+    /// Fuzzy match a string taken from each item against a query string, returning the
+    /// index and score of each item that matches, from best to worst. Matches with the same
+    /// score are ordered using [`TieBreak::DEFAULT`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// struct Command {
+    ///     name: &'static str,
+    /// }
+    ///
+    /// let commands = [
+    ///     Command { name: "Close Window" },
+    ///     Command { name: "Open File" },
+    ///     Command { name: "Open Folder" },
+    /// ];
+    /// let mut matcher = code_fuzzy_match::FuzzyMatcher::new();
+    /// let ranked = matcher.rank_by(&commands, |command| command.name, "open f");
+    /// assert_eq!(ranked.iter().map(|(idx, _)| *idx).collect::<Vec<_>>(), [1, 2]);
+    /// ```
+    pub fn rank_by<T>(
+        &mut self,
+        items: &[T],
+        key: impl Fn(&T) -> &str,
+        query: &str,
+    ) -> Vec<(usize, usize)> {
+        let ranked = self.rank(items.iter().map(&key), query, TieBreak::DEFAULT);
+        ranked
+            .into_iter()
+            .map(|ranked| (ranked.index, ranked.score))
+            .collect()
+    }
+
+    /// Same as [`rank_by`](Self::rank_by), but returns an identifier taken from each item in
+    /// place of its index, so that results can be matched up with items after the list of
+    /// items has changed. Matches with the same score are ordered using
+    /// [`TieBreak::DEFAULT`], and then by identifier.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// struct Command {
+    ///     id: u64,
+    ///     name: &'static str,
+    /// }
+    ///
+    /// let commands = [
+    ///     Command { id: 30, name: "Close Window" },
+    ///     Command { id: 10, name: "Open File" },
+    ///     Command { id: 20, name: "Open Folder" },
+    /// ];
+    /// let mut matcher = code_fuzzy_match::FuzzyMatcher::new();
+    /// let ranked = matcher.rank_by_id(&commands, |command| command.id, |command| command.name, "open f");
+    /// assert_eq!(ranked.iter().map(|(id, _)| *id).collect::<Vec<_>>(), [10, 20]);
+    /// ```
+    pub fn rank_by_id<T, I: Ord>(
+        &mut self,
+        items: &[T],
+        id: impl Fn(&T) -> I,
+        key: impl Fn(&T) -> &str,
+        query: &str,
+    ) -> Vec<(I, usize)> {
+        let ranked = self.rank_ids(
+            items.iter().map(|item| (id(item), key(item))),
+            query,
+            TieBreak::DEFAULT,
+        );
+        ranked
+            .into_iter()
+            .map(|ranked| (ranked.index, ranked.score))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{sort_matches, Budget, FuzzyMatcher, RankCursor, RankedMatch, TieBreak};
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_tie_breaks() {
+        let targets = ["foo_b", "foo_a", "foo_ab", "bar"];
+        let mut matcher = FuzzyMatcher::new();
+        let ranked = matcher.rank(targets, "foo", TieBreak::DEFAULT);
+        assert!(ranked.iter().all(|m| m.score == ranked[0].score));
+        assert_eq!(
+            ranked.iter().map(|m| m.target).collect::<Vec<_>>(),
+            ["foo_a", "foo_b", "foo_ab"]
+        );
+
+        // Without tie breaks, equal scores keep their order in the batch
+        let mut matches: Vec<RankedMatch> = ranked.clone();
+        sort_matches(&mut matches, &[]);
+        assert_eq!(
+            matches.iter().map(|m| m.index).collect::<Vec<_>>(),
+            [0, 1, 2]
+        );
+
+        let ranked = matcher.rank(targets, "foo", &[TieBreak::Lexicographic]);
+        assert_eq!(
+            ranked.iter().map(|m| m.target).collect::<Vec<_>>(),
+            ["foo_a", "foo_ab", "foo_b"]
+        );
+
+        // Ranking items by key gives the same order as ranking the keys
+        let items = targets.map(|target| (target, target.len()));
+        let ranked = matcher.rank_by(&items, |item| item.0, "foo");
+        assert_eq!(
+            ranked.iter().map(|(idx, _)| *idx).collect::<Vec<_>>(),
+            [1, 0, 2]
+        );
+    }
+
+    #[test]
+    fn test_rank_ids() {
+        let mut matcher = FuzzyMatcher::new();
+        let mut targets = alloc::vec![(7u64, "foo_b"), (3, "foo_a"), (9, "bar"), (5, "foo")];
+        let ranked = matcher.rank_ids(targets.iter().copied(), "foo", &[]);
+        assert_eq!(
+            ranked
+                .iter()
+                .map(|m| (m.index, m.target))
+                .collect::<Vec<_>>(),
+            [(5, "foo"), (3, "foo_a"), (7, "foo_b")]
+        );
+
+        // Identifiers are unchanged when targets are removed or inserted before them
+        targets.remove(0);
+        targets.insert(0, (11, "foo_c"));
+        let ranked = matcher.rank_ids(targets.iter().copied(), "foo", TieBreak::DEFAULT);
+        assert_eq!(
+            ranked.iter().map(|m| m.index).collect::<Vec<_>>(),
+            [5, 3, 11]
+        );
+        let ranked = matcher.rank_by_id(&targets, |item| item.0, |item| item.1, "foo");
+        assert_eq!(
+            ranked.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+            [5, 3, 11]
+        );
+    }
+
+    #[test]
+    fn test_rank_cancellable() {
+        let targets: Vec<String> = (0..1000).map(|idx| format!("file_{idx}.rs")).collect();
+        let mut matcher = FuzzyMatcher::new();
+        let expected = matcher.rank(targets.iter().map(String::as_str), "f1", &[]);
+
+        let mut checks = 0;
+        let ranked =
+            matcher.rank_cancellable(targets.iter().map(String::as_str), "f1", &[], || {
+                checks += 1;
+                false
+            });
+        assert_eq!(ranked, Some(expected));
+        assert!(checks > 1);
+
+        // Cancelling part way through stops matching
+        let mut matched = 0;
+        let mut checks = 0;
+        let ranked = matcher.rank_cancellable(
+            targets.iter().map(|target| {
+                matched += 1;
+                target.as_str()
+            }),
+            "f1",
+            &[],
+            || {
+                checks += 1;
+                checks > 2
+            },
+        );
+        assert!(ranked.is_none());
+        assert!(matched < targets.len());
+    }
+
+    #[test]
+    fn test_rank_with_budget() {
+        let targets: Vec<String> = (0..1000).map(|idx| format!("file_{idx}.rs")).collect();
+        let mut matcher = FuzzyMatcher::new();
+        let expected = matcher.rank(targets.iter().map(String::as_str), "f1", &[]);
+
+        let mut cursor = RankCursor::new();
+        let mut calls = 0;
+        let ranked = loop {
+            let ranked =
+                matcher.rank_with_budget(&targets, "f1", &[], Budget::Candidates(100), &mut cursor);
+            calls += 1;
+            assert_eq!(cursor.position(), targets.len().min(calls * 100));
+            if cursor.is_complete() {
+                break ranked;
+            }
+        };
+        assert_eq!(calls, 10);
+        assert_eq!(ranked, expected);
+
+        // A time budget always makes progress
+        #[cfg(feature = "std")]
+        {
+            let mut cursor = RankCursor::new();
+            let budget = Budget::Time(std::time::Duration::ZERO);
+            matcher.rank_with_budget(&targets, "f1", &[], budget, &mut cursor);
+            assert!(cursor.position() > 0);
+        }
+    }
+}
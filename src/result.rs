@@ -0,0 +1,217 @@
+//! Match results with secondary signals for ranking and display.
+
+use crate::matrix::{is_word_start, UnitMatch};
+use crate::{char_match_kind, FuzzyMatcher, ScoreModel};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// Result of a fuzzy match, with the matched positions and secondary signals that are
+/// useful for breaking ties between matches with the same score.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Match {
+    /// Score of the match, as returned by [`FuzzyMatcher::fuzzy_match`].
+    pub score: usize,
+    /// Character indices into the target string of each matched character, in ascending
+    /// order.
+    pub positions: Vec<usize>,
+    /// Length of the longest run of consecutive matched characters.
+    pub longest_run: usize,
+    /// Number of matched characters that are at the start of a word in the target.
+    pub word_starts_hit: usize,
+    /// True if every matched character has the same case in the query and the target.
+    pub exact_case: bool,
+    /// True if the query is equal to the entire target, comparing characters using the
+    /// same rules as the rest of the match, including case folding and equivalent characters.
+    pub exact_match: bool,
+    /// True if the target was truncated before matching, as configured by the `truncation`
+    /// option.
+    pub truncated: bool,
+}
+
+impl Match {
+    /// Returns the matched positions merged into ranges of consecutive character indices,
+    /// for highlighting the match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut matcher = code_fuzzy_match::FuzzyMatcher::new();
+    /// let result = matcher.fuzzy_match_details("the quick brown fox", "bro fox").unwrap();
+    /// assert_eq!(result.ranges(), vec![10..13, 15..19]);
+    /// ```
+    pub fn ranges(&self) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        for &position in &self.positions {
+            match ranges.last_mut() {
+                Some(range) if range.end == position => range.end += 1,
+                _ => ranges.push(position..position + 1),
+            }
+        }
+        ranges
+    }
+}
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Fuzzy match a string against a query string. Returns the score along with the
+    /// matched positions and other details of the match, or `None` if the query does not
+    /// match the target string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut matcher = code_fuzzy_match::FuzzyMatcher::new();
+    /// let result = matcher.fuzzy_match_details("the quick brown fox", "bro fox").unwrap();
+    /// assert_eq!(result.positions, vec![10, 11, 12, 15, 16, 17, 18]);
+    /// assert_eq!(result.longest_run, 4);
+    /// assert_eq!(result.word_starts_hit, 2);
+    /// assert!(result.exact_case);
+    /// ```
+    pub fn fuzzy_match_details(&mut self, target: &str, query: &str) -> Option<Match> {
+        self.load(target, query);
+        self.loaded_details(false)
+    }
+
+    /// Same as [`fuzzy_match_details`](Self::fuzzy_match_details), for a target given as
+    /// characters, as with [`fuzzy_match_chars`](Self::fuzzy_match_chars). Positions are
+    /// indices into the characters of the target.
+    pub fn fuzzy_match_chars_details(
+        &mut self,
+        target: impl IntoIterator<Item = char>,
+        query: &str,
+    ) -> Option<Match> {
+        self.load_chars(target.into_iter(), query);
+        self.loaded_details(false)
+    }
+
+    /// Returns the details of the best match of the loaded target. If `masked` is set, the
+    /// position mask must have been filled for the loaded target.
+    pub(crate) fn loaded_details(&mut self, masked: bool) -> Option<Match> {
+        let score = self.compute(true, masked)?;
+
+        let mut word_starts_hit = 0;
+        let mut exact_case = true;
+        for (position, query_idx) in self
+            .matrix
+            .positions
+            .iter()
+            .zip(self.matrix.query_indices.iter())
+        {
+            let window_idx = position - self.window_offset;
+            if is_word_start(&self.target_classes, window_idx) {
+                word_starts_hit += 1;
+            }
+            if char_match_kind(
+                self.query_chars[*query_idx],
+                self.target_chars[window_idx],
+                &self.options,
+            ) != Some(UnitMatch::Exact)
+            {
+                exact_case = false;
+            }
+        }
+
+        // Transposed characters are matched out of order, so runs are found after sorting
+        let positions = self.matched_positions();
+        let mut longest_run = 0;
+        let mut run = 0;
+        for (idx, position) in positions.iter().enumerate() {
+            if idx > 0 && positions[idx - 1] + 1 == *position {
+                run += 1;
+            } else {
+                run = 1;
+            }
+            longest_run = longest_run.max(run);
+        }
+
+        Some(Match {
+            score,
+            positions,
+            longest_run,
+            word_starts_hit,
+            exact_case,
+            exact_match: self.is_exact_match(),
+            truncated: self.truncated,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FuzzyMatcher, MatchOptions};
+    use alloc::vec;
+
+    #[test]
+    fn test_match_details() {
+        let mut matcher = FuzzyMatcher::new();
+        const TARGET: &str = "The quick brown fox jumps over the lazy dog.";
+        for query in ["fox", "The quick", "jmp the do", "xz", "cat"] {
+            assert_eq!(
+                matcher.fuzzy_match_details(TARGET, query).map(|m| m.score),
+                matcher.fuzzy_match(TARGET, query)
+            );
+        }
+
+        let result = matcher
+            .fuzzy_match_details("camelCaseWords", "cacw")
+            .unwrap();
+        assert_eq!(result.positions, vec![0, 1, 5, 9]);
+        assert_eq!(result.longest_run, 2);
+        assert_eq!(result.word_starts_hit, 3);
+        assert!(!result.exact_case);
+        assert!(!result.exact_match);
+        assert!(!result.truncated);
+        assert_eq!(result.ranges(), vec![0..2, 5..6, 9..10]);
+
+        // Positions are in target order even when characters are transposed
+        let mut matcher = FuzzyMatcher::with_options(MatchOptions {
+            transpositions: true,
+            ..MatchOptions::default()
+        });
+        let result = matcher.fuzzy_match_details("the", "teh").unwrap();
+        assert_eq!(result.positions, vec![0, 1, 2]);
+        assert_eq!(result.longest_run, 3);
+
+        // Targets given as characters have the same details
+        let mut matcher = FuzzyMatcher::new();
+        let rope = ["camel", "Case", "Words"];
+        assert_eq!(
+            matcher.fuzzy_match_chars_details(rope.iter().flat_map(|s| s.chars()), "cacw"),
+            matcher.fuzzy_match_details("camelCaseWords", "cacw")
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        use crate::{Match, Truncation};
+
+        let options = MatchOptions {
+            gap_penalty: 3,
+            truncation: Some(Truncation::Basename(64)),
+            equivalences: vec!["-_".into()],
+            ..MatchOptions::default()
+        };
+        let json = serde_json::to_string(&options).unwrap();
+        assert_eq!(
+            serde_json::from_str::<MatchOptions>(&json).unwrap(),
+            options
+        );
+
+        // Missing options take their default values
+        let partial: MatchOptions = serde_json::from_str(r#"{"gap_penalty": 3}"#).unwrap();
+        assert_eq!(
+            partial,
+            MatchOptions {
+                gap_penalty: 3,
+                ..MatchOptions::default()
+            }
+        );
+
+        let result = FuzzyMatcher::with_options(options)
+            .fuzzy_match_details("src/my_crate.rs", "my-crate")
+            .unwrap();
+        let json = serde_json::to_string(&result).unwrap();
+        assert_eq!(serde_json::from_str::<Match>(&json).unwrap(), result);
+    }
+}
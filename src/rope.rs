@@ -0,0 +1,69 @@
+//! Matching within [`ropey`] ropes, for searching the open buffers of an editor.
+
+use crate::{FuzzyMatcher, Match, ScoreModel};
+use alloc::vec::Vec;
+use ropey::RopeSlice;
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Fuzzy match a rope, or a slice of one, against a query string without copying it
+    /// into a contiguous string. Positions in the result are character indices into the
+    /// slice, and can be converted to lines and columns with [`Match::line_columns`].
+    /// Returns `None` if the query does not match the slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ropey::Rope;
+    ///
+    /// let mut matcher = code_fuzzy_match::FuzzyMatcher::new();
+    /// let rope = Rope::from_str("fn main() {\n    run_app();\n}\n");
+    /// let result = matcher.fuzzy_match_rope(rope.slice(..), "run").unwrap();
+    /// assert_eq!(result.positions, vec![16, 17, 18]);
+    /// assert_eq!(result.line_columns(rope.slice(..)), vec![(1, 4), (1, 5), (1, 6)]);
+    /// ```
+    pub fn fuzzy_match_rope(&mut self, target: RopeSlice, query: &str) -> Option<Match> {
+        self.fuzzy_match_chars_details(target.chars(), query)
+    }
+}
+
+impl Match {
+    /// Returns the zero based line and column of each matched position in the rope slice
+    /// that was matched by [`FuzzyMatcher::fuzzy_match_rope`]. Columns are counted in
+    /// characters from the start of the line.
+    pub fn line_columns(&self, target: RopeSlice) -> Vec<(usize, usize)> {
+        self.positions
+            .iter()
+            .map(|position| {
+                let line = target.char_to_line(*position);
+                (line, position - target.line_to_char(line))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FuzzyMatcher;
+    use alloc::vec;
+    use ropey::Rope;
+
+    #[test]
+    fn test_rope_match() {
+        let text = "use std::io;\n\nfn read_config() {}\nfn write_config() {}\n";
+        let rope = Rope::from_str(text);
+        let mut matcher = FuzzyMatcher::new();
+        let result = matcher.fuzzy_match_rope(rope.slice(..), "wconf").unwrap();
+        assert_eq!(result, matcher.fuzzy_match_details(text, "wconf").unwrap());
+        assert_eq!(
+            result.line_columns(rope.slice(..)),
+            vec![(3, 3), (3, 9), (3, 10), (3, 11), (3, 12)]
+        );
+
+        // Positions within a slice are relative to the slice
+        let slice = rope.slice(rope.line_to_char(2)..);
+        let result = matcher.fuzzy_match_rope(slice, "wconf").unwrap();
+        assert_eq!(result.positions[0], 23);
+        assert_eq!(result.line_columns(slice)[0], (1, 3));
+        assert!(matcher.fuzzy_match_rope(slice, "std").is_none());
+    }
+}
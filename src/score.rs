@@ -0,0 +1,167 @@
+//! Types that scores can be returned as.
+
+use core::fmt::Debug;
+
+/// A type that match scores can be returned as, such as with
+/// [`FuzzyMatcher::fuzzy_match_as`](crate::FuzzyMatcher::fuzzy_match_as).
+///
+/// The score of a match is the sum of the score of each matched character, minus penalties
+/// such as the `gap_penalty` and `typo_penalty` options, and can be zero or negative when
+/// the penalties outweigh the bonuses. Scores returned as `usize`, as by
+/// [`fuzzy_match`](crate::FuzzyMatcher::fuzzy_match), are raised to one so that every match
+/// has a positive score, which loses the order between heavily penalized matches. Scores
+/// returned as `isize` keep the full range, so that penalties can always lower the score.
+///
+/// # Examples
+///
+/// ```
+/// use code_fuzzy_match::{FuzzyMatcher, MatchOptions};
+///
+/// let mut matcher = FuzzyMatcher::with_options(MatchOptions {
+///     gap_penalty: 2,
+///     ..MatchOptions::default()
+/// });
+/// let target = "a_long_way_to_b";
+/// assert_eq!(matcher.fuzzy_match(target, "ab"), Some(1));
+/// assert_eq!(matcher.fuzzy_match_as::<isize>(target, "ab"), Some(-2));
+/// ```
+pub trait Score: Copy + Ord + Debug {
+    /// Score of a query equal to the entire target when the `pin_exact_matches` option is
+    /// set. This must be higher than the score of any other match.
+    const EXACT_MATCH: Self;
+
+    /// Converts the sum of the character scores and penalties of a match.
+    fn from_sum(sum: isize) -> Self;
+
+    /// Adds a bonus for the match as a whole, such as the `substring_bonus` option,
+    /// saturating instead of overflowing.
+    fn add_bonus(self, bonus: usize) -> Self;
+}
+
+impl Score for usize {
+    const EXACT_MATCH: Self = usize::MAX;
+
+    #[inline]
+    fn from_sum(sum: isize) -> Self {
+        sum.max(1) as usize
+    }
+
+    #[inline]
+    fn add_bonus(self, bonus: usize) -> Self {
+        self.saturating_add(bonus)
+    }
+}
+
+impl Score for isize {
+    const EXACT_MATCH: Self = isize::MAX;
+
+    #[inline]
+    fn from_sum(sum: isize) -> Self {
+        sum
+    }
+
+    #[inline]
+    fn add_bonus(self, bonus: usize) -> Self {
+        self.saturating_add_unsigned(bonus)
+    }
+}
+
+/// Score of a match before it is converted to a [`Score`] type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum RawScore {
+    /// The query is equal to the entire target, and the `pin_exact_matches` option is set.
+    Exact,
+    /// Sum of the character scores and penalties, and the bonus for the match as a whole.
+    Sum { sum: isize, bonus: usize },
+}
+
+impl RawScore {
+    #[inline]
+    pub(crate) fn into_score<S: Score>(self) -> S {
+        match self {
+            RawScore::Exact => S::EXACT_MATCH,
+            RawScore::Sum { sum, bonus } => S::from_sum(sum).add_bonus(bonus),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FuzzyMatcher, MatchOptions};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_signed_score() {
+        let targets = [
+            "abc",
+            "a_list_of_bc",
+            "a_long_list_of_bc",
+            "src/abc.rs",
+            "xaxbxc",
+            "nothing",
+        ];
+        let options = [
+            MatchOptions::default(),
+            MatchOptions {
+                gap_penalty: 5,
+                leading_gap_penalty: 3,
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                gap_penalty: 5,
+                substring_bonus: 100,
+                pin_exact_matches: true,
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                gap_penalty: 5,
+                max_typos: 1,
+                typo_penalty: 50,
+                ..MatchOptions::default()
+            },
+        ];
+        for options in options {
+            let mut matcher = FuzzyMatcher::with_options(options.clone());
+            let signed: Vec<Option<isize>> = targets
+                .iter()
+                .map(|target| matcher.fuzzy_match_as(target, "abc"))
+                .collect();
+            if options.substring_bonus == 0 {
+                // Unsigned scores are raised to one
+                for (target, signed) in targets.iter().zip(signed.iter()) {
+                    let expected = signed.map(|score| score.max(1) as usize);
+                    assert_eq!(matcher.fuzzy_match(target, "abc"), expected, "{target}");
+                }
+            }
+
+            // The batch matrix gives the same signed scores
+            let mut expected: Vec<(usize, isize)> = signed
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, score)| Some((idx, (*score)?)))
+                .collect();
+            expected.sort_by_key(|(_, score)| core::cmp::Reverse(*score));
+            let ranked: Vec<(usize, isize)> = matcher
+                .rank_items_as(
+                    targets.iter().enumerate(),
+                    |(_, target)| target,
+                    "abc",
+                    false,
+                )
+                .into_iter()
+                .map(|result| (result.item.0, result.score))
+                .collect();
+            assert_eq!(ranked, expected);
+        }
+
+        let mut matcher = FuzzyMatcher::with_options(MatchOptions {
+            gap_penalty: 5,
+            ..MatchOptions::default()
+        });
+        let short_gap = matcher.fuzzy_match_as::<isize>("a_list_of_bc", "abc");
+        let long_gap = matcher.fuzzy_match_as::<isize>("a_long_list_of_bc", "abc");
+        assert!(long_gap < short_gap && long_gap < Some(0));
+        assert_eq!(matcher.fuzzy_match("a_list_of_bc", "abc"), Some(1));
+        assert_eq!(matcher.fuzzy_match("a_long_list_of_bc", "abc"), Some(1));
+    }
+}
@@ -0,0 +1,136 @@
+//! Access to every cell of the scoring matrix, for visualizing how a match was scored.
+
+use crate::matrix::{MatrixInput, UNREACHABLE};
+use crate::{char_match_kind, FuzzyMatcher, ScoreModel};
+use alloc::vec::Vec;
+
+/// Every cell of the scoring matrix of a match, along with the alignment that was chosen,
+/// as returned by [`FuzzyMatcher::fuzzy_match_matrix`]. Each row is a character of the
+/// query and each column is a character of the target, so tools can render the matrix as
+/// a heatmap when tuning bonuses.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScoreMatrix {
+    /// Characters of the query, one for each row.
+    pub query: Vec<char>,
+    /// Characters of the target that were matched against, one for each column. These are
+    /// the characters within the window of a truncated target, after any folding or
+    /// skipping set by the options.
+    pub target: Vec<char>,
+    /// Column matched by each row in the chosen alignment.
+    pub path: Vec<usize>,
+    /// Score of the chosen alignment computed by the matrix, scaled by the
+    /// `length_normalization` option but before the `substring_bonus` is added. Scores of
+    /// the cells are not scaled.
+    pub score: isize,
+    scores: Vec<isize>,
+    matched: Vec<bool>,
+}
+
+impl ScoreMatrix {
+    /// Returns the best score for matching the query characters up to and including
+    /// `row` within the target characters up to and including `column`, or `None` if they
+    /// can't be matched there, or the cell was skipped because it can't be part of a match
+    /// of the whole query.
+    pub fn score(&self, row: usize, column: usize) -> Option<isize> {
+        let score = self.scores[row * self.target.len() + column];
+        (score != UNREACHABLE).then_some(score)
+    }
+
+    /// Returns true if the score of the cell came from matching the query character of
+    /// `row` with the target character of `column`, rather than from an earlier column.
+    pub fn is_match(&self, row: usize, column: usize) -> bool {
+        self.matched[row * self.target.len() + column]
+    }
+}
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Fuzzy match a string against a query string, returning every cell of the scoring
+    /// matrix and the chosen alignment, or `None` if the query does not match the target
+    /// string. The matrix is always computed in full from the start of the target to the
+    /// end, without typos, transpositions, the reverse pass of `bidirectional` matching or
+    /// the `max_gap` limit, so the alignment can differ from
+    /// [`fuzzy_match_details`](Self::fuzzy_match_details) when those options are set. The
+    /// [`VsCodeCompat`](crate::ScoringProfile::VsCodeCompat) profile is scored with the
+    /// general rules.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut matcher = code_fuzzy_match::FuzzyMatcher::new();
+    /// let matrix = matcher.fuzzy_match_matrix("FooBar", "fb").unwrap();
+    /// assert_eq!(matrix.path, vec![0, 3]);
+    /// assert!(matrix.is_match(1, 3));
+    /// assert_eq!(matrix.score(1, 2), None);
+    /// assert_eq!(matrix.score(1, 5), Some(matrix.score));
+    /// ```
+    pub fn fuzzy_match_matrix(&mut self, target: &str, query: &str) -> Option<ScoreMatrix> {
+        self.load(target, query);
+        let target_chars = &self.target_chars;
+        let query_chars = &self.query_chars;
+        let options = &self.options;
+        let input = MatrixInput {
+            record_positions: true,
+            ..MatrixInput::with_model(
+                &self.target_classes,
+                query_chars.len(),
+                options,
+                &self.model,
+            )
+        };
+
+        self.matrix.recorded_scores = Some(Vec::new());
+        let score = self.matrix.compute(&input, |query_idx, target_idx| {
+            char_match_kind(query_chars[query_idx], target_chars[target_idx], options)
+        });
+        let scores = self.matrix.recorded_scores.take().unwrap_or_default();
+        let score = score?;
+
+        Some(ScoreMatrix {
+            query: query_chars.clone(),
+            target: target_chars.clone(),
+            path: self.matrix.positions.clone(),
+            score,
+            scores,
+            matched: self.matrix.trace().to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FuzzyMatcher, MatchOptions};
+
+    #[test]
+    fn test_score_matrix() {
+        let mut matcher = FuzzyMatcher::with_options(MatchOptions {
+            gap_penalty: 1,
+            ..MatchOptions::default()
+        });
+        const TARGET: &str = "the quick brown fox";
+        let matrix = matcher.fuzzy_match_matrix(TARGET, "qbf").unwrap();
+        let details = matcher.fuzzy_match_details(TARGET, "qbf").unwrap();
+        assert_eq!(matrix.path, details.positions);
+        assert_eq!(matrix.score as usize, details.score);
+        assert_eq!(matrix.query.len(), 3);
+        assert_eq!(matrix.target.len(), TARGET.len());
+
+        // Every cell of the path is a match, and the score is carried to the end of the
+        // last row
+        for (row, column) in matrix.path.iter().enumerate() {
+            assert!(matrix.is_match(row, *column));
+        }
+        assert_eq!(matrix.score(2, TARGET.len() - 1), Some(matrix.score));
+        assert_eq!(matrix.score(0, 3), None);
+        // The gap penalty is taken from scores carried along a row
+        assert_eq!(
+            matrix.score(0, 5),
+            matrix.score(0, 4).map(|score| score - 1)
+        );
+
+        assert!(matcher.fuzzy_match_matrix(TARGET, "xyz").is_none());
+        // Later matches don't record the matrix
+        assert_eq!(matcher.fuzzy_match(TARGET, "qbf"), Some(details.score));
+        assert!(matcher.matrix.recorded_scores.is_none());
+    }
+}
@@ -0,0 +1,338 @@
+//! Match results carrying caller-provided items.
+
+use crate::{CharMask, FuzzyMatcher, Score, ScoreModel};
+use alloc::collections::BinaryHeap;
+use alloc::vec::Vec;
+use core::cmp::{Ordering, Reverse};
+
+/// An item that matched a query, along with its score.
+///
+/// Results are ordered by score, so that the best match is the greatest and can be taken from
+/// the top of a [`BinaryHeap`](alloc::collections::BinaryHeap). Results with the same score
+/// are ordered by item, with smaller items being greater so that they rank first. Positions
+/// are not considered when comparing results. Scores are `usize` unless another [`Score`]
+/// type is requested, such as with [`FuzzyMatcher::rank_items_as`].
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Scored<T, S = usize> {
+    /// The item that matched.
+    pub item: T,
+    /// Score of the match.
+    pub score: S,
+    /// Character indices into the target string of each matched character, in ascending
+    /// order, if they were requested.
+    pub positions: Option<Vec<usize>>,
+}
+
+impl<T: PartialEq, S: Score> PartialEq for Scored<T, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.score == other.score && self.item == other.item
+    }
+}
+
+impl<T: Eq, S: Score> Eq for Scored<T, S> {}
+
+impl<T: PartialOrd, S: Score> PartialOrd for Scored<T, S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.score.cmp(&other.score) {
+            Ordering::Equal => other.item.partial_cmp(&self.item),
+            ordering => Some(ordering),
+        }
+    }
+}
+
+impl<T: Ord, S: Score> Ord for Scored<T, S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score
+            .cmp(&other.score)
+            .then_with(|| other.item.cmp(&self.item))
+    }
+}
+
+/// Merges lists of results that are each sorted from best to worst, returning the `k` best
+/// results from best to worst. Results are ordered as [`Scored`] compares them, so results
+/// with the same score are ordered by item, and results that compare equal are taken from
+/// the earlier list first. When candidates are sharded across processes and each shard
+/// returns its own best `k` results, such as with [`Index::query`](crate::Index::query),
+/// the merged results are the same as ranking every candidate in a single process.
+///
+/// Lists are read lazily, so only the results up to the `k`th best are taken from each
+/// list. Lists that are not sorted in the order of [`Scored`] give an unspecified order.
+///
+/// # Examples
+///
+/// ```
+/// use code_fuzzy_match::{merge_topk, Scored};
+///
+/// let result = |item, score: usize| Scored {
+///     item,
+///     score,
+///     positions: None,
+/// };
+/// let shards = vec![
+///     vec![result("src/lib.rs", 30), result("lib.rs", 20)],
+///     vec![result("lib", 30), result("src/lib/mod.rs", 10)],
+/// ];
+/// let merged = merge_topk(shards, 3);
+/// let items: Vec<&str> = merged.iter().map(|result| result.item).collect();
+/// assert_eq!(items, ["lib", "src/lib.rs", "lib.rs"]);
+/// ```
+pub fn merge_topk<T: Ord, S: Score, L: IntoIterator<Item = Scored<T, S>>>(
+    lists: impl IntoIterator<Item = L>,
+    k: usize,
+) -> Vec<Scored<T, S>> {
+    let mut lists: Vec<L::IntoIter> = lists.into_iter().map(IntoIterator::into_iter).collect();
+    let mut merged = Vec::new();
+    if k == 0 {
+        return merged;
+    }
+
+    // The heap holds the next result of each list, with the best result on top, breaking
+    // ties by taking from the earlier list
+    let mut heads = BinaryHeap::with_capacity(lists.len());
+    for (idx, list) in lists.iter_mut().enumerate() {
+        if let Some(result) = list.next() {
+            heads.push((result, Reverse(idx)));
+        }
+    }
+    while merged.len() < k {
+        let Some((result, Reverse(idx))) = heads.pop() else {
+            break;
+        };
+        merged.push(result);
+        if let Some(next) = lists[idx].next() {
+            heads.push((next, Reverse(idx)));
+        }
+    }
+    merged
+}
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Fuzzy match a string taken from each item against a query string, returning the
+    /// items that match from best to worst. Items with the same score are kept in their
+    /// original order. If `positions` is set, the positions of the matched characters are
+    /// included in each result. Scores include the boosts from selections recorded with
+    /// [`record_selection`](Self::record_selection).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::BinaryHeap;
+    ///
+    /// let mut matcher = code_fuzzy_match::FuzzyMatcher::new();
+    /// let items = [("lib.rs", 1), ("main.rs", 2), ("Cargo.toml", 3)];
+    /// let results = matcher.rank_items(items, |item| item.0, "rs", true);
+    /// assert_eq!(results[0].item, ("lib.rs", 1));
+    /// assert_eq!(results[0].positions, Some(vec![4, 5]));
+    ///
+    /// let heap: BinaryHeap<_> = results.into_iter().collect();
+    /// assert_eq!(heap.peek().unwrap().item, ("lib.rs", 1));
+    /// ```
+    pub fn rank_items<T>(
+        &mut self,
+        items: impl IntoIterator<Item = T>,
+        key: impl Fn(&T) -> &str,
+        query: &str,
+        positions: bool,
+    ) -> Vec<Scored<T>> {
+        self.rank_items_as(items, key, query, positions)
+    }
+
+    /// Same as [`rank_items`](Self::rank_items), but returns scores as the given [`Score`]
+    /// type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use code_fuzzy_match::{FuzzyMatcher, MatchOptions, Scored};
+    ///
+    /// let mut matcher = FuzzyMatcher::with_options(MatchOptions {
+    ///     gap_penalty: 5,
+    ///     ..MatchOptions::default()
+    /// });
+    /// let items = ["a_long_list_of_bc", "a_list_of_bc", "abc"];
+    /// let results: Vec<Scored<&str, isize>> =
+    ///     matcher.rank_items_as(items, |item| item, "abc", false);
+    /// assert_eq!(results[0].item, "abc");
+    /// assert_eq!(results[1].item, "a_list_of_bc");
+    /// assert!(results[1].score > results[2].score);
+    /// assert!(results[2].score < 0);
+    /// ```
+    pub fn rank_items_as<T, S: Score>(
+        &mut self,
+        items: impl IntoIterator<Item = T>,
+        key: impl Fn(&T) -> &str,
+        query: &str,
+        positions: bool,
+    ) -> Vec<Scored<T, S>> {
+        self.rank_items_boosted(items, key, |_| 0, query, positions)
+    }
+
+    /// Same as [`rank_items_as`](Self::rank_items_as), but adds a static boost taken from
+    /// each item to its score before ranking. This lets matches of more important items,
+    /// such as functions and types in a symbol picker, rank above matches of similar
+    /// quality for less important items, such as local variables.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use code_fuzzy_match::{FuzzyMatcher, Scored};
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// enum Kind {
+    ///     Function,
+    ///     Variable,
+    /// }
+    ///
+    /// let symbols = [("parse_len", Kind::Variable), ("parse_line", Kind::Function)];
+    /// let boost = |symbol: &(&str, Kind)| match symbol.1 {
+    ///     Kind::Function => 10,
+    ///     Kind::Variable => 0,
+    /// };
+    /// let mut matcher = FuzzyMatcher::new();
+    /// let results: Vec<Scored<_>> =
+    ///     matcher.rank_items_boosted(symbols, |symbol| symbol.0, boost, "parse", false);
+    /// assert_eq!(results[0].item.1, Kind::Function);
+    /// assert_eq!(results[0].score, results[1].score + 10);
+    /// ```
+    pub fn rank_items_boosted<T, S: Score>(
+        &mut self,
+        items: impl IntoIterator<Item = T>,
+        key: impl Fn(&T) -> &str,
+        boost: impl Fn(&T) -> usize,
+        query: &str,
+        positions: bool,
+    ) -> Vec<Scored<T, S>> {
+        let mut results: Vec<Scored<T, S>> = if positions {
+            let query_mask = self.query_mask(query);
+            items
+                .into_iter()
+                .filter_map(|item| {
+                    if !self.prefilter(&query_mask, &CharMask::new(key(&item))) {
+                        return None;
+                    }
+                    let target = key(&item);
+                    self.load(target, query);
+                    let score = self.compute(true, false)?;
+                    let positions = self.matched_positions();
+                    Some(Scored {
+                        item,
+                        score,
+                        positions: Some(positions),
+                    })
+                })
+                .collect()
+        } else {
+            self.match_batch(items, &key, query)
+                .into_iter()
+                .map(|(item, score)| Scored {
+                    item,
+                    score,
+                    positions: None,
+                })
+                .collect()
+        };
+        if self.options.natural_order(query) {
+            return results;
+        }
+        let boosts = self.feedback.boosts_for(query);
+        for result in &mut results {
+            let mut bonus = boost(&result.item);
+            if let Some(boosts) = boosts {
+                bonus += self.feedback.candidate_boost(boosts, key(&result.item));
+            }
+            result.score = result.score.add_bonus(bonus);
+        }
+        results.sort_by_key(|result| Reverse(result.score));
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{merge_topk, FuzzyMatcher, Index, MatchOptions, Scored};
+    use alloc::collections::BinaryHeap;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_scored() {
+        let mut matcher = FuzzyMatcher::new();
+        let results =
+            matcher.rank_items(["foo_b", "bar", "foo_a", "foo"], |item| item, "foo", false);
+        assert_eq!(
+            results.iter().map(|r| r.item).collect::<Vec<_>>(),
+            ["foo", "foo_b", "foo_a"]
+        );
+        assert!(results.iter().all(|r| r.positions.is_none()));
+
+        // Heaps pop the best score first, then the smallest item
+        let mut heap: BinaryHeap<Scored<&str>> = results.into_iter().collect();
+        assert_eq!(heap.pop().unwrap().item, "foo");
+        assert_eq!(heap.pop().unwrap().item, "foo_a");
+        assert_eq!(heap.pop().unwrap().item, "foo_b");
+    }
+
+    #[test]
+    fn test_boosted_items() {
+        let mut matcher = FuzzyMatcher::new();
+        let items = [("foo_bar", 0), ("foo", 0), ("foo_baz", 20)];
+        let results: Vec<Scored<_>> =
+            matcher.rank_items_boosted(items, |item| item.0, |item| item.1, "foo", true);
+        assert_eq!(
+            results.iter().map(|r| r.item.0).collect::<Vec<_>>(),
+            ["foo_baz", "foo", "foo_bar"]
+        );
+        assert_eq!(
+            results[0].score,
+            matcher.fuzzy_match("foo_baz", "foo").unwrap() + 20
+        );
+        assert_eq!(results[0].positions, Some(alloc::vec![0, 1, 2]));
+
+        // Boosts are added to the scores of pinned exact matches without overflowing
+        let mut matcher = FuzzyMatcher::with_options(MatchOptions {
+            pin_exact_matches: true,
+            ..MatchOptions::default()
+        });
+        let results: Vec<Scored<_, isize>> =
+            matcher.rank_items_boosted(items, |item| item.0, |_| 5, "foo", false);
+        assert_eq!(results[0].item.0, "foo");
+        assert_eq!(results[0].score, isize::MAX);
+    }
+
+    #[test]
+    fn test_merge_topk() {
+        let candidates: Vec<alloc::string::String> = (0..60)
+            .map(|idx| alloc::format!("src/{}/lib_{}.rs", idx % 7, idx % 5))
+            .collect();
+        let mut index = Index::new();
+        for candidate in &candidates {
+            index.add(candidate);
+        }
+        let expected = index.query("srclib1", 10);
+
+        // Shard the candidates, keeping identifiers from the whole list
+        let shards: Vec<Vec<Scored<usize>>> = (0..3)
+            .map(|shard| {
+                let mut index = Index::new();
+                let ids: Vec<usize> = (shard..candidates.len()).step_by(3).collect();
+                for id in &ids {
+                    index.add(&candidates[*id]);
+                }
+                index
+                    .query("srclib1", 10)
+                    .into_iter()
+                    .map(|result| Scored {
+                        item: ids[result.item],
+                        ..result
+                    })
+                    .collect()
+            })
+            .collect();
+        let merged = merge_topk(shards.clone(), 10);
+        assert_eq!(merged, expected);
+        let total: usize = shards.iter().map(Vec::len).sum();
+        assert_eq!(merge_topk(shards.clone(), 100).len(), total);
+        assert!(merge_topk(shards, 0).is_empty());
+        assert!(merge_topk(Vec::<Vec<Scored<usize>>>::new(), 5).is_empty());
+    }
+}
@@ -0,0 +1,367 @@
+//! Matching without heap allocations, using memory provided by the caller.
+
+use crate::matrix::{normalize_length, score_cell, CharClass, MatrixInput, MatrixRow, UNREACHABLE};
+use crate::{
+    char_match_kind, kana, separators, truncate, whitespace, EmptyQueryPolicy, MatchOptions,
+    ScoringProfile, EXACT_MATCH_SCORE,
+};
+
+/// Memory for matching targets of up to `N` characters with [`fuzzy_match_in`], along with
+/// the options to match with. The memory is held inline, so the scratch space lives wherever
+/// the caller places it, such as on the stack or in a `static`, and matching never uses the
+/// heap allocator.
+///
+/// Each character of capacity takes 37 bytes on 64-bit targets. Only the first `N`
+/// characters of longer targets are matched, after applying the `max_target_len` and
+/// `truncation` options.
+///
+/// Typos, transpositions, bidirectional and right to left matching, camel humps, gap limits,
+/// synonyms, full case folding, ignored characters and the
+/// [`VsCodeCompat`](crate::ScoringProfile::VsCodeCompat) profile are not supported. Setting
+/// those options panics in debug builds. Creating the options may allocate, but matching
+/// with them does not.
+pub struct MatchScratch<const N: usize> {
+    options: MatchOptions,
+    chars: [char; N],
+    classes: [CharClass; N],
+    /// Scores and sequential match counts of the current and previous query characters,
+    /// alternating between the two entries.
+    scores: [[isize; N]; 2],
+    seq_match_counts: [[usize; N]; 2],
+}
+
+impl<const N: usize> MatchScratch<N> {
+    /// Creates scratch space for matching with the default options.
+    pub fn new() -> Self {
+        Self::with_options(MatchOptions::default())
+    }
+
+    /// Creates scratch space for matching with the given options.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, panics if the options use a feature that isn't supported.
+    pub fn with_options(options: MatchOptions) -> Self {
+        check_options(&options);
+        MatchScratch {
+            options,
+            chars: ['\0'; N],
+            classes: [CharClass::Other; N],
+            scores: [[UNREACHABLE; N]; 2],
+            seq_match_counts: [[0; N]; 2],
+        }
+    }
+
+    /// Returns the options used for matching.
+    pub fn options(&self) -> &MatchOptions {
+        &self.options
+    }
+
+    /// Changes the options used for matching.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, panics if the options use a feature that isn't supported.
+    pub fn set_options(&mut self, options: MatchOptions) {
+        check_options(&options);
+        self.options = options;
+    }
+}
+
+/// Returns the name of the first option that is set to a value [`MatchScratch`] doesn't
+/// support, if any.
+fn unsupported_option(options: &MatchOptions) -> Option<&'static str> {
+    #[cfg(feature = "case-folding")]
+    if options.full_case_folding {
+        return Some("full_case_folding");
+    }
+    [
+        (options.bidirectional, "bidirectional"),
+        (options.right_to_left, "right_to_left"),
+        (options.max_typos != 0, "max_typos"),
+        (options.transpositions, "transpositions"),
+        (options.max_gap.is_some(), "max_gap"),
+        (options.camel_humps, "camel_humps"),
+        (!options.synonyms.is_empty(), "synonyms"),
+        (!options.ignored_chars.is_empty(), "ignored_chars"),
+        (options.profile == ScoringProfile::VsCodeCompat, "profile"),
+    ]
+    .into_iter()
+    .find_map(|(unsupported, name)| unsupported.then_some(name))
+}
+
+fn check_options(options: &MatchOptions) {
+    debug_assert!(
+        unsupported_option(options).is_none(),
+        "MatchScratch doesn't support the `{}` option",
+        unsupported_option(options).unwrap_or_default()
+    );
+}
+
+impl<const N: usize> Default for MatchScratch<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Fuzzy match a string against a query string without allocating, using the memory and
+/// options in `scratch`. Returns a score that is higher for a more confident match, or
+/// `None` if the query does not match the target string. Scores are the same as
+/// [`FuzzyMatcher::fuzzy_match`](crate::FuzzyMatcher::fuzzy_match) with the same options.
+///
+/// # Examples
+///
+/// ```
+/// use code_fuzzy_match::{fuzzy_match_in, MatchScratch};
+///
+/// let mut scratch = MatchScratch::<64>::new();
+/// assert!(fuzzy_match_in("Open File", "of", &mut scratch).is_some());
+/// assert!(fuzzy_match_in("Close Window", "of", &mut scratch).is_none());
+/// ```
+pub fn fuzzy_match_in<const N: usize>(
+    target: &str,
+    query: &str,
+    scratch: &mut MatchScratch<N>,
+) -> Option<usize> {
+    let options = &scratch.options;
+    let window = truncate::target_window(target, options);
+    let mut truncated = window.len() < target.len();
+    let mut target = &target[window];
+
+    let fold = |c| {
+        if options.kana_folding {
+            kana::fold(c)
+        } else {
+            c
+        }
+    };
+    let mut target_len = 0;
+    for (idx, c) in target.char_indices() {
+        if target_len == N {
+            target = &target[..idx];
+            truncated = true;
+            break;
+        }
+        let c = fold(c);
+        scratch.chars[target_len] = c;
+        scratch.classes[target_len] = CharClass::of(c);
+        target_len += 1;
+    }
+    let chars = &scratch.chars[..target_len];
+    let classes = &mut scratch.classes[..target_len];
+    separators::mark_path_separators(
+        target,
+        target.char_indices().map(|(idx, _)| idx),
+        classes,
+        &options.path_separators,
+    );
+    if options.digit_word_starts {
+        crate::digits::mark_digit_boundaries(chars.iter().copied(), classes);
+    }
+    #[cfg(feature = "unicode-words")]
+    if options.unicode_words {
+        crate::words::mark_word_starts(target, target.char_indices().map(|(idx, _)| idx), classes);
+    }
+
+    let query = whitespace::query_chars(query, options);
+    let query_len = query.clone().count();
+    if options.ignore_extension && !query.clone().any(|c| c == '.') {
+        let name_start = classes
+            .iter()
+            .rposition(|class| *class == CharClass::PathSeparator)
+            .map_or(0, |idx| idx + 1);
+        if let Some(dot) = chars[name_start..]
+            .iter()
+            .rposition(|c| *c == '.')
+            .filter(|dot| *dot > 0)
+        {
+            target_len = name_start + dot;
+        }
+    }
+    let chars = &chars[..target_len];
+    let classes = &classes[..target_len];
+
+    if query_len == 0 {
+        return (options.empty_query != EmptyQueryPolicy::NoMatch).then_some(1);
+    }
+    if target_len == 0 {
+        return None;
+    }
+    let input = MatrixInput::new(classes, query_len, options);
+
+    // Every cell of the matrix is computed, which gives the same result as the early exits
+    // of the matrix used by `FuzzyMatcher`.
+    for (query_idx, query_char) in query.clone().map(fold).enumerate() {
+        let row = MatrixRow {
+            query_idx,
+            first: query_idx == 0,
+            last: query_idx + 1 == query_len,
+            keep_later: false,
+        };
+        let [even_score, odd_score] = &mut scratch.scores;
+        let [even_seq, odd_seq] = &mut scratch.seq_match_counts;
+        let (prev_score, score, prev_seq_match_counts, seq_match_counts) = if query_idx % 2 == 0 {
+            (&*odd_score, even_score, &*odd_seq, even_seq)
+        } else {
+            (&*even_score, odd_score, &*even_seq, odd_seq)
+        };
+
+        for i in 0..target_len {
+            let carried = if i == 0 { UNREACHABLE } else { score[i - 1] };
+            let diagonal = || {
+                if i == 0 {
+                    (UNREACHABLE, 0)
+                } else {
+                    (prev_score[i - 1], prev_seq_match_counts[i - 1])
+                }
+            };
+            let unit_match = || char_match_kind(query_char, chars[i], options);
+            let cell = score_cell(&input, row, i, carried, diagonal, unit_match);
+            score[i] = cell.score;
+            seq_match_counts[i] = cell.seq_match_count;
+        }
+    }
+
+    let score = scratch.scores[(query_len - 1) % 2][target_len - 1];
+    if score == UNREACHABLE {
+        return None;
+    }
+    let score = normalize_length(options, score, target_len, query_len);
+    if options.pin_exact_matches
+        && !truncated
+        && query_len == target_len
+        && query
+            .clone()
+            .map(fold)
+            .zip(chars.iter())
+            .all(|(q, t)| char_match_kind(q, *t, options).is_some())
+    {
+        return Some(EXACT_MATCH_SCORE);
+    }
+    let substring = options.substring_bonus != 0
+        && query_len <= target_len
+        && (0..=target_len - query_len).any(|start| {
+            query
+                .clone()
+                .map(fold)
+                .zip(chars[start..].iter())
+                .all(|(q, t)| char_match_kind(q, *t, options).is_some())
+        });
+    if substring {
+        return Some((score.max(1) as usize).saturating_add(options.substring_bonus));
+    }
+    Some(score.max(1) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        fuzzy_match_in, EmptyQueryPolicy, FuzzyMatcher, MatchOptions, MatchScratch, ScoringProfile,
+        Truncation,
+    };
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[test]
+    fn test_fuzzy_match_in() {
+        let targets = [
+            "",
+            "lib",
+            "src/lib.rs",
+            "lib.rs",
+            ".gitignore",
+            "crate::matrix::MatrixInput",
+            "The quick brown fox jumps over the lazy dog.",
+            "Grüße aus Köln",
+        ];
+        let queries = [
+            "", "lib", "LIB", "srs", "rs", "s.r", "mi", "fox dog", "köln", "xyz",
+        ];
+        for options in [
+            MatchOptions::default(),
+            MatchOptions {
+                gap_penalty: 2,
+                leading_gap_penalty: 1,
+                whole_word_bonus: 5,
+                contiguous_query_bonus: 7,
+                substring_bonus: 50,
+                length_normalization: 16,
+                pin_exact_matches: true,
+                path_separators: vec!["::".to_string()],
+                ignore_extension: true,
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                require_word_start: true,
+                truncation: Some(Truncation::Last(12)),
+                empty_query: EmptyQueryPolicy::MatchAllZeroScore,
+                profile: ScoringProfile::FilePath,
+                ..MatchOptions::default()
+            },
+        ] {
+            let mut matcher = FuzzyMatcher::with_options(options.clone());
+            let mut scratch = MatchScratch::<64>::with_options(options);
+            for target in targets {
+                for query in queries {
+                    assert_eq!(
+                        fuzzy_match_in(target, query, &mut scratch),
+                        matcher.fuzzy_match(target, query),
+                        "{target} {query}"
+                    );
+                }
+            }
+        }
+
+        // Rows left over from an earlier match don't carry into the next one
+        let mut scratch = MatchScratch::<64>::new();
+        let fresh = fuzzy_match_in("xxab", "ab", &mut scratch);
+        assert_eq!(fresh, FuzzyMatcher::new().fuzzy_match("xxab", "ab"));
+        for (target, query) in [("ab", "ab"), ("src/lib.rs", "srs"), ("xxab", "ab")] {
+            fuzzy_match_in(target, query, &mut scratch);
+            assert_eq!(fuzzy_match_in("xxab", "ab", &mut scratch), fresh);
+        }
+
+        // Longer targets are truncated to the capacity of the scratch space
+        let mut scratch = MatchScratch::<8>::with_options(MatchOptions {
+            pin_exact_matches: true,
+            ..MatchOptions::default()
+        });
+        assert!(fuzzy_match_in("src/lib.rs", "src/lib", &mut scratch).is_some());
+        assert!(fuzzy_match_in("src/lib.rs", "lib.rs", &mut scratch).is_none());
+        assert!(fuzzy_match_in("abcdefghi", "abcdefgh", &mut scratch) < Some(usize::MAX));
+
+        // Options that aren't supported are named
+        let unsupported = [
+            MatchOptions {
+                camel_humps: true,
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                max_gap: Some(1),
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                profile: ScoringProfile::VsCodeCompat,
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                synonyms: vec![("cfg".to_string(), "config".to_string())],
+                ..MatchOptions::default()
+            },
+        ];
+        let names = unsupported.iter().map(super::unsupported_option);
+        assert!(names.eq(["camel_humps", "max_gap", "profile", "synonyms"].map(Some)));
+        assert_eq!(super::unsupported_option(&MatchOptions::default()), None);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "MatchScratch doesn't support the `max_typos` option")]
+    fn test_unsupported_options() {
+        let mut scratch = MatchScratch::<64>::new();
+        scratch.set_options(MatchOptions {
+            max_typos: 1,
+            ..MatchOptions::default()
+        });
+    }
+}
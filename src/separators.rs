@@ -0,0 +1,87 @@
+//! Detection of configurable multi-character path separators.
+
+use crate::matrix::CharClass;
+use alloc::string::String;
+
+/// Marks every unit of the target that is part of one of the `separators` as a path
+/// separator. `unit_starts` yields the byte offset of each unit of the target in order, and
+/// `classes` holds the classification of each unit.
+pub(crate) fn mark_path_separators(
+    target: &str,
+    unit_starts: impl Iterator<Item = usize> + Clone,
+    classes: &mut [CharClass],
+    separators: &[String],
+) {
+    for separator in separators.iter().filter(|separator| !separator.is_empty()) {
+        let mut occurrences = target
+            .match_indices(separator.as_str())
+            .map(|(start, separator)| start..start + separator.len())
+            .peekable();
+        for (unit_idx, unit_start) in unit_starts.clone().enumerate() {
+            while occurrences
+                .next_if(|range| range.end <= unit_start)
+                .is_some()
+            {}
+            match occurrences.peek() {
+                Some(range) if range.contains(&unit_start) => {
+                    classes[unit_idx] = CharClass::PathSeparator;
+                }
+                Some(_) => (),
+                None => break,
+            }
+        }
+    }
+}
+
+/// Same as [`mark_path_separators`], for a target given as characters, where each
+/// character is a unit.
+pub(crate) fn mark_path_separator_chars(
+    target: &[char],
+    classes: &mut [CharClass],
+    separators: &[String],
+) {
+    for separator in separators.iter().filter(|separator| !separator.is_empty()) {
+        let len = separator.chars().count();
+        let mut idx = 0;
+        while idx + len <= target.len() {
+            if target[idx..idx + len].iter().copied().eq(separator.chars()) {
+                classes[idx..idx + len].fill(CharClass::PathSeparator);
+                idx += len;
+            } else {
+                idx += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FuzzyMatcher, MatchOptions};
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[test]
+    fn test_path_separators() {
+        let mut default_matcher = FuzzyMatcher::new();
+        let mut rust_matcher = FuzzyMatcher::with_options(MatchOptions {
+            path_separators: vec!["::".to_string()],
+            ..MatchOptions::default()
+        });
+
+        // "::" is scored like "/" in a path
+        const TARGET: &str = "std::collections::HashMap";
+        let explanation = rust_matcher.fuzzy_match_explain(TARGET, "::hash").unwrap();
+        assert_eq!(explanation.chars[0].bonuses.path_separator, 5);
+        assert_eq!(explanation.chars[1].bonuses.path_separator, 5);
+        assert!(
+            rust_matcher.fuzzy_match(TARGET, "::hash")
+                > default_matcher.fuzzy_match(TARGET, "::hash")
+        );
+
+        // A single ':' is not part of the separator
+        assert_eq!(
+            rust_matcher.fuzzy_match("key: value", ": v"),
+            default_matcher.fuzzy_match("key: value", ": v")
+        );
+    }
+}
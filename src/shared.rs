@@ -0,0 +1,163 @@
+//! Matcher that can be shared between threads.
+
+use crate::{FuzzyMatcher, Match, MatchOptions, RankedMatch, TieBreak};
+use alloc::vec::Vec;
+use std::sync::Mutex;
+
+/// Fuzzy matcher that can be shared between threads, such as in an `Arc` inside a server.
+/// Matching methods take `&self`. Each call borrows scratch memory from a pool, so threads
+/// only wait on each other to take and return a [`FuzzyMatcher`] from the pool, never while
+/// matching. The pool grows to the number of threads matching at the same time.
+///
+/// # Examples
+///
+/// ```
+/// use code_fuzzy_match::SharedMatcher;
+/// use std::sync::Arc;
+///
+/// let matcher = Arc::new(SharedMatcher::new());
+/// let threads: Vec<_> = ["lib", "main"]
+///     .into_iter()
+///     .map(|query| {
+///         let matcher = matcher.clone();
+///         std::thread::spawn(move || matcher.fuzzy_match("src/lib.rs", query))
+///     })
+///     .collect();
+/// let scores: Vec<_> = threads.into_iter().map(|thread| thread.join().unwrap()).collect();
+/// assert!(scores[0].is_some());
+/// assert!(scores[1].is_none());
+/// ```
+pub struct SharedMatcher {
+    options: MatchOptions,
+    pool: Mutex<Vec<FuzzyMatcher>>,
+}
+
+impl SharedMatcher {
+    /// Creates a new shared matcher.
+    pub fn new() -> Self {
+        Self::with_options(MatchOptions::default())
+    }
+
+    /// Creates a new shared matcher with the given options.
+    pub fn with_options(options: MatchOptions) -> Self {
+        SharedMatcher {
+            options,
+            pool: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns the options used by this matcher.
+    pub fn options(&self) -> &MatchOptions {
+        &self.options
+    }
+
+    /// Calls `f` with a matcher from the pool, which is returned to the pool afterwards.
+    /// Use this for any [`FuzzyMatcher`] method that isn't provided directly.
+    pub fn with_matcher<R>(&self, f: impl FnOnce(&mut FuzzyMatcher) -> R) -> R {
+        let pooled = self.lock_pool().pop();
+        let mut matcher =
+            pooled.unwrap_or_else(|| FuzzyMatcher::with_options(self.options.clone()));
+        let result = f(&mut matcher);
+        self.lock_pool().push(matcher);
+        result
+    }
+
+    /// Fuzzy match a string against a query string, as described in
+    /// [`FuzzyMatcher::fuzzy_match`].
+    pub fn fuzzy_match(&self, target: &str, query: &str) -> Option<usize> {
+        self.with_matcher(|matcher| matcher.fuzzy_match(target, query))
+    }
+
+    /// Fuzzy match a string against a query string, returning details about the match, as
+    /// described in [`FuzzyMatcher::fuzzy_match_details`].
+    pub fn fuzzy_match_details(&self, target: &str, query: &str) -> Option<Match> {
+        self.with_matcher(|matcher| matcher.fuzzy_match_details(target, query))
+    }
+
+    /// Fuzzy match each of the targets against a query string, returning the targets that
+    /// match from best to worst, as described in [`FuzzyMatcher::rank`].
+    pub fn rank<'a>(
+        &self,
+        targets: impl IntoIterator<Item = &'a str>,
+        query: &str,
+        tie_breaks: &[TieBreak],
+    ) -> Vec<RankedMatch<'a>> {
+        self.with_matcher(|matcher| matcher.rank(targets, query, tie_breaks))
+    }
+
+    /// Releases the scratch memory of every matcher in the pool that isn't in use.
+    pub fn shrink_to_fit(&self) {
+        self.lock_pool().clear();
+    }
+
+    fn lock_pool(&self) -> std::sync::MutexGuard<'_, Vec<FuzzyMatcher>> {
+        // A panic while matching never leaves a matcher in the pool, so the pool is still
+        // usable if the lock was poisoned.
+        self.pool
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl Default for SharedMatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FuzzyMatcher, MatchOptions, SharedMatcher};
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::sync::Arc;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_shared_matcher() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SharedMatcher>();
+
+        let options = MatchOptions {
+            gap_penalty: 1,
+            ..MatchOptions::default()
+        };
+        let matcher = Arc::new(SharedMatcher::with_options(options.clone()));
+        let targets: Arc<Vec<String>> =
+            Arc::new((0..1000).map(|idx| format!("src/file_{idx}.rs")).collect());
+        let queries = ["f1", "src9", "rs", "x"];
+
+        let threads: Vec<_> = queries
+            .into_iter()
+            .map(|query| {
+                let matcher = matcher.clone();
+                let targets = targets.clone();
+                std::thread::spawn(move || {
+                    let ranked = matcher.rank(targets.iter().map(String::as_str), query, &[]);
+                    ranked
+                        .iter()
+                        .map(|ranked| (ranked.index, ranked.score))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        let mut expected_matcher = FuzzyMatcher::with_options(options);
+        for (query, thread) in queries.iter().zip(threads) {
+            let expected: Vec<_> = expected_matcher
+                .rank(targets.iter().map(String::as_str), query, &[])
+                .iter()
+                .map(|ranked| (ranked.index, ranked.score))
+                .collect();
+            assert_eq!(thread.join().unwrap(), expected);
+        }
+
+        assert_eq!(
+            matcher.fuzzy_match_details("src/lib.rs", "lib"),
+            expected_matcher.fuzzy_match_details("src/lib.rs", "lib")
+        );
+        assert!(!matcher.pool.lock().unwrap().is_empty());
+        matcher.shrink_to_fit();
+        assert!(matcher.pool.lock().unwrap().is_empty());
+    }
+}
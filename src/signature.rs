@@ -0,0 +1,331 @@
+//! Signatures of the scoring parameters of a matcher, for checking that two matchers rank
+//! targets identically.
+//!
+//! Scores are computed with integer arithmetic only, so identical inputs and parameters
+//! give identical scores on every platform. The signature is a 64-bit FNV-1a hash of the
+//! parameters, each written as little-endian 64-bit integers regardless of the width of
+//! `usize` or the byte order of the platform, so it is the same everywhere as well.
+
+use crate::matrix::{char_score, CharClass, MatrixInput, UnitMatch};
+use crate::{
+    ByteDecoding, CaseLocale, EmptyQueryPolicy, FuzzyMatcher, LongTargetPolicy, ScoreModel,
+    ScoringProfile, Truncation,
+};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Classes of the target used to probe the scoring model. Every class appears at the start,
+/// middle and end of a word and after each kind of separator.
+const PROBE_CLASSES: &[CharClass] = &[
+    CharClass::Other,
+    CharClass::Other,
+    CharClass::PathSeparator,
+    CharClass::Uppercase,
+    CharClass::Other,
+    CharClass::Uppercase,
+    CharClass::Separator,
+    CharClass::WordStart,
+    CharClass::Other,
+    CharClass::PathSeparator,
+    CharClass::Other,
+    CharClass::Separator,
+    CharClass::Other,
+];
+
+/// Length of the query used to probe the scoring model.
+const PROBE_QUERY_LEN: usize = 4;
+
+/// FNV-1a hasher over a platform independent encoding of values.
+struct SignatureHasher(u64);
+
+impl SignatureHasher {
+    fn bytes(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn usize(&mut self, value: usize) {
+        self.u64(value as u64);
+    }
+
+    fn bool(&mut self, value: bool) {
+        self.u64(value as u64);
+    }
+
+    fn str(&mut self, value: &str) {
+        self.usize(value.len());
+        self.bytes(value.as_bytes());
+    }
+
+    fn option_usize(&mut self, value: Option<usize>) {
+        match value {
+            None => self.u64(0),
+            Some(value) => {
+                self.u64(1);
+                self.usize(value);
+            }
+        }
+    }
+
+    fn truncation(&mut self, truncation: Truncation) {
+        let (tag, len) = match truncation {
+            Truncation::First(len) => (0, len),
+            Truncation::Last(len) => (1, len),
+            Truncation::Basename(len) => (2, len),
+        };
+        self.u64(tag);
+        self.usize(len);
+    }
+}
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Returns a hash of every parameter that affects the scores and matched positions of
+    /// this matcher: the version of the scoring rules, the options, and the scoring model.
+    /// Scores are computed with integer arithmetic only and the hash doesn't depend on the
+    /// platform, so two matchers with the same signature give identical scores for the same
+    /// target and query on any platform, and distributed systems can compare signatures to
+    /// check that result lists ranked on different machines can be merged.
+    ///
+    /// The `position_index` and `dedup_targets` options don't change scores and aren't part
+    /// of the signature.
+    /// Scoring models are identified by the scores they award on a fixed set of sample
+    /// matches, so custom models that only differ elsewhere have the same signature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use code_fuzzy_match::{FuzzyMatcher, MatchOptions};
+    ///
+    /// let matcher = FuzzyMatcher::new();
+    /// let other = FuzzyMatcher::with_options(MatchOptions {
+    ///     position_index: true,
+    ///     ..MatchOptions::default()
+    /// });
+    /// assert_eq!(matcher.score_signature(), other.score_signature());
+    ///
+    /// let penalized = FuzzyMatcher::with_options(MatchOptions {
+    ///     gap_penalty: 1,
+    ///     ..MatchOptions::default()
+    /// });
+    /// assert_ne!(matcher.score_signature(), penalized.score_signature());
+    /// ```
+    pub fn score_signature(&self) -> u64 {
+        let options = &self.options;
+        let mut hasher = SignatureHasher(FNV_OFFSET_BASIS);
+        hasher.u64(options.score_version.number() as u64);
+
+        hasher.bool(options.bidirectional);
+        hasher.bool(options.right_to_left);
+        hasher.usize(options.max_typos);
+        hasher.usize(options.typo_penalty);
+        match &options.keyboard_layout {
+            None => hasher.u64(0),
+            Some(layout) => {
+                hasher.u64(1);
+                hasher.usize(layout.rows().len());
+                for row in layout.rows() {
+                    hasher.str(row);
+                }
+            }
+        }
+        hasher.usize(options.adjacent_key_penalty);
+        hasher.bool(options.transpositions);
+        hasher.usize(options.transposition_penalty);
+        hasher.usize(options.gap_penalty);
+        hasher.usize(options.leading_gap_penalty);
+        hasher.usize(options.max_leading_gap_penalty);
+        hasher.option_usize(options.max_gap);
+        hasher.usize(options.whole_word_bonus);
+        hasher.usize(options.contiguous_query_bonus);
+        hasher.usize(options.substring_bonus);
+        hasher.usize(options.length_normalization);
+        hasher.bool(options.pin_exact_matches);
+        hasher.bool(options.require_word_start);
+        hasher.bool(options.camel_humps);
+        hasher.bool(options.digit_word_starts);
+        match options.truncation {
+            None => hasher.u64(0),
+            Some(truncation) => {
+                hasher.u64(1);
+                hasher.truncation(truncation);
+            }
+        }
+        hasher.option_usize(options.max_target_len);
+        match options.long_target_policy {
+            LongTargetPolicy::Reject => hasher.u64(0),
+            LongTargetPolicy::Truncate => hasher.u64(1),
+            LongTargetPolicy::Window(truncation) => {
+                hasher.u64(2);
+                hasher.truncation(truncation);
+            }
+        }
+        hasher.u64(match options.empty_query {
+            EmptyQueryPolicy::NoMatch => 0,
+            EmptyQueryPolicy::MatchAllZeroScore => 1,
+            EmptyQueryPolicy::MatchAllNaturalOrder => 2,
+        });
+        hasher.usize(options.path_separators.len());
+        for separator in &options.path_separators {
+            hasher.str(separator);
+        }
+        hasher.bool(options.slash_equivalence);
+        hasher.bool(options.trim_query);
+        hasher.bool(options.collapse_query_spaces);
+        hasher.bool(options.space_matches_separators);
+        hasher.usize(options.equivalences.len());
+        for group in &options.equivalences {
+            hasher.str(group);
+        }
+        hasher.usize(options.synonyms.len());
+        for (a, b) in &options.synonyms {
+            hasher.str(a);
+            hasher.str(b);
+        }
+        hasher.usize(options.synonym_penalty);
+        hasher.str(&options.ignored_chars);
+        hasher.bool(options.ignore_extension);
+        hasher.bool(options.kana_folding);
+        hasher.u64(match options.case_locale {
+            CaseLocale::Default => 0,
+            CaseLocale::Turkish => 1,
+        });
+        // Options of features that aren't enabled are hashed as if they were disabled, so
+        // that builds with different features agree when the options aren't used
+        #[cfg(feature = "case-folding")]
+        hasher.bool(options.full_case_folding);
+        #[cfg(not(feature = "case-folding"))]
+        hasher.bool(false);
+        hasher.u64(match options.byte_decoding {
+            ByteDecoding::Lossy => 0,
+            ByteDecoding::Latin1 => 1,
+        });
+        hasher.u64(match options.profile {
+            ScoringProfile::General => 0,
+            ScoringProfile::FilePath => 1,
+            ScoringProfile::VsCodeCompat => 2,
+        });
+        #[cfg(feature = "unicode-words")]
+        hasher.bool(options.unicode_words);
+        #[cfg(not(feature = "unicode-words"))]
+        hasher.bool(false);
+
+        // Probe the scoring model, or the custom model of the options that takes precedence
+        // over it
+        let input = MatrixInput::with_model(PROBE_CLASSES, PROBE_QUERY_LEN, options, &self.model);
+        for i in 0..PROBE_CLASSES.len() {
+            for seq_match_count in 0..PROBE_QUERY_LEN {
+                for kind in [UnitMatch::Folded, UnitMatch::Exact] {
+                    hasher.usize(char_score(&input, i, seq_match_count, kind));
+                }
+            }
+        }
+        hasher.0
+    }
+
+    /// Returns true if this matcher and `other` have the same
+    /// [`score_signature`](Self::score_signature), so that they give identical scores for
+    /// the same target and query.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use code_fuzzy_match::{FuzzyMatcher, MatchOptions, ScoringProfile};
+    ///
+    /// let matcher = FuzzyMatcher::new();
+    /// assert!(matcher.score_eq(&FuzzyMatcher::new()));
+    /// assert!(!matcher.score_eq(&FuzzyMatcher::with_options(MatchOptions {
+    ///     profile: ScoringProfile::FilePath,
+    ///     ..MatchOptions::default()
+    /// })));
+    /// ```
+    pub fn score_eq<N: ScoreModel>(&self, other: &FuzzyMatcher<N>) -> bool {
+        self.score_signature() == other.score_signature()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ConstProfile, ConstScoreModel, CustomScoreModel, FuzzyMatcher, MatchOptions, ScoreContext,
+        ScoreModel,
+    };
+    use alloc::string::String;
+    use alloc::vec;
+
+    #[test]
+    fn test_score_signature() {
+        // The signature and scores of the default options are the same on every platform
+        let mut matcher = FuzzyMatcher::new();
+        assert_eq!(matcher.score_signature(), 0x2aa3_c76f_2ba0_1026);
+        for (target, query, score) in [
+            ("src/lib.rs", "lib", 21),
+            ("FuzzyMatcher::match_batch", "fmb", 17),
+            ("Grüße aus Köln", "köln", 41),
+        ] {
+            assert_eq!(matcher.fuzzy_match(target, query), Some(score), "{target}");
+        }
+
+        // Every option that changes scores changes the signature
+        let default = FuzzyMatcher::new().score_signature();
+        for options in [
+            MatchOptions {
+                whole_word_bonus: 5,
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                max_gap: Some(0),
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                path_separators: vec![String::from("::")],
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                equivalences: vec![String::from("-_")],
+                ..MatchOptions::default()
+            },
+        ] {
+            assert_ne!(
+                FuzzyMatcher::with_options(options).score_signature(),
+                default
+            );
+        }
+
+        // Equal models have equal signatures, whether set in the options or the matcher
+        struct WordStarts;
+        impl ConstProfile for WordStarts {
+            const WORD_START_BONUS: usize = 10;
+        }
+        struct Flat;
+        impl ScoreModel for Flat {
+            fn score(&self, _: &ScoreContext) -> usize {
+                1
+            }
+        }
+        let const_model = FuzzyMatcher::with_model(
+            ConstScoreModel::<WordStarts>::new(),
+            MatchOptions::default(),
+        );
+        let custom_model = FuzzyMatcher::with_options(MatchOptions {
+            score_model: Some(CustomScoreModel::new(ConstScoreModel::<WordStarts>::new())),
+            ..MatchOptions::default()
+        });
+        assert!(const_model.score_eq(&custom_model));
+        assert!(!const_model.score_eq(&matcher));
+        let flat = FuzzyMatcher::with_options(MatchOptions {
+            score_model: Some(CustomScoreModel::new(Flat)),
+            ..MatchOptions::default()
+        });
+        assert!(!flat.score_eq(&matcher));
+        assert!(
+            FuzzyMatcher::with_model(crate::DefaultScoreModel, MatchOptions::default())
+                .score_eq(&matcher)
+        );
+    }
+}
@@ -0,0 +1,172 @@
+//! Vectorized comparison of ASCII query characters against the target.
+//!
+//! Instead of comparing one query character with one target character for each cell of
+//! the matrix, each query character is compared with the whole target up front, 16 target
+//! characters at a time, and the matrix looks the results up in a table.
+
+use crate::matrix::UnitMatch;
+use crate::{char_match_kind, CaseLocale, MatchOptions};
+use alloc::vec::Vec;
+
+/// Value in the match table for target characters that don't match.
+const NO_MATCH: u8 = 0;
+/// Value in the match table for target characters that match ignoring case.
+const FOLDED: u8 = 1;
+/// Value in the match table for target characters that match exactly.
+const EXACT: u8 = 2;
+
+/// Fills `table` with the result of comparing each query character with each target
+/// character, with one row of `target_chars.len()` entries per query character. Returns
+/// false without filling the table if the vectorized comparison can't be used, which is
+/// the case when the query has non-ASCII characters, equivalences are in use, the query has
+/// an `i` that follows the rules of another case locale, or a space that matches separators.
+pub(crate) fn fill_match_table(
+    table: &mut Vec<u8>,
+    query_chars: &[char],
+    target_chars: &[char],
+    options: &MatchOptions,
+) -> bool {
+    if !options.equivalences.is_empty() || !query_chars.iter().all(char::is_ascii) {
+        return false;
+    }
+    if options.space_matches_separators && query_chars.contains(&' ') {
+        return false;
+    }
+    if options.case_locale != CaseLocale::Default
+        && query_chars.iter().any(|c| matches!(c, 'i' | 'I'))
+    {
+        return false;
+    }
+
+    table.clear();
+    table.resize(query_chars.len() * target_chars.len(), NO_MATCH);
+    if target_chars.is_empty() {
+        return true;
+    }
+    for (row, query_char) in table
+        .chunks_exact_mut(target_chars.len())
+        .zip(query_chars.iter())
+    {
+        let vectorized = compare_row(row, *query_char as u8, target_chars, options);
+        for (entry, target_char) in row[vectorized..]
+            .iter_mut()
+            .zip(target_chars[vectorized..].iter())
+        {
+            *entry = match char_match_kind(*query_char, *target_char, options) {
+                Some(UnitMatch::Exact) => EXACT,
+                Some(UnitMatch::Folded) => FOLDED,
+                None => NO_MATCH,
+            };
+        }
+    }
+    true
+}
+
+/// Converts an entry of the match table into the result of the comparison.
+#[inline]
+pub(crate) fn unit_match(entry: u8) -> Option<UnitMatch> {
+    match entry {
+        EXACT => Some(UnitMatch::Exact),
+        FOLDED => Some(UnitMatch::Folded),
+        _ => None,
+    }
+}
+
+/// Compares an ASCII query character with as many target characters as possible using
+/// vector instructions, returning the number of target characters that were compared.
+#[cfg(target_arch = "x86_64")]
+fn compare_row(row: &mut [u8], query: u8, target_chars: &[char], options: &MatchOptions) -> usize {
+    use core::arch::x86_64::*;
+
+    let slash = options.slash_equivalence && matches!(query, b'/' | b'\\');
+    let folded_query = query.to_ascii_lowercase();
+    let mut compared = 0;
+    for (out, chunk) in row.chunks_exact_mut(16).zip(target_chars.chunks_exact(16)) {
+        // SAFETY: SSE2 is always available on x86_64, and every load and store is within
+        // the bounds of a 16 character chunk of the target or a 16 byte chunk of the row.
+        unsafe {
+            let ptr = chunk.as_ptr() as *const __m128i;
+            // Narrow the characters to bytes. Non-ASCII characters saturate to bytes of
+            // 128 or above, which never match an ASCII query character.
+            let low = _mm_packs_epi32(_mm_loadu_si128(ptr), _mm_loadu_si128(ptr.add(1)));
+            let high = _mm_packs_epi32(_mm_loadu_si128(ptr.add(2)), _mm_loadu_si128(ptr.add(3)));
+            let bytes = _mm_packus_epi16(low, high);
+
+            let exact = _mm_cmpeq_epi8(bytes, _mm_set1_epi8(query as i8));
+            let folded = if slash {
+                _mm_or_si128(
+                    _mm_cmpeq_epi8(bytes, _mm_set1_epi8(b'/' as i8)),
+                    _mm_cmpeq_epi8(bytes, _mm_set1_epi8(b'\\' as i8)),
+                )
+            } else {
+                // Bytes of 128 or above are negative, so they are never uppercase
+                let uppercase = _mm_and_si128(
+                    _mm_cmpgt_epi8(bytes, _mm_set1_epi8(b'A' as i8 - 1)),
+                    _mm_cmplt_epi8(bytes, _mm_set1_epi8(b'Z' as i8 + 1)),
+                );
+                let lowercase = _mm_or_si128(bytes, _mm_and_si128(uppercase, _mm_set1_epi8(0x20)));
+                _mm_cmpeq_epi8(lowercase, _mm_set1_epi8(folded_query as i8))
+            };
+
+            // Comparison results are all ones when true, so subtracting them counts them
+            let kinds = _mm_sub_epi8(_mm_sub_epi8(_mm_setzero_si128(), folded), exact);
+            _mm_storeu_si128(out.as_mut_ptr() as *mut __m128i, kinds);
+        }
+        compared += 16;
+    }
+    compared
+}
+
+/// Vector instructions are only used on x86_64, other targets compare every character
+/// individually.
+#[cfg(not(target_arch = "x86_64"))]
+fn compare_row(_: &mut [u8], _: u8, _: &[char], _: &MatchOptions) -> usize {
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fill_match_table, unit_match};
+    use crate::{char_match_kind, MatchOptions};
+    use alloc::string::String;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_match_table() {
+        let target: Vec<char> = "C:\\Program Files/ÄÖÜ_Grüße/@[`{z]/ZAza-09 K long enough"
+            .chars()
+            .collect();
+        let query: Vec<char> = "/\\aZ@[`{_-09 kKå".chars().collect();
+        let mut table = Vec::new();
+
+        for options in [
+            MatchOptions::default(),
+            MatchOptions {
+                slash_equivalence: false,
+                ..MatchOptions::default()
+            },
+        ] {
+            // Non-ASCII query characters are not supported
+            assert!(!fill_match_table(&mut table, &query, &target, &options));
+
+            let query = &query[..query.len() - 1];
+            assert!(fill_match_table(&mut table, query, &target, &options));
+            for (query_idx, query_char) in query.iter().enumerate() {
+                for (target_idx, target_char) in target.iter().enumerate() {
+                    assert_eq!(
+                        unit_match(table[query_idx * target.len() + target_idx]),
+                        char_match_kind(*query_char, *target_char, &options),
+                        "{query_char} {target_char}"
+                    );
+                }
+            }
+        }
+
+        let options = MatchOptions {
+            equivalences: vec![String::from("-_")],
+            ..MatchOptions::default()
+        };
+        assert!(!fill_match_table(&mut table, &['a'], &target, &options));
+    }
+}
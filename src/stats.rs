@@ -0,0 +1,97 @@
+//! Counters of the work done by a matcher, for tuning prefilters and measuring performance.
+
+use crate::{FuzzyMatcher, ScoreModel};
+
+/// Counters of the work done by a matcher, as returned by [`FuzzyMatcher::stats`].
+/// Counters accumulate over every call to the matcher until
+/// [`reset_stats`](FuzzyMatcher::reset_stats) is called, so resetting them before a batch
+/// gives the counters of that batch.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MatchStats {
+    /// Number of targets checked against the prefilter of a query by the batch, filtering,
+    /// ranking and index APIs.
+    pub candidates: usize,
+    /// Number of candidates rejected by the prefilter without running the scoring matrix.
+    pub prefiltered: usize,
+    /// Number of times the scoring matrix was run for a target, including targets scored
+    /// several at a time by the batch APIs.
+    pub matrix_runs: usize,
+    /// Number of cells of the scoring matrix computed, counted as comparisons of a query
+    /// character with a target character. This includes the scans that find which cells
+    /// can be part of a match. Cells of the `VsCodeCompat` profile aren't counted.
+    pub cells: usize,
+}
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Returns the counters of the work done by this matcher since it was created or since
+    /// [`reset_stats`](Self::reset_stats) was last called.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut matcher = code_fuzzy_match::FuzzyMatcher::new();
+    /// matcher.reset_stats();
+    /// let mut items = vec!["src/lib.rs", "Cargo.toml"];
+    /// matcher.retain_matches(&mut items, |item| item, "lib", false);
+    /// assert_eq!(items, ["src/lib.rs"]);
+    /// let stats = matcher.stats();
+    /// assert_eq!(stats.candidates, 2);
+    /// assert_eq!(stats.prefiltered, 1);
+    /// assert_eq!(stats.matrix_runs, 1);
+    /// ```
+    pub fn stats(&self) -> MatchStats {
+        MatchStats {
+            matrix_runs: self.stats.matrix_runs + self.matrix.stats.matrix_runs,
+            cells: self.stats.cells + self.matrix.stats.cells,
+            ..self.stats
+        }
+    }
+
+    /// Resets the counters returned by [`stats`](Self::stats) to zero.
+    pub fn reset_stats(&mut self) {
+        self.stats = MatchStats::default();
+        self.matrix.stats = MatchStats::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FuzzyMatcher, MatchOptions, MatchStats};
+    use alloc::vec;
+
+    #[test]
+    fn test_stats() {
+        let mut matcher = FuzzyMatcher::new();
+        assert_eq!(matcher.stats(), MatchStats::default());
+
+        // Single matches run the matrix without a prefilter
+        assert!(matcher.fuzzy_match("abc", "ac").is_some());
+        let stats = matcher.stats();
+        assert_eq!(stats.candidates, 0);
+        assert_eq!(stats.matrix_runs, 1);
+        assert!(stats.cells >= 2);
+
+        // Batches count every candidate, whether or not it is scored several at a time
+        for options in [
+            MatchOptions::default(),
+            MatchOptions {
+                max_typos: 1,
+                ..MatchOptions::default()
+            },
+        ] {
+            let mut matcher = FuzzyMatcher::with_options(options);
+            let mut targets = vec!["src/lib.rs", "src/main.rs", "Cargo.toml", "lib"];
+            matcher.retain_matches(&mut targets, |target| target, "lib", false);
+            assert_eq!(targets, ["src/lib.rs", "lib"]);
+            let stats = matcher.stats();
+            assert_eq!(stats.candidates, 4);
+            assert_eq!(stats.prefiltered, 2);
+            assert_eq!(stats.matrix_runs, 2);
+            assert!(stats.cells >= 3 * 2);
+
+            matcher.reset_stats();
+            assert_eq!(matcher.stats(), MatchStats::default());
+        }
+    }
+}
@@ -0,0 +1,384 @@
+//! Matching of targets that arrive in chunks, without holding the whole target in memory.
+
+use crate::digits;
+use crate::matrix::{
+    char_score, is_word_start, leading_gap_penalty, normalize_length, penalize, signed, CharClass,
+    MatrixInput, UNREACHABLE,
+};
+use crate::score::RawScore;
+use crate::{char_match_kind, FuzzyMatcher, MatchOptions, ScoreModel, ScoringProfile};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// State of a match of a target fed in chunks.
+#[derive(Default)]
+pub(crate) struct StreamState {
+    active: bool,
+    /// Set when the options need the whole target, in which case the target is collected in
+    /// `decoded_target` and matched when finished.
+    buffered: bool,
+    query: String,
+    /// Score and sequential match count of each query character, as of the last scored
+    /// target character. These are the cells of one column of the scoring matrix.
+    scores: Vec<isize>,
+    seq_match_counts: Vec<usize>,
+    /// Whether the query up to and including each character matches the last characters
+    /// of the target.
+    partial_substring: Vec<bool>,
+    substring: bool,
+    exact: bool,
+    target_len: usize,
+    /// Classes of the characters before the next one to be scored, up to the length of the
+    /// query, which is as far back as a run of sequential matches can reach.
+    prev_classes: Vec<CharClass>,
+    /// Classes given to the scoring model for the character being scored.
+    classes: Vec<CharClass>,
+    /// Last character received, which is scored once the class of the character after it
+    /// is known.
+    pending: Option<(char, CharClass)>,
+}
+
+/// Returns true if targets can be scored one character at a time with these options.
+fn streamable(options: &MatchOptions, query_len: usize) -> bool {
+    #[cfg(feature = "unicode-words")]
+    if options.unicode_words {
+        return false;
+    }
+    #[cfg(feature = "case-folding")]
+    if options.full_case_folding {
+        return false;
+    }
+    let max_typos = options.max_typos.min(query_len.saturating_sub(1) / 2);
+    options.profile == ScoringProfile::General
+        && !options.bidirectional
+        && !options.right_to_left
+        && max_typos == 0
+        && !(options.transpositions && query_len > 1)
+        && options.truncation.is_none()
+        && options.max_target_len.is_none()
+        && options.path_separators.is_empty()
+        && options.ignored_chars.is_empty()
+        && options.synonyms.is_empty()
+        && !options.ignore_extension
+        && !options.camel_humps
+        && options.max_gap.is_none()
+        && query_len != 0
+}
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Starts matching a target that will be given in chunks with [`feed`](Self::feed),
+    /// such as data arriving over a socket or from a decompressor. Call
+    /// [`finish`](Self::finish) after the last chunk to get the score. Starting a new match
+    /// discards any match in progress.
+    ///
+    /// Only the state of the scoring matrix for the most recent character is kept between
+    /// chunks, so memory use depends on the length of the query and not the target. Options
+    /// that need the whole target, such as `max_typos`, `transpositions`, `bidirectional`,
+    /// `truncation`, `max_target_len`, `path_separators` and scoring profiles other than
+    /// [`ScoringProfile::General`], instead collect the target and match it when finished.
+    /// Scores are the same as for [`fuzzy_match`](Self::fuzzy_match) on the whole target.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut matcher = code_fuzzy_match::FuzzyMatcher::new();
+    /// matcher.begin("bro fox");
+    /// for chunk in ["the qu", "ick br", "own fox"] {
+    ///     matcher.feed(chunk);
+    /// }
+    /// assert_eq!(
+    ///     matcher.finish(),
+    ///     matcher.fuzzy_match("the quick brown fox", "bro fox")
+    /// );
+    /// ```
+    pub fn begin(&mut self, query: &str) {
+        self.load_query(query);
+        let state = &mut self.stream;
+        state.active = true;
+        state.query.clear();
+        state.query.push_str(query);
+        let query_len = self.query_chars.len();
+        state.buffered = !streamable(&self.options, query_len);
+        if state.buffered {
+            self.decoded_target.clear();
+            return;
+        }
+
+        state.scores.clear();
+        state.scores.resize(query_len, UNREACHABLE);
+        state.seq_match_counts.clear();
+        state.seq_match_counts.resize(query_len, 0);
+        state.partial_substring.clear();
+        state.partial_substring.resize(query_len, false);
+        state.substring = false;
+        state.exact = true;
+        state.target_len = 0;
+        state.prev_classes.clear();
+        state.pending = None;
+    }
+
+    /// Feeds the next chunk of the target started with [`begin`](Self::begin). Chunks fed
+    /// without a match in progress are ignored.
+    pub fn feed(&mut self, chunk: &str) {
+        if !self.stream.active {
+            return;
+        }
+        if self.stream.buffered {
+            self.decoded_target.push_str(chunk);
+            return;
+        }
+        for c in chunk.chars() {
+            let c = if self.options.kana_folding {
+                crate::kana::fold(c)
+            } else {
+                c
+            };
+            self.stream_char(c);
+        }
+    }
+
+    /// Finishes the match started with [`begin`](Self::begin), returning the score of the
+    /// target fed since then, or `None` if the query does not match it or no match was in
+    /// progress.
+    pub fn finish(&mut self) -> Option<usize> {
+        if !core::mem::take(&mut self.stream.active) {
+            return None;
+        }
+        if self.stream.buffered {
+            let target = core::mem::take(&mut self.decoded_target);
+            let query = core::mem::take(&mut self.stream.query);
+            let score = self.fuzzy_match(&target, &query);
+            self.decoded_target = target;
+            self.stream.query = query;
+            return score;
+        }
+
+        if let Some((c, class)) = self.stream.pending.take() {
+            self.score_column(c, class, None);
+        }
+        self.truncated = false;
+        let state = &self.stream;
+        let query_len = self.query_chars.len();
+        let sum = *state.scores.last()?;
+        if sum == UNREACHABLE {
+            return None;
+        }
+        if self.options.pin_exact_matches && state.exact && state.target_len == query_len {
+            return Some(RawScore::Exact.into_score());
+        }
+        let bonus = if state.substring {
+            self.options.substring_bonus
+        } else {
+            0
+        };
+        let sum = normalize_length(&self.options, sum, state.target_len, query_len);
+        Some(RawScore::Sum { sum, bonus }.into_score())
+    }
+
+    fn stream_char(&mut self, c: char) {
+        let state = &mut self.stream;
+        let class = match CharClass::of(c) {
+            CharClass::Other
+                if self.options.digit_word_starts
+                    && state
+                        .pending
+                        .is_some_and(|(prev, _)| digits::is_boundary(prev, c)) =>
+            {
+                CharClass::WordStart
+            }
+            class => class,
+        };
+        let query_chars = &self.query_chars;
+        let options = &self.options;
+
+        let target_idx = state.target_len;
+        state.target_len += 1;
+        state.exact = state.exact
+            && query_chars
+                .get(target_idx)
+                .is_some_and(|q| char_match_kind(*q, c, options).is_some());
+        if options.substring_bonus != 0 && !state.substring {
+            let partial = &mut state.partial_substring;
+            for query_idx in (0..partial.len()).rev() {
+                partial[query_idx] = (query_idx == 0 || partial[query_idx - 1])
+                    && char_match_kind(query_chars[query_idx], c, options).is_some();
+            }
+            state.substring = partial.last().is_some_and(|found| *found);
+        }
+
+        if let Some((pending, pending_class)) = state.pending.replace((c, class)) {
+            self.score_column(pending, pending_class, Some(class));
+        }
+    }
+
+    /// Advances the scoring matrix by one target character, given the class of the
+    /// character after it, if any.
+    fn score_column(&mut self, c: char, class: CharClass, next_class: Option<CharClass>) {
+        let state = &mut self.stream;
+        let target_idx = state.target_len - 1 - usize::from(next_class.is_some());
+
+        // Scoring looks at the classes from the start of a run of sequential matches to the
+        // character after the matched one, so only those are given.
+        let query_len = self.query_chars.len();
+        let index = state.prev_classes.len();
+        state.classes.clear();
+        state.classes.extend_from_slice(&state.prev_classes);
+        state.classes.push(class);
+        state.classes.extend(next_class);
+        if state.prev_classes.len() == query_len {
+            state.prev_classes.remove(0);
+        }
+        state.prev_classes.push(class);
+        let classes = &state.classes[..];
+
+        let options = &self.options;
+        let input = MatrixInput::with_model(classes, query_len, options, &self.model);
+        let gap_penalty = signed(options.gap_penalty);
+        for query_idx in (0..query_len).rev() {
+            let carried = state.scores[query_idx];
+            let prev_target_score = if carried == UNREACHABLE || query_idx + 1 == query_len {
+                carried
+            } else {
+                penalize(carried, gap_penalty)
+            };
+            let (prev_query_score, seq_match_count) = if query_idx == 0 {
+                (0, 0)
+            } else {
+                (
+                    state.scores[query_idx - 1],
+                    state.seq_match_counts[query_idx - 1],
+                )
+            };
+
+            let kind = if prev_query_score == UNREACHABLE
+                || (query_idx == 0 && options.require_word_start && !is_word_start(classes, index))
+            {
+                None
+            } else {
+                char_match_kind(self.query_chars[query_idx], c, options)
+            };
+            let new_score = kind.map(|kind| {
+                let mut score = prev_query_score.saturating_add(signed(char_score(
+                    &input,
+                    index,
+                    seq_match_count,
+                    kind,
+                )));
+                if query_idx == 0 {
+                    score = penalize(score, signed(leading_gap_penalty(options, target_idx)));
+                }
+                score
+            });
+            match new_score {
+                Some(new_score) if new_score >= prev_target_score => {
+                    state.scores[query_idx] = new_score;
+                    state.seq_match_counts[query_idx] = seq_match_count + 1;
+                }
+                _ => {
+                    state.scores[query_idx] = prev_target_score;
+                    state.seq_match_counts[query_idx] = 0;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{EmptyQueryPolicy, FuzzyMatcher, MatchOptions, ScoringProfile};
+
+    #[test]
+    fn test_streamed_target() {
+        let options = [
+            MatchOptions::default(),
+            MatchOptions {
+                gap_penalty: 2,
+                leading_gap_penalty: 1,
+                whole_word_bonus: 10,
+                contiguous_query_bonus: 20,
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                substring_bonus: 100,
+                pin_exact_matches: true,
+                length_normalization: 8,
+                require_word_start: true,
+                ..MatchOptions::default()
+            },
+            // Options that need the whole target
+            MatchOptions {
+                max_typos: 1,
+                transpositions: true,
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                profile: ScoringProfile::FilePath,
+                bidirectional: true,
+                ..MatchOptions::default()
+            },
+        ];
+        let targets = [
+            "The quick brown fox jumps over the lazy dog.",
+            "src/matrix/tolerant.rs",
+            "camelCaseWords",
+            "Grüße aus Köln",
+            "fox",
+            "",
+        ];
+        let queries = [
+            "fox",
+            "The quick",
+            "jmp the do",
+            "mat/tol",
+            "cCW",
+            "köln",
+            "x",
+        ];
+        for options in options {
+            let mut matcher = FuzzyMatcher::with_options(options);
+            for target in targets {
+                for query in queries {
+                    let expected = matcher.fuzzy_match(target, query);
+                    for split in (0..=target.len()).filter(|idx| target.is_char_boundary(*idx)) {
+                        matcher.begin(query);
+                        matcher.feed(&target[..split]);
+                        matcher.feed("");
+                        matcher.feed(&target[split..]);
+                        assert_eq!(matcher.finish(), expected, "{target:?} {query:?} {split}");
+                    }
+                }
+            }
+        }
+
+        // Nothing is matched without a match in progress
+        let mut matcher = FuzzyMatcher::new();
+        matcher.feed("fox");
+        assert_eq!(matcher.finish(), None);
+        matcher.begin("fox");
+        matcher.feed("fox");
+        assert!(matcher.finish().is_some());
+        assert_eq!(matcher.finish(), None);
+
+        // Empty and blank queries are matched as `fuzzy_match` does
+        for empty_query in [
+            EmptyQueryPolicy::NoMatch,
+            EmptyQueryPolicy::MatchAllZeroScore,
+        ] {
+            let mut matcher = FuzzyMatcher::with_options(MatchOptions {
+                trim_query: true,
+                empty_query,
+                ..MatchOptions::default()
+            });
+            for query in ["", "  "] {
+                matcher.begin(query);
+                matcher.feed("ab");
+                matcher.feed("c");
+                assert_eq!(
+                    matcher.finish(),
+                    matcher.fuzzy_match("abc", query),
+                    "{query:?}"
+                );
+            }
+        }
+    }
+}
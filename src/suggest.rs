@@ -0,0 +1,156 @@
+//! "Did you mean" suggestions for mistyped input, such as unknown command line subcommands.
+
+use crate::{FuzzyMatcher, MatchOptions};
+use alloc::vec::Vec;
+
+/// A candidate suggested for mistyped input by [`suggest`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Suggestion<'a> {
+    /// The suggested candidate.
+    pub candidate: &'a str,
+    /// Index of the candidate in the list of candidates.
+    pub index: usize,
+    /// Confidence that the candidate is what was meant, from 0 to 1. Candidates equal to the
+    /// input, ignoring case, have a confidence of 1.
+    pub confidence: f64,
+}
+
+/// Options for matching typed input against candidates. Typos and swapped characters are
+/// allowed, and characters skipped in the candidate are penalized, since input is expected
+/// to be the whole candidate rather than an abbreviation of it.
+fn suggest_options() -> MatchOptions {
+    MatchOptions {
+        max_typos: 2,
+        transpositions: true,
+        gap_penalty: 2,
+        leading_gap_penalty: 2,
+        ..MatchOptions::default()
+    }
+}
+
+/// Returns up to `max_suggestions` candidates that `input` is likely a mistyped version of,
+/// from most to least likely, for messages like "unknown command `instal`, did you mean
+/// `install`?". Only candidates with a confidence of at least `min_confidence`, from 0 to 1,
+/// are returned. A minimum confidence of about 0.7 works well for short words such as
+/// command names.
+///
+/// Confidence is based on the proportion of characters that the input and the candidate have
+/// in common, reduced for each gap between matched characters of the candidate, and slightly
+/// reduced when the fuzzy match score is lower than the score of matching the input against
+/// itself. The match allows typos and swapped characters.
+///
+/// # Examples
+///
+/// ```
+/// use code_fuzzy_match::suggest;
+///
+/// let commands = ["install", "uninstall", "list", "search"];
+/// let suggestions = suggest(commands, "instal", 1, 0.7);
+/// assert_eq!(suggestions[0].candidate, "install");
+/// assert_eq!(suggest(commands, "lsit", 1, 0.7)[0].candidate, "list");
+/// assert!(suggest(commands, "remove", 1, 0.7).is_empty());
+/// ```
+pub fn suggest<'a>(
+    candidates: impl IntoIterator<Item = &'a str>,
+    input: &str,
+    max_suggestions: usize,
+    min_confidence: f64,
+) -> Vec<Suggestion<'a>> {
+    let mut matcher = FuzzyMatcher::with_options(suggest_options());
+    let ideal_score = match matcher.fuzzy_match(input, input) {
+        Some(score) => score as f64,
+        None => return Vec::new(),
+    };
+    let input_len = input.chars().count();
+
+    let mut suggestions: Vec<Suggestion<'a>> = candidates
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, candidate)| {
+            let confidence = if candidate
+                .chars()
+                .flat_map(char::to_lowercase)
+                .eq(input.chars().flat_map(char::to_lowercase))
+            {
+                1.0
+            } else {
+                let result = matcher.fuzzy_match_details(candidate, input)?;
+                let candidate_len = candidate.chars().count();
+                let matched = result.positions.len();
+                let common = (2 * matched) as f64 / (input_len + candidate_len) as f64;
+                let gaps = result
+                    .positions
+                    .windows(2)
+                    .filter(|pair| pair[1] != pair[0] + 1)
+                    .count();
+                let score_ratio = (result.score as f64 / ideal_score).min(1.0);
+                let gap_factor = (1.0 - 0.1 * gaps as f64).max(0.0);
+                let confidence = common * gap_factor * (0.9 + 0.1 * score_ratio);
+                // An inexact match never has full confidence
+                confidence.min(0.99)
+            };
+            (confidence >= min_confidence).then_some(Suggestion {
+                candidate,
+                index,
+                confidence,
+            })
+        })
+        .collect();
+
+    // Stable sort keeps candidates with equal confidence in their original order
+    suggestions.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    suggestions.truncate(max_suggestions);
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::suggest;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_suggest() {
+        let commands = [
+            "install",
+            "uninstall",
+            "update",
+            "upgrade",
+            "list",
+            "search",
+            "status",
+            "stash",
+        ];
+        let names = |input: &str, max_suggestions: usize| -> Vec<&str> {
+            suggest(commands, input, max_suggestions, 0.7)
+                .iter()
+                .map(|suggestion| suggestion.candidate)
+                .collect()
+        };
+
+        assert_eq!(names("instal", 1), ["install"]);
+        assert_eq!(names("intsall", 1), ["install"]);
+        assert_eq!(names("lsit", 1), ["list"]);
+        assert_eq!(names("udpate", 1), ["update"]);
+        assert_eq!(names("stats", 1), ["status"]);
+        assert_eq!(names("stahs", 1), ["stash"]);
+        assert_eq!(names("instll", 1), ["install"]);
+        assert_eq!(names("serch", 1), ["search"]);
+        assert_eq!(names("staus", 2), ["status", "stash"]);
+        assert!(names("remove", 3).is_empty());
+        assert!(names("", 3).is_empty());
+
+        // Exact matches have full confidence, and rank first
+        let suggestions = suggest(commands, "STASH", 3, 0.0);
+        assert_eq!(suggestions[0].candidate, "stash");
+        assert_eq!(suggestions[0].index, 7);
+        assert_eq!(suggestions[0].confidence, 1.0);
+        assert!(suggestions.len() <= 3);
+        assert!(suggestions[1..]
+            .iter()
+            .all(|suggestion| suggestion.confidence < 1.0));
+        assert!(suggestions
+            .windows(2)
+            .all(|pair| pair[0].confidence >= pair[1].confidence));
+    }
+}
@@ -0,0 +1,179 @@
+//! Expansion of query words into their synonyms, for the `synonyms` option.
+
+use crate::matrix::signed;
+use crate::score::RawScore;
+use crate::{FuzzyMatcher, ScoreModel};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Maximum number of forms of a query tried in place of the query itself, so that a query
+/// with many words that have synonyms doesn't multiply the cost of a match without bound.
+const MAX_EXPANSIONS: usize = 16;
+
+/// Returns true if `word` is equal to `form`, ignoring case.
+fn word_eq(word: &[char], form: &str) -> bool {
+    word.iter()
+        .flat_map(|c| c.to_lowercase())
+        .eq(form.chars().flat_map(char::to_lowercase))
+}
+
+/// Returns the forms of `query` with one or more of its space separated words replaced by a
+/// synonym, not including the query itself.
+pub(crate) fn expand(query: &[char], synonyms: &[(String, String)]) -> Vec<String> {
+    // Each partial form of the query, and whether a synonym was used in it. The first form
+    // always holds the words of the query as they are.
+    let mut forms: Vec<(String, bool)> = alloc::vec![(String::new(), false)];
+    for (idx, word) in query.split(|c| *c == ' ').enumerate() {
+        let replacements: Vec<&str> = synonyms
+            .iter()
+            .filter_map(|(a, b)| {
+                if word_eq(word, a) {
+                    Some(b.as_str())
+                } else if word_eq(word, b) {
+                    Some(a.as_str())
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        let mut next = Vec::with_capacity(forms.len() * (replacements.len() + 1));
+        for (form, replaced) in &forms {
+            let mut form = form.clone();
+            if idx > 0 {
+                form.push(' ');
+            }
+            let mut original = form.clone();
+            original.extend(word.iter());
+            next.push((original, *replaced));
+            for replacement in &replacements {
+                let mut form = form.clone();
+                form.push_str(replacement);
+                next.push((form, true));
+            }
+        }
+        next.truncate(MAX_EXPANSIONS + 1);
+        forms = next;
+    }
+    forms
+        .into_iter()
+        .filter(|(_, replaced)| *replaced)
+        .map(|(form, _)| form)
+        .collect()
+}
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Runs the scoring matrix for the loaded query and for each of its forms using
+    /// synonyms, keeping the best match. Matches using synonyms are penalized by the
+    /// `synonym_penalty` option. The form of the query with the best match is left loaded,
+    /// along with its positions if they are recorded.
+    pub(crate) fn compute_synonyms(&mut self, record_positions: bool) -> Option<RawScore> {
+        let expansions = expand(&self.query_chars, &self.options.synonyms);
+        let direct = self.compute_raw(record_positions, false);
+        if expansions.is_empty() {
+            return direct;
+        }
+        let query: String = self.query_chars.iter().collect();
+
+        let penalty = signed(self.options.synonym_penalty);
+        let mut best = direct.map(|score| (score, None));
+        for (idx, expansion) in expansions.iter().enumerate() {
+            self.load_query(expansion);
+            let score = match self.compute_raw(record_positions, false) {
+                Some(RawScore::Sum { sum, bonus }) => RawScore::Sum {
+                    sum: sum.saturating_sub(penalty),
+                    bonus,
+                },
+                Some(RawScore::Exact) => RawScore::Exact,
+                None => continue,
+            };
+            if best.is_none_or(|(best, _)| score.into_score::<isize>() > best.into_score::<isize>())
+            {
+                best = Some((score, Some(idx)));
+            }
+        }
+
+        // Load the form of the best match again, recomputing its positions unless it was
+        // the last form computed
+        let best_idx = best.and_then(|(_, idx)| idx);
+        match best_idx {
+            Some(idx) => self.load_query(&expansions[idx]),
+            None => self.load_query(&query),
+        }
+        if record_positions && best.is_some() && best_idx != Some(expansions.len() - 1) {
+            self.compute_raw(true, false);
+        }
+        best.map(|(score, _)| score)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::expand;
+    use crate::{FuzzyMatcher, MatchOptions};
+    use alloc::string::String;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_synonyms() {
+        let synonyms = vec![
+            (String::from("rm"), String::from("remove")),
+            (String::from("cfg"), String::from("config")),
+        ];
+        let chars = |query: &str| query.chars().collect::<Vec<char>>();
+        assert_eq!(
+            expand(&chars("RM cfg"), &synonyms),
+            ["RM config", "remove cfg", "remove config"]
+        );
+        assert!(expand(&chars("rmx"), &synonyms).is_empty());
+
+        let mut matcher = FuzzyMatcher::with_options(MatchOptions {
+            synonyms,
+            ..MatchOptions::default()
+        });
+        let mut plain = FuzzyMatcher::new();
+
+        // Either form matches targets containing the other, with a penalty
+        assert!(plain.fuzzy_match("rm_file", "remove").is_none());
+        let via_synonym = matcher.fuzzy_match("rm_file", "remove").unwrap();
+        assert_eq!(via_synonym, plain.fuzzy_match("rm_file", "rm").unwrap() - 5);
+        assert!(plain.fuzzy_match("remove_file", "rm").is_some());
+        assert!(matcher.fuzzy_match("remove_file", "rm") > plain.fuzzy_match("remove_file", "rm"));
+
+        // Positions are those of the best form of the query
+        let result = matcher.fuzzy_match_details("load_config", "cfg").unwrap();
+        assert_eq!(result.positions, [5, 6, 7, 8, 9, 10]);
+        assert_eq!(
+            Some(result.score),
+            matcher.fuzzy_match("load_config", "cfg")
+        );
+        let result = matcher
+            .fuzzy_match_details("rm file", "remove file")
+            .unwrap();
+        assert_eq!(result.positions, [0, 1, 2, 3, 4, 5, 6]);
+
+        // Direct matches rank above matches of the same quality through a synonym
+        let direct = matcher.fuzzy_match("remove_file", "remove").unwrap();
+        assert!(direct > matcher.fuzzy_match("rm_file", "remove").unwrap());
+        assert_eq!(direct, plain.fuzzy_match("remove_file", "remove").unwrap());
+
+        // The prefilter lets through targets that only match through a synonym
+        let mut targets = vec!["rm_file", "Cargo.lock", "remove_all"];
+        matcher.retain_matches(&mut targets, |target| target, "remove", true);
+        assert_eq!(targets, ["remove_all", "rm_file"]);
+
+        // Huge penalties don't wrap around into bonuses
+        for synonym_penalty in [0x8000_0000_0000_0000, usize::MAX] {
+            let mut matcher = FuzzyMatcher::with_options(MatchOptions {
+                synonym_penalty,
+                ..matcher.options().clone()
+            });
+            assert_eq!(
+                matcher.fuzzy_match("src/config.rs", "cfg"),
+                plain.fuzzy_match("src/config.rs", "cfg")
+            );
+            assert_eq!(matcher.fuzzy_match("rm_file", "remove"), Some(1));
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! Spans and events for batch operations, emitted with the `tracing` feature.
+
+use crate::Score;
+use std::time::Instant;
+use tracing::span::EnteredSpan;
+
+/// Span of a batch operation, entered from the time the operation starts until its result is
+/// reported.
+pub(crate) struct BatchTrace {
+    _span: EnteredSpan,
+    start: Instant,
+}
+
+impl BatchTrace {
+    /// Enters a span for the batch operation named `operation` matching `query`.
+    pub(crate) fn start(operation: &'static str, query: &str) -> Self {
+        BatchTrace {
+            _span: tracing::debug_span!("batch", operation, query).entered(),
+            start: Instant::now(),
+        }
+    }
+
+    /// Emits an event with the number of candidates considered, the number of matches and
+    /// the best score among them, and the time taken since the operation started.
+    pub(crate) fn finish<S: Score>(self, candidates: usize, scores: impl Iterator<Item = S>) {
+        let (matches, top_score) = scores.fold((0, None), |(matches, top), score| {
+            (matches + 1, top.max(Some(score)))
+        });
+        tracing::debug!(
+            candidates,
+            matches,
+            top_score = ?top_score,
+            elapsed_us = self.start.elapsed().as_micros() as u64,
+            "batch matched"
+        );
+    }
+
+    /// Emits an event for a batch operation that was cancelled after considering
+    /// `candidates` candidates.
+    pub(crate) fn cancel(self, candidates: usize) {
+        tracing::debug!(
+            candidates,
+            elapsed_us = self.start.elapsed().as_micros() as u64,
+            "batch cancelled"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FuzzyMatcher;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use core::fmt::Debug;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    /// Records the fields of every span and event as text.
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl Visit for Recorder {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0
+                .lock()
+                .unwrap()
+                .push(alloc::format!("{}={value:?}", field.name()));
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes) -> Id {
+            span.record(&mut self.clone());
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _: &Id, _: &Record) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event) {
+            event.record(&mut self.clone());
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    fn test_batch_trace() {
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), || {
+            let mut matcher = FuzzyMatcher::new();
+            let mut items = alloc::vec!["src/lib.rs", "Cargo.toml", "lib"];
+            matcher.retain_matches(&mut items, |item| item, "lib", false);
+        });
+        let fields = recorder.0.lock().unwrap().clone();
+        assert!(fields.contains(&String::from("operation=\"match_batch\"")));
+        assert!(fields.contains(&String::from("query=\"lib\"")));
+        assert!(fields.contains(&String::from("candidates=3")));
+        assert!(fields.contains(&String::from("matches=2")));
+        assert!(fields
+            .iter()
+            .any(|field| field.starts_with("top_score=Some(")));
+        assert!(fields.iter().any(|field| field.starts_with("elapsed_us=")));
+    }
+}
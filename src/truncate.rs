@@ -0,0 +1,255 @@
+//! Truncation of very long targets before matching.
+
+use crate::MatchOptions;
+use core::ops::Range;
+
+/// Limits matching to a window of a long target string, so that the time taken to match
+/// extremely long targets is predictable. Windows never split a grapheme cluster, so a
+/// window may hold slightly fewer characters than requested. Positions reported for a
+/// truncated target are still relative to the start of the full target string.
+///
+/// Matching within a window scores it as if the window was the entire target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Truncation {
+    /// Only match within the first N characters of the target. This can also limit matching
+    /// to a label at the start of a longer target, such as the title of a command followed
+    /// by its description, with the rest of the target not contributing to the score.
+    First(usize),
+    /// Only match within the last N characters of the target.
+    Last(usize),
+    /// Only match within the last N characters of the target if it contains the entire
+    /// basename of a path, otherwise only match within the first N characters of the
+    /// basename. The basename is the text after the last `/` or `\` in the target.
+    Basename(usize),
+}
+
+impl Truncation {
+    /// Returns the byte range of the window of `target` to match within.
+    pub(crate) fn window(&self, target: &str) -> Range<usize> {
+        match *self {
+            Truncation::First(len) => 0..first_chars_end(target, len),
+            Truncation::Last(len) => last_chars_start(target, len)..target.len(),
+            Truncation::Basename(len) => {
+                let basename_start = target.rfind(['/', '\\']).map_or(0, |idx| idx + 1);
+                let basename = &target[basename_start..];
+                let basename_end = first_chars_end(basename, len);
+                if basename_end < basename.len() {
+                    basename_start..basename_start + basename_end
+                } else {
+                    last_chars_start(target, len)..target.len()
+                }
+            }
+        }
+    }
+}
+
+/// How targets longer than the `max_target_len` option are handled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LongTargetPolicy {
+    /// Targets longer than the limit never match.
+    #[default]
+    Reject,
+    /// Only match within the first `max_target_len` characters of the target.
+    Truncate,
+    /// Only match within the given window of the target. This allows a smaller window
+    /// than the limit, such as matching only the start of very long lines.
+    Window(Truncation),
+}
+
+/// Returns the byte range of the window of `target` to match within, as configured by the
+/// `max_target_len`, `long_target_policy` and `truncation` options. The `truncation` option
+/// is applied within the window chosen for long targets. Rejected targets have an empty
+/// window.
+pub(crate) fn target_window(target: &str, options: &MatchOptions) -> Range<usize> {
+    // Targets with no more bytes than the limit can't have more characters than it, which
+    // avoids walking the characters of most targets.
+    let limited = match options.max_target_len {
+        Some(max_len) if target.len() > max_len && target.chars().nth(max_len).is_some() => {
+            match &options.long_target_policy {
+                LongTargetPolicy::Reject => return 0..0,
+                LongTargetPolicy::Truncate => 0..first_chars_end(target, max_len),
+                LongTargetPolicy::Window(truncation) => truncation.window(target),
+            }
+        }
+        _ => 0..target.len(),
+    };
+    match &options.truncation {
+        Some(truncation) => {
+            let window = truncation.window(&target[limited.clone()]);
+            limited.start + window.start..limited.start + window.end
+        }
+        None => limited,
+    }
+}
+
+/// Returns the byte offset of the end of the first `len` characters of `text`, moved back if
+/// needed to avoid splitting a grapheme cluster.
+fn first_chars_end(text: &str, len: usize) -> usize {
+    let mut end = text
+        .char_indices()
+        .nth(len)
+        .map_or(text.len(), |(idx, _)| idx);
+    while end > 0 && end < text.len() && !is_cluster_boundary(text, end) {
+        end = text[..end]
+            .char_indices()
+            .next_back()
+            .map_or(0, |(idx, _)| idx);
+    }
+    end
+}
+
+/// Returns the byte offset of the start of the last `len` characters of `text`, moved
+/// forward if needed to avoid splitting a grapheme cluster.
+fn last_chars_start(text: &str, len: usize) -> usize {
+    if len == 0 {
+        return text.len();
+    }
+    let mut start = text
+        .char_indices()
+        .rev()
+        .nth(len - 1)
+        .map_or(0, |(idx, _)| idx);
+    while start > 0 && start < text.len() && !is_cluster_boundary(text, start) {
+        start += text[start..].chars().next().map_or(0, char::len_utf8);
+    }
+    start
+}
+
+#[cfg(feature = "graphemes")]
+fn is_cluster_boundary(text: &str, idx: usize) -> bool {
+    unicode_segmentation::GraphemeCursor::new(idx, text.len(), true)
+        .is_boundary(text, 0)
+        .unwrap_or(true)
+}
+
+/// Without grapheme segmentation tables, avoid splitting the most common kinds of
+/// multi-character clusters: combining marks, variation selectors, emoji modifiers and tags,
+/// and sequences joined with a zero width joiner.
+#[cfg(not(feature = "graphemes"))]
+fn is_cluster_boundary(text: &str, idx: usize) -> bool {
+    let extends = text[idx..].chars().next().is_some_and(|c| {
+        matches!(c,
+            '\u{300}'..='\u{36f}'
+            | '\u{1ab0}'..='\u{1aff}'
+            | '\u{1dc0}'..='\u{1dff}'
+            | '\u{200d}'
+            | '\u{20d0}'..='\u{20ff}'
+            | '\u{fe00}'..='\u{fe0f}'
+            | '\u{fe20}'..='\u{fe2f}'
+            | '\u{1f3fb}'..='\u{1f3ff}'
+            | '\u{e0020}'..='\u{e007f}'
+            | '\u{e0100}'..='\u{e01ef}')
+    });
+    let joined = text[..idx].ends_with('\u{200d}');
+    !extends && !joined
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FuzzyMatcher, LongTargetPolicy, MatchOptions, Truncation};
+
+    fn matcher(truncation: Truncation) -> FuzzyMatcher {
+        FuzzyMatcher::with_options(MatchOptions {
+            truncation: Some(truncation),
+            ..MatchOptions::default()
+        })
+    }
+
+    #[test]
+    fn test_truncation() {
+        let mut first = matcher(Truncation::First(5));
+        assert!(first.fuzzy_match("hello world", "hello").is_some());
+        assert!(first.truncated());
+        assert!(first.fuzzy_match("hello world", "world").is_none());
+        assert!(first.fuzzy_match("hello", "hello").is_some());
+        assert!(!first.truncated());
+
+        // Text after the window doesn't change the score
+        let mut label = matcher(Truncation::First(9));
+        let target = "Open File: choose a file from disk to open";
+        assert_eq!(
+            label.fuzzy_match(target, "of"),
+            FuzzyMatcher::new().fuzzy_match("Open File", "of")
+        );
+        assert!(label.fuzzy_match(target, "disk").is_none());
+        let result = label.fuzzy_match_details(target, "file").unwrap();
+        assert_eq!(result.positions, [5, 6, 7, 8]);
+
+        let mut last = matcher(Truncation::Last(5));
+        assert!(last.fuzzy_match("hello world", "hello").is_none());
+        assert!(last.fuzzy_match("hello world", "world").is_some());
+        assert!(last.truncated());
+
+        // Basename fits in the window along with part of the directory
+        let mut basename = matcher(Truncation::Basename(12));
+        assert!(basename
+            .fuzzy_match("some/long/path/file.rs", "path/file")
+            .is_some());
+        assert!(basename
+            .fuzzy_match("some/long/path/file.rs", "long")
+            .is_none());
+
+        // Basename is longer than the window
+        let mut basename = matcher(Truncation::Basename(4));
+        assert!(basename
+            .fuzzy_match("some/long/path/file.rs", "file")
+            .is_some());
+        assert!(basename
+            .fuzzy_match("some/long/path/file.rs", "rs")
+            .is_none());
+    }
+
+    #[test]
+    fn test_truncation_clusters() {
+        // Cutting after three characters would separate the emoji from its skin tone
+        let window = Truncation::First(3).window("ab👍🏽cd");
+        assert_eq!(window, 0..2);
+        let window = Truncation::Last(3).window("ab👍🏽cd");
+        assert_eq!(window, 10..12);
+
+        // Combining accents stay with their base character
+        let window = Truncation::First(4).window("cafe\u{301}");
+        assert_eq!(window, 0..3);
+    }
+
+    #[test]
+    fn test_max_target_len() {
+        let limited = |policy| {
+            FuzzyMatcher::with_options(MatchOptions {
+                max_target_len: Some(8),
+                long_target_policy: policy,
+                ..MatchOptions::default()
+            })
+        };
+
+        let mut reject = limited(LongTargetPolicy::Reject);
+        assert!(reject.fuzzy_match("src/lib.rs", "src").is_none());
+        assert!(reject.truncated());
+        assert!(reject.fuzzy_match("lib.rs", "lib").is_some());
+        assert!(!reject.truncated());
+        // The limit is in characters, not bytes
+        assert!(reject.fuzzy_match("Grüße.rs", "gr").is_some());
+
+        let mut truncate = limited(LongTargetPolicy::Truncate);
+        assert!(truncate.fuzzy_match("src/lib.rs", "src/lib").is_some());
+        assert!(truncate.truncated());
+        assert!(truncate.fuzzy_match("src/lib.rs", "rs").is_none());
+
+        let mut window = limited(LongTargetPolicy::Window(Truncation::Basename(2)));
+        assert!(window.fuzzy_match("src/lib.rs", "li").is_some());
+        assert!(window.fuzzy_match("src/lib.rs", "lib").is_none());
+        assert!(window.fuzzy_match("lib.rs", "lib.rs").is_some());
+
+        // Truncation applies within the window for long targets
+        let mut both = FuzzyMatcher::with_options(MatchOptions {
+            max_target_len: Some(8),
+            long_target_policy: LongTargetPolicy::Truncate,
+            truncation: Some(Truncation::Last(3)),
+            ..MatchOptions::default()
+        });
+        assert!(both.fuzzy_match("src/lib.rs", "ib.").is_some());
+        assert!(both.fuzzy_match("src/lib.rs", "rs").is_none());
+    }
+}
@@ -0,0 +1,106 @@
+//! Matching of targets encoded as UTF-16, as used by editors and Windows APIs.
+
+use crate::{FuzzyMatcher, ScoreModel};
+use alloc::vec::Vec;
+use core::char::decode_utf16;
+
+/// Result of a fuzzy match against a UTF-16 target.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Utf16Match {
+    /// Score of the match. Scores are comparable with those returned by
+    /// [`FuzzyMatcher::fuzzy_match`].
+    pub score: usize,
+    /// Offsets in UTF-16 code units of the start of each matched character in the target, in
+    /// increasing order.
+    pub positions: Vec<usize>,
+}
+
+fn decode(target: &[u16]) -> impl Iterator<Item = char> + '_ {
+    decode_utf16(target.iter().copied()).map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+}
+
+impl<M: ScoreModel> FuzzyMatcher<M> {
+    /// Fuzzy match a UTF-16 string against a query string. The target is decoded directly into
+    /// the matcher's buffers, and unpaired surrogates are decoded as U+FFFD replacement
+    /// characters. Returns the score along with the offsets of the matched characters in
+    /// UTF-16 code units, or `None` if the query does not match the target.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut matcher = code_fuzzy_match::FuzzyMatcher::new();
+    /// let target: Vec<u16> = "😀 smile".encode_utf16().collect();
+    /// let result = matcher.fuzzy_match_utf16(&target, "sm").unwrap();
+    /// assert_eq!(result.positions, vec![3, 4]);
+    /// assert_eq!(Some(result.score), matcher.fuzzy_match("😀 smile", "sm"));
+    /// ```
+    pub fn fuzzy_match_utf16(&mut self, target: &[u16], query: &str) -> Option<Utf16Match> {
+        self.load_chars(decode(target), query);
+        let score = self.compute(true, false)?;
+
+        let char_positions = self.matched_positions();
+        let mut positions = Vec::with_capacity(char_positions.len());
+        let mut char_positions = char_positions.into_iter().peekable();
+        let mut offset = 0;
+        for (char_idx, c) in decode(target).enumerate() {
+            if char_positions.next_if_eq(&char_idx).is_some() {
+                positions.push(offset);
+            }
+            if char_positions.peek().is_none() {
+                break;
+            }
+            offset += c.len_utf16();
+        }
+        Some(Utf16Match { score, positions })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FuzzyMatcher, MatchOptions, Truncation};
+    use alloc::string::ToString;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_utf16_match() {
+        let options = [
+            MatchOptions::default(),
+            MatchOptions {
+                path_separators: vec!["::".to_string()],
+                ..MatchOptions::default()
+            },
+            MatchOptions {
+                truncation: Some(Truncation::Last(12)),
+                ..MatchOptions::default()
+            },
+        ];
+        for options in options {
+            let mut matcher = FuzzyMatcher::with_options(options);
+            for (target, query) in [
+                ("std::collections::HashMap", "::hash"),
+                ("The quick brown fox", "bro fox"),
+                ("👍🏽 thumbs::up 𝄞", "up𝄞"),
+            ] {
+                let utf16: Vec<u16> = target.encode_utf16().collect();
+                let result = matcher.fuzzy_match_utf16(&utf16, query).unwrap();
+                let expected = matcher.fuzzy_match_details(target, query).unwrap();
+                assert_eq!(result.score, expected.score);
+                let expected_positions: Vec<usize> = expected
+                    .positions
+                    .iter()
+                    .map(|idx| target.chars().take(*idx).map(char::len_utf16).sum())
+                    .collect();
+                assert_eq!(result.positions, expected_positions);
+            }
+        }
+
+        // Unpaired surrogates are replaced and still take up one code unit
+        let mut matcher = FuzzyMatcher::new();
+        let target = [0xd800, 'a' as u16, 0xdc00, 'b' as u16];
+        let result = matcher.fuzzy_match_utf16(&target, "ab").unwrap();
+        assert_eq!(result.positions, vec![1, 3]);
+        assert!(matcher.fuzzy_match_utf16(&target, "c").is_none());
+    }
+}
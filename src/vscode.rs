@@ -0,0 +1,533 @@
+//! Scoring compatible with the `fuzzyScore` function of Visual Studio Code, used by
+//! [`ScoringProfile::VsCodeCompat`](crate::ScoringProfile::VsCodeCompat).
+//!
+//! The alignment rules of `fuzzyScore` differ from the scoring matrix used by the rest of
+//! the crate: moving past a target character after a match is penalized, a match may jump
+//! over a single character to stay earlier in the target, and the path is adjusted while
+//! tracing back to prefer longer contiguous runs. This is a direct port of that algorithm,
+//! so that matches, positions and orderings are the same as the TypeScript implementation.
+
+use alloc::vec::Vec;
+
+/// Maximum number of characters of the query and target considered by `fuzzyScore`.
+const MAX_LEN: usize = 128;
+
+/// Offset added to scores, which can be negative in `fuzzyScore`, so that every match has a
+/// positive score without changing the order of matches. Larger than the largest penalty
+/// possible with targets and queries of up to [`MAX_LEN`] characters.
+const SCORE_OFFSET: isize = 4096;
+
+/// Direction a cell of the matrix was reached from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Arrow {
+    Diag,
+    Left,
+    LeftLeft,
+}
+
+/// Scratch memory for matching with the `fuzzyScore` algorithm. The matrix has a row for
+/// each query character and a column for each target character, with an extra first row
+/// and column.
+#[derive(Default)]
+pub(crate) struct VsCodeBuffers {
+    query_lower: Vec<char>,
+    target_lower: Vec<char>,
+    min_match_pos: Vec<usize>,
+    max_match_pos: Vec<usize>,
+    table: Vec<isize>,
+    diag: Vec<usize>,
+    arrows: Vec<Arrow>,
+}
+
+/// Lowercases a character the way JavaScript does for characters that lowercase to a
+/// single character.
+fn lowercase(c: char) -> char {
+    let mut lower = c.to_lowercase();
+    match (lower.next(), lower.next()) {
+        (Some(lower), None) => lower,
+        _ => c,
+    }
+}
+
+/// Characters that separate words, including the emoji ranges that `fuzzyScore` treats as
+/// separators.
+fn is_separator(c: char) -> bool {
+    matches!(
+        c,
+        '_' | '-'
+            | '.'
+            | ' '
+            | '/'
+            | '\\'
+            | '\''
+            | '"'
+            | ':'
+            | '$'
+            | '<'
+            | '>'
+            | '('
+            | ')'
+            | '['
+            | ']'
+            | '{'
+            | '}'
+    ) || matches!(
+        c as u32,
+        0x1f1e6..=0x1f1ff
+            | 8986
+            | 8987
+            | 9200
+            | 9203
+            | 9728..=10175
+            | 11088
+            | 11093
+            | 127744..=128591
+            | 128640..=128764
+            | 128992..=129008
+            | 129280..=129535
+            | 129648..=129782
+    )
+}
+
+impl VsCodeBuffers {
+    /// Scores a query against a target with the `fuzzyScore` algorithm. When `positions` is
+    /// given, it is filled with the target indices of the matched characters, in query order.
+    pub(crate) fn compute(
+        &mut self,
+        target: &[char],
+        query: &[char],
+        positions: Option<&mut Vec<usize>>,
+    ) -> Option<isize> {
+        let query_len = query.len().min(MAX_LEN);
+        let target_len = target.len().min(MAX_LEN);
+        let (target, query) = (&target[..target_len], &query[..query_len]);
+        if query_len == 0 || target_len == 0 || query_len > target_len {
+            return None;
+        }
+        self.query_lower.clear();
+        self.query_lower
+            .extend(query.iter().copied().map(lowercase));
+        self.target_lower.clear();
+        self.target_lower
+            .extend(target.iter().copied().map(lowercase));
+        if !self.fill_match_bounds() {
+            return None;
+        }
+
+        let width = target_len + 1;
+        self.table.clear();
+        self.table.resize((query_len + 1) * width, 0);
+        self.diag.clear();
+        self.diag.resize((query_len + 1) * width, 0);
+        self.arrows.clear();
+        self.arrows.resize((query_len + 1) * width, Arrow::Diag);
+
+        let mut strong_first_match = false;
+        for query_idx in 0..query_len {
+            let row = (query_idx + 1) * width;
+            let prev_row = query_idx * width;
+            let min_pos = self.min_match_pos[query_idx];
+            let max_pos = self.max_match_pos[query_idx];
+            let next_max_pos = self
+                .max_match_pos
+                .get(query_idx + 1)
+                .copied()
+                .unwrap_or(target_len);
+
+            for target_idx in min_pos..next_max_pos {
+                let column = target_idx + 1;
+                let diag_score = if target_idx <= max_pos {
+                    self.char_score(
+                        target,
+                        query,
+                        query_idx,
+                        target_idx,
+                        self.diag[prev_row + column - 1] == 0,
+                        &mut strong_first_match,
+                    )
+                    .map(|score| score + self.table[prev_row + column - 1])
+                } else {
+                    None
+                };
+
+                // Moving past a target character right after a match starts a gap
+                let left_score = (target_idx > min_pos).then(|| {
+                    let penalty = if self.diag[row + column - 1] > 0 {
+                        5
+                    } else {
+                        0
+                    };
+                    self.table[row + column - 1] - penalty
+                });
+                let left_left_score = (target_idx > min_pos + 1 && self.diag[row + column - 1] > 0)
+                    .then(|| {
+                        let penalty = if self.diag[row + column - 2] > 0 {
+                            5
+                        } else {
+                            0
+                        };
+                        self.table[row + column - 2] - penalty
+                    });
+
+                // Earlier matches in the target are preferred on ties
+                let cell = row + column;
+                if let Some(score) = left_left_score.filter(|score| {
+                    left_score.is_none_or(|left| *score >= left)
+                        && diag_score.is_none_or(|diag| *score >= diag)
+                }) {
+                    self.table[cell] = score;
+                    self.arrows[cell] = Arrow::LeftLeft;
+                    self.diag[cell] = 0;
+                } else if let Some(score) =
+                    left_score.filter(|score| diag_score.is_none_or(|diag| *score >= diag))
+                {
+                    self.table[cell] = score;
+                    self.arrows[cell] = Arrow::Left;
+                    self.diag[cell] = 0;
+                } else {
+                    // The first possible position of each query character always matches
+                    self.table[cell] = diag_score?;
+                    self.arrows[cell] = Arrow::Diag;
+                    self.diag[cell] = self.diag[prev_row + column - 1] + 1;
+                }
+            }
+        }
+
+        if !strong_first_match {
+            return None;
+        }
+
+        let mut score = self.table[query_len * width + target_len];
+        let last_match_column = self.trace(target, positions);
+        if target_len == query_len {
+            // Matching every character of the target
+            score += 2;
+        }
+        // Penalty for each skipped target character before the last match
+        score -= (last_match_column - query_len) as isize;
+        Some(score + SCORE_OFFSET)
+    }
+
+    /// Fills the earliest and latest target position that each query character can match
+    /// at. Returns false if the query does not occur in the target.
+    fn fill_match_bounds(&mut self) -> bool {
+        let (query_lower, target_lower) = (&self.query_lower, &self.target_lower);
+        self.min_match_pos.clear();
+        let mut query_chars = query_lower.iter().peekable();
+        for (target_idx, c) in target_lower.iter().enumerate() {
+            if query_chars.next_if_eq(&c).is_some() {
+                self.min_match_pos.push(target_idx);
+            }
+        }
+        if query_chars.peek().is_some() {
+            return false;
+        }
+
+        self.max_match_pos.clear();
+        self.max_match_pos.resize(query_lower.len(), 0);
+        let mut query_idx = query_lower.len();
+        for (target_idx, c) in target_lower.iter().enumerate().rev() {
+            if query_idx == 0 {
+                break;
+            }
+            if query_lower[query_idx - 1] == *c {
+                query_idx -= 1;
+                self.max_match_pos[query_idx] = target_idx;
+            }
+        }
+        true
+    }
+
+    /// Returns the score for matching a query character at the given target position, or
+    /// `None` if the characters differ. `new_run` is set if the previous query character was
+    /// not matched at the previous target position.
+    fn char_score(
+        &self,
+        target: &[char],
+        query: &[char],
+        query_idx: usize,
+        target_idx: usize,
+        new_run: bool,
+        strong_first_match: &mut bool,
+    ) -> Option<isize> {
+        let (query_lower, target_lower) = (&self.query_lower, &self.target_lower);
+        if query_lower[query_idx] != target_lower[target_idx] {
+            return None;
+        }
+        let is_upper = |idx: usize| target[idx] != target_lower[idx];
+        let follows_separator = target_idx > 0
+            && (is_separator(target_lower[target_idx - 1]) || target_lower[target_idx - 1] == '\t');
+        let same_case = query[query_idx] == target[target_idx];
+
+        let mut score = 1;
+        let mut gap_location = false;
+        if target_idx == query_idx {
+            // Common prefix
+            score = if same_case { 7 } else { 5 };
+        } else if is_upper(target_idx) && (target_idx == 0 || !is_upper(target_idx - 1)) {
+            // Start of a camel case word
+            score = if same_case { 7 } else { 5 };
+            gap_location = true;
+        } else if is_separator(target_lower[target_idx])
+            && (target_idx == 0 || !is_separator(target_lower[target_idx - 1]))
+        {
+            // Separator
+            score = 5;
+        } else if follows_separator {
+            // Start of a word after a separator
+            score = 5;
+            gap_location = true;
+        }
+
+        if score > 1 && query_idx == 0 {
+            *strong_first_match = true;
+        }
+        gap_location |= is_upper(target_idx) || follows_separator;
+
+        if query_idx == 0 {
+            if target_idx > 0 {
+                // Gap before the first match
+                score -= if gap_location { 3 } else { 5 };
+            }
+        } else if new_run {
+            score += if gap_location { 2 } else { 0 };
+        } else {
+            score += if gap_location { 0 } else { 1 };
+        }
+
+        if target_idx + 1 == target_lower.len() {
+            // Matches of the last character are treated as if a gap follows them, so that
+            // they don't gain an advantage from having no gap after them
+            score -= if gap_location { 3 } else { 5 };
+        }
+        Some(score)
+    }
+
+    /// Follows the arrows of the matrix back from the last cell, preferring longer
+    /// contiguous runs at the end of the target. Returns the column of the last match, and
+    /// fills `positions` with the target indices of the matches when given.
+    fn trace(&self, target: &[char], mut positions: Option<&mut Vec<usize>>) -> usize {
+        let width = target.len() + 1;
+        let mut row = self.query_lower.len();
+        let mut column = target.len();
+        let mut backwards_run = 0;
+        let mut last_match_column = 0;
+        if let Some(positions) = positions.as_mut() {
+            positions.clear();
+        }
+
+        while row >= 1 {
+            let mut diag_column = column;
+            while diag_column >= 1 {
+                match self.arrows[row * width + diag_column] {
+                    Arrow::LeftLeft => diag_column -= 2,
+                    Arrow::Left => diag_column -= 1,
+                    Arrow::Diag => break,
+                }
+            }
+
+            // Keep the contiguous run found so far if it is longer than the run at the
+            // earlier match
+            if backwards_run > 1
+                && self.query_lower[row - 1] == self.target_lower[column - 1]
+                && target[diag_column - 1] == self.target_lower[diag_column - 1]
+                && backwards_run + 1 > self.diag[row * width + diag_column]
+            {
+                diag_column = column;
+            }
+
+            if diag_column == column {
+                backwards_run += 1;
+            } else {
+                backwards_run = 1;
+            }
+            if last_match_column == 0 {
+                last_match_column = diag_column;
+            }
+
+            row -= 1;
+            column = diag_column - 1;
+            if let Some(positions) = positions.as_mut() {
+                positions.push(column);
+            }
+        }
+
+        if let Some(positions) = positions {
+            positions.reverse();
+        }
+        last_match_column
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FuzzyMatcher, MatchOptions, ScoringProfile};
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    fn matcher() -> FuzzyMatcher {
+        FuzzyMatcher::with_options(MatchOptions {
+            profile: ScoringProfile::VsCodeCompat,
+            ..MatchOptions::default()
+        })
+    }
+
+    /// Returns the target with each matched character preceded by `^`, as in the tests of
+    /// Visual Studio Code.
+    fn highlight(target: &str, query: &str) -> Option<String> {
+        let positions = matcher().fuzzy_match_details(target, query)?.positions;
+        let mut result = String::new();
+        for (idx, c) in target.chars().enumerate() {
+            if positions.contains(&idx) {
+                result.push('^');
+            }
+            result.push(c);
+        }
+        Some(result)
+    }
+
+    /// Returns the index of the target with the highest score, preferring earlier targets.
+    fn top(query: &str, targets: &[&str]) -> Option<usize> {
+        let mut matcher = matcher();
+        let scores: Vec<Option<usize>> = targets
+            .iter()
+            .map(|target| matcher.fuzzy_match(target, query))
+            .collect();
+        let best = scores.iter().flatten().max()?;
+        scores.iter().position(|score| score.as_ref() == Some(best))
+    }
+
+    #[test]
+    fn test_vscode_compat() {
+        let cases = [
+            ("ab", "abA", Some("^a^bA")),
+            ("ccm", "cacmelCase", Some("^ca^c^melCase")),
+            ("bti", "the_black_knight", None),
+            ("ccm", "camelCase", None),
+            ("cmcm", "camelCase", None),
+            ("BK", "the_black_knight", Some("the_^black_^knight")),
+            ("KeyboardLayout=", "KeyboardLayout", None),
+            (
+                "LLL",
+                "SVisualLoggerLogsList",
+                Some("SVisual^Logger^Logs^List"),
+            ),
+            ("LLLL", "SVilLoLosLi", None),
+            ("LLLL", "SVisualLoggerLogsList", None),
+            ("TEdit", "TextEdit", Some("^Text^E^d^i^t")),
+            ("TEdit", "TextEditor", Some("^Text^E^d^i^tor")),
+            ("TEdit", "Textedit", Some("^Text^e^d^i^t")),
+            ("TEdit", "text_edit", Some("^text_^e^d^i^t")),
+            ("Tedit", "TextEdit", Some("^Text^E^d^i^t")),
+            ("ba", "?AB?", None),
+            ("bkn", "the_black_knight", Some("the_^black_^k^night")),
+            ("bt", "the_black_knight", Some("the_^black_knigh^t")),
+            ("ccm", "camelCasecm", Some("^camel^Casec^m")),
+            ("fdm", "findModel", Some("^fin^d^Model")),
+            ("fob", "foobar", Some("^f^oo^bar")),
+            ("fobz", "foobar", None),
+            ("foobar", "foobar", Some("^f^o^o^b^a^r")),
+            (
+                "form",
+                "editor.formatOnSave",
+                Some("editor.^f^o^r^matOnSave"),
+            ),
+            ("g p", "Git: Pull", Some("^Git:^ ^Pull")),
+            ("gip", "Git: Pull", Some("^G^it: ^Pull")),
+            ("gp", "Git: Pull", Some("^Git: ^Pull")),
+            ("gp", "Git_Git_Pull", Some("^Git_Git_^Pull")),
+            ("is", "ImportStatement", Some("^Import^Statement")),
+            ("is", "isValid", Some("^i^sValid")),
+            ("lowrd", "lowWord", Some("^l^o^wWo^r^d")),
+            ("myvable", "myvariable", Some("^m^y^v^aria^b^l^e")),
+            ("no", "", None),
+            ("no", "match", None),
+            ("ob", "foobar", None),
+            (
+                "sl",
+                "SVisualLoggerLogsList",
+                Some("^SVisual^LoggerLogsList"),
+            ),
+            (
+                "sllll",
+                "SVisualLoggerLogsList",
+                Some("^SVisua^l^Logger^Logs^List"),
+            ),
+            ("Three", "HTMLHRElement", None),
+            ("Three", "Three", Some("^T^h^r^e^e")),
+            ("fo", "barfoo", None),
+            ("fo", "bar_foo", Some("bar_^f^oo")),
+            ("fo", "bar_Foo", Some("bar_^F^oo")),
+            ("fo", "bar foo", Some("bar ^f^oo")),
+            ("fo", "bar.foo", Some("bar.^f^oo")),
+            ("fo", "bar/foo", Some("bar/^f^oo")),
+            ("fo", "bar\\foo", Some("bar\\^f^oo")),
+        ];
+        for (query, target, expected) in cases {
+            assert_eq!(
+                highlight(target, query).as_deref(),
+                expected,
+                "{query} {target}"
+            );
+        }
+
+        let rankings: [(&str, usize, &[&str]); 16] = [
+            ("cons", 2, &["ArrayBufferConstructor", "Console", "console"]),
+            ("Foo", 1, &["foo", "Foo", "foo"]),
+            ("onMess", 1, &["onmessage", "onMessage", "onThisMegaEscape"]),
+            ("CC", 1, &["camelCase", "CamelCase"]),
+            ("cC", 0, &["camelCase", "CamelCase"]),
+            ("p", 4, &["parse", "posix", "pafdsa", "path", "p"]),
+            ("pa", 0, &["parse", "pafdsa", "path"]),
+            (
+                "log",
+                3,
+                &[
+                    "HTMLOptGroupElement",
+                    "ScrollLogicalPosition",
+                    "SVGFEMorphologyElement",
+                    "log",
+                    "logger",
+                ],
+            ),
+            ("e", 2, &["AbstractWorker", "ActiveXObject", "else"]),
+            (
+                "workbench.sideb",
+                1,
+                &[
+                    "workbench.editor.defaultSideBySideLayout",
+                    "workbench.sideBar.location",
+                ],
+            ),
+            (
+                "editor.r",
+                2,
+                &[
+                    "diffEditor.renderSideBySide",
+                    "editor.overviewRulerlanes",
+                    "editor.renderControlCharacter",
+                    "editor.renderWhitespace",
+                ],
+            ),
+            ("-mo", 1, &["-ms-ime-mode", "-moz-columns"]),
+            (
+                "convertModelPosition",
+                0,
+                &[
+                    "convertModelPositionToViewPosition",
+                    "convertViewToModelPosition",
+                ],
+            ),
+            ("is", 0, &["isValidViewletId", "import statement"]),
+            (
+                "title",
+                1,
+                &["files.trimTrailingWhitespace", "window.title"],
+            ),
+            ("const", 1, &["constructor", "const", "cuOnstrul"]),
+        ];
+        for (query, expected, targets) in rankings {
+            assert_eq!(top(query, targets), Some(expected), "{query}");
+        }
+    }
+}
@@ -0,0 +1,294 @@
+//! Walking a directory tree to find the files to match, for file pickers.
+
+use crate::{Budget, CandidateSet};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Callback deciding which entries are skipped, given the path relative to the root and
+/// whether it is a directory.
+type IgnoreFn = dyn Fn(&Path, bool) -> bool + Send + Sync;
+
+enum WalkState {
+    NotStarted,
+    /// Entries left to visit, relative to the root, with the next entry last.
+    Entries(Vec<(PathBuf, bool)>),
+    #[cfg(feature = "gitignore")]
+    Gitignore(alloc::boxed::Box<ignore::Walk>),
+    Complete,
+}
+
+/// Walks a directory tree, adding the path of each file relative to the root to a
+/// [`CandidateSet`]. Walking is done a little at a time with [`walk`](FileWalker::walk),
+/// so a picker can query the candidates found so far and show results while files are
+/// still being discovered.
+///
+/// Entries of each directory are visited in order of their names. Symbolic links are added
+/// as files and are not followed, and directories that can't be read are skipped.
+///
+/// # Examples
+///
+/// ```no_run
+/// use code_fuzzy_match::{Budget, CandidateSet, FileWalker};
+/// use std::time::Duration;
+///
+/// let mut walker = FileWalker::new(".");
+/// walker.set_ignore(|path, is_dir| is_dir && path.ends_with("target"));
+/// let mut set = CandidateSet::new();
+/// loop {
+///     let complete = walker.walk(&mut set, Budget::Time(Duration::from_millis(16)))?;
+///     for result in set.query("lib", 10) {
+///         println!("{}", set.get(result.item).unwrap());
+///     }
+///     if complete {
+///         break;
+///     }
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct FileWalker {
+    root: PathBuf,
+    ignore: Option<Arc<IgnoreFn>>,
+    #[cfg(feature = "gitignore")]
+    gitignore: bool,
+    state: WalkState,
+}
+
+impl FileWalker {
+    /// Creates a walker for the directory tree at `root`. Nothing is read until the first
+    /// call to [`walk`](FileWalker::walk).
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        FileWalker {
+            root: root.into(),
+            ignore: None,
+            #[cfg(feature = "gitignore")]
+            gitignore: false,
+            state: WalkState::NotStarted,
+        }
+    }
+
+    /// Returns the root of the directory tree being walked.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Sets a callback that decides which entries to skip, given the path of the entry
+    /// relative to the root and whether it is a directory. Skipped directories are not
+    /// walked. Has no effect once walking has started.
+    pub fn set_ignore(&mut self, ignore: impl Fn(&Path, bool) -> bool + Send + Sync + 'static) {
+        self.ignore = Some(Arc::new(ignore));
+    }
+
+    /// Sets whether to skip the files ignored by `.gitignore`, `.ignore` and
+    /// `.git/info/exclude` files, along with `.git` directories. Ignore files are read even
+    /// if the tree is not in a Git repository. Has no effect once walking has started.
+    #[cfg(feature = "gitignore")]
+    pub fn set_gitignore(&mut self, gitignore: bool) {
+        self.gitignore = gitignore;
+    }
+
+    /// Returns true if every entry in the tree has been visited.
+    pub fn is_complete(&self) -> bool {
+        matches!(self.state, WalkState::Complete)
+    }
+
+    /// Visits as many entries as the budget allows, adding each file found to `set`, and
+    /// returns true once the whole tree has been walked. A [`Budget::Candidates`] budget
+    /// limits the number of entries visited, including directories and skipped entries.
+    /// Returns an error if the root directory can't be read.
+    pub fn walk(&mut self, set: &mut CandidateSet, budget: Budget) -> io::Result<bool> {
+        if let WalkState::NotStarted = self.state {
+            self.state = self.start()?;
+        }
+
+        let start = Instant::now();
+        let mut visited = 0;
+        while !self.is_complete() {
+            let exhausted = match budget {
+                Budget::Candidates(count) => visited >= count,
+                Budget::Time(duration) => visited > 0 && start.elapsed() >= duration,
+            };
+            if exhausted {
+                break;
+            }
+            if let Some(file) = self.next_file() {
+                set.insert(&file.to_string_lossy());
+            }
+            visited += 1;
+        }
+        Ok(self.is_complete())
+    }
+
+    fn start(&self) -> io::Result<WalkState> {
+        // The root is always read first, so that an error is returned if it can't be read
+        let mut entries = Vec::new();
+        read_dir(&self.root, Path::new(""), &mut entries)?;
+
+        #[cfg(feature = "gitignore")]
+        if self.gitignore {
+            let mut builder = ignore::WalkBuilder::new(&self.root);
+            builder
+                .standard_filters(false)
+                .git_ignore(true)
+                .git_exclude(true)
+                .ignore(true)
+                .parents(true)
+                .require_git(false)
+                .sort_by_file_name(|a, b| a.cmp(b));
+            let root = self.root.clone();
+            let ignore = self.ignore.clone();
+            builder.filter_entry(move |entry| {
+                let is_dir = entry
+                    .file_type()
+                    .is_some_and(|file_type| file_type.is_dir());
+                if is_dir && entry.file_name() == ".git" {
+                    return false;
+                }
+                let path = entry.path().strip_prefix(&root).unwrap_or(entry.path());
+                ignore.as_ref().is_none_or(|ignore| !ignore(path, is_dir))
+            });
+            let mut walk = builder.build();
+            // The first entry is the root itself
+            walk.next();
+            return Ok(WalkState::Gitignore(alloc::boxed::Box::new(walk)));
+        }
+
+        Ok(WalkState::Entries(entries))
+    }
+
+    /// Visits the next entry, returning its path relative to the root if it is a file that
+    /// was not skipped.
+    fn next_file(&mut self) -> Option<PathBuf> {
+        match &mut self.state {
+            WalkState::Entries(entries) => {
+                let Some((path, is_dir)) = entries.pop() else {
+                    self.state = WalkState::Complete;
+                    return None;
+                };
+                if self
+                    .ignore
+                    .as_ref()
+                    .is_some_and(|ignore| ignore(&path, is_dir))
+                {
+                    return None;
+                }
+                if !is_dir {
+                    return Some(path);
+                }
+                // Directories that can't be read are skipped
+                let _ = read_dir(&self.root, &path, entries);
+                None
+            }
+            #[cfg(feature = "gitignore")]
+            WalkState::Gitignore(walk) => {
+                let Some(entry) = walk.next() else {
+                    self.state = WalkState::Complete;
+                    return None;
+                };
+                let entry = entry.ok()?;
+                if entry
+                    .file_type()
+                    .is_some_and(|file_type| file_type.is_dir())
+                {
+                    return None;
+                }
+                let path = entry.path().strip_prefix(&self.root).ok()?;
+                Some(path.to_path_buf())
+            }
+            WalkState::NotStarted | WalkState::Complete => None,
+        }
+    }
+}
+
+/// Adds the entries of the directory at `path` relative to `root` to the entries left to
+/// visit, so that they are visited next in order of their names.
+fn read_dir(root: &Path, path: &Path, entries: &mut Vec<(PathBuf, bool)>) -> io::Result<()> {
+    let mut dir_entries = Vec::new();
+    for entry in fs::read_dir(root.join(path))? {
+        let entry = entry?;
+        let is_dir = entry.file_type()?.is_dir();
+        dir_entries.push((path.join(entry.file_name()), is_dir));
+    }
+    dir_entries.sort_unstable_by(|a, b| b.0.cmp(&a.0));
+    entries.extend(dir_entries);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Budget, CandidateSet, FileWalker};
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use std::fs;
+    use std::path::MAIN_SEPARATOR;
+
+    #[test]
+    fn test_file_walker() {
+        let root = std::env::temp_dir().join("code_fuzzy_match_test_file_walker");
+        let _ = fs::remove_dir_all(&root);
+        for dir in ["src/matrix", "target/debug", "docs"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for file in [
+            ".gitignore",
+            "Cargo.toml",
+            "docs/notes.log",
+            "src/lib.rs",
+            "src/matrix/tolerant.rs",
+            "target/debug/app",
+        ] {
+            fs::write(root.join(file), "target/\n*.log\n").unwrap();
+        }
+        let path = |path: &str| path.replace('/', &String::from(MAIN_SEPARATOR));
+
+        // Files are found a little at a time, and can be queried along the way
+        let mut walker = FileWalker::new(&root);
+        walker.set_ignore(|path, is_dir| is_dir && path.ends_with("target"));
+        let mut set = CandidateSet::new();
+        assert!(!walker.walk(&mut set, Budget::Candidates(3)).unwrap());
+        assert_eq!(set.len(), 2);
+        assert!(set.query("lib", 10).is_empty());
+        while !walker.walk(&mut set, Budget::Candidates(1)).unwrap() {}
+        assert!(walker.is_complete());
+        let files: Vec<&str> = set.iter().map(|(_, file)| file).collect();
+        assert_eq!(
+            files,
+            [
+                ".gitignore",
+                "Cargo.toml",
+                &path("docs/notes.log"),
+                &path("src/lib.rs"),
+                &path("src/matrix/tolerant.rs"),
+            ]
+        );
+        let results = set.query("lib", 10);
+        assert_eq!(set.get(results[0].item), Some(&*path("src/lib.rs")));
+
+        #[cfg(feature = "gitignore")]
+        {
+            let mut walker = FileWalker::new(&root);
+            walker.set_gitignore(true);
+            walker.set_ignore(|path, _| path.ends_with("Cargo.toml"));
+            let mut set = CandidateSet::new();
+            assert!(walker
+                .walk(&mut set, Budget::Candidates(usize::MAX))
+                .unwrap());
+            let files: Vec<&str> = set.iter().map(|(_, file)| file).collect();
+            assert_eq!(
+                files,
+                [
+                    ".gitignore",
+                    &path("src/lib.rs"),
+                    &path("src/matrix/tolerant.rs")
+                ]
+            );
+        }
+
+        let mut walker = FileWalker::new(root.join("missing"));
+        assert!(walker.walk(&mut set, Budget::Candidates(1)).is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
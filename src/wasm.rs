@@ -0,0 +1,129 @@
+//! JavaScript bindings for WebAssembly, generated with `wasm-bindgen`.
+//!
+//! Match positions are returned as indices of UTF-16 code units, so that they can be used
+//! to index JavaScript strings directly.
+
+use crate::{FuzzyMatcher, TieBreak};
+use alloc::string::String;
+use alloc::vec::Vec;
+use wasm_bindgen::prelude::wasm_bindgen;
+
+/// Converts character indices into `target` to indices of UTF-16 code units.
+fn utf16_positions(target: &str, positions: &[usize]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(positions.len());
+    let mut positions = positions.iter().peekable();
+    let mut utf16_idx = 0;
+    for (char_idx, c) in target.chars().enumerate() {
+        if positions.next_if_eq(&&char_idx).is_some() {
+            result.push(utf16_idx);
+        }
+        utf16_idx += c.len_utf16() as u32;
+    }
+    result
+}
+
+/// Fuzzy match a string against a query string. Returns the score, or `undefined` if the
+/// query does not match the target string.
+#[wasm_bindgen(js_name = fuzzyMatch)]
+pub fn fuzzy_match(target: &str, query: &str) -> Option<usize> {
+    crate::fuzzy_match(target, query)
+}
+
+/// Fuzzy matcher that holds its memory between calls, for matching many targets.
+#[wasm_bindgen(js_name = FuzzyMatcher)]
+pub struct WasmMatcher {
+    matcher: FuzzyMatcher,
+}
+
+#[wasm_bindgen(js_class = FuzzyMatcher)]
+impl WasmMatcher {
+    /// Creates a new matcher with the default options.
+    #[wasm_bindgen(constructor)]
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        WasmMatcher {
+            matcher: FuzzyMatcher::new(),
+        }
+    }
+
+    /// Fuzzy match a string against a query string. Returns the score, or `undefined` if
+    /// the query does not match the target string.
+    #[wasm_bindgen(js_name = fuzzyMatch)]
+    pub fn fuzzy_match(&mut self, target: &str, query: &str) -> Option<usize> {
+        self.matcher.fuzzy_match(target, query)
+    }
+
+    /// Fuzzy match a string against a query string, returning the position in the target
+    /// of each matched character as a `Uint32Array`, or `undefined` if the query does not
+    /// match the target string.
+    #[wasm_bindgen(js_name = matchPositions)]
+    pub fn match_positions(&mut self, target: &str, query: &str) -> Option<Vec<u32>> {
+        let result = self.matcher.fuzzy_match_details(target, query)?;
+        Some(utf16_positions(target, &result.positions))
+    }
+
+    /// Fuzzy match each of the targets against a query string, returning the targets that
+    /// match from best to worst, with ties broken as in the Rust API.
+    pub fn rank(&mut self, targets: Vec<String>, query: &str) -> RankResults {
+        let ranked =
+            self.matcher
+                .rank(targets.iter().map(String::as_str), query, TieBreak::DEFAULT);
+        RankResults {
+            indices: ranked.iter().map(|ranked| ranked.index as u32).collect(),
+            scores: ranked.iter().map(|ranked| ranked.score as u32).collect(),
+        }
+    }
+}
+
+/// Targets that matched a query, from best to worst.
+#[wasm_bindgen]
+pub struct RankResults {
+    indices: Vec<u32>,
+    scores: Vec<u32>,
+}
+
+#[wasm_bindgen]
+impl RankResults {
+    /// Index of each matching target in the array of targets, as a `Uint32Array`.
+    #[wasm_bindgen(getter)]
+    pub fn indices(&self) -> Vec<u32> {
+        self.indices.clone()
+    }
+
+    /// Score of each matching target, as a `Uint32Array`.
+    #[wasm_bindgen(getter)]
+    pub fn scores(&self) -> Vec<u32> {
+        self.scores.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WasmMatcher;
+    use crate::FuzzyMatcher;
+    use alloc::string::{String, ToString};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_wasm_matcher() {
+        let mut matcher = WasmMatcher::new();
+        assert_eq!(
+            matcher.fuzzy_match("src/lib.rs", "lib"),
+            FuzzyMatcher::new().fuzzy_match("src/lib.rs", "lib")
+        );
+
+        // Characters outside the Basic Multilingual Plane take two UTF-16 code units
+        let positions = matcher.match_positions("a😀b", "ab").unwrap();
+        assert_eq!(positions, vec![0, 3]);
+        assert!(matcher.match_positions("a😀b", "c").is_none());
+
+        let targets: Vec<String> = ["src/lib.rs", "Cargo.toml", "lib"]
+            .iter()
+            .map(|target| target.to_string())
+            .collect();
+        let results = matcher.rank(targets, "lib");
+        assert_eq!(results.indices(), vec![2, 0]);
+        assert!(results.scores()[0] >= results.scores()[1]);
+    }
+}
@@ -0,0 +1,106 @@
+//! Handling of whitespace in queries, for the `trim_query`, `collapse_query_spaces` and
+//! `space_matches_separators` options.
+
+use crate::MatchOptions;
+
+/// Characters that [`CharClass::of`](crate::matrix::CharClass::of) classifies as separators
+/// or path separators, which a space in the query matches with the `space_matches_separators`
+/// option.
+pub(crate) const SEPARATOR_CHARS: &[char] = &['/', '\\', '_', '-', '.', ' ', '\'', '"', ':'];
+
+/// Returns the characters of `query` after trimming and collapsing its whitespace as set by
+/// the options. Runs of whitespace are collapsed into a single space.
+pub(crate) fn query_chars<'a>(
+    query: &'a str,
+    options: &MatchOptions,
+) -> impl Iterator<Item = char> + Clone + 'a {
+    let query = if options.trim_query {
+        query.trim()
+    } else {
+        query
+    };
+    let collapse = options.collapse_query_spaces;
+    query
+        .char_indices()
+        .filter(move |(idx, c)| {
+            !(collapse
+                && c.is_whitespace()
+                && query[..*idx]
+                    .chars()
+                    .next_back()
+                    .is_some_and(char::is_whitespace))
+        })
+        .map(move |(_, c)| {
+            if collapse && c.is_whitespace() {
+                ' '
+            } else {
+                c
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::query_chars;
+    use crate::{fuzzy_match_in, FuzzyMatcher, MatchOptions, MatchScratch};
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_query_whitespace() {
+        let options = MatchOptions {
+            trim_query: true,
+            collapse_query_spaces: true,
+            ..MatchOptions::default()
+        };
+        let chars = |query: &str| query_chars(query, &options).collect::<String>();
+        assert_eq!(chars("  open \t  file \n"), "open file");
+        assert_eq!(chars(" \t "), "");
+
+        // Stray spaces no longer prevent a match
+        let mut plain = FuzzyMatcher::new();
+        let mut matcher = FuzzyMatcher::with_options(options.clone());
+        assert!(plain.fuzzy_match("Open File", " open  file ").is_none());
+        assert_eq!(
+            matcher.fuzzy_match("Open File", " open  file "),
+            plain.fuzzy_match("Open File", "open file")
+        );
+        let mut scratch = MatchScratch::<64>::with_options(options);
+        assert_eq!(
+            fuzzy_match_in("Open File", " open  file ", &mut scratch),
+            plain.fuzzy_match("Open File", "open file")
+        );
+        let mut targets = alloc::vec!["Open File", "Close Window"];
+        matcher.retain_matches(&mut targets, |target| target, "open  file ", false);
+        assert_eq!(targets, ["Open File"]);
+
+        // Spaces match any separator, and still match spaces exactly
+        for position_index in [false, true] {
+            let mut matcher = FuzzyMatcher::with_options(MatchOptions {
+                space_matches_separators: true,
+                position_index,
+                ..MatchOptions::default()
+            });
+            for target in ["open_file", "open-file", "src/open/file.rs", "open file"] {
+                assert_eq!(
+                    plain.fuzzy_match(target, "open file").is_some(),
+                    target == "open file"
+                );
+                let result = matcher.fuzzy_match_details(target, "open file").unwrap();
+                assert_eq!(result.positions.len(), 9, "{target}");
+            }
+        }
+        let mut matcher = FuzzyMatcher::with_options(MatchOptions {
+            space_matches_separators: true,
+            ..MatchOptions::default()
+        });
+        assert!(matcher.fuzzy_match("openfile", "open file").is_none());
+        assert!(
+            matcher.fuzzy_match("open file", "open file")
+                > matcher.fuzzy_match("open_file", "open file")
+        );
+        let mut targets: Vec<&str> = alloc::vec!["open_file", "openfile"];
+        matcher.retain_matches(&mut targets, |target| target, "open file", false);
+        assert_eq!(targets, ["open_file"]);
+    }
+}
@@ -0,0 +1,59 @@
+//! Word start detection using Unicode word segmentation (UAX #29).
+
+use crate::matrix::CharClass;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Marks units of the target that begin a word according to Unicode word segmentation.
+/// `unit_starts` yields the byte offset of each unit of the target in order, and `classes`
+/// holds the classification of each unit.
+pub(crate) fn mark_word_starts(
+    target: &str,
+    unit_starts: impl Iterator<Item = usize>,
+    classes: &mut [CharClass],
+) {
+    let mut word_starts = target
+        .unicode_word_indices()
+        .map(|(start, _)| start)
+        .peekable();
+    for (unit_idx, unit_start) in unit_starts.enumerate() {
+        // Skip words that start in the middle of a unit, which can happen when units are
+        // larger than a character.
+        while word_starts.next_if(|start| *start < unit_start).is_some() {}
+        if word_starts.next_if_eq(&unit_start).is_some() && classes[unit_idx] == CharClass::Other {
+            classes[unit_idx] = CharClass::WordStart;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FuzzyMatcher, MatchOptions};
+
+    #[test]
+    fn test_unicode_word_starts() {
+        let mut default_matcher = FuzzyMatcher::new();
+        let mut words_matcher = FuzzyMatcher::with_options(MatchOptions {
+            unicode_words: true,
+            ..MatchOptions::default()
+        });
+
+        // "Open the file" in Japanese, where the katakana word "ファイル" follows a
+        // particle with no separator in between.
+        const TARGET: &str = "このファイルを開く";
+        let default_score = default_matcher.fuzzy_match(TARGET, "ファ").unwrap();
+        let words_score = words_matcher.fuzzy_match(TARGET, "ファ").unwrap();
+        assert!(words_score > default_score);
+
+        // Matching in the middle of the katakana word does not get a word start bonus
+        assert_eq!(
+            default_matcher.fuzzy_match(TARGET, "ァイ"),
+            words_matcher.fuzzy_match(TARGET, "ァイ")
+        );
+
+        // Existing word rules are unchanged for ASCII text
+        assert_eq!(
+            default_matcher.fuzzy_match("camelCaseWords", "Wor"),
+            words_matcher.fuzzy_match("camelCaseWords", "Wor")
+        );
+    }
+}
@@ -0,0 +1,255 @@
+//! Matching on a background thread, for user interfaces that can't block while matching.
+
+use crate::{Budget, FuzzyMatcher, MatchOptions, RankCursor, Scored, TieBreak};
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread::JoinHandle;
+
+/// Number of targets matched between each update sent by the worker.
+const UPDATE_INTERVAL: usize = 4096;
+
+/// A query sent to the worker thread.
+struct Request {
+    query: String,
+    generation: u64,
+}
+
+/// Results of a query sent to a [`MatcherWorker`]. Results are sent as matching
+/// progresses, with each update holding every match found so far.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WorkerResults {
+    /// Generation of the query that produced these results.
+    pub generation: u64,
+    /// Index of each matching target and its score, from best to worst, ordered as by
+    /// [`FuzzyMatcher::rank`] with [`TieBreak::DEFAULT`].
+    pub matches: Vec<Scored<usize>>,
+    /// True if every target has been matched, and no more updates will be sent for this
+    /// generation.
+    pub complete: bool,
+}
+
+/// Matches a list of targets on a background thread. Queries are tagged with a generation
+/// number that increases with each query, such as a counter incremented every time the user
+/// edits the query. Once a query is sent, work on queries with older generations stops and
+/// their results are discarded.
+///
+/// Dropping the worker stops the thread.
+///
+/// # Examples
+///
+/// ```
+/// use code_fuzzy_match::MatcherWorker;
+///
+/// let targets = vec!["src/lib.rs".to_string(), "lib".to_string(), "Cargo.toml".to_string()];
+/// let worker = MatcherWorker::new(targets, Default::default());
+/// worker.query("lib", 1);
+/// let results = loop {
+///     let results = worker.recv().unwrap();
+///     if results.complete {
+///         break results;
+///     }
+/// };
+/// let matches: Vec<&str> = results
+///     .matches
+///     .iter()
+///     .map(|result| worker.targets()[result.item].as_str())
+///     .collect();
+/// assert_eq!(matches, ["lib", "src/lib.rs"]);
+/// ```
+pub struct MatcherWorker {
+    targets: Arc<Vec<String>>,
+    requests: Option<Sender<Request>>,
+    results: Receiver<WorkerResults>,
+    latest_generation: Arc<AtomicU64>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MatcherWorker {
+    /// Starts a worker thread that matches the given targets using the given options.
+    pub fn new(targets: Vec<String>, options: MatchOptions) -> Self {
+        let targets = Arc::new(targets);
+        let latest_generation = Arc::new(AtomicU64::new(0));
+        let (requests, request_receiver) = mpsc::channel();
+        let (result_sender, results) = mpsc::channel();
+        let thread = {
+            let targets = targets.clone();
+            let latest_generation = latest_generation.clone();
+            std::thread::spawn(move || {
+                run(
+                    FuzzyMatcher::with_options(options),
+                    &targets,
+                    request_receiver,
+                    result_sender,
+                    &latest_generation,
+                )
+            })
+        };
+        MatcherWorker {
+            targets,
+            requests: Some(requests),
+            results,
+            latest_generation,
+            thread: Some(thread),
+        }
+    }
+
+    /// Returns the targets being matched. Results refer to targets by their index.
+    pub fn targets(&self) -> &[String] {
+        &self.targets
+    }
+
+    /// Starts matching a query in the background. Work on queries with an older generation
+    /// stops, and their pending results are discarded. Queries with a generation older than
+    /// the newest query already sent are ignored.
+    pub fn query(&self, query: &str, generation: u64) {
+        self.latest_generation
+            .fetch_max(generation, Ordering::Relaxed);
+        if let Some(requests) = &self.requests {
+            // The thread only stops when the worker is dropped
+            let _ = requests.send(Request {
+                query: query.into(),
+                generation,
+            });
+        }
+    }
+
+    /// Returns the most recent update for the newest query without blocking, or `None` if
+    /// there is no new update.
+    pub fn try_recv(&self) -> Option<WorkerResults> {
+        let mut newest = None;
+        loop {
+            match self.results.try_recv() {
+                Ok(results) if !self.is_superseded(&results) => newest = Some(results),
+                Ok(_) => (),
+                Err(TryRecvError::Empty | TryRecvError::Disconnected) => return newest,
+            }
+        }
+    }
+
+    /// Waits for the next update for the newest query. Returns `None` if the worker thread
+    /// has stopped.
+    pub fn recv(&self) -> Option<WorkerResults> {
+        loop {
+            let results = self.results.recv().ok()?;
+            if !self.is_superseded(&results) {
+                return Some(results);
+            }
+        }
+    }
+
+    fn is_superseded(&self, results: &WorkerResults) -> bool {
+        results.generation < self.latest_generation.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for MatcherWorker {
+    fn drop(&mut self) {
+        // Stop any query in progress, and close the channel to end the thread
+        self.latest_generation.store(u64::MAX, Ordering::Relaxed);
+        self.requests = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Main loop of the worker thread.
+fn run(
+    mut matcher: FuzzyMatcher,
+    targets: &[String],
+    requests: Receiver<Request>,
+    results: Sender<WorkerResults>,
+    latest_generation: &AtomicU64,
+) {
+    let is_superseded = |generation: u64| generation < latest_generation.load(Ordering::Relaxed);
+    while let Ok(mut request) = requests.recv() {
+        // Skip straight to the newest query that has been sent
+        while let Ok(newer) = requests.try_recv() {
+            if newer.generation >= request.generation {
+                request = newer;
+            }
+        }
+
+        let mut cursor = RankCursor::new();
+        while !is_superseded(request.generation) {
+            let ranked = matcher.rank_with_budget(
+                targets,
+                &request.query,
+                TieBreak::DEFAULT,
+                Budget::Candidates(UPDATE_INTERVAL),
+                &mut cursor,
+            );
+            let update = WorkerResults {
+                generation: request.generation,
+                matches: ranked
+                    .into_iter()
+                    .map(|ranked| Scored {
+                        item: ranked.index,
+                        score: ranked.score,
+                        positions: None,
+                    })
+                    .collect(),
+                complete: cursor.is_complete(),
+            };
+            if results.send(update).is_err() || cursor.is_complete() {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FuzzyMatcher, MatchOptions, MatcherWorker, TieBreak};
+    use alloc::format;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_matcher_worker() {
+        let targets: Vec<String> = (0..10_000).map(|idx| format!("file_{idx}.rs")).collect();
+        let worker = MatcherWorker::new(targets.clone(), MatchOptions::default());
+        let expected: Vec<(usize, usize)> = FuzzyMatcher::new()
+            .rank(targets.iter().map(String::as_str), "f12", TieBreak::DEFAULT)
+            .iter()
+            .map(|ranked| (ranked.index, ranked.score))
+            .collect();
+
+        // Updates are streamed as matching progresses
+        worker.query("f12", 1);
+        let mut updates = 0;
+        let results = loop {
+            let results = worker.recv().unwrap();
+            assert_eq!(results.generation, 1);
+            updates += 1;
+            if results.complete {
+                break results;
+            }
+        };
+        assert!(updates > 1);
+        let matches: Vec<(usize, usize)> = results
+            .matches
+            .iter()
+            .map(|result| (result.item, result.score))
+            .collect();
+        assert_eq!(matches, expected);
+
+        // Results of superseded queries are never returned
+        worker.query("f1", 2);
+        worker.query("f12", 3);
+        worker.query("f2", 2);
+        loop {
+            let results = worker.recv().unwrap();
+            assert_eq!(results.generation, 3);
+            if results.complete {
+                assert_eq!(results.matches.len(), expected.len());
+                break;
+            }
+        }
+        assert!(worker.try_recv().is_none());
+    }
+}